@@ -0,0 +1,104 @@
+/**
+ * Build the API server sidecar the desktop bundle ships as
+ * `src-tauri/binaries/ledgerhound-api-<target-triple>` (see `externalBin` in
+ * src-tauri/tauri.bundle.conf.json). `npm run tauri:build` runs this first.
+ *
+ * src-server/api.ts is bundled into one CommonJS file with esbuild and
+ * embedded in a copy of the running `node` as a single executable
+ * application. Prisma's query engine is a native library that can't be
+ * embedded, so it's copied into the same directory and bundled as a
+ * resource; the shell points PRISMA_QUERY_ENGINE_LIBRARY at it.
+ *
+ * Usage: npm run build:sidecar
+ * Set TAURI_ENV_TARGET_TRIPLE to name the binary for another triple (the
+ * binary is still built for the platform running this script).
+ */
+
+import { execFileSync } from 'node:child_process';
+import { copyFileSync, mkdirSync, readdirSync, rmSync, writeFileSync } from 'node:fs';
+import { join, resolve } from 'node:path';
+import { build } from 'esbuild';
+
+const projectRoot = resolve(import.meta.dirname, '..');
+const binariesDir = join(projectRoot, 'src-tauri', 'binaries');
+const workDir = join(projectRoot, 'target', 'sidecar');
+const SIDECAR_NAME = 'ledgerhound-api';
+// Fixed by Node; see https://nodejs.org/api/single-executable-applications.html
+const SEA_FUSE = 'NODE_SEA_FUSE_fce680ab2cc467b6e072b8b5df1996b2';
+
+function run(command: string, args: string[]): void {
+  execFileSync(command, args, { cwd: projectRoot, stdio: 'inherit' });
+}
+
+function targetTriple(): string {
+  if (process.env.TAURI_ENV_TARGET_TRIPLE) {
+    return process.env.TAURI_ENV_TARGET_TRIPLE;
+  }
+  const host = execFileSync('rustc', ['-vV'], { encoding: 'utf-8' })
+    .split('\n')
+    .find(line => line.startsWith('host:'));
+  if (!host) {
+    throw new Error('Could not read the host target triple from `rustc -vV`');
+  }
+  return host.slice('host:'.length).trim();
+}
+
+async function main(): Promise<void> {
+  const triple = targetTriple();
+  const exe = triple.includes('windows') ? '.exe' : '';
+  const binary = join(binariesDir, `${SIDECAR_NAME}-${triple}${exe}`);
+  rmSync(workDir, { recursive: true, force: true });
+  mkdirSync(workDir, { recursive: true });
+  mkdirSync(binariesDir, { recursive: true });
+
+  console.log('📦 Bundling src-server/api.ts...');
+  run('npx', ['prisma', 'generate']);
+  const bundle = join(workDir, 'api.cjs');
+  await build({
+    entryPoints: [join(projectRoot, 'src-server', 'api.ts')],
+    outfile: bundle,
+    bundle: true,
+    platform: 'node',
+    format: 'cjs',
+    target: `node${process.versions.node.split('.')[0]}`,
+    // api.ts is an ES module; give it a file URL for the executable.
+    define: { 'import.meta.url': 'import_meta_url' },
+    banner: { js: "const import_meta_url = require('node:url').pathToFileURL(__filename).href;" },
+    logLevel: 'warning',
+  });
+
+  console.log(`🔧 Building ${binary}...`);
+  const blob = join(workDir, 'sea-prep.blob');
+  const seaConfig = join(workDir, 'sea-config.json');
+  writeFileSync(
+    seaConfig,
+    JSON.stringify({ main: bundle, output: blob, disableExperimentalSEAWarning: true }, null, 2),
+  );
+  run(process.execPath, ['--experimental-sea-config', seaConfig]);
+  copyFileSync(process.execPath, binary);
+
+  const postjectArgs = ['--yes', 'postject', binary, 'NODE_SEA_BLOB', blob, '--sentinel-fuse', SEA_FUSE];
+  if (process.platform === 'darwin') {
+    run('codesign', ['--remove-signature', binary]);
+    run('npx', [...postjectArgs, '--macho-segment-name', 'NODE_SEA']);
+    run('codesign', ['--sign', '-', binary]);
+  } else {
+    run('npx', postjectArgs);
+  }
+
+  const engineDir = join(projectRoot, 'node_modules', '.prisma', 'client');
+  const engines = readdirSync(engineDir).filter(name => name.includes('query_engine') && name.endsWith('.node'));
+  if (engines.length === 0) {
+    throw new Error(`No Prisma query engine in ${engineDir}`);
+  }
+  for (const engine of engines) {
+    copyFileSync(join(engineDir, engine), join(binariesDir, engine));
+  }
+
+  console.log(`✅ Built ${binary} with ${engines.join(', ')}`);
+}
+
+main().catch(error => {
+  console.error('❌ Sidecar build failed:', error);
+  process.exit(1);
+});
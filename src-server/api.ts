@@ -1915,6 +1915,11 @@ app.get('/api/stripe/balance', async (req, res) => {
 // SYSTEM ENDPOINTS
 // ============================================================================
 
+// Lightweight liveness probe used by the desktop shell's sidecar supervisor
+app.get('/api/health', (_req, res) => {
+  res.json({ status: 'ok' });
+});
+
 const getGitInfo = () => {
   const run = (cmd: string) => {
     try { return execSync(cmd, { encoding: 'utf-8' }).trim(); }
@@ -2127,9 +2132,11 @@ app.get('/api/books/available-databases', (_req, res) => {
       const entries = readdirSync(booksDir, { withFileTypes: true });
       for (const entry of entries) {
         if (entry.isDirectory()) {
-          const dbFile = join(booksDir, entry.name, 'ledger.db');
-          if (existsSync(dbFile)) {
-            databases.push({ databasePath: `books/${entry.name}/ledger.db` });
+          // Books created before the .ledgerhound extension are ledger.db
+          const fileName = ['ledger.ledgerhound', 'ledger.db']
+            .find(name => existsSync(join(booksDir, entry.name, name)));
+          if (fileName) {
+            databases.push({ databasePath: `books/${entry.name}/${fileName}` });
           }
         }
       }
@@ -0,0 +1,36 @@
+//! Embeds `prisma/migrations/*/migration.sql` so the desktop app can migrate
+//! books without the Prisma CLI.
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+fn main() {
+  let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../prisma/migrations");
+  println!("cargo:rerun-if-changed={}", dir.display());
+
+  let mut migrations: Vec<(String, PathBuf)> = fs::read_dir(&dir)
+    .unwrap_or_else(|err| panic!("reading {}: {err}", dir.display()))
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.join("migration.sql").is_file())
+    .map(|path| {
+      let name = path.file_name().unwrap().to_string_lossy().into_owned();
+      let sql = fs::canonicalize(path.join("migration.sql")).unwrap();
+      println!("cargo:rerun-if-changed={}", sql.display());
+      (name, sql)
+    })
+    .collect();
+  // Prisma applies migrations in directory-name (timestamp) order.
+  migrations.sort();
+
+  let mut out = String::from("pub static MIGRATIONS: &[EmbeddedMigration] = &[\n");
+  for (name, sql) in migrations {
+    out.push_str(&format!(
+      "  EmbeddedMigration {{ name: {name:?}, sql: include_str!({:?}) }},\n",
+      sql.display().to_string()
+    ));
+  }
+  out.push_str("];\n");
+
+  let dest = PathBuf::from(env::var("OUT_DIR").unwrap()).join("migrations.rs");
+  fs::write(dest, out).unwrap();
+}
@@ -0,0 +1,466 @@
+//! Book snapshots, mirroring `BackupService` in `backupService.ts`.
+//!
+//! Snapshots are taken with SQLite's online backup API rather than
+//! `VACUUM INTO`, so they're consistent while the API server writes through
+//! the WAL. Each `backup-<type>-<time>.db` has a `<file>.manifest.json`
+//! beside it recording its SHA-256, which [`BackupDir::restore`] checks,
+//! along with `PRAGMA integrity_check`, before it touches the book.
+//! Snapshots of an encrypted book are encrypted with the book's key.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::encryption::{self, BookKey, EncryptionError};
+
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error(transparent)]
+  Encryption(#[from] EncryptionError),
+  #[error("Backup file not found: {0}")]
+  NotFound(String),
+  #[error("not a backup file name: {0}")]
+  InvalidFilename(String),
+  #[error("{0} has no manifest, so its checksum can't be verified")]
+  MissingManifest(String),
+  #[error("{filename} is corrupt: SHA-256 is {actual}, manifest says {expected}")]
+  ChecksumMismatch {
+    filename: String,
+    expected: String,
+    actual: String,
+  },
+  #[error("{filename} failed integrity_check: {details}")]
+  IntegrityCheck { filename: String, details: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupKind {
+  Manual,
+  Auto,
+  PreImport,
+  PreReconcile,
+}
+
+impl BackupKind {
+  pub const ALL: &'static [BackupKind] = &[
+    BackupKind::Manual,
+    BackupKind::Auto,
+    BackupKind::PreImport,
+    BackupKind::PreReconcile,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      BackupKind::Manual => "manual",
+      BackupKind::Auto => "auto",
+      BackupKind::PreImport => "pre-import",
+      BackupKind::PreReconcile => "pre-reconcile",
+    }
+  }
+
+  /// The kind in a `backup-<kind>-<time>.db` name. Other `.db` files count
+  /// as manual, as in the TS service.
+  fn from_filename(filename: &str) -> BackupKind {
+    let rest = filename.strip_prefix("backup-").unwrap_or_default();
+    BackupKind::ALL
+      .iter()
+      .copied()
+      .find(|kind| {
+        rest
+          .strip_prefix(kind.as_str())
+          .is_some_and(|tail| tail.starts_with('-'))
+      })
+      .unwrap_or(BackupKind::Manual)
+  }
+}
+
+/// Same shape as `BackupInfo` in `backupService.ts`, plus the checksum
+/// from the manifest when there is one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+  pub filename: String,
+  pub timestamp: DateTime<Utc>,
+  pub size: u64,
+  #[serde(rename = "type")]
+  pub kind: BackupKind,
+  pub sha256: Option<String>,
+}
+
+/// Written next to each snapshot as `<file>.manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+  pub filename: String,
+  pub created_at: DateTime<Utc>,
+  #[serde(rename = "type")]
+  pub kind: BackupKind,
+  pub size: u64,
+  pub sha256: String,
+  /// The book the snapshot was taken from.
+  pub source: Option<PathBuf>,
+  /// Encrypted with the book's key as it was when the snapshot was taken.
+  #[serde(default)]
+  pub encrypted: bool,
+}
+
+/// A directory of snapshots of one book.
+#[derive(Debug, Clone)]
+pub struct BackupDir {
+  dir: PathBuf,
+  key: Option<BookKey>,
+}
+
+impl BackupDir {
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    BackupDir {
+      dir: dir.into(),
+      key: None,
+    }
+  }
+
+  /// The key of the (encrypted) book, which snapshots are written and
+  /// verified with.
+  pub fn with_key(self, key: Option<BookKey>) -> Self {
+    BackupDir { key, ..self }
+  }
+
+  /// `backups/` next to the book, where migrations also leave their
+  /// snapshots.
+  pub fn for_book(book: &Path) -> Self {
+    BackupDir::new(
+      book
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups"),
+    )
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Snapshot the database open on `conn`, which must be keyed with this
+  /// directory's key.
+  pub fn create(&self, conn: &Connection, kind: BackupKind) -> Result<BackupInfo, BackupError> {
+    std::fs::create_dir_all(&self.dir)?;
+    let created_at = Utc::now();
+    let filename = format!(
+      "backup-{}-{}.db",
+      kind.as_str(),
+      created_at.format("%Y-%m-%dT%H-%M-%S-%3f")
+    );
+    let path = self.dir.join(&filename);
+    // Written under a temporary name so a half-finished snapshot is never
+    // listed.
+    let partial = self.dir.join(format!("{filename}.partial"));
+    encryption::copy_database(conn, &partial, self.key.as_ref())?;
+    let size = std::fs::metadata(&partial)?.len();
+    let sha256 = sha256_file(&partial)?;
+    let manifest = BackupManifest {
+      filename: filename.clone(),
+      created_at,
+      kind,
+      size,
+      sha256: sha256.clone(),
+      source: conn.path().filter(|p| !p.is_empty()).map(PathBuf::from),
+      encrypted: self.key.is_some(),
+    };
+    std::fs::write(
+      self.manifest_path(&filename),
+      serde_json::to_string_pretty(&manifest).expect("manifests serialize to JSON"),
+    )?;
+    std::fs::rename(&partial, &path)?;
+
+    Ok(BackupInfo {
+      filename,
+      timestamp: created_at,
+      size,
+      kind,
+      sha256: Some(sha256),
+    })
+  }
+
+  /// Every `.db` file in the directory, newest first.
+  pub fn list(&self) -> Result<Vec<BackupInfo>, BackupError> {
+    let entries = match std::fs::read_dir(&self.dir) {
+      Ok(entries) => entries,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(err) => return Err(err.into()),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+      let entry = entry?;
+      let filename = entry.file_name().to_string_lossy().into_owned();
+      if !filename.ends_with(".db") || !entry.file_type()?.is_file() {
+        continue;
+      }
+      let metadata = entry.metadata()?;
+      let manifest = self.manifest(&filename)?;
+      backups.push(BackupInfo {
+        timestamp: match &manifest {
+          Some(manifest) => manifest.created_at,
+          None => metadata.modified()?.into(),
+        },
+        size: metadata.len(),
+        kind: manifest
+          .as_ref()
+          .map_or_else(|| BackupKind::from_filename(&filename), |m| m.kind),
+        sha256: manifest.map(|m| m.sha256),
+        filename,
+      });
+    }
+    backups.sort_by(|a, b| {
+      b.timestamp
+        .cmp(&a.timestamp)
+        .then_with(|| b.filename.cmp(&a.filename))
+    });
+    Ok(backups)
+  }
+
+  /// Delete a snapshot and its manifest.
+  pub fn delete(&self, filename: &str) -> Result<(), BackupError> {
+    let path = self.existing(filename)?;
+    std::fs::remove_file(path)?;
+    match std::fs::remove_file(self.manifest_path(filename)) {
+      Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+      _ => Ok(()),
+    }
+  }
+
+  /// Delete all but the newest `keep` automatic snapshots. Returns how many
+  /// went. Manual, pre-import and pre-reconcile snapshots are the user's to
+  /// delete, and files without a manifest (older backups, pre-migration
+  /// copies) aren't ours to judge.
+  pub fn clean_old(&self, keep: usize) -> Result<usize, BackupError> {
+    let old: Vec<BackupInfo> = self
+      .list()?
+      .into_iter()
+      .filter(|backup| backup.kind == BackupKind::Auto && backup.sha256.is_some())
+      .skip(keep)
+      .collect();
+    for backup in &old {
+      self.delete(&backup.filename)?;
+    }
+    Ok(old.len())
+  }
+
+  /// Check a snapshot against its manifest and `PRAGMA integrity_check`.
+  ///
+  /// An encrypted snapshot is opened with `passphrase` if given, else with
+  /// the book's key, which only fits snapshots taken since the passphrase
+  /// last changed.
+  pub fn verify(
+    &self,
+    filename: &str,
+    passphrase: Option<&str>,
+  ) -> Result<BackupManifest, BackupError> {
+    let path = self.existing(filename)?;
+    let manifest = self
+      .manifest(filename)?
+      .ok_or_else(|| BackupError::MissingManifest(filename.to_string()))?;
+    self.verify_file(&path, &manifest, passphrase)?;
+    Ok(manifest)
+  }
+
+  /// Replace the book at `book` with a snapshot.
+  ///
+  /// Every connection to the book, including the API server's, must be
+  /// closed first. The snapshot is copied next to the book and verified
+  /// there, so what gets swapped in is exactly what was checked (see
+  /// [`swap_in`]).
+  ///
+  /// The restored book is encrypted the way the snapshot was, which may
+  /// not be how the book is now, and with the passphrase it had then (see
+  /// [`BackupDir::verify`]).
+  pub fn restore(
+    &self,
+    filename: &str,
+    book: &Path,
+    passphrase: Option<&str>,
+  ) -> Result<BackupManifest, BackupError> {
+    let manifest = self.verify(filename, passphrase)?;
+    let staged = sibling(book, ".restoring");
+    std::fs::copy(self.dir.join(filename), &staged)?;
+    if let Err(err) = self.verify_file(&staged, &manifest, passphrase) {
+      let _ = std::fs::remove_file(&staged);
+      return Err(err);
+    }
+    swap_in(&staged, book, ".before-restore")?;
+    Ok(manifest)
+  }
+
+  fn verify_file(
+    &self,
+    path: &Path,
+    manifest: &BackupManifest,
+    passphrase: Option<&str>,
+  ) -> Result<(), BackupError> {
+    let filename = &manifest.filename;
+    let actual = sha256_file(path)?;
+    if actual != manifest.sha256 {
+      return Err(BackupError::ChecksumMismatch {
+        filename: filename.clone(),
+        expected: manifest.sha256.clone(),
+        actual,
+      });
+    }
+
+    // The key from the snapshot's own salt, as it was when it was taken.
+    let key = match passphrase {
+      _ if !manifest.encrypted => None,
+      Some(passphrase) => Some(BookKey::for_book(path, passphrase)?),
+      None => self.key.clone(),
+    };
+    let conn = encryption::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY, key.as_ref())?;
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let problems: Vec<String> = stmt
+      .query_map([], |row| row.get(0))?
+      .collect::<rusqlite::Result<_>>()?;
+    if problems != ["ok"] {
+      return Err(BackupError::IntegrityCheck {
+        filename: filename.clone(),
+        details: problems.join("; "),
+      });
+    }
+    Ok(())
+  }
+
+  fn manifest_path(&self, filename: &str) -> PathBuf {
+    self.dir.join(format!("{filename}{MANIFEST_SUFFIX}"))
+  }
+
+  fn manifest(&self, filename: &str) -> Result<Option<BackupManifest>, BackupError> {
+    match std::fs::read_to_string(self.manifest_path(filename)) {
+      Ok(json) => Ok(serde_json::from_str(&json).ok()),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  /// The path of `filename`, which must be a bare file name in this
+  /// directory.
+  fn existing(&self, filename: &str) -> Result<PathBuf, BackupError> {
+    let bare = Path::new(filename)
+      .file_name()
+      .and_then(|name| name.to_str())
+      == Some(filename);
+    if !bare || !filename.ends_with(".db") {
+      return Err(BackupError::InvalidFilename(filename.to_string()));
+    }
+    let path = self.dir.join(filename);
+    if !path.is_file() {
+      return Err(BackupError::NotFound(filename.to_string()));
+    }
+    Ok(path)
+  }
+}
+
+/// Changes whenever anything commits to the database, through this
+/// connection or another, so a scheduler can tell whether a new snapshot is
+/// worth taking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeMarker {
+  data_version: i64,
+  total_changes: u64,
+}
+
+impl ChangeMarker {
+  pub fn of(conn: &Connection) -> rusqlite::Result<Self> {
+    Ok(ChangeMarker {
+      data_version: conn.query_row("PRAGMA data_version", [], |row| row.get(0))?,
+      total_changes: conn.total_changes(),
+    })
+  }
+}
+
+/// Hex SHA-256 of a file's contents.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+  let mut hasher = Sha256::new();
+  io::copy(&mut File::open(path)?, &mut hasher)?;
+  Ok(
+    hasher
+      .finalize()
+      .iter()
+      .map(|byte| format!("{byte:02x}"))
+      .collect(),
+  )
+}
+
+/// Replace `book` with `staged`. The old book and its WAL are moved aside
+/// to `<book><safety_suffix>` until the swap succeeds, and put back if it
+/// doesn't.
+pub(crate) fn swap_in(staged: &Path, book: &Path, safety_suffix: &str) -> io::Result<()> {
+  let safety = sibling(book, safety_suffix);
+  let companions = ["-wal", "-shm"];
+  std::fs::rename(book, &safety)?;
+  for suffix in companions {
+    rename_if_exists(&sibling(book, suffix), &sibling(&safety, suffix))?;
+  }
+  if let Err(err) = std::fs::rename(staged, book) {
+    let _ = std::fs::rename(&safety, book);
+    for suffix in companions {
+      let _ = rename_if_exists(&sibling(&safety, suffix), &sibling(book, suffix));
+    }
+    return Err(err);
+  }
+  for path in [
+    safety.clone(),
+    sibling(&safety, "-wal"),
+    sibling(&safety, "-shm"),
+  ] {
+    let _ = std::fs::remove_file(path);
+  }
+  Ok(())
+}
+
+/// `path` with `suffix` appended to its file name.
+pub(crate) fn sibling(path: &Path, suffix: &str) -> PathBuf {
+  let mut name = path.as_os_str().to_owned();
+  name.push(suffix);
+  PathBuf::from(name)
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+  match std::fs::rename(from, to) {
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+    result => result,
+  }
+}
+
+/// When the desktop shell takes snapshots on its own. Stored in the
+/// `backupSchedule` setting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupSchedule {
+  pub on_startup: bool,
+  /// Minutes between checks for changes since the last snapshot; 0 turns
+  /// periodic snapshots off.
+  pub interval_minutes: u32,
+  pub on_exit: bool,
+  /// Automatic snapshots to keep when rotating.
+  pub keep: usize,
+}
+
+impl Default for BackupSchedule {
+  fn default() -> Self {
+    BackupSchedule {
+      on_startup: true,
+      interval_minutes: 60,
+      on_exit: true,
+      keep: 10,
+    }
+  }
+}
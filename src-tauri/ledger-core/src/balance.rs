@@ -0,0 +1,65 @@
+//! Account balances, mirroring `AccountService.getAccountBalance` in
+//! `src/lib/services/accountService.ts`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::{Account, Posting, Transaction};
+use crate::money::Money;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BalanceOptions {
+  /// Only include transactions dated on or before this instant.
+  pub up_to: Option<DateTime<Utc>>,
+  pub cleared_only: bool,
+  pub reconciled_only: bool,
+}
+
+impl BalanceOptions {
+  fn includes(&self, transaction: &Transaction, posting: &Posting) -> bool {
+    !transaction.is_void()
+      && self.up_to.map_or(true, |up_to| transaction.date <= up_to)
+      && (!self.cleared_only || posting.cleared)
+      && (!self.reconciled_only || posting.reconciled)
+  }
+}
+
+/// Balance of one account: opening balance plus every non-void posting.
+pub fn account_balance(
+  account: &Account,
+  transactions: &[Transaction],
+  options: BalanceOptions,
+) -> Money {
+  let posted: Money = transactions
+    .iter()
+    .flat_map(|t| t.postings.iter().map(move |p| (t, p)))
+    .filter(|(t, p)| p.account_id == account.id && options.includes(t, p))
+    .map(|(_, p)| p.amount)
+    .sum();
+  account.opening_balance + posted
+}
+
+/// Balances of every account in one pass over the transactions.
+pub fn account_balances(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  options: BalanceOptions,
+) -> HashMap<String, Money> {
+  let mut balances: HashMap<String, Money> = accounts
+    .iter()
+    .map(|a| (a.id.clone(), a.opening_balance))
+    .collect();
+
+  for transaction in transactions {
+    for posting in &transaction.postings {
+      if !options.includes(transaction, posting) {
+        continue;
+      }
+      if let Some(balance) = balances.get_mut(&posting.account_id) {
+        *balance += posting.amount;
+      }
+    }
+  }
+  balances
+}
@@ -0,0 +1,410 @@
+//! The registry of a user's books, mirroring `BookManager` in
+//! `bookManager.ts`, which kept it in `localStorage`.
+//!
+//! The registry is a JSON file rewritten atomically on every change.
+//! Database paths are stored as given; relative ones (the `books/<id>/`
+//! layout the API server's `switchDatabase` expects) resolve against the
+//! registry's books root.
+
+use std::cmp::Reverse;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backup::sibling;
+use crate::migrate;
+
+const DEFAULT_DB_DIR: &str = "books";
+const DATABASE_FILE: &str = "ledger.ledgerhound";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error("books registry {path} is unreadable: {source}")]
+  Corrupt {
+    path: PathBuf,
+    #[source]
+    source: serde_json::Error,
+  },
+  #[error("Book not found")]
+  NotFound(String),
+  #[error("Invalid book list format: {0}")]
+  InvalidBookList(String),
+  #[error("{0} is not a Ledgerhound book")]
+  NotABook(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateFormat {
+  #[serde(rename = "DD/MM/YYYY")]
+  DayMonthYear,
+  #[serde(rename = "MM/DD/YYYY")]
+  MonthDayYear,
+  #[serde(rename = "YYYY-MM-DD")]
+  Iso,
+}
+
+/// Same shape as `Book` in `src/types/book.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Book {
+  pub id: String,
+  pub name: String,
+  pub owner_name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  pub database_path: PathBuf,
+  pub backup_path: PathBuf,
+  /// `MM-DD`, e.g. `07-01`.
+  pub fiscal_year_start: String,
+  pub currency: String,
+  pub date_format: DateFormat,
+  pub created_at: DateTime<Utc>,
+  pub last_accessed_at: DateTime<Utc>,
+  #[serde(default)]
+  pub is_active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBookData {
+  pub name: String,
+  pub owner_name: String,
+  pub description: Option<String>,
+  pub database_path: Option<PathBuf>,
+  pub backup_path: Option<PathBuf>,
+  pub fiscal_year_start: String,
+  pub currency: String,
+  pub date_format: DateFormat,
+}
+
+/// The fields `updateBook` may change; `None` leaves a field alone.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookUpdate {
+  pub name: Option<String>,
+  pub owner_name: Option<String>,
+  pub description: Option<String>,
+  pub database_path: Option<PathBuf>,
+  pub backup_path: Option<PathBuf>,
+  pub fiscal_year_start: Option<String>,
+  pub currency: Option<String>,
+  pub date_format: Option<DateFormat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookSummary {
+  pub id: String,
+  pub name: String,
+  pub owner_name: String,
+  pub last_accessed_at: DateTime<Utc>,
+}
+
+/// Whether a book's database is where the registry says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BookFileState {
+  Available,
+  /// Nothing at the path any more: moved, renamed or deleted.
+  Missing,
+  /// Something is there, but it isn't a book.
+  NotABook,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookFileStatus {
+  pub id: String,
+  pub database_path: PathBuf,
+  pub state: BookFileState,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryFile {
+  active_book_id: Option<String>,
+  books: Vec<Book>,
+}
+
+/// The books registry at `path`.
+#[derive(Debug)]
+pub struct BookRegistry {
+  path: PathBuf,
+  books_root: PathBuf,
+  file: RegistryFile,
+}
+
+impl BookRegistry {
+  /// Load the registry at `path`; a missing file is an empty registry.
+  /// Relative database paths resolve against `books_root`.
+  pub fn load(
+    path: impl Into<PathBuf>,
+    books_root: impl Into<PathBuf>,
+  ) -> Result<Self, RegistryError> {
+    let path = path.into();
+    let file = match std::fs::read_to_string(&path) {
+      Ok(json) => serde_json::from_str(&json).map_err(|source| RegistryError::Corrupt {
+        path: path.clone(),
+        source,
+      })?,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => RegistryFile::default(),
+      Err(err) => return Err(err.into()),
+    };
+    Ok(BookRegistry {
+      path,
+      books_root: books_root.into(),
+      file,
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn all_books(&self) -> Vec<Book> {
+    self
+      .file
+      .books
+      .iter()
+      .map(|b| self.with_active(b))
+      .collect()
+  }
+
+  pub fn book(&self, id: &str) -> Option<Book> {
+    self
+      .file
+      .books
+      .iter()
+      .find(|b| b.id == id)
+      .map(|b| self.with_active(b))
+  }
+
+  pub fn active_book(&self) -> Option<Book> {
+    self.book(self.file.active_book_id.as_deref()?)
+  }
+
+  pub fn is_first_run(&self) -> bool {
+    self.file.books.is_empty()
+  }
+
+  /// Add a book, under `books/<id>/` unless `data` says where.
+  pub fn create_book(&mut self, data: CreateBookData) -> Result<Book, RegistryError> {
+    let id = generate_id();
+    let default_dir = Path::new(DEFAULT_DB_DIR).join(&id);
+    let now = Utc::now();
+    let book = Book {
+      database_path: data
+        .database_path
+        .unwrap_or_else(|| default_dir.join(DATABASE_FILE)),
+      backup_path: data
+        .backup_path
+        .unwrap_or_else(|| default_dir.join("backups")),
+      id,
+      name: data.name,
+      owner_name: data.owner_name,
+      description: data.description,
+      fiscal_year_start: data.fiscal_year_start,
+      currency: data.currency,
+      date_format: data.date_format,
+      created_at: now,
+      last_accessed_at: now,
+      is_active: false,
+    };
+    self.file.books.push(book.clone());
+    self.save()?;
+    Ok(book)
+  }
+
+  pub fn update_book(&mut self, id: &str, update: BookUpdate) -> Result<Book, RegistryError> {
+    let book = self.find_mut(id)?;
+    if let Some(name) = update.name {
+      book.name = name;
+    }
+    if let Some(owner_name) = update.owner_name {
+      book.owner_name = owner_name;
+    }
+    if let Some(description) = update.description {
+      book.description = Some(description);
+    }
+    if let Some(database_path) = update.database_path {
+      book.database_path = database_path;
+    }
+    if let Some(backup_path) = update.backup_path {
+      book.backup_path = backup_path;
+    }
+    if let Some(fiscal_year_start) = update.fiscal_year_start {
+      book.fiscal_year_start = fiscal_year_start;
+    }
+    if let Some(currency) = update.currency {
+      book.currency = currency;
+    }
+    if let Some(date_format) = update.date_format {
+      book.date_format = date_format;
+    }
+    self.save()?;
+    Ok(self.book(id).expect("book was just updated"))
+  }
+
+  /// Remove a book from the registry. Its files are left alone.
+  pub fn delete_book(&mut self, id: &str) -> Result<(), RegistryError> {
+    let before = self.file.books.len();
+    self.file.books.retain(|b| b.id != id);
+    if self.file.books.len() == before {
+      return Err(RegistryError::NotFound(id.to_string()));
+    }
+    if self.file.active_book_id.as_deref() == Some(id) {
+      self.file.active_book_id = None;
+    }
+    self.save()
+  }
+
+  /// Make `id` the active book and note that it was accessed.
+  pub fn set_active_book(&mut self, id: &str) -> Result<Book, RegistryError> {
+    self.find_mut(id)?.last_accessed_at = Utc::now();
+    self.file.active_book_id = Some(id.to_string());
+    self.save()?;
+    Ok(self.book(id).expect("book was just activated"))
+  }
+
+  pub fn book_summaries(&self) -> Vec<BookSummary> {
+    self
+      .file
+      .books
+      .iter()
+      .map(|b| BookSummary {
+        id: b.id.clone(),
+        name: b.name.clone(),
+        owner_name: b.owner_name.clone(),
+        last_accessed_at: b.last_accessed_at,
+      })
+      .collect()
+  }
+
+  /// The most recently accessed books, newest first.
+  pub fn recent_books(&self, limit: usize) -> Vec<BookSummary> {
+    let mut summaries = self.book_summaries();
+    summaries.sort_by_key(|s| Reverse(s.last_accessed_at));
+    summaries.truncate(limit);
+    summaries
+  }
+
+  /// The books as a JSON array, in the format `exportBookList` wrote.
+  pub fn export_book_list(&self) -> String {
+    serde_json::to_string_pretty(&self.all_books()).expect("books serialize to JSON")
+  }
+
+  /// Replace the registry's books with an exported list. The active book
+  /// is kept if it's still in the list.
+  pub fn import_book_list(&mut self, json: &str) -> Result<(), RegistryError> {
+    let books: Vec<Book> =
+      serde_json::from_str(json).map_err(|err| RegistryError::InvalidBookList(err.to_string()))?;
+    let active = self
+      .file
+      .active_book_id
+      .take()
+      .filter(|id| books.iter().any(|b| &b.id == id));
+    self.file = RegistryFile {
+      active_book_id: active,
+      books,
+    };
+    self.save()
+  }
+
+  /// Where a book's database actually is, resolving relative paths.
+  pub fn resolve(&self, path: &Path) -> PathBuf {
+    self.books_root.join(path)
+  }
+
+  /// Check every book's database file, so the launcher can offer to
+  /// relocate the ones that have gone.
+  pub fn file_statuses(&self) -> Vec<BookFileStatus> {
+    self
+      .file
+      .books
+      .iter()
+      .map(|b| BookFileStatus {
+        id: b.id.clone(),
+        database_path: b.database_path.clone(),
+        state: file_state(&self.resolve(&b.database_path)),
+      })
+      .collect()
+  }
+
+  /// Point a book at its database's new location, which must hold a book.
+  /// A backup directory that was beside the old file moves with it.
+  pub fn relocate_book(&mut self, id: &str, database_path: PathBuf) -> Result<Book, RegistryError> {
+    if file_state(&self.resolve(&database_path)) != BookFileState::Available {
+      return Err(RegistryError::NotABook(database_path));
+    }
+    let book = self.find_mut(id)?;
+    let old_dir = book.database_path.parent().map(Path::to_path_buf);
+    if old_dir.is_some() && book.backup_path.parent() == old_dir.as_deref() {
+      if let (Some(dir), Some(name)) = (database_path.parent(), book.backup_path.file_name()) {
+        book.backup_path = dir.join(name);
+      }
+    }
+    book.database_path = database_path;
+    self.save()?;
+    Ok(self.book(id).expect("book was just relocated"))
+  }
+
+  fn find_mut(&mut self, id: &str) -> Result<&mut Book, RegistryError> {
+    self
+      .file
+      .books
+      .iter_mut()
+      .find(|b| b.id == id)
+      .ok_or_else(|| RegistryError::NotFound(id.to_string()))
+  }
+
+  fn with_active(&self, book: &Book) -> Book {
+    Book {
+      is_active: self.file.active_book_id.as_deref() == Some(&book.id),
+      ..book.clone()
+    }
+  }
+
+  /// Write to a temporary file, sync it and rename it over the registry, so
+  /// a crash leaves either the old registry or the new one.
+  fn save(&self) -> Result<(), RegistryError> {
+    if let Some(dir) = self.path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    let temp = sibling(&self.path, ".tmp");
+    let json = serde_json::to_string_pretty(&self.file).expect("registry serializes to JSON");
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp, &self.path)?;
+    Ok(())
+  }
+}
+
+/// See [`migrate::is_book`]; an encrypted book can't be looked into
+/// without its key, so one counts as long as it looks encrypted.
+fn file_state(path: &Path) -> BookFileState {
+  match std::fs::metadata(path) {
+    Err(_) => BookFileState::Missing,
+    Ok(_) if matches!(migrate::is_book(path), Ok(true)) => BookFileState::Available,
+    Ok(_) => BookFileState::NotABook,
+  }
+}
+
+/// `book_<millis>_<9 base-36 chars>`, like `generateId`.
+fn generate_id() -> String {
+  const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+  let mut random = uuid::Uuid::new_v4().as_u128();
+  let suffix: String = (0..9)
+    .map(|_| {
+      let digit = DIGITS[(random % 36) as usize] as char;
+      random /= 36;
+      digit
+    })
+    .collect();
+  format!("book_{}_{suffix}", Utc::now().timestamp_millis())
+}
@@ -0,0 +1,210 @@
+//! `accounts` table, mirroring `AccountService` in `accountService.ts`.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{
+  datetime, enum_column, optional_enum_column, Database, DbError, DbResult, SqlDateTime,
+};
+use crate::balance::BalanceOptions;
+use crate::model::{Account, AccountType};
+use crate::money::Money;
+
+const COLUMNS: &str = "id, name, full_path, type, kind, parent_id, level, subtype, is_real,
+  is_business_default, default_has_gst, opening_balance, opening_date, currency, ato_label,
+  archived, sort_order, created_at, updated_at";
+
+/// Standard accounting order, as `AccountService.TYPE_ORDER`.
+fn type_order(account_type: AccountType) -> u8 {
+  match account_type {
+    AccountType::Asset => 0,
+    AccountType::Liability => 1,
+    AccountType::Equity => 2,
+    AccountType::Income => 3,
+    AccountType::Expense => 4,
+  }
+}
+
+impl Database {
+  fn account_from_row(&self, row: &Row) -> rusqlite::Result<Account> {
+    let currency: String = row.get("currency")?;
+    Ok(Account {
+      id: row.get("id")?,
+      name: row.get("name")?,
+      full_path: row.get("full_path")?,
+      account_type: enum_column(row, "type")?,
+      kind: enum_column(row, "kind")?,
+      parent_id: row.get("parent_id")?,
+      level: row.get("level")?,
+      subtype: optional_enum_column(row, "subtype")?,
+      is_real: row.get("is_real")?,
+      is_business_default: row.get("is_business_default")?,
+      default_has_gst: row.get("default_has_gst")?,
+      opening_balance: self.money(row, "opening_balance")?,
+      opening_date: datetime(row, "opening_date")?,
+      currency: currency.parse().unwrap_or_default(),
+      ato_label: row.get("ato_label")?,
+      archived: row.get("archived")?,
+      sort_order: row.get("sort_order")?,
+      created_at: datetime(row, "created_at")?,
+      updated_at: datetime(row, "updated_at")?,
+    })
+  }
+
+  /// Accounts in accounting order (type, then sort order, then name).
+  pub fn accounts(&self, include_archived: bool) -> DbResult<Vec<Account>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM accounts WHERE ?1 OR archived = 0 ORDER BY sort_order, name"
+    ))?;
+    let mut accounts = stmt
+      .query_map([include_archived], |row| self.account_from_row(row))?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    accounts.sort_by_key(|a| type_order(a.account_type));
+    Ok(accounts)
+  }
+
+  /// Every account (archived included) keyed by id, as validation expects.
+  pub fn accounts_by_id(&self) -> DbResult<HashMap<String, Account>> {
+    Ok(
+      self
+        .accounts(true)?
+        .into_iter()
+        .map(|a| (a.id.clone(), a))
+        .collect(),
+    )
+  }
+
+  pub fn account(&self, id: &str) -> DbResult<Account> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM accounts WHERE id = ?1"),
+        [id],
+        |row| self.account_from_row(row),
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Account",
+        id: id.to_string(),
+      })
+  }
+
+  pub fn insert_account(&self, account: &Account) -> DbResult<()> {
+    self.conn.execute(
+      &format!("INSERT INTO accounts ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)"),
+      params![
+        account.id,
+        account.name,
+        account.full_path,
+        account.account_type.as_str(),
+        account.kind.as_str(),
+        account.parent_id,
+        account.level,
+        account.subtype.map(|s| s.as_str()),
+        account.is_real,
+        account.is_business_default,
+        account.default_has_gst,
+        self.encode(account.opening_balance),
+        SqlDateTime(account.opening_date),
+        account.currency.to_string(),
+        account.ato_label,
+        account.archived,
+        account.sort_order,
+        SqlDateTime(account.created_at),
+        SqlDateTime(account.updated_at),
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// Overwrite every column except `id` and `created_at`; bumps `updated_at`.
+  pub fn update_account(&self, account: &Account) -> DbResult<()> {
+    let changed = self.conn.execute(
+      "UPDATE accounts SET name = ?2, full_path = ?3, type = ?4, kind = ?5, parent_id = ?6,
+         level = ?7, subtype = ?8, is_real = ?9, is_business_default = ?10,
+         default_has_gst = ?11, opening_balance = ?12, opening_date = ?13, currency = ?14,
+         ato_label = ?15, archived = ?16, sort_order = ?17, updated_at = ?18
+       WHERE id = ?1",
+      params![
+        account.id,
+        account.name,
+        account.full_path,
+        account.account_type.as_str(),
+        account.kind.as_str(),
+        account.parent_id,
+        account.level,
+        account.subtype.map(|s| s.as_str()),
+        account.is_real,
+        account.is_business_default,
+        account.default_has_gst,
+        self.encode(account.opening_balance),
+        SqlDateTime(account.opening_date),
+        account.currency.to_string(),
+        account.ato_label,
+        account.archived,
+        account.sort_order,
+        super::now(),
+      ],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Account",
+        id: account.id.clone(),
+      });
+    }
+    Ok(())
+  }
+
+  /// Balance of one account, computed in SQL with the same rules as
+  /// [`crate::balance::account_balance`].
+  pub fn account_balance(&self, id: &str, options: BalanceOptions) -> DbResult<Money> {
+    let account = self.account(id)?;
+    let posted: Option<f64> = self.conn.query_row(
+      "SELECT SUM(p.amount) FROM postings p
+       JOIN transactions t ON t.id = p.transaction_id
+       WHERE p.account_id = ?1 AND t.status = 'NORMAL'
+         AND (?2 IS NULL OR t.date <= ?2)
+         AND (?3 = 0 OR p.cleared = 1)
+         AND (?4 = 0 OR p.reconciled = 1)",
+      params![
+        id,
+        options.up_to.map(SqlDateTime),
+        options.cleared_only,
+        options.reconciled_only,
+      ],
+      |row| row.get(0),
+    )?;
+    Ok(account.opening_balance + posted.map_or(Money::ZERO, |raw| self.money.decode(raw)))
+  }
+
+  /// Balances of every account (archived included), keyed by id.
+  pub fn account_balances(&self, options: BalanceOptions) -> DbResult<HashMap<String, Money>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT a.id, a.opening_balance, (
+         SELECT SUM(p.amount) FROM postings p
+         JOIN transactions t ON t.id = p.transaction_id
+         WHERE p.account_id = a.id AND t.status = 'NORMAL'
+           AND (?1 IS NULL OR t.date <= ?1)
+           AND (?2 = 0 OR p.cleared = 1)
+           AND (?3 = 0 OR p.reconciled = 1)
+       )
+       FROM accounts a",
+    )?;
+    let rows = stmt.query_map(
+      params![
+        options.up_to.map(SqlDateTime),
+        options.cleared_only,
+        options.reconciled_only,
+      ],
+      |row| {
+        let opening = self.money.decode(row.get(1)?);
+        let posted = row
+          .get::<_, Option<f64>>(2)?
+          .map_or(Money::ZERO, |raw| self.money.decode(raw));
+        Ok((row.get::<_, String>(0)?, opening + posted))
+      },
+    )?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+  }
+}
@@ -0,0 +1,165 @@
+//! `recurring_bills` table, mirroring `RecurringBillService` in
+//! `recurringBillService.ts`.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+
+use super::{datetime, enum_column, optional_datetime, Database, DbError, DbResult, SqlDateTime};
+use crate::model::RecurringBill;
+
+/// `GET /api/recurring-bills/count`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UpcomingBillCount {
+  pub upcoming: usize,
+  pub overdue: usize,
+}
+
+const COLUMNS: &str = "id, name, payee, expected_amount, frequency, due_day, start_date,
+  category_account_id, pay_from_account_id, status, last_paid_date, next_due_date, notes,
+  created_at, updated_at";
+
+impl Database {
+  fn bill_from_row(&self, row: &Row) -> rusqlite::Result<RecurringBill> {
+    Ok(RecurringBill {
+      id: row.get("id")?,
+      name: row.get("name")?,
+      payee: row.get("payee")?,
+      expected_amount: self.money(row, "expected_amount")?,
+      frequency: enum_column(row, "frequency")?,
+      due_day: row.get("due_day")?,
+      start_date: datetime(row, "start_date")?,
+      category_account_id: row.get("category_account_id")?,
+      pay_from_account_id: row.get("pay_from_account_id")?,
+      status: enum_column(row, "status")?,
+      last_paid_date: optional_datetime(row, "last_paid_date")?,
+      next_due_date: datetime(row, "next_due_date")?,
+      notes: row.get("notes")?,
+      created_at: datetime(row, "created_at")?,
+      updated_at: datetime(row, "updated_at")?,
+    })
+  }
+
+  /// Bills by next due date, then name.
+  pub fn recurring_bills(&self) -> DbResult<Vec<RecurringBill>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM recurring_bills ORDER BY next_due_date, name"
+    ))?;
+    let rows = stmt.query_map([], |row| self.bill_from_row(row))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+  }
+
+  pub fn recurring_bill(&self, id: &str) -> DbResult<RecurringBill> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM recurring_bills WHERE id = ?1"),
+        [id],
+        |row| self.bill_from_row(row),
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Recurring bill",
+        id: id.to_string(),
+      })
+  }
+
+  pub fn insert_recurring_bill(&self, bill: &RecurringBill) -> DbResult<()> {
+    self.conn.execute(
+      &format!(
+        "INSERT INTO recurring_bills ({COLUMNS})
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"
+      ),
+      params![
+        bill.id,
+        bill.name,
+        bill.payee,
+        self.encode(bill.expected_amount),
+        bill.frequency.as_str(),
+        bill.due_day,
+        SqlDateTime(bill.start_date),
+        bill.category_account_id,
+        bill.pay_from_account_id,
+        bill.status.as_str(),
+        bill.last_paid_date.map(SqlDateTime),
+        SqlDateTime(bill.next_due_date),
+        bill.notes,
+        SqlDateTime(bill.created_at),
+        SqlDateTime(bill.updated_at),
+      ],
+    )?;
+    Ok(())
+  }
+
+  pub fn update_recurring_bill(&self, bill: &RecurringBill) -> DbResult<()> {
+    let changed = self.conn.execute(
+      "UPDATE recurring_bills SET name = ?2, payee = ?3, expected_amount = ?4, frequency = ?5,
+         due_day = ?6, start_date = ?7, category_account_id = ?8, pay_from_account_id = ?9,
+         status = ?10, last_paid_date = ?11, next_due_date = ?12, notes = ?13, updated_at = ?14
+       WHERE id = ?1",
+      params![
+        bill.id,
+        bill.name,
+        bill.payee,
+        self.encode(bill.expected_amount),
+        bill.frequency.as_str(),
+        bill.due_day,
+        SqlDateTime(bill.start_date),
+        bill.category_account_id,
+        bill.pay_from_account_id,
+        bill.status.as_str(),
+        bill.last_paid_date.map(SqlDateTime),
+        SqlDateTime(bill.next_due_date),
+        bill.notes,
+        super::now(),
+      ],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Recurring bill",
+        id: bill.id.clone(),
+      });
+    }
+    Ok(())
+  }
+
+  pub fn delete_recurring_bill(&self, id: &str) -> DbResult<()> {
+    self
+      .conn
+      .execute("DELETE FROM recurring_bills WHERE id = ?1", [id])?;
+    Ok(())
+  }
+
+  /// Active bills due on or before `cutoff`, soonest first.
+  pub fn bills_due_by(&self, cutoff: DateTime<Utc>) -> DbResult<Vec<RecurringBill>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM recurring_bills
+       WHERE status = 'ACTIVE' AND next_due_date <= ?1
+       ORDER BY next_due_date"
+    ))?;
+    let rows = stmt.query_map([SqlDateTime(cutoff)], |row| self.bill_from_row(row))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+  }
+
+  /// Active bills due within `days_ahead` days of `today`, split by whether
+  /// they're already overdue.
+  pub fn upcoming_bill_count(
+    &self,
+    today: NaiveDate,
+    days_ahead: u64,
+  ) -> DbResult<UpcomingBillCount> {
+    let cutoff = (today + Days::new(days_ahead))
+      .and_hms_opt(0, 0, 0)
+      .expect("midnight exists")
+      .and_utc();
+    let bills = self.bills_due_by(cutoff)?;
+    let overdue = bills
+      .iter()
+      .filter(|bill| bill.next_due_date.date_naive() < today)
+      .count();
+    Ok(UpcomingBillCount {
+      upcoming: bills.len() - overdue,
+      overdue,
+    })
+  }
+}
@@ -0,0 +1,247 @@
+//! `import_batches` table, mirroring the batch helpers in `importService.ts`,
+//! plus the mapping templates and duplicate check imports use.
+
+use chrono::{Duration, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::{datetime, new_id, Database, DbError, DbResult, SqlDateTime};
+use crate::import::{ColumnMapping, ImportPreview, MappingTemplate, StatementLine};
+use crate::model::{Account, AccountKind, AccountType, ImportBatch};
+use crate::money::{Currency, Money};
+use crate::rules::RuleContext;
+
+const COLUMNS: &str = "id, source_account_id, source_name, mapping_json, created_at";
+
+/// Where imported lines go when nothing says otherwise.
+const UNCATEGORIZED: &str = "Uncategorized";
+
+const TEMPLATE_KEY_PREFIX: &str = "import_mapping_template_";
+
+/// How far either side of a statement line's date a duplicate may be.
+const DUPLICATE_WINDOW_DAYS: i64 = 3;
+
+/// A mapping template's setting value.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateValue {
+  mapping: ColumnMapping,
+  account_id: Option<String>,
+}
+
+fn batch_from_row(row: &Row) -> rusqlite::Result<ImportBatch> {
+  Ok(ImportBatch {
+    id: row.get("id")?,
+    source_account_id: row.get("source_account_id")?,
+    source_name: row.get("source_name")?,
+    mapping_json: row.get("mapping_json")?,
+    created_at: datetime(row, "created_at")?,
+  })
+}
+
+impl Database {
+  /// Batches, newest first, optionally limited to one source account.
+  pub fn import_batches(&self, source_account_id: Option<&str>) -> DbResult<Vec<ImportBatch>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM import_batches
+       WHERE ?1 IS NULL OR source_account_id = ?1
+       ORDER BY created_at DESC"
+    ))?;
+    let batches = stmt.query_map([source_account_id], batch_from_row)?;
+    Ok(batches.collect::<rusqlite::Result<_>>()?)
+  }
+
+  pub fn import_batch(&self, id: &str) -> DbResult<ImportBatch> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM import_batches WHERE id = ?1"),
+        [id],
+        batch_from_row,
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Import batch",
+        id: id.to_string(),
+      })
+  }
+
+  pub fn insert_import_batch(&self, batch: &ImportBatch) -> DbResult<()> {
+    self.conn.execute(
+      &format!("INSERT INTO import_batches ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5)"),
+      params![
+        batch.id,
+        batch.source_account_id,
+        batch.source_name,
+        batch.mapping_json,
+        SqlDateTime(batch.created_at),
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// Delete a batch. Its transactions stay, with `import_batch_id` set to
+  /// NULL by the foreign key.
+  pub fn delete_import_batch(&self, id: &str) -> DbResult<()> {
+    self
+      .conn
+      .execute("DELETE FROM import_batches WHERE id = ?1", [id])?;
+    Ok(())
+  }
+
+  /// Save a CSV column mapping under `name`, replacing any template whose
+  /// name only differs in case or spacing.
+  pub fn save_import_mapping_template(
+    &self,
+    name: &str,
+    mapping: &ColumnMapping,
+    account_id: Option<&str>,
+  ) -> DbResult<()> {
+    let slug = name
+      .to_lowercase()
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join("_");
+    self.set_setting_json(
+      &format!("{TEMPLATE_KEY_PREFIX}{slug}"),
+      &TemplateValue {
+        mapping: mapping.clone(),
+        account_id: account_id.map(String::from),
+      },
+    )
+  }
+
+  /// Saved templates, only those for `account_id` if one is given. Names
+  /// come back lower case, as they're stored.
+  pub fn import_mapping_templates(
+    &self,
+    account_id: Option<&str>,
+  ) -> DbResult<Vec<MappingTemplate>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT key, value FROM settings WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key",
+    )?;
+    let rows = stmt.query_map([TEMPLATE_KEY_PREFIX], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut templates = Vec::new();
+    for row in rows {
+      let (key, value) = row?;
+      // Templates that no longer parse are skipped rather than failing the list.
+      let Ok(value) = serde_json::from_str::<TemplateValue>(&value) else {
+        continue;
+      };
+      if account_id.is_some_and(|id| value.account_id.as_deref() != Some(id)) {
+        continue;
+      }
+      templates.push(MappingTemplate {
+        name: key[TEMPLATE_KEY_PREFIX.len()..].replace('_', " "),
+        mapping: value.mapping,
+        account_id: value.account_id,
+      });
+    }
+    Ok(templates)
+  }
+
+  /// Whether `account_id` seems to have `line` already: a transaction with
+  /// the line's external id, or one within three days for the same amount
+  /// whose payee contains the line's (or that a rule renamed from it).
+  pub fn is_duplicate_import(&self, account_id: &str, line: &StatementLine) -> DbResult<bool> {
+    if let Some(external_id) = &line.external_id {
+      let mut stmt = self.conn.prepare_cached(
+        "SELECT EXISTS (
+           SELECT 1 FROM transactions t JOIN postings p ON p.transaction_id = t.id
+           WHERE t.external_id = ?1 AND p.account_id = ?2
+         )",
+      )?;
+      if stmt.query_row(params![external_id, account_id], |row| row.get(0))? {
+        return Ok(true);
+      }
+    }
+
+    let window = Duration::days(DUPLICATE_WINDOW_DAYS);
+    let date = line.date_time();
+    // Half a cent either side, in whatever units the book stores.
+    let tolerance = self.encode(Money::from_cents(1)) / 2.0;
+    let mut stmt = self.conn.prepare_cached(
+      "SELECT EXISTS (
+         SELECT 1 FROM transactions t JOIN postings p ON p.transaction_id = t.id
+         WHERE p.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3
+           AND abs(p.amount - ?4) < ?5
+           AND (instr(lower(t.payee), lower(?6)) > 0
+             OR CASE WHEN json_valid(t.metadata)
+                  THEN json_extract(t.metadata, '$.originalDescription') = ?6 END)
+       )",
+    )?;
+    Ok(stmt.query_row(
+      params![
+        account_id,
+        SqlDateTime(date - window),
+        SqlDateTime(date + window),
+        self.encode(line.amount),
+        tolerance,
+        line.payee,
+      ],
+      |row| row.get(0),
+    )?)
+  }
+
+  /// Check each line for duplicates in `account_id` and match its payee
+  /// against the memorized rules that apply on import.
+  pub fn preview_import(
+    &self,
+    account_id: &str,
+    lines: Vec<StatementLine>,
+  ) -> DbResult<Vec<ImportPreview>> {
+    let rules = self.rule_matcher()?;
+    lines
+      .into_iter()
+      .map(|parsed| {
+        Ok(ImportPreview {
+          is_duplicate: self.is_duplicate_import(account_id, &parsed)?,
+          matched_rule: rules.find(&parsed.payee, RuleContext::Import).cloned(),
+          parsed,
+        })
+      })
+      .collect()
+  }
+
+  /// The "Uncategorized" expense category, created if the book doesn't have
+  /// one yet, as `importTransactions` does.
+  pub(crate) fn uncategorized_account_id(&self) -> DbResult<String> {
+    let existing = self
+      .conn
+      .query_row(
+        "SELECT id FROM accounts WHERE name = ?1 AND type = 'EXPENSE' LIMIT 1",
+        [UNCATEGORIZED],
+        |row| row.get(0),
+      )
+      .optional()?;
+    if let Some(id) = existing {
+      return Ok(id);
+    }
+    let now = Utc::now();
+    let account = Account {
+      id: new_id(),
+      name: UNCATEGORIZED.into(),
+      full_path: Some(format!("Expense/{UNCATEGORIZED}")),
+      account_type: AccountType::Expense,
+      kind: AccountKind::Category,
+      parent_id: None,
+      level: 1,
+      subtype: None,
+      is_real: false,
+      is_business_default: false,
+      default_has_gst: true,
+      opening_balance: Money::ZERO,
+      opening_date: now,
+      currency: Currency::default(),
+      ato_label: None,
+      archived: false,
+      sort_order: 0,
+      created_at: now,
+      updated_at: now,
+    };
+    self.insert_account(&account)?;
+    Ok(account.id)
+  }
+}
@@ -0,0 +1,204 @@
+//! Direct SQLite access to a Ledgerhound book.
+//!
+//! Reads and writes the tables Prisma manages, using the column names from
+//! `prisma/schema.prisma` and the value encodings Prisma uses on SQLite:
+//! enums as their names, booleans as 0/1 and `DateTime` as Unix epoch
+//! milliseconds. Money columns are decoded according to the book's
+//! [`MoneyStorage`].
+
+mod accounts;
+mod bills;
+mod imports;
+mod qif;
+mod reconciliations;
+mod register;
+mod rules;
+mod settings;
+mod tax;
+mod transactions;
+mod transfers;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{Connection, Row, ToSql};
+
+use crate::money::Money;
+use crate::money_storage::{self, CentsMigrationError, CentsMigrationReport, MoneyStorage};
+use crate::validation::ValidationError;
+
+pub use bills::UpcomingBillCount;
+pub use register::{
+  RegisterCursor, RegisterEntry, RegisterFilter, RegisterPosting, RegisterWindow,
+};
+pub use transfers::{TransferCommitResult, TransferPair};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error(transparent)]
+  Validation(#[from] ValidationError),
+  #[error("{entity} {id} not found")]
+  NotFound { entity: &'static str, id: String },
+  #[error("{0}")]
+  Conflict(String),
+}
+
+pub type DbResult<T> = Result<T, DbError>;
+
+/// Apply the same PRAGMAs `src/lib/db.ts` applies to every connection.
+pub fn apply_pragmas(conn: &Connection) -> rusqlite::Result<()> {
+  // journal_mode returns the resulting mode as a row, so it can't go through execute().
+  conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))?;
+  conn.pragma_update(None, "busy_timeout", 5000)?;
+  conn.pragma_update(None, "foreign_keys", "ON")?;
+  Ok(())
+}
+
+/// An open book.
+pub struct Database {
+  conn: Connection,
+  money: MoneyStorage,
+}
+
+impl Database {
+  pub fn open(path: impl AsRef<Path>) -> DbResult<Self> {
+    Self::from_connection(Connection::open(path)?)
+  }
+
+  pub fn from_connection(conn: Connection) -> DbResult<Self> {
+    apply_pragmas(&conn)?;
+    let money = money_storage::money_storage(&conn)?;
+    Ok(Database { conn, money })
+  }
+
+  pub fn connection(&self) -> &Connection {
+    &self.conn
+  }
+
+  pub fn money_storage(&self) -> MoneyStorage {
+    self.money
+  }
+
+  /// Convert this book's money columns to integer cents (see [`money_storage::migrate_to_cents`]).
+  pub fn migrate_to_cents(&mut self) -> Result<CentsMigrationReport, CentsMigrationError> {
+    let report = money_storage::migrate_to_cents(&mut self.conn)?;
+    self.money = MoneyStorage::Cents;
+    Ok(report)
+  }
+
+  fn money(&self, row: &Row, column: &str) -> rusqlite::Result<Money> {
+    Ok(self.money.decode(row.get(column)?))
+  }
+
+  fn optional_money(&self, row: &Row, column: &str) -> rusqlite::Result<Option<Money>> {
+    Ok(
+      row
+        .get::<_, Option<f64>>(column)?
+        .map(|raw| self.money.decode(raw)),
+    )
+  }
+
+  fn encode(&self, money: Money) -> f64 {
+    self.money.encode(money)
+  }
+}
+
+/// A fresh primary key in the format Prisma's `@default(uuid())` produces.
+pub fn new_id() -> String {
+  uuid::Uuid::new_v4().to_string()
+}
+
+/// A `DateTime` column.
+///
+/// Prisma writes epoch milliseconds; rows created by SQL defaults
+/// (`CURRENT_TIMESTAMP`) or older tooling hold text, so both are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SqlDateTime(pub DateTime<Utc>);
+
+impl FromSql for SqlDateTime {
+  fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+    match value {
+      ValueRef::Integer(millis) => Utc
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(SqlDateTime)
+        .ok_or(FromSqlError::OutOfRange(millis)),
+      ValueRef::Real(millis) => Utc
+        .timestamp_millis_opt(millis as i64)
+        .single()
+        .map(SqlDateTime)
+        .ok_or(FromSqlError::OutOfRange(millis as i64)),
+      ValueRef::Text(text) => {
+        let text = std::str::from_utf8(text).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+        parse_datetime_text(text)
+          .map(SqlDateTime)
+          .ok_or(FromSqlError::InvalidType)
+      }
+      _ => Err(FromSqlError::InvalidType),
+    }
+  }
+}
+
+impl ToSql for SqlDateTime {
+  fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+    Ok(ToSqlOutput::from(self.0.timestamp_millis()))
+  }
+}
+
+fn parse_datetime_text(text: &str) -> Option<DateTime<Utc>> {
+  if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+    return Some(parsed.with_timezone(&Utc));
+  }
+  ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+pub(crate) fn datetime(row: &Row, column: &str) -> rusqlite::Result<DateTime<Utc>> {
+  Ok(row.get::<_, SqlDateTime>(column)?.0)
+}
+
+pub(crate) fn optional_datetime(
+  row: &Row,
+  column: &str,
+) -> rusqlite::Result<Option<DateTime<Utc>>> {
+  Ok(row.get::<_, Option<SqlDateTime>>(column)?.map(|d| d.0))
+}
+
+/// Read an enum column stored as its Prisma name.
+pub(crate) fn enum_column<T>(row: &Row, column: &str) -> rusqlite::Result<T>
+where
+  T: FromStr<Err = crate::model::UnknownVariant>,
+{
+  let text: String = row.get(column)?;
+  parse_enum(row, column, &text)
+}
+
+pub(crate) fn optional_enum_column<T>(row: &Row, column: &str) -> rusqlite::Result<Option<T>>
+where
+  T: FromStr<Err = crate::model::UnknownVariant>,
+{
+  row
+    .get::<_, Option<String>>(column)?
+    .map(|text| parse_enum(row, column, &text))
+    .transpose()
+}
+
+fn parse_enum<T>(row: &Row, column: &str, text: &str) -> rusqlite::Result<T>
+where
+  T: FromStr<Err = crate::model::UnknownVariant>,
+{
+  text.parse().map_err(|err| {
+    let index = row.as_ref().column_index(column).unwrap_or_default();
+    rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(err))
+  })
+}
+
+pub(crate) fn now() -> SqlDateTime {
+  SqlDateTime(Utc::now())
+}
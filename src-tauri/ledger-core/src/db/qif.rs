@@ -0,0 +1,253 @@
+//! Importing QIF sections into an account and exporting an account as QIF.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::{new_id, Database, DbResult};
+use crate::balance::BalanceOptions;
+use crate::import::qif::category_path;
+use crate::import::{
+  write_qif, ClearedStatus, DateOrder, QifCategory, QifImportSummary, QifTransaction,
+  StatementBalance,
+};
+use crate::model::{Account, AccountKind, AccountType, ImportBatch, Posting, Transaction};
+use crate::money::Money;
+
+/// The book's accounts by the names a QIF file would use for them.
+struct CategoryIndex<'a> {
+  /// Lower-case category paths, with and without the `Expense`/`Income` root.
+  categories: HashMap<String, Vec<&'a Account>>,
+  /// Lower-case names of real accounts.
+  transfers: HashMap<String, &'a Account>,
+}
+
+impl<'a> CategoryIndex<'a> {
+  fn new(accounts: &'a [Account]) -> Self {
+    let mut categories: HashMap<String, Vec<&Account>> = HashMap::new();
+    let mut transfers = HashMap::new();
+    for account in accounts {
+      match account.kind {
+        AccountKind::Category => {
+          let mut keys = vec![category_path(account).to_lowercase()];
+          if let Some(full_path) = &account.full_path {
+            keys.push(full_path.replace('/', ":").to_lowercase());
+          }
+          keys.dedup();
+          for key in keys {
+            categories.entry(key).or_default().push(account);
+          }
+        }
+        AccountKind::Transfer => {
+          transfers
+            .entry(account.name.to_lowercase())
+            .or_insert(account);
+        }
+      }
+    }
+    CategoryIndex {
+      categories,
+      transfers,
+    }
+  }
+
+  /// The account for a category or transfer. Where a path is both an
+  /// income and an expense category, money out picks the expense.
+  fn resolve(&self, category: &QifCategory, money_out: bool) -> Option<&'a str> {
+    let account = match category {
+      QifCategory::Transfer(name) => *self.transfers.get(&name.to_lowercase())?,
+      QifCategory::Category(path) => {
+        let candidates = self.categories.get(&path.trim().to_lowercase())?;
+        let preferred = if money_out {
+          AccountType::Expense
+        } else {
+          AccountType::Income
+        };
+        candidates
+          .iter()
+          .find(|account| account.account_type == preferred)
+          .or_else(|| candidates.first())?
+      }
+    };
+    Some(&account.id)
+  }
+}
+
+impl Database {
+  /// Categories and `[transfer]` accounts in `transactions` the book has
+  /// nothing matching, as written in the file.
+  pub fn unknown_qif_categories(&self, transactions: &[QifTransaction]) -> DbResult<Vec<String>> {
+    let accounts = self.accounts(true)?;
+    let index = CategoryIndex::new(&accounts);
+    let mut unknown = BTreeSet::new();
+    for transaction in transactions {
+      for (category, _, amount) in lines(transaction) {
+        if let Some(category) = category {
+          if index.resolve(category, amount.is_negative()).is_none() {
+            unknown.insert(category.to_string());
+          }
+        }
+      }
+    }
+    Ok(unknown.into_iter().collect())
+  }
+
+  /// Import a QIF section's transactions into `account_id` as one batch.
+  /// Category paths are matched against `fullPath` and `[transfers]`
+  /// against account names, case-insensitively; lines with no category or
+  /// one the book doesn't have go to "Uncategorized". The `C` flag sets the
+  /// account posting's `cleared` (and `reconciled` for `X`).
+  ///
+  /// With `skip_duplicates`, records the account already has are left out,
+  /// so the other side of a transfer already imported from another
+  /// account's file isn't entered twice.
+  pub fn import_qif(
+    &mut self,
+    account_id: &str,
+    source_name: &str,
+    transactions: &[QifTransaction],
+    skip_duplicates: bool,
+  ) -> DbResult<QifImportSummary> {
+    let accounts = self.accounts(true)?;
+    let index = CategoryIndex::new(&accounts);
+    let batch = ImportBatch {
+      id: new_id(),
+      source_account_id: account_id.to_string(),
+      source_name: source_name.to_string(),
+      mapping_json: r#"{"format":"QIF"}"#.into(),
+      created_at: Utc::now(),
+    };
+    self.insert_import_batch(&batch)?;
+
+    let mut summary = QifImportSummary {
+      batch_id: batch.id,
+      imported: 0,
+      duplicates: 0,
+      unknown_categories: Vec::new(),
+    };
+    let mut unknown = BTreeSet::new();
+    let mut uncategorized: Option<String> = None;
+    for record in transactions {
+      let line = record.statement_line();
+      if skip_duplicates && self.is_duplicate_import(account_id, &line)? {
+        summary.duplicates += 1;
+        continue;
+      }
+
+      let id = new_id();
+      let now = Utc::now();
+      let posting = |account_id: &str, amount: Money| Posting {
+        id: new_id(),
+        transaction_id: id.clone(),
+        account_id: account_id.to_string(),
+        amount,
+        is_business: false,
+        gst_code: None,
+        gst_rate: None,
+        gst_amount: None,
+        category_split_label: None,
+        cleared: false,
+        reconciled: false,
+        reconcile_id: None,
+        created_at: now,
+      };
+      let mut postings = vec![Posting {
+        cleared: record.cleared != ClearedStatus::Uncleared,
+        reconciled: record.cleared == ClearedStatus::Reconciled,
+        ..posting(account_id, record.amount)
+      }];
+      for (category, label, amount) in lines(record) {
+        let target = match category.and_then(|c| index.resolve(c, amount.is_negative())) {
+          Some(target) => target.to_string(),
+          None => {
+            unknown.extend(category.map(ToString::to_string));
+            match &uncategorized {
+              Some(target) => target.clone(),
+              None => uncategorized
+                .insert(self.uncategorized_account_id()?)
+                .clone(),
+            }
+          }
+        };
+        postings.push(Posting {
+          category_split_label: label.map(String::from),
+          ..posting(&target, -amount)
+        });
+      }
+
+      self.insert_transaction(&Transaction {
+        id: id.clone(),
+        date: line.date_time(),
+        payee: line.payee,
+        memo: record.memo.clone(),
+        reference: record.reference.clone(),
+        tags: None,
+        metadata: None,
+        import_batch_id: Some(summary.batch_id.clone()),
+        external_id: None,
+        status: Default::default(),
+        created_at: now,
+        updated_at: now,
+        postings,
+      })?;
+      summary.imported += 1;
+    }
+    summary.unknown_categories = unknown.into_iter().collect();
+    Ok(summary)
+  }
+
+  /// `account_id`'s transactions dated within `[from, to]` as QIF, led by an
+  /// opening balance record: the account's opening balance, or its balance
+  /// the day before `from`.
+  pub fn export_qif(
+    &self,
+    account_id: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    dates: DateOrder,
+  ) -> DbResult<String> {
+    let account = self.account(account_id)?;
+    let opening_balance = match from {
+      Some(from) => StatementBalance {
+        amount: self.account_balance(
+          account_id,
+          BalanceOptions {
+            up_to: Some(from - Duration::milliseconds(1)),
+            ..BalanceOptions::default()
+          },
+        )?,
+        date: from.date_naive(),
+      },
+      None => StatementBalance {
+        amount: account.opening_balance,
+        date: account.opening_date.date_naive(),
+      },
+    };
+    let transactions: Vec<_> = self
+      .transactions_between(from, to)?
+      .into_iter()
+      .filter(|t| t.postings.iter().any(|p| p.account_id == account_id))
+      .collect();
+    Ok(write_qif(
+      &account,
+      Some(opening_balance).filter(|b| !b.amount.is_zero()),
+      &transactions,
+      &self.accounts_by_id()?,
+      dates,
+    ))
+  }
+}
+
+/// The other side of a record: its splits with their memos, or its one
+/// category.
+fn lines(transaction: &QifTransaction) -> Vec<(Option<&QifCategory>, Option<&str>, Money)> {
+  if transaction.splits.is_empty() {
+    vec![(transaction.category.as_ref(), None, transaction.amount)]
+  } else {
+    transaction
+      .splits
+      .iter()
+      .map(|split| (split.category.as_ref(), split.memo.as_deref(), split.amount))
+      .collect()
+  }
+}
@@ -0,0 +1,268 @@
+//! `reconciliations` table, mirroring `ReconciliationService` in
+//! `reconciliationService.ts`.
+
+use rusqlite::{params, OptionalExtension, Row, TransactionBehavior};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::{datetime, new_id, Database, DbError, DbResult, SqlDateTime};
+use crate::balance::BalanceOptions;
+use crate::import::{StatementBalance, StatementLine};
+use crate::matching::{match_statement_lines, LedgerItem, ReconciliationMatchPreview};
+use crate::model::{Reconciliation, TransactionStatus};
+use crate::money::Money;
+
+const COLUMNS: &str = "id, account_id, statement_start_date, statement_end_date,
+  statement_start_balance, statement_end_balance, notes, locked, created_at";
+
+impl Database {
+  fn reconciliation_from_row(&self, row: &Row) -> rusqlite::Result<Reconciliation> {
+    Ok(Reconciliation {
+      id: row.get("id")?,
+      account_id: row.get("account_id")?,
+      statement_start_date: datetime(row, "statement_start_date")?,
+      statement_end_date: datetime(row, "statement_end_date")?,
+      statement_start_balance: self.money(row, "statement_start_balance")?,
+      statement_end_balance: self.money(row, "statement_end_balance")?,
+      notes: row.get("notes")?,
+      locked: row.get("locked")?,
+      created_at: datetime(row, "created_at")?,
+    })
+  }
+
+  /// Reconciliations, newest first, optionally for one account.
+  pub fn reconciliations(&self, account_id: Option<&str>) -> DbResult<Vec<Reconciliation>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM reconciliations
+       WHERE ?1 IS NULL OR account_id = ?1
+       ORDER BY created_at DESC"
+    ))?;
+    let rows = stmt.query_map([account_id], |row| self.reconciliation_from_row(row))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+  }
+
+  pub fn reconciliation(&self, id: &str) -> DbResult<Reconciliation> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM reconciliations WHERE id = ?1"),
+        [id],
+        |row| self.reconciliation_from_row(row),
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Reconciliation",
+        id: id.to_string(),
+      })
+  }
+
+  pub fn insert_reconciliation(&self, reconciliation: &Reconciliation) -> DbResult<()> {
+    self.conn.execute(
+      &format!(
+        "INSERT INTO reconciliations ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+      ),
+      params![
+        reconciliation.id,
+        reconciliation.account_id,
+        SqlDateTime(reconciliation.statement_start_date),
+        SqlDateTime(reconciliation.statement_end_date),
+        self.encode(reconciliation.statement_start_balance),
+        self.encode(reconciliation.statement_end_balance),
+        reconciliation.notes,
+        reconciliation.locked,
+        SqlDateTime(reconciliation.created_at),
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// Start reconciling `account_id` against an imported statement's opening
+  /// and closing balances. If a session for the same balances already
+  /// exists, as when a statement is imported twice, that one is returned.
+  pub fn start_statement_reconciliation(
+    &self,
+    account_id: &str,
+    opening: StatementBalance,
+    closing: StatementBalance,
+    notes: Option<String>,
+  ) -> DbResult<Reconciliation> {
+    let existing = self
+      .reconciliations(Some(account_id))?
+      .into_iter()
+      .find(|r| {
+        r.statement_start_date == opening.date_time()
+          && r.statement_end_date == closing.date_time()
+          && r.statement_start_balance == opening.amount
+          && r.statement_end_balance == closing.amount
+      });
+    if let Some(reconciliation) = existing {
+      return Ok(reconciliation);
+    }
+    let reconciliation = Reconciliation {
+      id: new_id(),
+      account_id: account_id.to_string(),
+      statement_start_date: opening.date_time(),
+      statement_end_date: closing.date_time(),
+      statement_start_balance: opening.amount,
+      statement_end_balance: closing.amount,
+      notes,
+      locked: false,
+      created_at: Utc::now(),
+    };
+    self.insert_reconciliation(&reconciliation)?;
+    Ok(reconciliation)
+  }
+
+  pub fn set_reconciliation_locked(&self, id: &str, locked: bool) -> DbResult<()> {
+    let changed = self.conn.execute(
+      "UPDATE reconciliations SET locked = ?2 WHERE id = ?1",
+      params![id, locked],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Reconciliation",
+        id: id.to_string(),
+      });
+    }
+    Ok(())
+  }
+
+  /// Attach postings to a reconciliation, marking them reconciled and
+  /// cleared, like `reconcilePostings`. Postings of other accounts are ignored.
+  pub fn reconcile_postings(
+    &mut self,
+    reconciliation_id: &str,
+    posting_ids: &[String],
+  ) -> DbResult<usize> {
+    let reconciliation = self.reconciliation(reconciliation_id)?;
+    if reconciliation.locked {
+      return Err(DbError::Conflict(
+        "Cannot modify a locked reconciliation".into(),
+      ));
+    }
+    let tx = self.conn.transaction()?;
+    let mut changed = 0;
+    {
+      let mut stmt = tx.prepare(
+        "UPDATE postings SET reconciled = 1, cleared = 1, reconcile_id = ?2
+         WHERE id = ?1 AND account_id = ?3",
+      )?;
+      for id in posting_ids {
+        changed += stmt.execute(params![id, reconciliation_id, reconciliation.account_id])?;
+      }
+    }
+    tx.commit()?;
+    Ok(changed)
+  }
+
+  /// Delete an unlocked reconciliation, un-reconciling its postings first,
+  /// like `deleteReconciliation`.
+  pub fn delete_reconciliation(&mut self, id: &str) -> DbResult<()> {
+    if self.reconciliation(id)?.locked {
+      return Err(DbError::Conflict(
+        "Cannot delete a locked reconciliation. Unlock it first.".into(),
+      ));
+    }
+    let tx = self
+      .conn
+      .transaction_with_behavior(TransactionBehavior::Immediate)?;
+    tx.execute(
+      "UPDATE postings SET reconciled = 0, reconcile_id = NULL WHERE reconcile_id = ?1",
+      [id],
+    )?;
+    tx.execute("DELETE FROM reconciliations WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Postings to `account_id` not yet reconciled, oldest first, like the
+  /// ledger side of `matchTransactions`. The range is widened a day either
+  /// side for time zones.
+  pub fn unreconciled_postings(
+    &self,
+    account_id: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> DbResult<Vec<LedgerItem>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT DISTINCT t.id FROM transactions t JOIN postings p ON p.transaction_id = t.id
+       WHERE p.account_id = ?1 AND p.reconciled = 0 AND t.status = ?2
+         AND (?3 IS NULL OR t.date >= ?3) AND (?4 IS NULL OR t.date <= ?4)",
+    )?;
+    let ids = stmt
+      .query_map(
+        params![
+          account_id,
+          TransactionStatus::Normal.as_str(),
+          from.map(|from| SqlDateTime(from - Duration::days(1))),
+          to.map(|to| SqlDateTime(to + Duration::days(1))),
+        ],
+        |row| row.get::<_, String>(0),
+      )?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    let mut transactions: Vec<_> = self.transactions_by_id(&ids)?.into_values().collect();
+    transactions.sort_by_key(|t| (t.date, t.created_at));
+
+    let mut items = Vec::new();
+    for transaction in transactions {
+      let original_description = transaction
+        .metadata
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        .and_then(|metadata| metadata["originalDescription"].as_str().map(String::from));
+      for posting in &transaction.postings {
+        if posting.account_id != account_id || posting.reconciled {
+          continue;
+        }
+        items.push(LedgerItem {
+          transaction_id: transaction.id.clone(),
+          posting_id: posting.id.clone(),
+          date: transaction.date,
+          payee: transaction.payee.clone(),
+          memo: transaction.memo.clone(),
+          amount: posting.amount,
+          cleared: posting.cleared,
+          original_description: original_description.clone(),
+        });
+      }
+    }
+    Ok(items)
+  }
+
+  /// Pair statement lines with the account's unreconciled postings and
+  /// explain what's left of the difference, so a month can be finished in
+  /// one pass. The range defaults to the lines' first and last dates, and
+  /// the closing balance to the last line's running balance.
+  pub fn match_statement_lines(
+    &self,
+    account_id: &str,
+    lines: Vec<StatementLine>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    closing_balance: Option<Money>,
+  ) -> DbResult<ReconciliationMatchPreview> {
+    let from = from.or_else(|| lines.iter().map(StatementLine::date_time).min());
+    let to = to.or_else(|| lines.iter().map(StatementLine::date_time).max());
+    let statement_balance =
+      closing_balance.or_else(|| lines.iter().rev().find_map(|line| line.balance));
+    let ledger = self.unreconciled_postings(account_id, from, to)?;
+    // Counted to the same cut-off as the postings above, so a posting is
+    // either in both or in neither.
+    let ledger_balance = self.account_balance(
+      account_id,
+      BalanceOptions {
+        up_to: to.map(|to| to + Duration::days(1)),
+        ..BalanceOptions::default()
+      },
+    )?;
+    let rules = self.memorized_rules()?;
+    Ok(match_statement_lines(
+      lines,
+      ledger,
+      &rules,
+      statement_balance,
+      ledger_balance,
+    ))
+  }
+}
@@ -0,0 +1,299 @@
+//! Account register, mirroring `TransactionService.getRegisterEntries`.
+//!
+//! Unlike the TypeScript version the register is paged: the running balance
+//! is computed in SQL over every non-void posting of the account, so each
+//! page carries the true balance after its rows whatever filter is applied.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::named_params;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, DbResult, SqlDateTime};
+use crate::model::{Account, Posting, TransactionStatus};
+use crate::money::Money;
+
+/// Same fields as `RegisterFilter` in `src/types/index.ts`, minus the ones the
+/// register endpoint never honoured (tags, amount range).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegisterFilter {
+  pub date_from: Option<DateTime<Utc>>,
+  pub date_to: Option<DateTime<Utc>>,
+  /// Case-insensitive substring of the payee, memo or reference.
+  pub search: Option<String>,
+  pub cleared_only: bool,
+  pub reconciled_only: bool,
+  pub business_only: bool,
+  pub personal_only: bool,
+}
+
+/// A posting with its account, like Prisma's `PostingWithAccount`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterPosting {
+  #[serde(flatten)]
+  pub posting: Posting,
+  pub account: Account,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterEntry {
+  /// Transaction id.
+  pub id: String,
+  /// This account's posting; `None` for the opening balance row.
+  pub posting_id: Option<String>,
+  pub date: DateTime<Utc>,
+  pub payee: String,
+  pub memo: Option<String>,
+  pub reference: Option<String>,
+  pub tags: Option<Vec<String>>,
+  /// Money out of the account (negative posting), as a positive amount.
+  pub debit: Option<Money>,
+  /// Money into the account (positive posting).
+  pub credit: Option<Money>,
+  pub running_balance: Money,
+  pub postings: Vec<RegisterPosting>,
+  pub status: TransactionStatus,
+  pub cleared: bool,
+  pub reconciled: bool,
+  pub is_business: bool,
+  /// `categorySplitLabel` of this account's posting.
+  pub split_label: Option<String>,
+}
+
+/// A page of the register and how many register rows it covered, which is
+/// more than `entries.len()` when a row's transaction vanished mid-read.
+/// Page by `rows`, not by the entries returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterWindow {
+  pub entries: Vec<RegisterEntry>,
+  pub rows: usize,
+}
+
+/// Offsets for reading a register of `total` rows a page at a time, as
+/// `stream_register` sends it to the frontend. The first page is always read
+/// (it carries the opening balance row), and a short page ends the walk, so
+/// rows deleted since `total` was counted can't leave it waiting for them.
+#[derive(Debug, Clone)]
+pub struct RegisterCursor {
+  offset: usize,
+  total: usize,
+  page_size: usize,
+  done: bool,
+}
+
+impl RegisterCursor {
+  pub fn new(total: usize, page_size: usize) -> Self {
+    Self {
+      offset: 0,
+      total,
+      page_size: page_size.max(1),
+      done: false,
+    }
+  }
+
+  pub fn page_size(&self) -> usize {
+    self.page_size
+  }
+
+  /// Where the next page starts, or `None` once the register is done.
+  pub fn next_offset(&self) -> Option<usize> {
+    (!self.done && (self.offset == 0 || self.offset < self.total)).then_some(self.offset)
+  }
+
+  /// Move past a page that covered `rows` register rows
+  /// ([`RegisterWindow::rows`]).
+  pub fn advance(&mut self, rows: usize) {
+    self.offset += rows;
+    self.done = rows < self.page_size || self.offset >= self.total;
+  }
+}
+
+/// Every non-void posting of the account in register order, numbered and with
+/// the running sum of amounts. Filters apply to the outer query so they don't
+/// change the running sum.
+const REGISTER_CTE: &str = "WITH register AS (
+  SELECT p.id AS posting_id, p.transaction_id, p.cleared, p.reconciled, p.is_business,
+    t.date, t.payee, t.memo, t.reference,
+    SUM(p.amount) OVER win AS running,
+    ROW_NUMBER() OVER win AS seq
+  FROM postings p
+  JOIN transactions t ON t.id = p.transaction_id
+  WHERE p.account_id = :account AND t.status = 'NORMAL'
+  WINDOW win AS (ORDER BY t.date, p.created_at, p.rowid ROWS UNBOUNDED PRECEDING)
+)";
+
+const REGISTER_FILTER: &str = "(:from IS NULL OR date >= :from)
+  AND (:to IS NULL OR date <= :to)
+  AND (:search IS NULL
+    OR instr(lower(payee), lower(:search)) > 0
+    OR instr(lower(COALESCE(memo, '')), lower(:search)) > 0
+    OR instr(lower(COALESCE(reference, '')), lower(:search)) > 0)
+  AND (:cleared = 0 OR cleared = 1)
+  AND (:reconciled = 0 OR reconciled = 1)
+  AND (:business = 0 OR is_business = 1)
+  AND (:personal = 0 OR is_business = 0)";
+
+impl Database {
+  /// Number of register rows matching `filter` (the opening balance row not
+  /// included).
+  pub fn register_len(&self, account_id: &str, filter: &RegisterFilter) -> DbResult<usize> {
+    let search = filter.search.as_deref().filter(|s| !s.is_empty());
+    let count: i64 = self.conn.query_row(
+      &format!("{REGISTER_CTE} SELECT COUNT(*) FROM register WHERE {REGISTER_FILTER}"),
+      named_params! {
+        ":account": account_id,
+        ":from": filter.date_from.map(SqlDateTime),
+        ":to": filter.date_to.map(SqlDateTime),
+        ":search": search,
+        ":cleared": filter.cleared_only,
+        ":reconciled": filter.reconciled_only,
+        ":business": filter.business_only,
+        ":personal": filter.personal_only,
+      },
+      |row| row.get(0),
+    )?;
+    Ok(count as usize)
+  }
+
+  /// Up to `limit` register rows matching `filter`, oldest first, skipping
+  /// the first `offset` matches.
+  pub fn register_page(
+    &self,
+    account_id: &str,
+    filter: &RegisterFilter,
+    offset: usize,
+    limit: usize,
+  ) -> DbResult<Vec<RegisterEntry>> {
+    Ok(
+      self
+        .register_window(account_id, filter, offset, limit)?
+        .entries,
+    )
+  }
+
+  /// [`Database::register_page`] with the number of register rows it
+  /// covered. The rows and their transactions are read in one snapshot.
+  pub fn register_window(
+    &self,
+    account_id: &str,
+    filter: &RegisterFilter,
+    offset: usize,
+    limit: usize,
+  ) -> DbResult<RegisterWindow> {
+    // Unless the caller already has one open.
+    let _snapshot = match self.conn.is_autocommit() {
+      true => Some(self.conn.unchecked_transaction()?),
+      false => None,
+    };
+    let account = self.account(account_id)?;
+    let search = filter.search.as_deref().filter(|s| !s.is_empty());
+
+    let mut stmt = self.conn.prepare(&format!(
+      "{REGISTER_CTE} SELECT posting_id, transaction_id, running FROM register
+       WHERE {REGISTER_FILTER} ORDER BY seq LIMIT :limit OFFSET :offset"
+    ))?;
+    let rows = stmt
+      .query_map(
+        named_params! {
+          ":account": account_id,
+          ":from": filter.date_from.map(SqlDateTime),
+          ":to": filter.date_to.map(SqlDateTime),
+          ":search": search,
+          ":cleared": filter.cleared_only,
+          ":reconciled": filter.reconciled_only,
+          ":business": filter.business_only,
+          ":personal": filter.personal_only,
+          ":limit": limit as i64,
+          ":offset": offset as i64,
+        },
+        |row| {
+          Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, f64>(2)?,
+          ))
+        },
+      )?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let ids: Vec<&str> = rows.iter().map(|(_, t, _)| t.as_str()).collect();
+    let transactions = self.transactions_by_id(&ids)?;
+    let mut accounts: HashMap<String, Account> = HashMap::new();
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for (posting_id, transaction_id, running) in &rows {
+      // The snapshot keeps the API server's writes out, but a row that
+      // still doesn't match up is skipped rather than failing the page.
+      let Some(transaction) = transactions.get(transaction_id) else {
+        continue;
+      };
+      let Some(own) = transaction.postings.iter().find(|p| &p.id == posting_id) else {
+        continue;
+      };
+      let amount = own.amount;
+
+      let mut postings = Vec::with_capacity(transaction.postings.len());
+      for posting in &transaction.postings {
+        if !accounts.contains_key(&posting.account_id) {
+          let other = self.account(&posting.account_id)?;
+          accounts.insert(other.id.clone(), other);
+        }
+        postings.push(RegisterPosting {
+          posting: posting.clone(),
+          account: accounts[&posting.account_id].clone(),
+        });
+      }
+
+      entries.push(RegisterEntry {
+        id: transaction.id.clone(),
+        posting_id: Some(own.id.clone()),
+        date: transaction.date,
+        payee: transaction.payee.clone(),
+        memo: transaction.memo.clone(),
+        reference: transaction.reference.clone(),
+        tags: transaction.tags.as_ref().map(|_| transaction.tag_list()),
+        debit: amount.is_negative().then(|| amount.abs()),
+        credit: amount.is_positive().then_some(amount),
+        running_balance: account.opening_balance + self.money.decode(*running),
+        status: transaction.status,
+        cleared: own.cleared,
+        reconciled: own.reconciled,
+        is_business: own.is_business,
+        split_label: own.category_split_label.clone(),
+        postings,
+      });
+    }
+    Ok(RegisterWindow {
+      entries,
+      rows: rows.len(),
+    })
+  }
+
+  /// The synthetic first row of every register, as the TypeScript service
+  /// returns it.
+  pub fn register_opening_entry(&self, account_id: &str) -> DbResult<RegisterEntry> {
+    let account = self.account(account_id)?;
+    Ok(RegisterEntry {
+      id: format!("opening-{account_id}"),
+      posting_id: None,
+      date: account.opening_date,
+      payee: "Opening Balance".into(),
+      memo: None,
+      reference: None,
+      tags: None,
+      debit: None,
+      credit: None,
+      running_balance: account.opening_balance,
+      postings: Vec::new(),
+      status: TransactionStatus::Normal,
+      cleared: true,
+      reconciled: true,
+      is_business: false,
+      split_label: None,
+    })
+  }
+}
@@ -0,0 +1,134 @@
+//! `memorized_rules` table, mirroring `MemorizedRuleService` in
+//! `memorizedRuleService.ts`.
+
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{datetime, enum_column, Database, DbError, DbResult, SqlDateTime};
+use crate::model::MemorizedRule;
+use crate::rules::RuleMatcher;
+
+const COLUMNS: &str = "id, name, match_type, match_value, default_payee, default_account_id,
+  default_splits, apply_on_import, apply_on_manual_entry, priority, created_at, updated_at";
+
+fn rule_from_row(row: &Row) -> rusqlite::Result<MemorizedRule> {
+  Ok(MemorizedRule {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    match_type: enum_column(row, "match_type")?,
+    match_value: row.get("match_value")?,
+    default_payee: row.get("default_payee")?,
+    default_account_id: row.get("default_account_id")?,
+    default_splits: row.get("default_splits")?,
+    apply_on_import: row.get("apply_on_import")?,
+    apply_on_manual_entry: row.get("apply_on_manual_entry")?,
+    priority: row.get("priority")?,
+    created_at: datetime(row, "created_at")?,
+    updated_at: datetime(row, "updated_at")?,
+  })
+}
+
+impl Database {
+  /// Rules in evaluation order: priority, then name.
+  pub fn memorized_rules(&self) -> DbResult<Vec<MemorizedRule>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {COLUMNS} FROM memorized_rules ORDER BY priority, name"
+    ))?;
+    let rules = stmt.query_map([], rule_from_row)?;
+    Ok(rules.collect::<rusqlite::Result<_>>()?)
+  }
+
+  /// Every rule, compiled for matching.
+  pub fn rule_matcher(&self) -> DbResult<RuleMatcher> {
+    Ok(RuleMatcher::new(self.memorized_rules()?))
+  }
+
+  pub fn memorized_rule(&self, id: &str) -> DbResult<MemorizedRule> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {COLUMNS} FROM memorized_rules WHERE id = ?1"),
+        [id],
+        rule_from_row,
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Memorized rule",
+        id: id.to_string(),
+      })
+  }
+
+  pub fn insert_memorized_rule(&self, rule: &MemorizedRule) -> DbResult<()> {
+    self.conn.execute(
+      &format!(
+        "INSERT INTO memorized_rules ({COLUMNS})
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+      ),
+      params![
+        rule.id,
+        rule.name,
+        rule.match_type.as_str(),
+        rule.match_value,
+        rule.default_payee,
+        rule.default_account_id,
+        rule.default_splits,
+        rule.apply_on_import,
+        rule.apply_on_manual_entry,
+        rule.priority,
+        SqlDateTime(rule.created_at),
+        SqlDateTime(rule.updated_at),
+      ],
+    )?;
+    Ok(())
+  }
+
+  pub fn update_memorized_rule(&self, rule: &MemorizedRule) -> DbResult<()> {
+    let changed = self.conn.execute(
+      "UPDATE memorized_rules SET name = ?2, match_type = ?3, match_value = ?4,
+         default_payee = ?5, default_account_id = ?6, default_splits = ?7,
+         apply_on_import = ?8, apply_on_manual_entry = ?9, priority = ?10, updated_at = ?11
+       WHERE id = ?1",
+      params![
+        rule.id,
+        rule.name,
+        rule.match_type.as_str(),
+        rule.match_value,
+        rule.default_payee,
+        rule.default_account_id,
+        rule.default_splits,
+        rule.apply_on_import,
+        rule.apply_on_manual_entry,
+        rule.priority,
+        super::now(),
+      ],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Memorized rule",
+        id: rule.id.clone(),
+      });
+    }
+    Ok(())
+  }
+
+  pub fn delete_memorized_rule(&self, id: &str) -> DbResult<()> {
+    self
+      .conn
+      .execute("DELETE FROM memorized_rules WHERE id = ?1", [id])?;
+    Ok(())
+  }
+
+  /// Give each rule its index in `rule_ids` as priority, like `reorderRules`.
+  pub fn reorder_memorized_rules(&mut self, rule_ids: &[String]) -> DbResult<()> {
+    let tx = self.conn.transaction()?;
+    {
+      let now = super::now();
+      let mut stmt =
+        tx.prepare("UPDATE memorized_rules SET priority = ?2, updated_at = ?3 WHERE id = ?1")?;
+      for (priority, id) in rule_ids.iter().enumerate() {
+        stmt.execute(params![id, priority as i64, now])?;
+      }
+    }
+    tx.commit()?;
+    Ok(())
+  }
+}
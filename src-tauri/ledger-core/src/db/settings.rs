@@ -0,0 +1,74 @@
+//! `settings` table, mirroring `SettingsService` in `settingsService.ts`.
+//! Values are stored JSON-encoded.
+
+use rusqlite::{params, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{datetime, new_id, Database, DbResult};
+use crate::backup::BackupSchedule;
+use crate::model::Setting;
+
+const BACKUP_SCHEDULE_KEY: &str = "backupSchedule";
+
+impl Database {
+  pub fn setting(&self, key: &str) -> DbResult<Option<Setting>> {
+    Ok(
+      self
+        .conn
+        .query_row(
+          "SELECT id, key, value, created_at, updated_at FROM settings WHERE key = ?1",
+          [key],
+          |row| {
+            Ok(Setting {
+              id: row.get("id")?,
+              key: row.get("key")?,
+              value: row.get("value")?,
+              created_at: datetime(row, "created_at")?,
+              updated_at: datetime(row, "updated_at")?,
+            })
+          },
+        )
+        .optional()?,
+    )
+  }
+
+  /// Decode a setting's JSON value. Missing keys and values that don't
+  /// deserialize as `T` both read as `None`.
+  pub fn setting_json<T: DeserializeOwned>(&self, key: &str) -> DbResult<Option<T>> {
+    Ok(
+      self
+        .setting(key)?
+        .and_then(|setting| serde_json::from_str(&setting.value).ok()),
+    )
+  }
+
+  pub fn set_setting_json<T: Serialize + ?Sized>(&self, key: &str, value: &T) -> DbResult<()> {
+    let json = serde_json::to_string(value).expect("setting values serialize to JSON");
+    let now = super::now();
+    self.conn.execute(
+      "INSERT INTO settings (id, key, value, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)
+       ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+      params![new_id(), key, json, now],
+    )?;
+    Ok(())
+  }
+
+  /// Returns whether the key existed.
+  pub fn delete_setting(&self, key: &str) -> DbResult<bool> {
+    Ok(
+      self
+        .conn
+        .execute("DELETE FROM settings WHERE key = ?1", [key])?
+        > 0,
+    )
+  }
+
+  pub fn backup_schedule(&self) -> DbResult<BackupSchedule> {
+    Ok(self.setting_json(BACKUP_SCHEDULE_KEY)?.unwrap_or_default())
+  }
+
+  pub fn save_backup_schedule(&self, schedule: &BackupSchedule) -> DbResult<()> {
+    self.set_setting_json(BACKUP_SCHEDULE_KEY, schedule)
+  }
+}
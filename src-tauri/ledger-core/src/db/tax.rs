@@ -0,0 +1,60 @@
+//! Tax tables and PAYG instalments kept in the `settings` table, as
+//! `TaxService` stores them.
+
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+
+use super::{Database, DbResult};
+use crate::tax::{default_tax_tables, FinancialYear, PaygConfig, TaxTablesConfig, BUILT_IN_YEARS};
+
+const TAX_TABLES_PREFIX: &str = "taxTables_";
+const PAYG_PREFIX: &str = "payg_";
+
+impl Database {
+  /// The tables saved for `year`, or the built-in ones.
+  pub fn tax_tables(&self, year: FinancialYear) -> DbResult<TaxTablesConfig> {
+    Ok(
+      self
+        .setting_json(&format!("{TAX_TABLES_PREFIX}{year}"))?
+        .unwrap_or_else(|| default_tax_tables(year)),
+    )
+  }
+
+  pub fn save_tax_tables(&self, tables: &TaxTablesConfig) -> DbResult<()> {
+    self.set_setting_json(
+      &format!("{TAX_TABLES_PREFIX}{}", tables.financial_year),
+      tables,
+    )
+  }
+
+  /// Years with built-in or saved tables, oldest first.
+  pub fn available_financial_years(&self) -> DbResult<Vec<FinancialYear>> {
+    let mut stmt = self
+      .conn
+      .prepare("SELECT key FROM settings WHERE key LIKE ?1 || '%'")?;
+    let saved = stmt
+      .query_map([TAX_TABLES_PREFIX], |row| row.get::<_, String>(0))?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    let years: BTreeSet<FinancialYear> = BUILT_IN_YEARS
+      .iter()
+      .copied()
+      .chain(saved.iter().map(|key| &key[TAX_TABLES_PREFIX.len()..]))
+      .filter_map(|year| year.parse().ok())
+      .collect();
+    Ok(years.into_iter().collect())
+  }
+
+  /// The PAYG instalments recorded for `year`, with statuses as of `today`.
+  pub fn payg_config(&self, year: FinancialYear, today: NaiveDate) -> DbResult<Option<PaygConfig>> {
+    let mut config: Option<PaygConfig> = self.setting_json(&format!("{PAYG_PREFIX}{year}"))?;
+    if let Some(config) = &mut config {
+      config.update_statuses(today);
+    }
+    Ok(config)
+  }
+
+  pub fn save_payg_config(&self, config: &PaygConfig) -> DbResult<()> {
+    self.set_setting_json(&format!("{PAYG_PREFIX}{}", config.financial_year), config)
+  }
+}
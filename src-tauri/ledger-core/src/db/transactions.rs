@@ -0,0 +1,333 @@
+//! `transactions` and `postings` tables, mirroring `TransactionService` in
+//! `transactionService.ts`.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, params_from_iter, OptionalExtension, Row, TransactionBehavior};
+
+use super::{
+  datetime, enum_column, optional_enum_column, Database, DbError, DbResult, SqlDateTime,
+};
+use crate::model::{Posting, Transaction, TransactionStatus};
+use crate::validation::validate_transaction;
+
+const TRANSACTION_COLUMNS: &str = "id, date, payee, memo, reference, tags, metadata,
+  import_batch_id, external_id, status, created_at, updated_at";
+
+const POSTING_COLUMNS: &str = "id, transaction_id, account_id, amount, is_business, gst_code,
+  gst_rate, gst_amount, category_split_label, cleared, reconciled, reconcile_id, created_at";
+
+/// Ids per `IN (...)` list, well under SQLITE_MAX_VARIABLE_NUMBER.
+const IN_LIST_CHUNK: usize = 500;
+
+impl Database {
+  /// Map a row selected with the transaction columns; postings are left empty.
+  pub(crate) fn transaction_from_row(&self, row: &Row) -> rusqlite::Result<Transaction> {
+    Ok(Transaction {
+      id: row.get("id")?,
+      date: datetime(row, "date")?,
+      payee: row.get("payee")?,
+      memo: row.get("memo")?,
+      reference: row.get("reference")?,
+      tags: row.get("tags")?,
+      metadata: row.get("metadata")?,
+      import_batch_id: row.get("import_batch_id")?,
+      external_id: row.get("external_id")?,
+      status: enum_column(row, "status")?,
+      created_at: datetime(row, "created_at")?,
+      updated_at: datetime(row, "updated_at")?,
+      postings: Vec::new(),
+    })
+  }
+
+  /// Map a row selected with the posting columns.
+  pub(crate) fn posting_from_row(&self, row: &Row) -> rusqlite::Result<Posting> {
+    Ok(Posting {
+      id: row.get("id")?,
+      transaction_id: row.get("transaction_id")?,
+      account_id: row.get("account_id")?,
+      amount: self.money(row, "amount")?,
+      is_business: row.get("is_business")?,
+      gst_code: optional_enum_column(row, "gst_code")?,
+      gst_rate: row.get("gst_rate")?,
+      gst_amount: self.optional_money(row, "gst_amount")?,
+      category_split_label: row.get("category_split_label")?,
+      cleared: row.get("cleared")?,
+      reconciled: row.get("reconciled")?,
+      reconcile_id: row.get("reconcile_id")?,
+      created_at: datetime(row, "created_at")?,
+    })
+  }
+
+  /// A transaction with its postings.
+  pub fn transaction(&self, id: &str) -> DbResult<Transaction> {
+    let mut transaction = self
+      .conn
+      .query_row(
+        &format!("SELECT {TRANSACTION_COLUMNS} FROM transactions WHERE id = ?1"),
+        [id],
+        |row| self.transaction_from_row(row),
+      )
+      .optional()?
+      .ok_or_else(|| DbError::NotFound {
+        entity: "Transaction",
+        id: id.to_string(),
+      })?;
+    transaction.postings = self.postings_of(&[id])?.remove(id).unwrap_or_default();
+    Ok(transaction)
+  }
+
+  /// Transactions dated within `[from, to]` (either bound optional), oldest
+  /// first, with their postings. Void transactions are included.
+  pub fn transactions_between(
+    &self,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+  ) -> DbResult<Vec<Transaction>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {TRANSACTION_COLUMNS} FROM transactions
+       WHERE (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR date <= ?2)
+       ORDER BY date, created_at"
+    ))?;
+    let mut transactions = stmt
+      .query_map(params![from.map(SqlDateTime), to.map(SqlDateTime)], |row| {
+        self.transaction_from_row(row)
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    self.attach_postings(&mut transactions)?;
+    Ok(transactions)
+  }
+
+  /// Transactions with the given ids, with their postings, keyed by id.
+  /// Unknown ids are skipped.
+  pub(crate) fn transactions_by_id(&self, ids: &[&str]) -> DbResult<HashMap<String, Transaction>> {
+    let mut transactions = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(IN_LIST_CHUNK) {
+      let placeholders = vec!["?"; chunk.len()].join(", ");
+      let mut stmt = self.conn.prepare(&format!(
+        "SELECT {TRANSACTION_COLUMNS} FROM transactions WHERE id IN ({placeholders})"
+      ))?;
+      let rows = stmt.query_map(params_from_iter(chunk), |row| {
+        self.transaction_from_row(row)
+      })?;
+      for transaction in rows {
+        transactions.push(transaction?);
+      }
+    }
+    self.attach_postings(&mut transactions)?;
+    Ok(
+      transactions
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect(),
+    )
+  }
+
+  /// Fill in `postings` for each transaction.
+  pub(crate) fn attach_postings(&self, transactions: &mut [Transaction]) -> DbResult<()> {
+    let ids: Vec<&str> = transactions.iter().map(|t| t.id.as_str()).collect();
+    let mut postings = self.postings_of(&ids)?;
+    for transaction in transactions {
+      transaction.postings = postings.remove(&transaction.id).unwrap_or_default();
+    }
+    Ok(())
+  }
+
+  /// Postings of the given transactions, grouped by transaction id, each group
+  /// in creation order.
+  fn postings_of(&self, transaction_ids: &[&str]) -> DbResult<HashMap<String, Vec<Posting>>> {
+    let mut grouped: HashMap<String, Vec<Posting>> = HashMap::new();
+    for chunk in transaction_ids.chunks(IN_LIST_CHUNK) {
+      let placeholders = vec!["?"; chunk.len()].join(", ");
+      let mut stmt = self.conn.prepare(&format!(
+        "SELECT {POSTING_COLUMNS} FROM postings WHERE transaction_id IN ({placeholders})
+         ORDER BY created_at, rowid"
+      ))?;
+      let rows = stmt.query_map(params_from_iter(chunk), |row| self.posting_from_row(row))?;
+      for posting in rows {
+        let posting = posting?;
+        grouped
+          .entry(posting.transaction_id.clone())
+          .or_default()
+          .push(posting);
+      }
+    }
+    Ok(grouped)
+  }
+
+  /// Validate and insert a transaction with its postings in one SQLite
+  /// transaction, like `createTransaction`.
+  pub fn insert_transaction(&mut self, transaction: &Transaction) -> DbResult<()> {
+    validate_transaction(transaction, &self.accounts_by_id()?)?;
+    let tx = self
+      .conn
+      .transaction_with_behavior(TransactionBehavior::Immediate)?;
+    tx.execute(
+      &format!(
+        "INSERT INTO transactions ({TRANSACTION_COLUMNS})
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+      ),
+      params![
+        transaction.id,
+        SqlDateTime(transaction.date),
+        transaction.payee,
+        transaction.memo,
+        transaction.reference,
+        transaction.tags,
+        transaction.metadata,
+        transaction.import_batch_id,
+        transaction.external_id,
+        transaction.status.as_str(),
+        SqlDateTime(transaction.created_at),
+        SqlDateTime(transaction.updated_at),
+      ],
+    )?;
+    insert_postings(&tx, self.money, &transaction.id, &transaction.postings)?;
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Replace a transaction's header and postings, like `updateTransaction`
+  /// with postings supplied.
+  pub fn update_transaction(&mut self, transaction: &Transaction) -> DbResult<()> {
+    validate_transaction(transaction, &self.accounts_by_id()?)?;
+    let tx = self
+      .conn
+      .transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let changed = tx.execute(
+      "UPDATE transactions SET date = ?2, payee = ?3, memo = ?4, reference = ?5, tags = ?6,
+         metadata = ?7, import_batch_id = ?8, external_id = ?9, status = ?10, updated_at = ?11
+       WHERE id = ?1",
+      params![
+        transaction.id,
+        SqlDateTime(transaction.date),
+        transaction.payee,
+        transaction.memo,
+        transaction.reference,
+        transaction.tags,
+        transaction.metadata,
+        transaction.import_batch_id,
+        transaction.external_id,
+        transaction.status.as_str(),
+        super::now(),
+      ],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Transaction",
+        id: transaction.id.clone(),
+      });
+    }
+    tx.execute(
+      "DELETE FROM postings WHERE transaction_id = ?1",
+      [&transaction.id],
+    )?;
+    insert_postings(&tx, self.money, &transaction.id, &transaction.postings)?;
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Delete a transaction and its postings. Refused once any posting is
+  /// reconciled, as in `deleteTransaction`.
+  pub fn delete_transaction(&self, id: &str) -> DbResult<()> {
+    let reconciled: i64 = self.conn.query_row(
+      "SELECT COUNT(*) FROM postings WHERE transaction_id = ?1 AND reconciled = 1",
+      [id],
+      |row| row.get(0),
+    )?;
+    if reconciled > 0 {
+      return Err(DbError::Conflict(
+        "Cannot delete transaction with reconciled postings. Void it instead.".into(),
+      ));
+    }
+    // Postings go with it through ON DELETE CASCADE.
+    let changed = self
+      .conn
+      .execute("DELETE FROM transactions WHERE id = ?1", [id])?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Transaction",
+        id: id.to_string(),
+      });
+    }
+    Ok(())
+  }
+
+  pub fn void_transaction(&self, id: &str) -> DbResult<()> {
+    let changed = self.conn.execute(
+      "UPDATE transactions SET status = ?2, updated_at = ?3 WHERE id = ?1",
+      params![id, TransactionStatus::Void.as_str(), super::now()],
+    )?;
+    if changed == 0 {
+      return Err(DbError::NotFound {
+        entity: "Transaction",
+        id: id.to_string(),
+      });
+    }
+    Ok(())
+  }
+
+  /// Set or clear the cleared flag on postings, like `markCleared`.
+  pub fn mark_cleared(&mut self, posting_ids: &[String], cleared: bool) -> DbResult<usize> {
+    let tx = self.conn.transaction()?;
+    let mut changed = 0;
+    {
+      let mut stmt = tx.prepare("UPDATE postings SET cleared = ?2 WHERE id = ?1")?;
+      for id in posting_ids {
+        changed += stmt.execute(params![id, cleared])?;
+      }
+    }
+    tx.commit()?;
+    Ok(changed)
+  }
+
+  /// Reconciling also clears; un-reconciling detaches the posting from its
+  /// reconciliation, like `markReconciled`.
+  pub fn mark_reconciled(&mut self, posting_ids: &[String], reconciled: bool) -> DbResult<usize> {
+    let sql = if reconciled {
+      "UPDATE postings SET reconciled = 1, cleared = 1 WHERE id = ?1"
+    } else {
+      "UPDATE postings SET reconciled = 0, reconcile_id = NULL WHERE id = ?1"
+    };
+    let tx = self.conn.transaction()?;
+    let mut changed = 0;
+    {
+      let mut stmt = tx.prepare(sql)?;
+      for id in posting_ids {
+        changed += stmt.execute([id])?;
+      }
+    }
+    tx.commit()?;
+    Ok(changed)
+  }
+}
+
+pub(super) fn insert_postings(
+  conn: &rusqlite::Connection,
+  money: crate::money_storage::MoneyStorage,
+  transaction_id: &str,
+  postings: &[Posting],
+) -> rusqlite::Result<()> {
+  let mut stmt = conn.prepare(&format!(
+    "INSERT INTO postings ({POSTING_COLUMNS})
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"
+  ))?;
+  for posting in postings {
+    stmt.execute(params![
+      posting.id,
+      transaction_id,
+      posting.account_id,
+      money.encode(posting.amount),
+      posting.is_business,
+      posting.gst_code.map(|c| c.as_str()),
+      posting.gst_rate,
+      posting.gst_amount.map(|m| money.encode(m)),
+      posting.category_split_label,
+      posting.cleared,
+      posting.reconciled,
+      posting.reconcile_id,
+      SqlDateTime(posting.created_at),
+    ])?;
+  }
+  Ok(())
+}
@@ -0,0 +1,342 @@
+//! Finding and merging the two halves of transfers imported separately into
+//! each account, mirroring `TransferMatchingService` in
+//! `transferMatchingService.ts`. Matching itself is in [`crate::matching`].
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::{params, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+use super::transactions::insert_postings;
+use super::{new_id, Database, DbError, DbResult, SqlDateTime};
+use crate::matching::{
+  match_transfer_candidates, TransferCandidate, TransferMatchConfig, TransferMatchPreview,
+  TransferMatchType,
+};
+use crate::model::{Account, AccountKind, Posting, Transaction, TransactionStatus};
+use crate::money::Money;
+use crate::validation::validate_transaction;
+
+const CONFIG_PREFIX: &str = "transferMatching_";
+
+/// Two transactions to merge into one transfer; the first is kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferPair {
+  pub candidate_a_id: String,
+  pub candidate_b_id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferCommitResult {
+  pub merged: usize,
+  pub skipped: usize,
+  /// Why each skipped pair couldn't be merged.
+  pub errors: Vec<String>,
+}
+
+/// The setting key for a pair of accounts, the same whichever way round.
+fn config_key(account_a: &str, account_b: &str) -> String {
+  let (first, second) = if account_a <= account_b {
+    (account_a, account_b)
+  } else {
+    (account_b, account_a)
+  };
+  format!("{CONFIG_PREFIX}{first}_{second}")
+}
+
+/// The first of a transaction's postings to an account of `kind`.
+fn posting_of<'a>(
+  transaction: &'a Transaction,
+  accounts: &HashMap<String, Account>,
+  kind: AccountKind,
+) -> Option<&'a Posting> {
+  transaction.postings.iter().find(|posting| {
+    accounts
+      .get(&posting.account_id)
+      .is_some_and(|account| account.kind == kind)
+  })
+}
+
+/// Whether two real-account amounts can be the sides of one transfer: money
+/// out of one account and into the other.
+fn opposite_signs(a: Money, b: Money) -> bool {
+  a.is_positive() && b.is_negative() || a.is_negative() && b.is_positive()
+}
+
+impl Database {
+  /// How transfers between the two accounts are matched: what was saved
+  /// for them, or the defaults.
+  pub fn transfer_match_config(
+    &self,
+    account_a: &str,
+    account_b: &str,
+  ) -> DbResult<TransferMatchConfig> {
+    Ok(
+      self
+        .setting_json(&config_key(account_a, account_b))?
+        .unwrap_or_default(),
+    )
+  }
+
+  pub fn save_transfer_match_config(
+    &self,
+    account_a: &str,
+    account_b: &str,
+    config: &TransferMatchConfig,
+  ) -> DbResult<()> {
+    self.set_setting_json(&config_key(account_a, account_b), config)
+  }
+
+  /// Transactions on `account_id` that look like one side of a transfer,
+  /// oldest first, like `findTransferCandidates`: exactly two postings, the
+  /// other to a category that's uncategorised or about transfers, or a
+  /// payee with a transfer keyword. The range is widened a day either side
+  /// for time zones.
+  pub fn transfer_candidates(
+    &self,
+    account_id: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    config: &TransferMatchConfig,
+  ) -> DbResult<Vec<TransferCandidate>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT DISTINCT t.id FROM transactions t JOIN postings p ON p.transaction_id = t.id
+       WHERE p.account_id = ?1 AND t.status = ?2
+         AND (?3 IS NULL OR t.date >= ?3) AND (?4 IS NULL OR t.date <= ?4)",
+    )?;
+    let ids = stmt
+      .query_map(
+        params![
+          account_id,
+          TransactionStatus::Normal.as_str(),
+          from.map(|from| SqlDateTime(from - Duration::days(1))),
+          to.map(|to| SqlDateTime(to + Duration::days(1))),
+        ],
+        |row| row.get::<_, String>(0),
+      )?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    let mut transactions: Vec<Transaction> = self.transactions_by_id(&ids)?.into_values().collect();
+    transactions.sort_by_key(|t| (t.date, t.created_at));
+    let accounts = self.accounts_by_id()?;
+
+    let mut candidates = Vec::new();
+    for transaction in transactions {
+      let [first, second] = transaction.postings.as_slice() else {
+        continue;
+      };
+      let (real, other) = match (
+        first.account_id == account_id,
+        second.account_id == account_id,
+      ) {
+        (true, false) => (first, second),
+        (false, true) => (second, first),
+        _ => continue,
+      };
+      // A posting to another real account is already a proper transfer.
+      let Some(category) = accounts
+        .get(&other.account_id)
+        .filter(|account| account.kind == AccountKind::Category)
+      else {
+        continue;
+      };
+      if !config.is_transfer_likely(&category.name, &transaction.payee) {
+        continue;
+      }
+      candidates.push(TransferCandidate {
+        transaction_id: transaction.id.clone(),
+        date: transaction.date,
+        payee: transaction.payee.clone(),
+        memo: transaction.memo.clone(),
+        account_id: account_id.to_string(),
+        posting_id: real.id.clone(),
+        amount: real.amount,
+        category_posting_id: other.id.clone(),
+        category_account_id: category.id.clone(),
+        category_name: category.name.clone(),
+        is_reconciled: real.reconciled,
+      });
+    }
+    Ok(candidates)
+  }
+
+  /// Propose transfers between two accounts, like `matchTransfers`, using
+  /// the pair's saved configuration.
+  pub fn match_transfers(
+    &self,
+    account_a: &str,
+    account_b: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> DbResult<TransferMatchPreview> {
+    let config = self.transfer_match_config(account_a, account_b)?;
+    let candidates_a = self.transfer_candidates(account_a, from, to, &config)?;
+    let candidates_b = self.transfer_candidates(account_b, from, to, &config)?;
+    Ok(match_transfer_candidates(
+      candidates_a,
+      candidates_b,
+      &config,
+    ))
+  }
+
+  /// Merge each pair into one transfer, like `commitMatches`. Each pair is
+  /// merged on its own, so one that can't be doesn't stop the rest.
+  pub fn commit_transfer_matches(
+    &mut self,
+    pairs: &[TransferPair],
+  ) -> DbResult<TransferCommitResult> {
+    let mut result = TransferCommitResult::default();
+    for pair in pairs {
+      match self.merge_transfer(&pair.candidate_a_id, &pair.candidate_b_id) {
+        Ok(()) => result.merged += 1,
+        Err(DbError::Sqlite(err)) => return Err(DbError::Sqlite(err)),
+        Err(err) => {
+          result.skipped += 1;
+          result.errors.push(err.to_string());
+        }
+      }
+    }
+    Ok(result)
+  }
+
+  /// Merge every exact match between the two accounts in one go. An exact
+  /// match can still have both sides going the same way, which isn't a
+  /// transfer, so those are left for the user to look at.
+  pub fn commit_exact_transfer_matches(
+    &mut self,
+    account_a: &str,
+    account_b: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> DbResult<TransferCommitResult> {
+    let pairs: Vec<TransferPair> = self
+      .match_transfers(account_a, account_b, from, to)?
+      .matches
+      .into_iter()
+      .filter(|m| {
+        m.match_type == TransferMatchType::Exact
+          && opposite_signs(m.candidate_a.amount, m.candidate_b.amount)
+      })
+      .map(|m| TransferPair {
+        candidate_a_id: m.candidate_a.transaction_id,
+        candidate_b_id: m.candidate_b.transaction_id,
+      })
+      .collect();
+    self.commit_transfer_matches(&pairs)
+  }
+
+  /// Make transaction A the whole transfer: its category posting is
+  /// replaced by B's real account posting, B's amount kept and A's set to
+  /// balance it, dated the earlier of the two. B is deleted. Both must be
+  /// plain two-posting transactions; a split would lose its other postings.
+  fn merge_transfer(&mut self, a_id: &str, b_id: &str) -> DbResult<()> {
+    // Read under the write lock, so nothing changes A or B before they're
+    // rewritten.
+    let tx = rusqlite::Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+    let a = self.transaction(a_id)?;
+    let b = self.transaction(b_id)?;
+    if a.postings.len() != 2 || b.postings.len() != 2 {
+      return Err(DbError::Conflict(
+        "Only transactions with exactly two postings can be merged".into(),
+      ));
+    }
+    let accounts = self.accounts_by_id()?;
+    let (Some(a_real), Some(_), Some(b_real)) = (
+      posting_of(&a, &accounts, AccountKind::Transfer),
+      posting_of(&a, &accounts, AccountKind::Category),
+      posting_of(&b, &accounts, AccountKind::Transfer),
+    ) else {
+      return Err(DbError::Conflict(
+        "Cannot identify transfer postings".into(),
+      ));
+    };
+    if a_id == b_id || a_real.account_id == b_real.account_id {
+      return Err(DbError::Conflict(
+        "Both transactions post to the same account".into(),
+      ));
+    }
+    // A's side is set to balance B's, which would flip its sign.
+    if !opposite_signs(a_real.amount, b_real.amount) {
+      return Err(DbError::Conflict(
+        "Both transactions move money the same way".into(),
+      ));
+    }
+    if a_real.reconciled || b_real.reconciled {
+      return Err(DbError::Conflict(
+        "Cannot merge reconciled transactions".into(),
+      ));
+    }
+
+    let mut metadata = a
+      .metadata
+      .as_deref()
+      .and_then(|json| serde_json::from_str::<serde_json::Map<_, _>>(json).ok())
+      .unwrap_or_default();
+    metadata.insert("transferMatched".into(), true.into());
+    metadata.insert("mergedTransactionId".into(), b.id.clone().into());
+    metadata.insert("mergedPayee".into(), b.payee.clone().into());
+    metadata.insert(
+      "mergedAt".into(),
+      Utc::now()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+        .into(),
+    );
+    let payee = [&a.payee, &b.payee]
+      .into_iter()
+      .find(|payee| !payee.is_empty())
+      .cloned()
+      .unwrap_or_else(|| "Transfer".to_string());
+    let merged = Transaction {
+      date: a.date.min(b.date),
+      payee,
+      metadata: Some(serde_json::Value::Object(metadata).to_string()),
+      postings: vec![
+        Posting {
+          amount: -b_real.amount,
+          ..a_real.clone()
+        },
+        Posting {
+          id: new_id(),
+          transaction_id: a.id.clone(),
+          account_id: b_real.account_id.clone(),
+          amount: b_real.amount,
+          is_business: b_real.is_business,
+          gst_code: None,
+          gst_rate: None,
+          gst_amount: None,
+          category_split_label: None,
+          cleared: b_real.cleared,
+          reconciled: false,
+          reconcile_id: None,
+          created_at: Utc::now(),
+        },
+      ],
+      ..a.clone()
+    };
+    validate_transaction(&merged, &accounts)?;
+
+    tx.execute(
+      "UPDATE transactions SET date = ?2, payee = ?3, metadata = ?4, updated_at = ?5
+       WHERE id = ?1",
+      params![
+        merged.id,
+        SqlDateTime(merged.date),
+        merged.payee,
+        merged.metadata,
+        super::now(),
+      ],
+    )?;
+    tx.execute(
+      "DELETE FROM postings WHERE transaction_id = ?1",
+      [&merged.id],
+    )?;
+    insert_postings(&tx, self.money, &merged.id, &merged.postings)?;
+    // B's postings go with it through ON DELETE CASCADE.
+    tx.execute("DELETE FROM transactions WHERE id = ?1", [&b.id])?;
+    tx.commit()?;
+    Ok(())
+  }
+}
@@ -0,0 +1,249 @@
+//! Passphrase-encrypted books, using SQLCipher.
+//!
+//! The key is derived here rather than by SQLCipher: Argon2id stretches the
+//! passphrase with a random 16-byte salt into a 256-bit key, and SQLCipher
+//! is handed the raw key and salt (`x'<key><salt>'`). SQLCipher writes that
+//! salt into the first 16 bytes of the file, so the book carries everything
+//! needed to derive its key again and nothing is stored beside it.
+//!
+//! Prisma can't open SQLCipher files, so an encrypted book is only usable
+//! through the native commands.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use zeroize::Zeroizing;
+
+use crate::backup::{sibling, swap_in};
+
+pub const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// SQLite's smallest page size; every database file is a multiple of it.
+const MIN_PAGE_SIZE: u64 = 512;
+/// The header every plaintext SQLite file starts with.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Argon2id with OWASP's recommended 19 MiB, two passes. Changing these
+/// makes existing books unreadable.
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_PASSES: u32 = 2;
+const ARGON2_LANES: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error("This book is encrypted; enter its passphrase to open it")]
+  Locked,
+  #[error("Wrong passphrase")]
+  WrongPassphrase,
+  #[error("Passphrase must not be empty")]
+  EmptyPassphrase,
+  #[error("key derivation failed: {0}")]
+  KeyDerivation(String),
+}
+
+/// A book's SQLCipher key and the salt it was derived with. The key bytes
+/// are wiped when dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BookKey {
+  key: Zeroizing<[u8; KEY_LEN]>,
+  salt: [u8; SALT_LEN],
+}
+
+impl fmt::Debug for BookKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("BookKey").finish_non_exhaustive()
+  }
+}
+
+impl BookKey {
+  /// A key for a new book, with a fresh random salt.
+  pub fn generate(passphrase: &str) -> Result<Self, EncryptionError> {
+    BookKey::derive(passphrase, random_bytes()?)
+  }
+
+  /// The key for the encrypted book at `path`, using the salt in its header.
+  /// Whether the passphrase is right only shows when the book is opened.
+  pub fn for_book(path: &Path, passphrase: &str) -> Result<Self, EncryptionError> {
+    let mut salt = [0; SALT_LEN];
+    File::open(path)?.read_exact(&mut salt)?;
+    BookKey::derive(passphrase, salt)
+  }
+
+  pub fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, EncryptionError> {
+    Ok(BookKey {
+      key: derive_key(passphrase, &salt)?,
+      salt,
+    })
+  }
+
+  pub fn salt(&self) -> [u8; SALT_LEN] {
+    self.salt
+  }
+
+  /// The key as SQLCipher's raw key-and-salt literal.
+  fn sqlcipher_literal(&self) -> Zeroizing<String> {
+    let mut literal = Zeroizing::new(String::with_capacity(3 + 2 * (KEY_LEN + SALT_LEN)));
+    literal.push_str("x'");
+    for byte in self.key.iter().chain(&self.salt) {
+      literal.push_str(&format!("{byte:02X}"));
+    }
+    literal.push('\'');
+    literal
+  }
+
+  /// Key `conn`. Must come before anything else reads the database.
+  pub fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+    conn.pragma_update(None, "key", &*self.sqlcipher_literal())
+  }
+}
+
+/// Stretch `passphrase` into a 256-bit key with Argon2id.
+pub(crate) fn derive_key(
+  passphrase: &str,
+  salt: &[u8],
+) -> Result<Zeroizing<[u8; KEY_LEN]>, EncryptionError> {
+  if passphrase.is_empty() {
+    return Err(EncryptionError::EmptyPassphrase);
+  }
+  let params = Params::new(
+    ARGON2_MEMORY_KIB,
+    ARGON2_PASSES,
+    ARGON2_LANES,
+    Some(KEY_LEN),
+  )
+  .map_err(|err| EncryptionError::KeyDerivation(err.to_string()))?;
+  let mut key = Zeroizing::new([0; KEY_LEN]);
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+    .map_err(|err| EncryptionError::KeyDerivation(err.to_string()))?;
+  Ok(key)
+}
+
+/// Bytes from the OS's secure random source, for salts and nonces.
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N], EncryptionError> {
+  let mut bytes = [0; N];
+  getrandom::fill(&mut bytes).map_err(|err| EncryptionError::KeyDerivation(err.to_string()))?;
+  Ok(bytes)
+}
+
+/// Whether the file at `path` is encrypted: it's made of whole pages, as
+/// SQLCipher writes it, but doesn't start with SQLite's header. Missing and
+/// empty files aren't.
+pub fn is_encrypted(path: &Path) -> io::Result<bool> {
+  let mut header = [0; SQLITE_HEADER.len()];
+  let read = File::open(path).and_then(|mut file| {
+    file.read_exact(&mut header)?;
+    file.metadata()
+  });
+  match read {
+    Ok(metadata) => Ok(&header != SQLITE_HEADER && metadata.len() % MIN_PAGE_SIZE == 0),
+    Err(err)
+      if matches!(
+        err.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::UnexpectedEof
+      ) =>
+    {
+      Ok(false)
+    }
+    Err(err) => Err(err),
+  }
+}
+
+/// Open the book at `path` (creating it if needed), keyed with `key` if
+/// given, and check that it can be read.
+pub fn open(path: &Path, key: Option<&BookKey>) -> Result<Connection, EncryptionError> {
+  open_with_flags(path, OpenFlags::default(), key)
+}
+
+pub fn open_with_flags(
+  path: &Path,
+  flags: OpenFlags,
+  key: Option<&BookKey>,
+) -> Result<Connection, EncryptionError> {
+  let conn = Connection::open_with_flags(path, flags)?;
+  if let Some(key) = key {
+    key.apply(&conn)?;
+  }
+  match conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(())) {
+    Ok(()) => Ok(conn),
+    Err(err) if err.sqlite_error_code() == Some(ErrorCode::NotADatabase) => Err(match key {
+      Some(_) => EncryptionError::WrongPassphrase,
+      None => EncryptionError::Locked,
+    }),
+    Err(err) => Err(err.into()),
+  }
+}
+
+/// Copy the database open on `conn` to `destination` with the online backup
+/// API, as a standalone (non-WAL) file encrypted with `key`, which must be
+/// the key `conn` was opened with.
+pub fn copy_database(
+  conn: &Connection,
+  destination: &Path,
+  key: Option<&BookKey>,
+) -> Result<(), EncryptionError> {
+  let mut copy = Connection::open(destination)?;
+  if let Some(key) = key {
+    key.apply(&copy)?;
+  }
+  Backup::new(conn, &mut copy)?.run_to_completion(100, std::time::Duration::ZERO, None)?;
+  // The copy inherits WAL mode; make it a standalone file, as
+  // `VACUUM INTO` would, so opening it doesn't leave -wal/-shm behind.
+  copy.query_row("PRAGMA journal_mode=DELETE", [], |_| Ok(()))?;
+  Ok(())
+}
+
+/// Re-encrypt the book at `path` from `from` to `to`. Either may be `None`,
+/// so this also encrypts a plaintext book and decrypts an encrypted one.
+///
+/// The book is exported to a new file with `sqlcipher_export` and swapped in
+/// only once that succeeds. Nothing else may have the book open.
+pub fn rekey(
+  path: &Path,
+  from: Option<&BookKey>,
+  to: Option<&BookKey>,
+) -> Result<(), EncryptionError> {
+  let staged = sibling(path, ".rekeying");
+  remove_if_exists(&staged)?;
+
+  let exported = (|| -> Result<(), EncryptionError> {
+    let conn = open(path, from)?;
+    let literal = to.map(BookKey::sqlcipher_literal).unwrap_or_default();
+    conn.execute(
+      "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+      (staged.to_string_lossy(), literal.as_str()),
+    )?;
+    conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()))?;
+    conn.execute("DETACH DATABASE rekeyed", [])?;
+    // Closing the last connection checkpoints the WAL into the old file,
+    // which is about to be replaced anyway.
+    conn.close().map_err(|(_, err)| err)?;
+    // Make sure the new file opens with the new key before it replaces the
+    // old one.
+    open(&staged, to)?;
+    Ok(())
+  })();
+  if let Err(err) = exported {
+    let _ = std::fs::remove_file(&staged);
+    return Err(err);
+  }
+
+  swap_in(&staged, path, ".before-rekey")?;
+  Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+  match std::fs::remove_file(path) {
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+    result => result,
+  }
+}
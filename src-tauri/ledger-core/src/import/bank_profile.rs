@@ -0,0 +1,589 @@
+//! Bank profiles: how to read one bank's PDF statements, as data.
+//!
+//! A profile is a TOML file (see `bank-profiles/` for the ones built in)
+//! giving the patterns that recognise the bank's statements, find the
+//! account number, period and balances, and pick transaction rows out of
+//! the statement text. Adding a bank is adding a file, either to
+//! `bank-profiles/` or to the profiles folder in the app's data directory,
+//! where a file with a built-in profile's `id` replaces it.
+//!
+//! Patterns are [`regex`] syntax and use named groups for what they find:
+//!
+//! - `account_number`: `number` (spaces and dashes are dropped), and
+//!   `account_name`: `name`. Without one, the first group.
+//! - `period`: `start` and `end`, read with [`parse_date`].
+//! - `opening_balance`, `closing_balance`: `amount`.
+//! - `rows`: `date` (optional; undated rows such as interest charges are
+//!   dated the end of the period), `description`, `balance` (optional),
+//!   and either `amount` or `debit` and `credit`.
+//!
+//! Statement patterns run over the whole text, so `\s` spans lines; the
+//! others run over one line at a time.
+
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use super::{parse_amount, parse_date, ImportError, RowError, StatementLine};
+use crate::money::Money;
+
+/// The profiles that ship with the app, most specific first.
+const BUILT_IN: [&str; 3] = [
+  include_str!("../../bank-profiles/commbank-cc.toml"),
+  include_str!("../../bank-profiles/commbank-savings.toml"),
+  include_str!("../../bank-profiles/generic.toml"),
+];
+
+/// How a row's `amount` is signed on the statement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmountSign {
+  /// Money in is positive, as on most transaction accounts.
+  #[default]
+  CreditPositive,
+  /// Money out is positive, as on most credit card statements.
+  DebitPositive,
+  /// Amounts are unsigned; whether each is money in or out is told by the
+  /// running balance, or failing that by `debit_keywords`.
+  Balance,
+}
+
+/// How sure a profile is that it read a statement properly, going by how
+/// much of it was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+  High,
+  Medium,
+  Low,
+}
+
+/// What a profile read from a statement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfStatement {
+  /// The profile that read it.
+  pub profile_id: String,
+  pub account_id: Option<String>,
+  pub account_name: Option<String>,
+  /// The statement period.
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+  pub opening_balance: Option<Money>,
+  pub closing_balance: Option<Money>,
+  pub lines: Vec<StatementLine>,
+  pub errors: Vec<RowError>,
+  pub confidence: Confidence,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+  id: String,
+  name: String,
+  #[serde(default)]
+  detect: Vec<Vec<String>>,
+  #[serde(default)]
+  amounts: AmountSign,
+  #[serde(default)]
+  statement: StatementPatterns<String>,
+  transactions: TransactionPatterns<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatementPatterns<P> {
+  #[serde(default = "Vec::new")]
+  account_number: Vec<P>,
+  #[serde(default = "Vec::new")]
+  account_name: Vec<P>,
+  #[serde(default = "Vec::new")]
+  period: Vec<P>,
+  #[serde(default = "Vec::new")]
+  opening_balance: Vec<P>,
+  #[serde(default = "Vec::new")]
+  closing_balance: Vec<P>,
+}
+
+impl<P> Default for StatementPatterns<P> {
+  fn default() -> Self {
+    StatementPatterns {
+      account_number: Vec::new(),
+      account_name: Vec::new(),
+      period: Vec::new(),
+      opening_balance: Vec::new(),
+      closing_balance: Vec::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionPatterns<P> {
+  /// Lines that start the transaction list, such as its column headings.
+  /// Without any, the whole statement is read.
+  #[serde(default = "Vec::new")]
+  start: Vec<P>,
+  /// Lines that end it, until the next `start`.
+  #[serde(default = "Vec::new")]
+  end: Vec<P>,
+  /// Lines in the list that aren't transactions, such as page headers.
+  #[serde(default = "Vec::new")]
+  skip: Vec<P>,
+  /// For rows that wrap: the line a row starts on. The lines after it are
+  /// joined on until the next row, a blank line or a `skip`ped one, and a
+  /// row that none of `rows` match is reported.
+  #[serde(default)]
+  row_start: Option<P>,
+  rows: Vec<P>,
+  /// The [`chrono` format](chrono::format::strftime) of row dates. Without
+  /// a year in it, the year is the statement period's. Without a format,
+  /// dates are read with [`parse_date`].
+  #[serde(default)]
+  date_format: Option<String>,
+  #[serde(default)]
+  debit_keywords: Vec<String>,
+}
+
+/// A bank's statement layout, ready to read statements with.
+#[derive(Debug, Clone)]
+pub struct BankProfile {
+  pub id: String,
+  pub name: String,
+  detect: Vec<Vec<Regex>>,
+  amounts: AmountSign,
+  statement: StatementPatterns<Regex>,
+  transactions: TransactionPatterns<Regex>,
+}
+
+impl BankProfile {
+  /// Read a profile file, checking its patterns.
+  pub fn from_toml(text: &str) -> Result<Self, ImportError> {
+    Self::parse(text).map_err(ImportError::Profile)
+  }
+
+  fn parse(text: &str) -> Result<Self, String> {
+    let file: ProfileFile = toml::from_str(text).map_err(|err| err.to_string())?;
+    let id = file.id;
+    let compile = |pattern: &String| Regex::new(pattern).map_err(|err| format!("{id}: {err}"));
+    let compile_all =
+      |patterns: &Vec<String>| patterns.iter().map(compile).collect::<Result<Vec<_>, _>>();
+    let require = |patterns: &[Regex], field: &str, groups: &[&str]| {
+      for pattern in patterns {
+        if !groups
+          .iter()
+          .any(|group| pattern.capture_names().flatten().any(|name| name == *group))
+        {
+          return Err(format!(
+            "{id}: a {field} pattern has no `{}` group",
+            groups.join("` or `")
+          ));
+        }
+      }
+      Ok(())
+    };
+
+    let statement = StatementPatterns {
+      account_number: compile_all(&file.statement.account_number)?,
+      account_name: compile_all(&file.statement.account_name)?,
+      period: compile_all(&file.statement.period)?,
+      opening_balance: compile_all(&file.statement.opening_balance)?,
+      closing_balance: compile_all(&file.statement.closing_balance)?,
+    };
+    require(&statement.period, "period", &["start"])?;
+    require(&statement.period, "period", &["end"])?;
+    require(&statement.opening_balance, "opening_balance", &["amount"])?;
+    require(&statement.closing_balance, "closing_balance", &["amount"])?;
+
+    let transactions = TransactionPatterns {
+      start: compile_all(&file.transactions.start)?,
+      end: compile_all(&file.transactions.end)?,
+      skip: compile_all(&file.transactions.skip)?,
+      row_start: file
+        .transactions
+        .row_start
+        .as_ref()
+        .map(compile)
+        .transpose()?,
+      rows: compile_all(&file.transactions.rows)?,
+      date_format: file.transactions.date_format,
+      debit_keywords: file
+        .transactions
+        .debit_keywords
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect(),
+    };
+    if transactions.rows.is_empty() {
+      return Err(format!("{id}: there are no rows"));
+    }
+    require(&transactions.rows, "rows", &["description"])?;
+    require(&transactions.rows, "rows", &["amount", "debit", "credit"])?;
+
+    Ok(BankProfile {
+      detect: file
+        .detect
+        .iter()
+        .map(compile_all)
+        .collect::<Result<_, _>>()?,
+      id,
+      name: file.name,
+      amounts: file.amounts,
+      statement,
+      transactions,
+    })
+  }
+
+  /// The profiles that ship with the app.
+  pub fn built_in() -> Vec<BankProfile> {
+    BUILT_IN
+      .iter()
+      .map(|text| BankProfile::from_toml(text).expect("built-in bank profiles are valid"))
+      .collect()
+  }
+
+  /// Whether `text` looks like this bank's statement: every pattern in one
+  /// of the `detect` sets matches. A profile without any is a fallback.
+  pub fn detects(&self, text: &str) -> bool {
+    self
+      .detect
+      .iter()
+      .any(|set| set.iter().all(|pattern| pattern.is_match(text)))
+  }
+
+  fn is_fallback(&self) -> bool {
+    self.detect.is_empty()
+  }
+
+  /// Read a statement's text.
+  pub fn read(&self, text: &str) -> PdfStatement {
+    let patterns = &self.statement;
+    let account_id = first_capture(&patterns.account_number, text, "number").map(|number| {
+      number
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+    });
+    let account_name = first_capture(&patterns.account_name, text, "name");
+    let (start, end) = patterns
+      .period
+      .iter()
+      .find_map(|pattern| {
+        let captures = pattern.captures(text)?;
+        Some((
+          parse_date(captures.name("start")?.as_str()),
+          parse_date(captures.name("end")?.as_str()),
+        ))
+      })
+      .unwrap_or_default();
+    let balance = |patterns: &[Regex]| {
+      first_capture(patterns, text, "amount").and_then(|amount| parse_statement_amount(&amount))
+    };
+    let opening_balance = balance(&patterns.opening_balance);
+
+    let mut reader = RowReader {
+      profile: self,
+      start,
+      end,
+      previous_balance: opening_balance,
+      lines: Vec::new(),
+      errors: Vec::new(),
+    };
+    let transactions = &self.transactions;
+    let mut in_list = transactions.start.is_empty();
+    let mut pending: Option<(u64, String)> = None;
+    for (index, line) in text.lines().enumerate() {
+      let number = index as u64 + 1;
+      let line = line.trim();
+      let matches = |patterns: &[Regex]| patterns.iter().any(|pattern| pattern.is_match(line));
+      let starts_list = matches(&transactions.start);
+      let ends_list = matches(&transactions.end);
+      if starts_list || ends_list || !in_list || line.is_empty() || matches(&transactions.skip) {
+        reader.finish(pending.take());
+        in_list = starts_list || (in_list && !ends_list);
+        continue;
+      }
+      match &transactions.row_start {
+        Some(row_start) if row_start.is_match(line) => {
+          reader.finish(pending.replace((number, line.to_string())));
+        }
+        Some(_) => {
+          if let Some((_, row)) = &mut pending {
+            row.push(' ');
+            row.push_str(line);
+          }
+        }
+        None => {
+          reader.row(number, line, false);
+        }
+      }
+    }
+    reader.finish(pending);
+
+    let mut statement = PdfStatement {
+      profile_id: self.id.clone(),
+      account_id,
+      account_name,
+      start,
+      end,
+      opening_balance,
+      closing_balance: balance(&patterns.closing_balance),
+      lines: reader.lines,
+      errors: reader.errors,
+      confidence: Confidence::Low,
+    };
+    statement.confidence = confidence(&statement);
+    statement
+  }
+}
+
+/// The first of `patterns` to match `text`, by its `group` or else its first
+/// group.
+fn first_capture(patterns: &[Regex], text: &str, group: &str) -> Option<String> {
+  patterns.iter().find_map(|pattern| {
+    let captures = pattern.captures(text)?;
+    let found = captures.name(group).or_else(|| captures.get(1))?;
+    Some(found.as_str().trim().to_string())
+  })
+}
+
+/// Transaction rows read so far, and the balance the next one follows.
+struct RowReader<'a> {
+  profile: &'a BankProfile,
+  start: Option<NaiveDate>,
+  end: Option<NaiveDate>,
+  previous_balance: Option<Money>,
+  lines: Vec<StatementLine>,
+  errors: Vec<RowError>,
+}
+
+impl RowReader<'_> {
+  fn finish(&mut self, row: Option<(u64, String)>) {
+    if let Some((line, text)) = row {
+      self.row(line, &text, true);
+    }
+  }
+
+  /// Read a row. Rows `required` to be transactions are reported if none
+  /// of the profile's patterns match them.
+  fn row(&mut self, line: u64, text: &str, required: bool) {
+    let Some(captures) = self
+      .profile
+      .transactions
+      .rows
+      .iter()
+      .find_map(|pattern| pattern.captures(text))
+    else {
+      if required {
+        self.errors.push(RowError {
+          line,
+          message: format!("Unrecognised row: {text}"),
+        });
+      }
+      return;
+    };
+    match self.statement_line(line, &captures) {
+      Ok(Some(parsed)) => {
+        if parsed.balance.is_some() {
+          self.previous_balance = parsed.balance;
+        }
+        self.lines.push(parsed);
+      }
+      Ok(None) => {}
+      Err(message) => self.errors.push(RowError { line, message }),
+    }
+  }
+
+  /// The row's transaction, or nothing for rows of no amount, such as a
+  /// waived fee.
+  fn statement_line(
+    &self,
+    line: u64,
+    captures: &Captures,
+  ) -> Result<Option<StatementLine>, String> {
+    let group = |name: &str| captures.name(name).map(|found| found.as_str().trim());
+    let amount_of = |name: &str| match group(name) {
+      Some(text) => parse_statement_amount(text)
+        .map(Some)
+        .ok_or_else(|| format!("Unrecognised amount: {text}")),
+      None => Ok(None),
+    };
+
+    let date = match group("date") {
+      Some(text) => self.row_date(text)?,
+      None => self
+        .end
+        .ok_or("Undated row and no statement period to date it by")?,
+    };
+    let description = group("description")
+      .unwrap_or_default()
+      .trim_end_matches(['$', '(', ')'])
+      .trim();
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    let balance = amount_of("balance")?;
+    let (debit, credit) = (amount_of("debit")?, amount_of("credit")?);
+    let amount = if debit.is_some() || credit.is_some() {
+      credit.unwrap_or(Money::ZERO).abs() - debit.unwrap_or(Money::ZERO).abs()
+    } else {
+      let amount = amount_of("amount")?.unwrap_or(Money::ZERO);
+      match self.profile.amounts {
+        AmountSign::CreditPositive => amount,
+        AmountSign::DebitPositive => -amount,
+        AmountSign::Balance => self.signed_by_balance(amount.abs(), balance, &description),
+      }
+    };
+    if amount.is_zero() {
+      return Ok(None);
+    }
+    Ok(Some(StatementLine {
+      line,
+      date,
+      payee: description,
+      amount,
+      reference: None,
+      external_id: None,
+      memo: None,
+      balance,
+    }))
+  }
+
+  /// Money in if the balance went up by `amount`, out if it went down;
+  /// failing that, out if the description has a debit keyword (or there
+  /// are none to go by).
+  fn signed_by_balance(&self, amount: Money, balance: Option<Money>, description: &str) -> Money {
+    if let (Some(previous), Some(balance)) = (self.previous_balance, balance) {
+      if balance - previous == amount {
+        return amount;
+      }
+      if previous - balance == amount {
+        return -amount;
+      }
+    }
+    let keywords = &self.profile.transactions.debit_keywords;
+    let description = description.to_lowercase();
+    if keywords.is_empty() || keywords.iter().any(|word| description.contains(word)) {
+      -amount
+    } else {
+      amount
+    }
+  }
+
+  /// A row's date, taking the year from the statement period if the
+  /// profile's dates don't have one.
+  fn row_date(&self, text: &str) -> Result<NaiveDate, String> {
+    let unrecognised = || format!("Unrecognised date: {text}");
+    let Some(format) = &self.profile.transactions.date_format else {
+      return parse_date(text).ok_or_else(unrecognised);
+    };
+    if format.contains("%Y") || format.contains("%y") {
+      return NaiveDate::parse_from_str(text, format).map_err(|_| unrecognised());
+    }
+    let end = self
+      .end
+      .or(self.start)
+      .ok_or("No statement period to tell the year by")?;
+    let in_year =
+      |year: i32| NaiveDate::parse_from_str(&format!("{text} {year}"), &format!("{format} %Y"));
+    let date = in_year(end.year()).map_err(|_| unrecognised())?;
+    // Across new year, December's rows are the year before.
+    if date > end {
+      return in_year(end.year() - 1).map_err(|_| unrecognised());
+    }
+    Ok(date)
+  }
+}
+
+/// A statement amount, as [`parse_amount`] reads them, and also with a
+/// trailing minus (`4,113.69-`) or a space for the decimal point
+/// (`2,621 43`, as CommBank has printed them since May 2026).
+fn parse_statement_amount(text: &str) -> Option<Money> {
+  let text = text.trim();
+  let (text, negative) = match text.strip_suffix('-') {
+    Some(rest) => (rest.trim_end(), true),
+    None => (text, false),
+  };
+  let bytes = text.as_bytes();
+  let digits_end = text
+    .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace())
+    .len();
+  let spaced_cents = digits_end >= 4
+    && bytes[digits_end - 3] == b' '
+    && bytes[digits_end - 4].is_ascii_digit()
+    && bytes[digits_end - 2..digits_end]
+      .iter()
+      .all(u8::is_ascii_digit);
+  let amount = if spaced_cents {
+    let mut fixed = text.to_string();
+    fixed.replace_range(digits_end - 3..digits_end - 2, ".");
+    parse_amount(&fixed)?
+  } else {
+    parse_amount(text)?
+  };
+  Some(if negative { -amount } else { amount })
+}
+
+/// As `assessConfidence` scores it: an account number and a period count
+/// most, then balances and transactions, and rows with running balances.
+fn confidence(statement: &PdfStatement) -> Confidence {
+  let lines = &statement.lines;
+  let with_balance = lines.iter().filter(|line| line.balance.is_some()).count();
+  let score = [
+    (statement.account_id.is_some(), 20),
+    (statement.start.is_some() && statement.end.is_some(), 20),
+    (statement.opening_balance.is_some(), 10),
+    (statement.closing_balance.is_some(), 10),
+    (!lines.is_empty(), 20),
+    (lines.len() > 10, 10),
+    (!lines.is_empty() && with_balance * 5 > lines.len() * 4, 10),
+  ]
+  .iter()
+  .filter(|(counts, _)| *counts)
+  .map(|(_, points)| points)
+  .sum::<u32>();
+  match score {
+    70.. => Confidence::High,
+    40.. => Confidence::Medium,
+    _ => Confidence::Low,
+  }
+}
+
+/// The profile for a statement's text: the first that recognises it, or
+/// else the first fallback.
+pub fn detect_bank_profile<'a>(text: &str, profiles: &'a [BankProfile]) -> Option<&'a BankProfile> {
+  profiles
+    .iter()
+    .find(|profile| profile.detects(text))
+    .or_else(|| profiles.iter().find(|profile| profile.is_fallback()))
+}
+
+/// The `*.toml` profiles in `dir`, in name order, then the built-in ones
+/// they don't replace. A missing `dir` just has none.
+pub fn load_bank_profiles(dir: &Path) -> Result<Vec<BankProfile>, ImportError> {
+  let mut paths = match std::fs::read_dir(dir) {
+    Ok(entries) => entries
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<Vec<_>, _>>()?,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+    Err(err) => return Err(err.into()),
+  };
+  paths.retain(|path| path.extension().is_some_and(|ext| ext == "toml"));
+  paths.sort();
+
+  let mut profiles = Vec::new();
+  for path in paths {
+    let text = std::fs::read_to_string(&path)?;
+    let profile = BankProfile::parse(&text).map_err(|err| {
+      let name = path.file_name().unwrap_or_default().to_string_lossy();
+      ImportError::Profile(format!("{name}: {err}"))
+    })?;
+    profiles.push(profile);
+  }
+  for built_in in BankProfile::built_in() {
+    if !profiles.iter().any(|profile| profile.id == built_in.id) {
+      profiles.push(built_in);
+    }
+  }
+  Ok(profiles)
+}
@@ -0,0 +1,325 @@
+//! ISO 20022 camt.053 bank-to-customer statements, the XML end-of-day
+//! statements business banking portals export.
+//!
+//! Elements are matched by local name, so any version of the schema
+//! (camt.053.001.02 to .08) and any namespace prefix reads the same. Only
+//! booked entries are read; a batched entry becomes one line for its total,
+//! as it appears on the statement.
+
+use chrono::NaiveDate;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::{decode_text, BankStatement, ImportError, RowError, StatementBalance, StatementLine};
+use crate::money::Money;
+
+/// What banks put in `EndToEndId` when there isn't one.
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+/// Read every `Stmt` in a camt.053 file.
+pub fn read_camt053(bytes: &[u8]) -> Result<Vec<BankStatement>, ImportError> {
+  let text = decode_text(bytes);
+  if !text.contains("BkToCstmrStmt") {
+    return Err(ImportError::Camt(
+      "there is no BkToCstmrStmt element".into(),
+    ));
+  }
+  let mut reader = Reader::from_str(&text);
+  let mut statements = Vec::new();
+  let mut path: Vec<String> = Vec::new();
+  let mut content = String::new();
+  let mut statement: Option<BankStatement> = None;
+  let mut entry: Option<RawEntry> = None;
+  let mut balance: Option<RawBalance> = None;
+
+  loop {
+    let event = reader
+      .read_event()
+      .map_err(|err| ImportError::Camt(err.to_string()))?;
+    match event {
+      Event::Start(start) => {
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+        content.clear();
+        match name.as_str() {
+          "Stmt" => statement = Some(BankStatement::default()),
+          "Ntry" if statement.is_some() => {
+            let offset = reader.buffer_position() as usize;
+            entry = Some(RawEntry {
+              line: text[..offset.min(text.len())].matches('\n').count() as u64 + 1,
+              ..RawEntry::default()
+            });
+          }
+          "Bal" if statement.is_some() => balance = Some(RawBalance::default()),
+          "Amt" => {
+            let currency = start
+              .try_get_attribute("Ccy")
+              .ok()
+              .flatten()
+              .and_then(|attr| attr.unescape_value().ok().map(|v| v.into_owned()));
+            if let (Some(statement), Some(currency)) = (&mut statement, currency) {
+              statement.currency.get_or_insert(currency);
+            }
+          }
+          _ => {}
+        }
+        path.push(name);
+      }
+      Event::Text(chunk) => {
+        content.push_str(
+          &chunk
+            .decode()
+            .map_err(|err| ImportError::Camt(err.to_string()))?,
+        );
+      }
+      Event::CData(data) => {
+        content.push_str(
+          &data
+            .decode()
+            .map_err(|err| ImportError::Camt(err.to_string()))?,
+        );
+      }
+      Event::GeneralRef(reference) => {
+        let resolved = match reference.resolve_char_ref() {
+          Ok(Some(c)) => Some(c.to_string()),
+          _ => reference
+            .decode()
+            .ok()
+            .and_then(|name| quick_xml::escape::resolve_predefined_entity(&name))
+            .map(String::from),
+        };
+        content.push_str(&resolved.unwrap_or_default());
+      }
+      Event::End(_) => {
+        let value = content.trim().to_string();
+        content.clear();
+        let names: Vec<&str> = path.iter().map(String::as_str).collect();
+        if !value.is_empty() {
+          if let Some(entry) = &mut entry {
+            entry.value(&names, value);
+          } else if let Some(balance) = &mut balance {
+            balance.value(&names, value);
+          } else if let Some(statement) = &mut statement {
+            statement_value(statement, &names, value);
+          }
+        }
+        match names.last().copied() {
+          Some("Ntry") => {
+            if let (Some(raw), Some(statement)) = (entry.take(), &mut statement) {
+              raw.read_into(statement);
+            }
+          }
+          Some("Bal") => {
+            if let (Some(raw), Some(statement)) = (balance.take(), &mut statement) {
+              raw.read_into(statement);
+            }
+          }
+          Some("Stmt") => statements.extend(statement.take()),
+          _ => {}
+        }
+        path.pop();
+      }
+      Event::Eof => break,
+      _ => {}
+    }
+  }
+
+  if statements.is_empty() {
+    return Err(ImportError::Camt("there is no Stmt element".into()));
+  }
+  Ok(statements)
+}
+
+fn statement_value(statement: &mut BankStatement, path: &[&str], value: String) {
+  match path {
+    [.., "Stmt", "Id"] => statement.statement_id = Some(value),
+    [.., "Acct", "Id", "IBAN"] | [.., "Acct", "Id", "Othr", "Id"] => {
+      statement.account_id.get_or_insert(value);
+    }
+    [.., "Acct", "Ccy"] => statement.currency = Some(value),
+    _ => {}
+  }
+}
+
+/// A `Bal`'s values.
+#[derive(Default)]
+struct RawBalance {
+  code: Option<String>,
+  amount: Option<String>,
+  credit_debit: Option<String>,
+  date: Option<String>,
+}
+
+impl RawBalance {
+  fn value(&mut self, path: &[&str], value: String) {
+    let field = match path {
+      [.., "Tp", "CdOrPrtry", "Cd"] => &mut self.code,
+      [.., "Bal", "Amt"] => &mut self.amount,
+      [.., "Bal", "CdtDbtInd"] => &mut self.credit_debit,
+      [.., "Bal", "Dt", "Dt" | "DtTm"] => &mut self.date,
+      _ => return,
+    };
+    *field = Some(value);
+  }
+
+  /// Keep the opening (`OPBD`, or `PRCD` where banks report the previous
+  /// closing balance instead) and closing (`CLBD`) balances.
+  fn read_into(self, statement: &mut BankStatement) {
+    let parsed = signed_amount(self.amount.as_deref(), self.credit_debit.as_deref())
+      .zip(self.date.as_deref().and_then(parse_iso_date))
+      .map(|(amount, date)| StatementBalance { amount, date });
+    match self.code.as_deref() {
+      Some("OPBD") => statement.opening_balance = parsed,
+      Some("PRCD") if statement.opening_balance.is_none() => statement.opening_balance = parsed,
+      Some("CLBD") => statement.closing_balance = parsed,
+      _ => {}
+    }
+  }
+}
+
+/// An `Ntry`'s values, taking the first transaction details for batches.
+#[derive(Default)]
+struct RawEntry {
+  line: u64,
+  amount: Option<String>,
+  credit_debit: Option<String>,
+  status: Option<String>,
+  booking_date: Option<String>,
+  value_date: Option<String>,
+  entry_reference: Option<String>,
+  servicer_reference: Option<String>,
+  end_to_end_id: Option<String>,
+  debtor: Option<String>,
+  creditor: Option<String>,
+  remittance: Vec<String>,
+  additional_info: Option<String>,
+}
+
+impl RawEntry {
+  fn value(&mut self, path: &[&str], value: String) {
+    let field = match path {
+      [.., "Ntry", "Amt"] => &mut self.amount,
+      [.., "Ntry", "CdtDbtInd"] => &mut self.credit_debit,
+      [.., "Ntry", "Sts"] | [.., "Ntry", "Sts", "Cd"] => &mut self.status,
+      [.., "BookgDt", "Dt" | "DtTm"] => &mut self.booking_date,
+      [.., "ValDt", "Dt" | "DtTm"] => &mut self.value_date,
+      [.., "Ntry", "NtryRef"] => &mut self.entry_reference,
+      [.., "Ntry", "AcctSvcrRef"] | [.., "Refs", "AcctSvcrRef"] => &mut self.servicer_reference,
+      [.., "Refs", "EndToEndId"] => &mut self.end_to_end_id,
+      [.., "RmtInf", "Ustrd"] => {
+        self.remittance.push(value);
+        return;
+      }
+      [.., "AddtlNtryInf"] | [.., "AddtlTxInf"] => &mut self.additional_info,
+      _ if path.last() == Some(&"Nm") && path.contains(&"RltdPties") => {
+        let party = path.iter().skip_while(|name| **name != "RltdPties").nth(1);
+        match party {
+          Some(&"Dbtr") => &mut self.debtor,
+          Some(&"Cdtr") => &mut self.creditor,
+          _ => return,
+        }
+      }
+      _ => return,
+    };
+    // The entry's own value comes before its transaction details'.
+    if field.is_none() {
+      *field = Some(value);
+    }
+  }
+
+  fn read_into(self, statement: &mut BankStatement) {
+    // Pending entries are still to be booked; they'll be in a later statement.
+    if self
+      .status
+      .as_deref()
+      .is_some_and(|status| status != "BOOK")
+    {
+      return;
+    }
+    match self.statement_line() {
+      Ok(line) => statement.lines.push(line),
+      Err(err) => statement.errors.push(err),
+    }
+  }
+
+  fn statement_line(self) -> Result<StatementLine, RowError> {
+    let mut problems = Vec::new();
+    let mut missing = Vec::new();
+    let date = match self.booking_date.as_deref().or(self.value_date.as_deref()) {
+      Some(text) => parse_iso_date(text).or_else(|| {
+        problems.push(format!("Unrecognised date: {text}"));
+        None
+      }),
+      None => {
+        missing.push("BookgDt");
+        None
+      }
+    };
+    let amount = match (self.amount.as_deref(), self.credit_debit.as_deref()) {
+      (Some(text), indicator) => signed_amount(Some(text), indicator).or_else(|| {
+        problems.push(format!("Unrecognised amount: {text}"));
+        None
+      }),
+      (None, _) => {
+        missing.push("Amt");
+        None
+      }
+    };
+    if !missing.is_empty() {
+      problems.insert(
+        0,
+        format!("Missing required fields: {}", missing.join(", ")),
+      );
+    }
+
+    let money_out = amount.is_some_and(Money::is_negative);
+    let counterparty = if money_out {
+      self.creditor
+    } else {
+      self.debtor
+    };
+    let remittance = (!self.remittance.is_empty()).then(|| self.remittance.join(" "));
+    let (payee, memo) = match (counterparty, remittance, self.additional_info) {
+      (Some(name), remittance, info) => (name, remittance.or(info)),
+      (None, Some(remittance), info) => (remittance, info),
+      (None, None, Some(info)) => (info, None),
+      (None, None, None) => (String::new(), None),
+    };
+    let end_to_end_id = self.end_to_end_id.filter(|id| id != NOT_PROVIDED);
+
+    match (date, amount) {
+      (Some(date), Some(amount)) if problems.is_empty() => Ok(StatementLine {
+        line: self.line,
+        date,
+        payee,
+        amount,
+        reference: end_to_end_id.clone().or(self.entry_reference.clone()),
+        external_id: self
+          .servicer_reference
+          .or(self.entry_reference)
+          .or(end_to_end_id),
+        memo,
+        balance: None,
+      }),
+      _ => Err(RowError {
+        line: self.line,
+        message: problems.join("; "),
+      }),
+    }
+  }
+}
+
+/// An amount made negative by a `DBIT` indicator.
+fn signed_amount(amount: Option<&str>, credit_debit: Option<&str>) -> Option<Money> {
+  let amount = Money::parse(amount?).ok()?.abs();
+  Some(if credit_debit == Some("DBIT") {
+    -amount
+  } else {
+    amount
+  })
+}
+
+/// The date of an ISO date or datetime, `2026-01-31` or
+/// `2026-01-31T09:30:00+10:00`.
+fn parse_iso_date(text: &str) -> Option<NaiveDate> {
+  NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
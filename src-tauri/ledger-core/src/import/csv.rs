@@ -0,0 +1,250 @@
+//! CSV statements: `parseCSV` and the column mapping from `importService.ts`.
+//!
+//! Files are read a record at a time, so a 100k-row export never has to be
+//! held in memory. A byte-order mark is stripped, and UTF-16 files (Excel's
+//! "Unicode text") are transcoded. Fields that aren't valid UTF-8 are read
+//! as Windows-1252, which is what most bank exports without a BOM are.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read};
+
+use ::csv::{ByteRecord, ReaderBuilder};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{decode_text, parse_amount, parse_date, ImportError, RowError, StatementLine};
+
+/// Which column (counting from 0) holds each field. Amounts come either from
+/// `amount`, signed as the bank signs them, or from `debit` and `credit`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+  pub date: Option<usize>,
+  pub payee: Option<usize>,
+  /// Used for the payee when `payee` isn't mapped.
+  pub description: Option<usize>,
+  pub debit: Option<usize>,
+  pub credit: Option<usize>,
+  pub amount: Option<usize>,
+  pub reference: Option<usize>,
+  pub balance: Option<usize>,
+}
+
+/// A saved [`ColumnMapping`], optionally tied to one account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingTemplate {
+  pub name: String,
+  pub mapping: ColumnMapping,
+  pub account_id: Option<String>,
+}
+
+/// One CSV record, its fields trimmed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvRecord {
+  /// Line the record starts on, counting from 1. A quoted field can carry
+  /// the record over several lines.
+  pub line: u64,
+  pub values: Vec<String>,
+}
+
+/// Reads records from a CSV file, after its header row if it has one.
+pub struct CsvReader<R: Read> {
+  records: ::csv::Reader<RecordLines<DecodeReaderBytes<R, Vec<u8>>>>,
+  headers: Vec<String>,
+  record: ByteRecord,
+}
+
+impl<R: Read> CsvReader<R> {
+  pub fn new(reader: R, has_header: bool) -> Result<Self, ImportError> {
+    let decoded = DecodeReaderBytesBuilder::new()
+      .bom_sniffing(true)
+      .build(reader);
+    let mut reader = CsvReader {
+      records: ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(RecordLines::new(decoded)),
+      headers: Vec::new(),
+      record: ByteRecord::new(),
+    };
+    if has_header && reader.records.read_byte_record(&mut reader.record)? {
+      reader.records.get_mut().starts.pop_front();
+      reader.headers = reader.record.iter().map(decode_field).collect();
+    }
+    Ok(reader)
+  }
+
+  /// The header row; empty for a file without one.
+  pub fn headers(&self) -> &[String] {
+    &self.headers
+  }
+}
+
+impl<R: Read> Iterator for CsvReader<R> {
+  type Item = Result<CsvRecord, ImportError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.records.read_byte_record(&mut self.record) {
+      Ok(false) => None,
+      Ok(true) => Some(Ok(CsvRecord {
+        line: self.records.get_mut().starts.pop_front().unwrap_or(0),
+        values: self.record.iter().map(decode_field).collect(),
+      })),
+      Err(err) => Some(Err(err.into())),
+    }
+  }
+}
+
+/// Where [`RecordLines`] is within a record.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Lexer {
+  RecordStart,
+  FieldStart,
+  Unquoted,
+  Quoted,
+  /// Just past a quote inside a quoted field: the end of the field, or the
+  /// first half of an escaped `""`.
+  QuoteInQuoted,
+}
+
+/// Notes the line each record starts on as the CSV reader pulls bytes
+/// through, since the reader's own positions don't count the blank lines it
+/// skips. Also turns `\r\n` into `\n`, so a CRLF file's blank lines are
+/// skipped too and multiline fields don't keep the `\r`.
+struct RecordLines<R> {
+  inner: BufReader<R>,
+  line: u64,
+  lexer: Lexer,
+  starts: VecDeque<u64>,
+}
+
+impl<R: Read> RecordLines<R> {
+  fn new(inner: R) -> Self {
+    RecordLines {
+      inner: BufReader::new(inner),
+      line: 1,
+      lexer: Lexer::RecordStart,
+      starts: VecDeque::new(),
+    }
+  }
+
+  fn lex(&mut self, byte: u8) {
+    if self.lexer == Lexer::RecordStart && byte != b'\n' {
+      self.starts.push_back(self.line);
+      self.lexer = Lexer::FieldStart;
+    }
+    if byte == b'\n' {
+      self.line += 1;
+    }
+    self.lexer = match (self.lexer, byte) {
+      (Lexer::Quoted, b'"') => Lexer::QuoteInQuoted,
+      (Lexer::Quoted, _) => Lexer::Quoted,
+      (Lexer::QuoteInQuoted, b'"') | (Lexer::FieldStart, b'"') => Lexer::Quoted,
+      (_, b'\n') => Lexer::RecordStart,
+      (_, b',') => Lexer::FieldStart,
+      _ => Lexer::Unquoted,
+    };
+  }
+}
+
+impl<R: Read> Read for RecordLines<R> {
+  fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+    let mut written = 0;
+    while written < out.len() {
+      let Some(&byte) = self.inner.fill_buf()?.first() else {
+        break;
+      };
+      self.inner.consume(1);
+      if byte == b'\r' && self.inner.fill_buf()?.first() == Some(&b'\n') {
+        continue;
+      }
+      self.lex(byte);
+      out[written] = byte;
+      written += 1;
+    }
+    Ok(written)
+  }
+}
+
+fn decode_field(bytes: &[u8]) -> String {
+  decode_text(bytes).trim().to_string()
+}
+
+impl ColumnMapping {
+  /// Read a record as a statement line. Every problem with the record is
+  /// reported in the one error.
+  ///
+  /// With debit and credit columns, either may hold the amount and its sign
+  /// is ignored: debits are money out, credits money in.
+  pub fn statement_line(&self, record: &CsvRecord) -> Result<StatementLine, RowError> {
+    let field = |column: Option<usize>| {
+      column
+        .and_then(|c| record.values.get(c))
+        .map(String::as_str)
+        .filter(|value| !value.is_empty())
+    };
+    let mut missing = Vec::new();
+    let mut problems = Vec::new();
+
+    let date = match field(self.date) {
+      Some(text) => parse_date(text).or_else(|| {
+        problems.push(format!("Unrecognised date: {text}"));
+        None
+      }),
+      None => {
+        missing.push("date");
+        None
+      }
+    };
+    let unreadable = problems.len();
+    let mut amount_field = |column: Option<usize>| {
+      let text = field(column)?;
+      parse_amount(text).or_else(|| {
+        problems.push(format!("Unrecognised amount: {text}"));
+        None
+      })
+    };
+    let amount = if self.amount.is_some() {
+      amount_field(self.amount)
+    } else {
+      match (amount_field(self.debit), amount_field(self.credit)) {
+        (None, None) => None,
+        (debit, credit) => Some(credit.unwrap_or_default().abs() - debit.unwrap_or_default().abs()),
+      }
+    }
+    .filter(|amount| !amount.is_zero());
+    if amount.is_none() && problems.len() == unreadable {
+      missing.push("amount");
+    }
+    let payee = field(self.payee).or_else(|| field(self.description));
+    if payee.is_none() {
+      missing.push("payee/description");
+    }
+
+    if !missing.is_empty() {
+      problems.insert(
+        0,
+        format!("Missing required fields: {}", missing.join(", ")),
+      );
+    }
+    match (date, amount, payee) {
+      (Some(date), Some(amount), Some(payee)) if problems.is_empty() => Ok(StatementLine {
+        line: record.line,
+        date,
+        payee: payee.to_string(),
+        amount,
+        reference: field(self.reference).map(String::from),
+        // `importTransactions` kept the reference as the external id.
+        external_id: field(self.reference).map(String::from),
+        memo: None,
+        balance: field(self.balance).and_then(parse_amount),
+      }),
+      _ => Err(RowError {
+        line: record.line,
+        message: problems.join("; "),
+      }),
+    }
+  }
+}
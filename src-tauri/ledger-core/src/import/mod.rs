@@ -0,0 +1,224 @@
+//! Bank statement import, mirroring `ImportService` in
+//! `src/lib/services/importService.ts`.
+//!
+//! Each format's reader turns a statement into [`StatementLine`]s with
+//! amounts from the bank's side (positive is money in), which is also the
+//! sign of the source account's posting. Checking lines against the book for
+//! duplicates is shared (see [`crate::Database::preview_import`]).
+
+pub mod bank_profile;
+pub mod camt;
+pub mod csv;
+pub mod mt940;
+pub mod ofx;
+pub mod pdf;
+pub mod qif;
+
+use std::borrow::Cow;
+use std::io;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use encoding_rs::WINDOWS_1252;
+use serde::{Deserialize, Serialize};
+
+use crate::model::MemorizedRule;
+use crate::money::Money;
+
+pub use self::bank_profile::{
+  detect_bank_profile, load_bank_profiles, AmountSign, BankProfile, Confidence, PdfStatement,
+};
+pub use self::camt::read_camt053;
+pub use self::csv::{ColumnMapping, CsvReader, CsvRecord, MappingTemplate};
+pub use self::mt940::read_mt940;
+pub use self::ofx::{read_ofx, OfxStatement};
+pub use self::pdf::{extract_pdf_text, read_pdf_statement};
+pub use self::qif::{
+  read_qif, write_qif, ClearedStatus, DateOrder, QifAccount, QifAccountType, QifCategory,
+  QifImportSummary, QifSplit, QifTransaction,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Csv(#[from] ::csv::Error),
+  #[error("not an OFX file: {0}")]
+  Ofx(String),
+  #[error("not a QIF file: {0}")]
+  Qif(String),
+  #[error("not a camt.053 statement: {0}")]
+  Camt(String),
+  #[error("not an MT940 statement: {0}")]
+  Mt940(String),
+  #[error("can't read the PDF statement: {0}")]
+  Pdf(String),
+  #[error("invalid bank profile: {0}")]
+  Profile(String),
+}
+
+/// One transaction read from a statement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementLine {
+  /// Line in the file the transaction starts on, counting from 1.
+  pub line: u64,
+  pub date: NaiveDate,
+  pub payee: String,
+  pub amount: Money,
+  pub reference: Option<String>,
+  /// The bank's id for the transaction, kept as `Transaction.externalId`
+  /// so importing the same statement twice can be caught.
+  pub external_id: Option<String>,
+  pub memo: Option<String>,
+  /// Running balance after this line, if the statement has one.
+  pub balance: Option<Money>,
+}
+
+impl StatementLine {
+  /// Midnight UTC on the line's date, as transactions are dated.
+  pub fn date_time(&self) -> DateTime<Utc> {
+    self.date.and_time(chrono::NaiveTime::MIN).and_utc()
+  }
+}
+
+/// A balance the bank reported, such as an OFX ledger balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementBalance {
+  pub amount: Money,
+  pub date: NaiveDate,
+}
+
+impl StatementBalance {
+  /// Midnight UTC on the balance's date.
+  pub fn date_time(&self) -> DateTime<Utc> {
+    self.date.and_time(chrono::NaiveTime::MIN).and_utc()
+  }
+}
+
+/// A statement from a business banking export (camt.053 or MT940), with
+/// the balances it opens and closes on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankStatement {
+  /// The bank's id for the statement (camt `Stmt/Id`, MT940 `:20:`).
+  pub statement_id: Option<String>,
+  /// The account's IBAN or number.
+  pub account_id: Option<String>,
+  pub currency: Option<String>,
+  pub opening_balance: Option<StatementBalance>,
+  pub closing_balance: Option<StatementBalance>,
+  pub lines: Vec<StatementLine>,
+  pub errors: Vec<RowError>,
+}
+
+/// Read a camt.053 or MT940 file, telling them apart by whether it's XML.
+pub fn read_bank_statement(bytes: &[u8]) -> Result<Vec<BankStatement>, ImportError> {
+  let is_xml = decode_text(bytes)
+    .trim_start_matches('\u{feff}')
+    .trim_start()
+    .starts_with('<');
+  if is_xml {
+    read_camt053(bytes)
+  } else {
+    read_mt940(bytes)
+  }
+}
+
+/// A line that couldn't be read, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowError {
+  pub line: u64,
+  pub message: String,
+}
+
+/// A statement line, whether the book seems to have it already and the
+/// memorized rule that would categorise it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+  pub parsed: StatementLine,
+  pub is_duplicate: bool,
+  pub matched_rule: Option<MemorizedRule>,
+}
+
+const MONTHS: [&str; 12] = [
+  "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Parse a statement date: day first (`31/01/2026`, `1-2-26`, `31 Jan 2026`)
+/// as Australian banks write them, or ISO `2026-01-31`. A trailing time is
+/// ignored. Two-digit years are 1970–2069.
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+  let mut parts: Vec<&str> = text
+    .split(['/', '-', '.', ' '])
+    .filter(|part| !part.is_empty())
+    .collect();
+  if parts.len() == 4 && parts[3].contains(':') {
+    parts.pop();
+  }
+  let [first, month, last] = parts[..] else {
+    return None;
+  };
+  let (day, year) = if first.len() == 4 {
+    (last, first)
+  } else {
+    (first, last)
+  };
+  let month = match month.parse() {
+    Ok(number) => number,
+    Err(_) => month_number(month)?,
+  };
+  let year = match (year.len(), year.parse::<i32>().ok()?) {
+    (4, year) => year,
+    (2, year) if year < 70 => 2000 + year,
+    (2, year) => 1900 + year,
+    _ => return None,
+  };
+  NaiveDate::from_ymd_opt(year, month, day.parse().ok()?)
+}
+
+/// `Jan`, `JAN` or `January` as 1.
+fn month_number(name: &str) -> Option<u32> {
+  let prefix = name.get(..3)?.to_ascii_lowercase();
+  let index = MONTHS.iter().position(|m| *m == prefix)?;
+  Some(index as u32 + 1)
+}
+
+/// Parse a statement amount, ignoring `$`, thousands separators and spaces.
+/// Parentheses (accounting format) or a `DR` suffix make it negative; a `CR`
+/// suffix is positive.
+pub fn parse_amount(text: &str) -> Option<Money> {
+  let mut cleaned: String = text
+    .chars()
+    .filter(|c| !matches!(c, '$' | ',') && !c.is_whitespace())
+    .collect();
+  let mut negative = false;
+  let upper = cleaned.to_ascii_uppercase();
+  if let Some(rest) = upper.strip_suffix("DR") {
+    negative = true;
+    cleaned.truncate(rest.len());
+  } else if let Some(rest) = upper.strip_suffix("CR") {
+    cleaned.truncate(rest.len());
+  }
+  if let Some(inner) = cleaned
+    .strip_prefix('(')
+    .and_then(|rest| rest.strip_suffix(')'))
+  {
+    negative = true;
+    cleaned = inner.to_string();
+  }
+  let amount = Money::parse(&cleaned).ok()?;
+  Some(if negative { -amount.abs() } else { amount })
+}
+
+/// Text from a file that should be UTF-8 but, from older banking systems,
+/// is often Windows-1252.
+pub(crate) fn decode_text(bytes: &[u8]) -> Cow<'_, str> {
+  match std::str::from_utf8(bytes) {
+    Ok(text) => Cow::Borrowed(text),
+    Err(_) => WINDOWS_1252.decode_without_bom_handling(bytes).0,
+  }
+}
@@ -0,0 +1,352 @@
+//! SWIFT MT940 customer statements, which business banking portals export
+//! alongside (or instead of) camt.053.
+//!
+//! A statement is a run of `:tag:` fields: `:20:` starts it, `:25:` names the
+//! account, `:60F:`/`:62F:` are the opening and closing balances and each
+//! `:61:` is a transaction, described by the `:86:` after it. Statements
+//! split over several messages (`:60M:`/`:62M:` intermediate balances) are
+//! joined back together. The SWIFT envelope (`{1:...}{2:...}{4:`) is
+//! optional.
+
+use chrono::{Datelike, NaiveDate};
+
+use super::{decode_text, BankStatement, ImportError, RowError, StatementBalance, StatementLine};
+use crate::money::Money;
+
+/// What goes in a reference field when there isn't one.
+const NO_REFERENCE: &str = "NONREF";
+
+/// The width `:86:` lines are wrapped at, often mid-word.
+const NARRATIVE_WIDTH: usize = 65;
+
+/// Read every statement in an MT940 file.
+pub fn read_mt940(bytes: &[u8]) -> Result<Vec<BankStatement>, ImportError> {
+  let text = decode_text(bytes);
+  let mut statements: Vec<BankStatement> = Vec::new();
+  let mut statement: Option<BankStatement> = None;
+  let mut entry: Option<RawEntry> = None;
+
+  for field in fields(&text) {
+    if matches!(field.tag, "20" | "61" | "62F" | "62M") {
+      finish_entry(&mut entry, &mut statement);
+    }
+    let value = field.lines.join("\n");
+    match field.tag {
+      "20" => {
+        statements.extend(statement.take());
+        statement = Some(BankStatement {
+          statement_id: Some(value.clone()),
+          ..BankStatement::default()
+        });
+      }
+      "61" => {
+        entry = Some(RawEntry {
+          line: field.line,
+          lines: field.lines,
+          narrative: Vec::new(),
+        })
+      }
+      "86" => {
+        if let Some(entry) = &mut entry {
+          entry.narrative = field.lines;
+        }
+      }
+      _ => {}
+    }
+    let Some(current) = &mut statement else {
+      continue;
+    };
+    match field.tag {
+      "25" => current.account_id = Some(value),
+      // The continuation of the previous message's statement.
+      "60M"
+        if statements
+          .last()
+          .is_some_and(|last| last.account_id == current.account_id) =>
+      {
+        let mut continued = statements.pop().unwrap_or_default();
+        continued.lines.append(&mut current.lines);
+        continued.errors.append(&mut current.errors);
+        *current = continued;
+      }
+      "60F" | "60M" => {
+        if let Some((balance, currency)) = parse_balance(&value) {
+          current.opening_balance = Some(balance);
+          current.currency.get_or_insert(currency);
+        }
+      }
+      "62F" | "62M" => {
+        current.closing_balance = parse_balance(&value).map(|(balance, _)| balance);
+      }
+      _ => {}
+    }
+  }
+  finish_entry(&mut entry, &mut statement);
+  statements.extend(statement);
+
+  if statements.is_empty() {
+    return Err(ImportError::Mt940("there is no :20: field".into()));
+  }
+  Ok(statements)
+}
+
+/// A `:tag:` field and its continuation lines.
+struct Field<'a> {
+  line: u64,
+  tag: &'a str,
+  lines: Vec<&'a str>,
+}
+
+/// The fields of every message in `text`, ignoring the SWIFT envelope.
+fn fields(text: &str) -> Vec<Field<'_>> {
+  let mut fields: Vec<Field> = Vec::new();
+  let mut in_field = false;
+  for (index, line) in text.lines().enumerate() {
+    let mut line = line.trim_end();
+    // `{1:F01...}{2:I940...}{4:` opens a message and `-}` closes it.
+    if line.starts_with('{') {
+      match line.find("{4:") {
+        Some(start) => line = &line[start + 3..],
+        None => continue,
+      }
+    }
+    if line == "-" || line.starts_with("-}") {
+      in_field = false;
+      continue;
+    }
+    if let Some((tag, value)) = line
+      .strip_prefix(':')
+      .and_then(|rest| rest.split_once(':'))
+      .filter(|(tag, _)| is_tag(tag))
+    {
+      fields.push(Field {
+        line: index as u64 + 1,
+        tag,
+        lines: vec![value],
+      });
+      in_field = true;
+    } else if in_field && !line.is_empty() {
+      if let Some(field) = fields.last_mut() {
+        field.lines.push(line);
+      }
+    }
+  }
+  fields
+}
+
+/// `20`, `28C`, `60F` and so on.
+fn is_tag(tag: &str) -> bool {
+  let bytes = tag.as_bytes();
+  matches!(bytes.len(), 2 | 3)
+    && bytes[..2].iter().all(u8::is_ascii_digit)
+    && bytes[2..].iter().all(u8::is_ascii_uppercase)
+}
+
+fn finish_entry(entry: &mut Option<RawEntry>, statement: &mut Option<BankStatement>) {
+  let (Some(raw), Some(statement)) = (entry.take(), statement) else {
+    return;
+  };
+  match raw.statement_line() {
+    Ok(line) => statement.lines.push(line),
+    Err(err) => statement.errors.push(err),
+  }
+}
+
+/// A `:61:` field and the `:86:` after it.
+struct RawEntry<'a> {
+  line: u64,
+  lines: Vec<&'a str>,
+  narrative: Vec<&'a str>,
+}
+
+impl RawEntry<'_> {
+  fn statement_line(self) -> Result<StatementLine, RowError> {
+    let unreadable = || RowError {
+      line: self.line,
+      message: format!("Unrecognised :61: field: {}", self.lines[0]),
+    };
+    let parsed = parse_statement_line(self.lines[0]).ok_or_else(unreadable)?;
+    let supplementary = self
+      .lines
+      .get(1)
+      .map(|details| details.trim().to_string())
+      .filter(|details| !details.is_empty());
+    let (payee, purpose) = narrative(&self.narrative);
+    let customer_reference = Some(parsed.customer_reference.to_string())
+      .filter(|reference| !reference.is_empty() && reference != NO_REFERENCE);
+    let bank_reference = parsed
+      .bank_reference
+      .map(String::from)
+      .filter(|reference| !reference.is_empty() && reference != NO_REFERENCE);
+
+    let (payee, memo) = match (payee, purpose, supplementary) {
+      (Some(payee), purpose, supplementary) => (payee, purpose.or(supplementary)),
+      (None, Some(purpose), supplementary) => (purpose, supplementary),
+      (None, None, Some(supplementary)) => (supplementary, None),
+      (None, None, None) => (
+        customer_reference
+          .clone()
+          .unwrap_or_else(|| parsed.transaction_type.to_string()),
+        None,
+      ),
+    };
+    Ok(StatementLine {
+      line: self.line,
+      date: parsed.date,
+      payee,
+      amount: parsed.amount,
+      external_id: bank_reference.or(customer_reference.clone()),
+      reference: customer_reference,
+      memo,
+      balance: None,
+    })
+  }
+}
+
+/// The parts of a `:61:` line:
+/// `YYMMDD[MMDD](C|D|RC|RD)[funds code]amount type customer-ref[//bank-ref]`.
+struct ParsedLine<'a> {
+  /// The entry (booking) date, or the value date if there isn't one.
+  date: NaiveDate,
+  amount: Money,
+  transaction_type: &'a str,
+  customer_reference: &'a str,
+  bank_reference: Option<&'a str>,
+}
+
+fn parse_statement_line(line: &str) -> Option<ParsedLine<'_>> {
+  let value_date = parse_swift_date(line.get(..6)?)?;
+  let mut rest = &line[6..];
+  let mut date = value_date;
+  if rest.len() >= 4 && rest.as_bytes()[..4].iter().all(u8::is_ascii_digit) {
+    let (month, day) = (rest[..2].parse().ok()?, rest[2..4].parse().ok()?);
+    // The entry date can fall in the year either side of the value date.
+    let year = match month as i32 - value_date.month() as i32 {
+      gap if gap > 6 => value_date.year() - 1,
+      gap if gap < -6 => value_date.year() + 1,
+      _ => value_date.year(),
+    };
+    date = NaiveDate::from_ymd_opt(year, month, day)?;
+    rest = &rest[4..];
+  }
+  // Reversals of credits take money out; reversals of debits put it back.
+  let (negative, after_mark) = if let Some(after) = rest.strip_prefix("RC") {
+    (true, after)
+  } else if let Some(after) = rest.strip_prefix("RD") {
+    (false, after)
+  } else if let Some(after) = rest.strip_prefix('C') {
+    (false, after)
+  } else {
+    (true, rest.strip_prefix('D')?)
+  };
+  rest = after_mark;
+  // The optional funds code: the third letter of the currency code.
+  if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+    rest = &rest[1..];
+  }
+  let amount_len = rest
+    .find(|c: char| !c.is_ascii_digit() && c != ',')
+    .unwrap_or(rest.len());
+  let amount = parse_swift_amount(&rest[..amount_len])?;
+  rest = &rest[amount_len..];
+  let transaction_type = rest.get(..4)?;
+  let (customer_reference, bank_reference) = match rest[4..].split_once("//") {
+    Some((customer, bank)) => (customer.trim(), Some(bank.trim())),
+    None => (rest[4..].trim(), None),
+  };
+  Some(ParsedLine {
+    date,
+    amount: if negative { -amount } else { amount },
+    transaction_type,
+    customer_reference,
+    bank_reference,
+  })
+}
+
+/// The payee and purpose in an `:86:` field. Structured narratives (German
+/// banks' `?20`–`?29` purpose and `?32`/`?33` name subfields) are taken
+/// apart; free text is all description.
+fn narrative(lines: &[&str]) -> (Option<String>, Option<String>) {
+  let is_structured = lines.first().is_some_and(|first| {
+    first.starts_with('?')
+      || first.get(3..4) == Some("?") && first[..3].bytes().all(|b| b.is_ascii_digit())
+  });
+  if !is_structured {
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+      // Lines broken at the full width were broken mid-word.
+      if i > 0 && lines[i - 1].chars().count() < NARRATIVE_WIDTH {
+        text.push(' ');
+      }
+      text.push_str(line.trim());
+    }
+    let text = text.trim().to_string();
+    return ((!text.is_empty()).then_some(text), None);
+  }
+
+  let joined = lines.concat();
+  let mut purpose = String::new();
+  let mut name = String::new();
+  let mut booking_text = String::new();
+  for subfield in joined.split('?').skip(1) {
+    let (Some(Ok(code)), Some(value)) =
+      (subfield.get(..2).map(str::parse::<u8>), subfield.get(2..))
+    else {
+      continue;
+    };
+    match code {
+      0 => booking_text.push_str(value),
+      20..=29 | 60..=63 => purpose.push_str(value),
+      32 | 33 => name.push_str(value),
+      _ => {}
+    }
+  }
+  let non_empty = |text: String| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+  let purpose = non_empty(purpose);
+  match non_empty(name).or_else(|| non_empty(booking_text)) {
+    Some(payee) => (Some(payee), purpose),
+    None => (purpose, None),
+  }
+}
+
+/// A `:60F:`/`:62F:` balance, `C260131AUD1234,56`, and its currency.
+fn parse_balance(value: &str) -> Option<(StatementBalance, String)> {
+  let negative = match value.get(..1)? {
+    "C" => false,
+    "D" => true,
+    _ => return None,
+  };
+  let date = parse_swift_date(value.get(1..7)?)?;
+  let currency = value.get(7..10)?.to_string();
+  let amount = parse_swift_amount(value.get(10..)?.trim())?;
+  Some((
+    StatementBalance {
+      amount: if negative { -amount } else { amount },
+      date,
+    },
+    currency,
+  ))
+}
+
+/// `YYMMDD`, in 1970–2069.
+fn parse_swift_date(text: &str) -> Option<NaiveDate> {
+  if text.len() != 6 || !text.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  let year: i32 = text[..2].parse().ok()?;
+  let year = if year < 70 { 2000 + year } else { 1900 + year };
+  NaiveDate::from_ymd_opt(year, text[2..4].parse().ok()?, text[4..].parse().ok()?)
+}
+
+/// A SWIFT amount, with a comma for the decimal point and maybe nothing
+/// after it: `1234,56`, `100,`.
+fn parse_swift_amount(text: &str) -> Option<Money> {
+  if text.is_empty() {
+    return None;
+  }
+  let mut text = text.replace(',', ".");
+  if text.ends_with('.') {
+    text.push('0');
+  }
+  Money::parse(&text).ok()
+}
@@ -0,0 +1,353 @@
+//! OFX statements (and Quicken's QFX, which is OFX with an extra tag), as
+//! CBA, Westpac and ANZ offer them for download.
+//!
+//! OFX 1.x is SGML, where elements holding a value have no end tag; OFX 2.x
+//! is XML. Both are read the same way: a tag followed by text is a value, a
+//! tag followed by another tag opens an aggregate, and end tags only matter
+//! for the aggregates read here.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::{decode_text, ImportError, RowError, StatementBalance, StatementLine};
+use crate::money::Money;
+
+/// One bank or credit card statement (`STMTRS` or `CCSTMTRS`). A file can
+/// hold several, one per account.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfxStatement {
+  /// `CURDEF`, such as `AUD`.
+  pub currency: Option<String>,
+  pub bank_id: Option<String>,
+  pub account_id: Option<String>,
+  /// The period the transaction list covers.
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+  pub lines: Vec<StatementLine>,
+  pub errors: Vec<RowError>,
+  /// `LEDGERBAL`: what the account should reconcile to as of its date.
+  pub ledger_balance: Option<StatementBalance>,
+  pub available_balance: Option<StatementBalance>,
+}
+
+/// Read every statement in an OFX file.
+pub fn read_ofx(bytes: &[u8]) -> Result<Vec<OfxStatement>, ImportError> {
+  let text = decode_text(bytes);
+  let body = text
+    .find("<OFX>")
+    .or_else(|| text.find("<ofx>"))
+    .ok_or_else(|| ImportError::Ofx("there is no <OFX> element".into()))?;
+  let first_line = text[..body].matches('\n').count() as u64 + 1;
+
+  let mut reader = Reader::default();
+  for (line, event) in (Tokens {
+    text: &text[body..],
+    line: first_line,
+  }) {
+    reader.event(line, event);
+  }
+  reader.end_statement();
+  if reader.statements.is_empty() {
+    return Err(ImportError::Ofx(
+      "there is no bank or credit card statement".into(),
+    ));
+  }
+  Ok(reader.statements)
+}
+
+enum Event<'a> {
+  Open(String),
+  Close(String),
+  Value(String, &'a str),
+}
+
+/// Tags and values in an OFX body, with the line each starts on.
+struct Tokens<'a> {
+  text: &'a str,
+  line: u64,
+}
+
+impl<'a> Tokens<'a> {
+  fn advance(&mut self, len: usize) -> &'a str {
+    let (taken, rest) = self.text.split_at(len);
+    self.line += taken.matches('\n').count() as u64;
+    self.text = rest;
+    taken
+  }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+  type Item = (u64, Event<'a>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      self.advance(self.text.find('<')?);
+      let line = self.line;
+      let end = self.text.find('>')?;
+      let tag = self.advance(end + 1)[1..end].trim();
+      // Processing instructions (the OFX 2 header) and comments.
+      if tag.starts_with('?') || tag.starts_with('!') {
+        continue;
+      }
+      if let Some(name) = tag.strip_prefix('/') {
+        return Some((line, Event::Close(name.trim().to_ascii_uppercase())));
+      }
+      let name = tag.trim_end_matches('/').to_ascii_uppercase();
+      let value_len = self.text.find('<').unwrap_or(self.text.len());
+      let value = self.text[..value_len].trim();
+      if value.is_empty() {
+        return Some((line, Event::Open(name)));
+      }
+      self.advance(value_len);
+      return Some((line, Event::Value(name, value)));
+    }
+  }
+}
+
+#[derive(Default)]
+struct Reader {
+  statements: Vec<OfxStatement>,
+  statement: Option<OfxStatement>,
+  transaction: Option<RawTransaction>,
+  balance: Option<RawBalance>,
+}
+
+/// A `STMTTRN`'s values.
+#[derive(Default)]
+struct RawTransaction {
+  line: u64,
+  posted: Option<String>,
+  user_date: Option<String>,
+  amount: Option<String>,
+  fitid: Option<String>,
+  name: Option<String>,
+  memo: Option<String>,
+  check_number: Option<String>,
+  ref_number: Option<String>,
+}
+
+struct RawBalance {
+  ledger: bool,
+  amount: Option<String>,
+  date: Option<String>,
+}
+
+impl Reader {
+  fn event(&mut self, line: u64, event: Event) {
+    match event {
+      Event::Open(tag) => match tag.as_str() {
+        "STMTRS" | "CCSTMTRS" => {
+          self.end_statement();
+          self.statement = Some(OfxStatement::default());
+        }
+        "STMTTRN" => {
+          self.end_transaction();
+          self.transaction = Some(RawTransaction {
+            line,
+            ..RawTransaction::default()
+          });
+        }
+        "LEDGERBAL" | "AVAILBAL" => {
+          self.balance = Some(RawBalance {
+            ledger: tag == "LEDGERBAL",
+            amount: None,
+            date: None,
+          });
+        }
+        _ => {}
+      },
+      Event::Close(tag) => match tag.as_str() {
+        "STMTRS" | "CCSTMTRS" => self.end_statement(),
+        "STMTTRN" => self.end_transaction(),
+        "LEDGERBAL" | "AVAILBAL" => self.end_balance(),
+        _ => {}
+      },
+      Event::Value(tag, value) => self.value(&tag, unescape(value)),
+    }
+  }
+
+  fn value(&mut self, tag: &str, value: String) {
+    if let Some(transaction) = &mut self.transaction {
+      let field = match tag {
+        "DTPOSTED" => &mut transaction.posted,
+        "DTUSER" => &mut transaction.user_date,
+        "TRNAMT" => &mut transaction.amount,
+        "FITID" => &mut transaction.fitid,
+        "NAME" => &mut transaction.name,
+        "MEMO" => &mut transaction.memo,
+        "CHECKNUM" => &mut transaction.check_number,
+        "REFNUM" => &mut transaction.ref_number,
+        _ => return,
+      };
+      *field = Some(value);
+    } else if let Some(balance) = &mut self.balance {
+      match tag {
+        "BALAMT" => balance.amount = Some(value),
+        "DTASOF" => balance.date = Some(value),
+        _ => {}
+      }
+    } else if let Some(statement) = &mut self.statement {
+      match tag {
+        "CURDEF" => statement.currency = Some(value),
+        "BANKID" => statement.bank_id = Some(value),
+        "ACCTID" => statement.account_id = Some(value),
+        "DTSTART" => statement.start = parse_ofx_date(&value),
+        "DTEND" => statement.end = parse_ofx_date(&value),
+        _ => {}
+      }
+    }
+  }
+
+  fn end_transaction(&mut self) {
+    let (Some(transaction), Some(statement)) = (self.transaction.take(), &mut self.statement)
+    else {
+      return;
+    };
+    match transaction.statement_line() {
+      Ok(line) => statement.lines.push(line),
+      Err(err) => statement.errors.push(err),
+    }
+  }
+
+  fn end_balance(&mut self) {
+    let (Some(balance), Some(statement)) = (self.balance.take(), &mut self.statement) else {
+      return;
+    };
+    let parsed = balance
+      .amount
+      .as_deref()
+      .and_then(parse_ofx_amount)
+      .zip(balance.date.as_deref().and_then(parse_ofx_date))
+      .map(|(amount, date)| StatementBalance { amount, date });
+    if balance.ledger {
+      statement.ledger_balance = parsed;
+    } else {
+      statement.available_balance = parsed;
+    }
+  }
+
+  fn end_statement(&mut self) {
+    self.end_transaction();
+    self.end_balance();
+    self.statements.extend(self.statement.take());
+  }
+}
+
+impl RawTransaction {
+  fn statement_line(self) -> Result<StatementLine, RowError> {
+    let mut problems = Vec::new();
+    let mut missing = Vec::new();
+    let date = match self.posted.as_deref().or(self.user_date.as_deref()) {
+      Some(text) => parse_ofx_date(text).or_else(|| {
+        problems.push(format!("Unrecognised date: {text}"));
+        None
+      }),
+      None => {
+        missing.push("DTPOSTED");
+        None
+      }
+    };
+    let amount = match self.amount.as_deref() {
+      Some(text) => parse_ofx_amount(text).or_else(|| {
+        problems.push(format!("Unrecognised amount: {text}"));
+        None
+      }),
+      None => {
+        missing.push("TRNAMT");
+        None
+      }
+    };
+    // Banks that truncate NAME put the whole description in MEMO; others
+    // only fill in MEMO.
+    let (payee, memo) = match (self.name, self.memo) {
+      (Some(name), Some(memo)) if memo != name => (Some(name), Some(memo)),
+      (Some(name), _) => (Some(name), None),
+      (None, memo) => (memo, None),
+    };
+    if payee.is_none() {
+      missing.push("NAME");
+    }
+    if !missing.is_empty() {
+      problems.insert(
+        0,
+        format!("Missing required fields: {}", missing.join(", ")),
+      );
+    }
+
+    match (date, amount, payee) {
+      (Some(date), Some(amount), Some(payee)) if problems.is_empty() => Ok(StatementLine {
+        line: self.line,
+        date,
+        payee,
+        amount,
+        reference: self.check_number.or(self.ref_number),
+        external_id: self.fitid,
+        memo,
+        balance: None,
+      }),
+      _ => Err(RowError {
+        line: self.line,
+        message: problems.join("; "),
+      }),
+    }
+  }
+}
+
+/// The date part of an OFX datetime such as `20260131`,
+/// `20260131093000.000[+10:AEST]`.
+fn parse_ofx_date(text: &str) -> Option<NaiveDate> {
+  NaiveDate::parse_from_str(text.get(..8)?, "%Y%m%d").ok()
+}
+
+/// An OFX amount, whose decimal point may be a comma.
+fn parse_ofx_amount(text: &str) -> Option<Money> {
+  let text = if text.contains('.') {
+    text.to_string()
+  } else {
+    text.replace(',', ".")
+  };
+  Money::parse(&text).ok()
+}
+
+/// Replace XML/SGML character references.
+fn unescape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    out.push_str(&rest[..start]);
+    rest = &rest[start..];
+    let entity = rest
+      .find(';')
+      .map(|end| (&rest[1..end], end))
+      .and_then(|(name, end)| {
+        let c = match name {
+          "amp" => '&',
+          "lt" => '<',
+          "gt" => '>',
+          "quot" => '"',
+          "apos" => '\'',
+          _ => {
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+              Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+              None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+          }
+        };
+        Some((c, end))
+      });
+    match entity {
+      Some((c, end)) => {
+        out.push(c);
+        rest = &rest[end + 1..];
+      }
+      None => {
+        out.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
@@ -0,0 +1,318 @@
+//! Text from PDF bank statements, laid out as lines the way a bank prints
+//! them, for a [`BankProfile`] to read.
+//!
+//! Statements are typeset text, not tables: each page's text runs are placed
+//! by position, grouped into lines by baseline and ordered left to right,
+//! with a space wherever there's a gap between runs. Scanned statements have
+//! no text to read.
+
+use std::collections::BTreeMap;
+
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Encoding, Object};
+
+use super::{detect_bank_profile, BankProfile, ImportError, PdfStatement};
+
+/// Runs whose baselines are within this fraction of the font size are on
+/// the same line.
+const SAME_LINE: f32 = 0.4;
+
+/// A gap between runs wider than this fraction of the font size is a space.
+const WORD_GAP: f32 = 0.15;
+
+/// Glyph width, in thousandths of the font size, for fonts that don't list
+/// their widths.
+const DEFAULT_GLYPH_WIDTH: f32 = 500.0;
+
+/// Read a PDF statement with the first of `profiles` that recognises it.
+pub fn read_pdf_statement(
+  bytes: &[u8],
+  profiles: &[BankProfile],
+) -> Result<PdfStatement, ImportError> {
+  let text = extract_pdf_text(bytes)?;
+  let profile = detect_bank_profile(&text, profiles)
+    .ok_or_else(|| ImportError::Pdf("no bank profile recognises it".into()))?;
+  Ok(profile.read(&text))
+}
+
+/// Every page's text, pages separated by a blank line.
+pub fn extract_pdf_text(bytes: &[u8]) -> Result<String, ImportError> {
+  let mut document = Document::load_mem(bytes).map_err(|err| ImportError::Pdf(err.to_string()))?;
+  // Statements are often encrypted with only an owner password, which
+  // restricts editing but not reading.
+  if document.is_encrypted() && document.decrypt("").is_err() {
+    return Err(ImportError::Pdf(
+      "the statement is password protected".into(),
+    ));
+  }
+  let mut pages = Vec::new();
+  for page_id in document.get_pages().into_values() {
+    let runs = page_runs(&document, page_id).map_err(|err| ImportError::Pdf(err.to_string()))?;
+    pages.push(lay_out(runs));
+  }
+  if pages.iter().all(|page| page.trim().is_empty()) {
+    return Err(ImportError::Pdf(
+      "there is no text in it; scanned statements can't be read".into(),
+    ));
+  }
+  Ok(pages.join("\n\n"))
+}
+
+/// Text shown in one go, where it starts and how wide it is, in page space.
+struct Run {
+  x: f32,
+  y: f32,
+  width: f32,
+  size: f32,
+  text: String,
+}
+
+/// An affine transform, `[a b c d e f]` as PDF writes them.
+#[derive(Clone, Copy)]
+struct Matrix([f32; 6]);
+
+impl Matrix {
+  const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+  fn translate(x: f32, y: f32) -> Matrix {
+    Matrix([1.0, 0.0, 0.0, 1.0, x, y])
+  }
+
+  /// `self` then `other`.
+  fn then(self, other: Matrix) -> Matrix {
+    let [a, b, c, d, e, f] = self.0;
+    let [oa, ob, oc, od, oe, of] = other.0;
+    Matrix([
+      a * oa + b * oc,
+      a * ob + b * od,
+      c * oa + d * oc,
+      c * ob + d * od,
+      e * oa + f * oc + oe,
+      e * ob + f * od + of,
+    ])
+  }
+
+  fn scale(self) -> f32 {
+    let [_, _, c, d, _, _] = self.0;
+    (c * c + d * d).sqrt()
+  }
+}
+
+/// A page font: how to decode its strings and how wide its glyphs are.
+struct Font<'a> {
+  encoding: Option<Encoding<'a>>,
+  first_char: i64,
+  widths: Vec<f32>,
+}
+
+impl<'a> Font<'a> {
+  fn new(document: &'a Document, dict: &'a Dictionary) -> Self {
+    let number = |object: &Object| object.as_float().ok();
+    let widths = dict
+      .get(b"Widths")
+      .and_then(|widths| document.dereference(widths))
+      .and_then(|(_, widths)| widths.as_array())
+      .map(|widths| {
+        widths
+          .iter()
+          .map(|w| number(w).unwrap_or(DEFAULT_GLYPH_WIDTH))
+          .collect()
+      })
+      .unwrap_or_default();
+    Font {
+      encoding: dict.get_font_encoding(document).ok(),
+      first_char: dict.get(b"FirstChar").and_then(Object::as_i64).unwrap_or(0),
+      widths,
+    }
+  }
+
+  fn decode(&self, bytes: &[u8]) -> String {
+    match &self.encoding {
+      Some(encoding) => Document::decode_text(encoding, bytes).unwrap_or_default(),
+      None => String::from_utf8_lossy(bytes).into_owned(),
+    }
+  }
+
+  /// The width of `bytes` shown at size 1.
+  fn width(&self, bytes: &[u8], text: &str) -> f32 {
+    let thousandths: f32 = if self.widths.is_empty() {
+      text.chars().count() as f32 * DEFAULT_GLYPH_WIDTH
+    } else {
+      bytes
+        .iter()
+        .map(|&code| {
+          usize::try_from(i64::from(code) - self.first_char)
+            .ok()
+            .and_then(|index| self.widths.get(index).copied())
+            .unwrap_or(DEFAULT_GLYPH_WIDTH)
+        })
+        .sum()
+    };
+    thousandths / 1000.0
+  }
+}
+
+/// The text state a content stream builds up.
+struct TextState {
+  ctm: Matrix,
+  saved: Vec<Matrix>,
+  matrix: Matrix,
+  line_matrix: Matrix,
+  font: Option<Vec<u8>>,
+  size: f32,
+  leading: f32,
+}
+
+impl TextState {
+  fn next_line(&mut self, x: f32, y: f32) {
+    self.line_matrix = Matrix::translate(x, y).then(self.line_matrix);
+    self.matrix = self.line_matrix;
+  }
+
+  fn advance(&mut self, distance: f32) {
+    self.matrix = Matrix::translate(distance, 0.0).then(self.matrix);
+  }
+}
+
+fn page_runs(document: &Document, page_id: lopdf::ObjectId) -> lopdf::Result<Vec<Run>> {
+  let fonts: BTreeMap<Vec<u8>, Font> = document
+    .get_page_fonts(page_id)?
+    .into_iter()
+    .map(|(name, dict)| (name, Font::new(document, dict)))
+    .collect();
+  let content = Content::decode(&document.get_page_content(page_id)?)?;
+
+  let mut state = TextState {
+    ctm: Matrix::IDENTITY,
+    saved: Vec::new(),
+    matrix: Matrix::IDENTITY,
+    line_matrix: Matrix::IDENTITY,
+    font: None,
+    size: 0.0,
+    leading: 0.0,
+  };
+  let mut runs = Vec::new();
+  for operation in &content.operations {
+    let operands = &operation.operands;
+    let number = |index: usize| {
+      operands
+        .get(index)
+        .and_then(|o| o.as_float().ok())
+        .unwrap_or(0.0)
+    };
+    match operation.operator.as_str() {
+      "q" => state.saved.push(state.ctm),
+      "Q" => state.ctm = state.saved.pop().unwrap_or(Matrix::IDENTITY),
+      "cm" => {
+        let matrix = Matrix([0, 1, 2, 3, 4, 5].map(number));
+        state.ctm = matrix.then(state.ctm);
+      }
+      "BT" => {
+        state.matrix = Matrix::IDENTITY;
+        state.line_matrix = Matrix::IDENTITY;
+      }
+      "Tf" => {
+        state.font = operands
+          .first()
+          .and_then(|o| o.as_name().ok())
+          .map(<[u8]>::to_vec);
+        state.size = number(1);
+      }
+      "TL" => state.leading = number(0),
+      "Td" => state.next_line(number(0), number(1)),
+      "TD" => {
+        state.leading = -number(1);
+        state.next_line(number(0), number(1));
+      }
+      "Tm" => {
+        state.line_matrix = Matrix([0, 1, 2, 3, 4, 5].map(number));
+        state.matrix = state.line_matrix;
+      }
+      "T*" => state.next_line(0.0, -state.leading),
+      "Tj" | "TJ" => show_text(&mut state, &fonts, operands, &mut runs),
+      // Move to the next line, then show the text.
+      "'" | "\"" => {
+        state.next_line(0.0, -state.leading);
+        show_text(&mut state, &fonts, operands, &mut runs);
+      }
+      _ => {}
+    }
+  }
+  Ok(runs)
+}
+
+/// Add a `Tj` or `TJ`'s text, moving along past it.
+fn show_text(
+  state: &mut TextState,
+  fonts: &BTreeMap<Vec<u8>, Font>,
+  operands: &[Object],
+  runs: &mut Vec<Run>,
+) {
+  let Some(font) = state.font.as_ref().and_then(|name| fonts.get(name)) else {
+    return;
+  };
+  let shown: Vec<&Object> = match operands.last() {
+    Some(Object::Array(items)) => items.iter().collect(),
+    Some(string) => vec![string],
+    None => return,
+  };
+  for item in shown {
+    match item {
+      Object::String(bytes, _) => {
+        let text = font.decode(bytes);
+        let width = font.width(bytes, &text) * state.size;
+        let placed = state.matrix.then(state.ctm);
+        runs.push(Run {
+          x: placed.0[4],
+          y: placed.0[5],
+          width: width * placed.0[0].hypot(placed.0[1]),
+          size: state.size * placed.scale(),
+          text,
+        });
+        state.advance(width);
+      }
+      // Kerning, in thousandths of the font size, moving left.
+      other => {
+        if let Ok(adjustment) = other.as_float() {
+          state.advance(-adjustment / 1000.0 * state.size);
+        }
+      }
+    }
+  }
+}
+
+/// Runs as lines of text, top to bottom.
+fn lay_out(mut runs: Vec<Run>) -> String {
+  runs.retain(|run| !run.text.trim().is_empty());
+  runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+  let mut lines: Vec<Vec<Run>> = Vec::new();
+  for run in runs {
+    match lines.last_mut() {
+      Some(line) if (line[0].y - run.y).abs() <= line[0].size.max(1.0) * SAME_LINE => {
+        line.push(run)
+      }
+      _ => lines.push(vec![run]),
+    }
+  }
+
+  let mut text = String::new();
+  for mut line in lines {
+    line.sort_by(|a, b| a.x.total_cmp(&b.x));
+    let mut end: Option<f32> = None;
+    for run in line {
+      if let Some(end) = end {
+        let gap = run.x - end;
+        if gap > run.size * WORD_GAP && !text.ends_with(' ') && !run.text.starts_with(' ') {
+          text.push(' ');
+        }
+      }
+      text.push_str(&run.text);
+      end = Some(run.x + run.width);
+    }
+    let trimmed = text.trim_end().len();
+    text.truncate(trimmed);
+    text.push('\n');
+  }
+  text
+}
@@ -0,0 +1,592 @@
+//! QIF, the Quicken Interchange Format that Quicken, MYOB and older banking
+//! software read and write.
+//!
+//! A file is a series of `!Type:` sections, each a list of records of one
+//! letter field codes ending in `^`. Exports covering several accounts put an
+//! `!Account` record naming the account before each section. Only bank,
+//! cash, credit card and other asset/liability sections are read; investment
+//! sections and the category, class and memorised transaction lists are
+//! skipped.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{decode_text, parse_amount, ImportError, RowError, StatementBalance, StatementLine};
+use crate::model::{Account, AccountKind, AccountSubtype, AccountType, Transaction};
+use crate::money::Money;
+
+/// The payee Quicken gives the record carrying an account's opening balance.
+const OPENING_BALANCE_PAYEE: &str = "Opening Balance";
+
+/// How `D` dates are written. Quicken's Australian and UK editions write
+/// day first, the US edition month first; `!Option:DMY` and `!Option:MDY`
+/// in a file override this.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateOrder {
+  #[default]
+  DayFirst,
+  MonthFirst,
+}
+
+/// The `!Type:` of a section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QifAccountType {
+  Bank,
+  Cash,
+  CreditCard,
+  /// `Oth A`
+  Asset,
+  /// `Oth L`
+  Liability,
+}
+
+impl QifAccountType {
+  /// The name after `!Type:`.
+  pub fn header(self) -> &'static str {
+    match self {
+      QifAccountType::Bank => "Bank",
+      QifAccountType::Cash => "Cash",
+      QifAccountType::CreditCard => "CCard",
+      QifAccountType::Asset => "Oth A",
+      QifAccountType::Liability => "Oth L",
+    }
+  }
+
+  fn from_header(name: &str) -> Option<Self> {
+    [
+      QifAccountType::Bank,
+      QifAccountType::Cash,
+      QifAccountType::CreditCard,
+      QifAccountType::Asset,
+      QifAccountType::Liability,
+    ]
+    .into_iter()
+    .find(|kind| kind.header().eq_ignore_ascii_case(name))
+  }
+
+  /// The section an account is written as.
+  pub fn for_account(account: &Account) -> Self {
+    match (account.subtype, account.account_type) {
+      (Some(AccountSubtype::Card), _) => QifAccountType::CreditCard,
+      (Some(AccountSubtype::Cash), _) => QifAccountType::Cash,
+      (Some(AccountSubtype::Bank | AccountSubtype::Psp | AccountSubtype::SavingsGoal), _) => {
+        QifAccountType::Bank
+      }
+      (_, AccountType::Liability) => QifAccountType::Liability,
+      _ => QifAccountType::Asset,
+    }
+  }
+}
+
+/// A record's `L` or a split's `S`: a category path such as
+/// `Groceries:Supermarket`, or `[Name]` for a transfer to another account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QifCategory {
+  Category(String),
+  Transfer(String),
+}
+
+impl QifCategory {
+  /// Read an `L` or `S` value, dropping any `/Class`.
+  fn parse(text: &str) -> Option<Self> {
+    let text = text.split('/').next().unwrap_or_default().trim();
+    if let Some(name) = text
+      .strip_prefix('[')
+      .and_then(|rest| rest.strip_suffix(']'))
+    {
+      let name = name.trim();
+      return (!name.is_empty()).then(|| QifCategory::Transfer(name.to_string()));
+    }
+    (!text.is_empty()).then(|| QifCategory::Category(text.to_string()))
+  }
+}
+
+impl fmt::Display for QifCategory {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      QifCategory::Category(path) => f.write_str(path),
+      QifCategory::Transfer(name) => write!(f, "[{name}]"),
+    }
+  }
+}
+
+/// A record's `C` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClearedStatus {
+  #[default]
+  Uncleared,
+  /// `*` or `c`
+  Cleared,
+  /// `X` or `R`
+  Reconciled,
+}
+
+impl ClearedStatus {
+  fn parse(text: &str) -> Self {
+    match text.trim() {
+      "*" | "c" | "C" => ClearedStatus::Cleared,
+      "X" | "x" | "R" | "r" => ClearedStatus::Reconciled,
+      _ => ClearedStatus::Uncleared,
+    }
+  }
+}
+
+/// One line of a split transaction (`S`, `E` and `$`). The amount has the
+/// transaction's sign.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifSplit {
+  pub category: Option<QifCategory>,
+  pub memo: Option<String>,
+  pub amount: Money,
+}
+
+/// One transaction record. The amount is from the account's side: positive
+/// is money in, as for statement lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifTransaction {
+  /// Line in the file the record starts on, counting from 1.
+  pub line: u64,
+  pub date: NaiveDate,
+  pub amount: Money,
+  pub payee: Option<String>,
+  pub memo: Option<String>,
+  /// `N`: a cheque number or reference.
+  pub reference: Option<String>,
+  pub cleared: ClearedStatus,
+  pub category: Option<QifCategory>,
+  /// Empty unless the record is split, in which case `category` is ignored.
+  pub splits: Vec<QifSplit>,
+}
+
+impl QifTransaction {
+  /// The record as a statement line, for the duplicate check.
+  pub fn statement_line(&self) -> StatementLine {
+    StatementLine {
+      line: self.line,
+      date: self.date,
+      payee: self
+        .payee
+        .clone()
+        .or_else(|| self.memo.clone())
+        .unwrap_or_default(),
+      amount: self.amount,
+      reference: self.reference.clone(),
+      external_id: None,
+      memo: self.memo.clone(),
+      balance: None,
+    }
+  }
+
+  fn is_opening_balance(&self) -> bool {
+    self.splits.is_empty()
+      && matches!(self.category, Some(QifCategory::Transfer(_)))
+      && self
+        .payee
+        .as_deref()
+        .is_some_and(|payee| payee.eq_ignore_ascii_case(OPENING_BALANCE_PAYEE))
+  }
+}
+
+/// One `!Type:` section.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifAccount {
+  /// The `N` of the `!Account` record before the section, if there was one.
+  pub name: Option<String>,
+  pub account_type: QifAccountType,
+  /// Quicken's "Opening Balance" record, which is a transfer from the
+  /// account to itself rather than a transaction.
+  pub opening_balance: Option<StatementBalance>,
+  pub transactions: Vec<QifTransaction>,
+  pub errors: Vec<RowError>,
+}
+
+/// What importing a QIF section did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifImportSummary {
+  pub batch_id: String,
+  pub imported: usize,
+  /// Records skipped as already in the account.
+  pub duplicates: usize,
+  /// Categories and `[transfer]` accounts the book has nothing matching, as
+  /// written in the file. Their lines went to "Uncategorized".
+  pub unknown_categories: Vec<String>,
+}
+
+/// Read every bank, cash, credit card and other asset or liability section
+/// of a QIF file.
+pub fn read_qif(bytes: &[u8], dates: DateOrder) -> Result<Vec<QifAccount>, ImportError> {
+  let text = decode_text(bytes);
+  let mut reader = Reader {
+    dates,
+    accounts: Vec::new(),
+    section: Section::None,
+    account_name: None,
+    record: None,
+  };
+  for (index, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+    reader.line(index as u64 + 1, line);
+  }
+  reader.end_section();
+  if reader.accounts.is_empty() {
+    return Err(ImportError::Qif(
+      "there are no bank or credit card transactions".into(),
+    ));
+  }
+  Ok(reader.accounts)
+}
+
+enum Section {
+  None,
+  Account,
+  Transactions(QifAccount),
+  Skipped,
+}
+
+/// A record's fields in file order, with the line it starts on.
+struct RawRecord {
+  line: u64,
+  fields: Vec<(char, String)>,
+}
+
+struct Reader {
+  dates: DateOrder,
+  accounts: Vec<QifAccount>,
+  section: Section,
+  /// From the last `!Account` record, for the next `!Type:` section.
+  account_name: Option<String>,
+  record: Option<RawRecord>,
+}
+
+impl Reader {
+  fn line(&mut self, number: u64, line: &str) {
+    let line = line.trim_end();
+    if let Some(header) = line.strip_prefix('!') {
+      self.header(header.trim());
+      return;
+    }
+    let mut chars = line.chars();
+    let Some(code) = chars.next() else {
+      return;
+    };
+    if code == '^' {
+      self.end_record();
+      return;
+    }
+    self
+      .record
+      .get_or_insert_with(|| RawRecord {
+        line: number,
+        fields: Vec::new(),
+      })
+      .fields
+      .push((code.to_ascii_uppercase(), chars.as_str().trim().to_string()));
+  }
+
+  fn header(&mut self, header: &str) {
+    let lower = header.to_ascii_lowercase();
+    match lower.as_str() {
+      "option:dmy" => self.dates = DateOrder::DayFirst,
+      "option:mdy" => self.dates = DateOrder::MonthFirst,
+      // Switching into and out of a list of accounts; the `!Account`
+      // headers around it are what matter.
+      "option:autoswitch" | "clear:autoswitch" => {}
+      "account" => {
+        self.end_section();
+        self.section = Section::Account;
+      }
+      _ => {
+        self.end_section();
+        self.section = match lower
+          .strip_prefix("type:")
+          .and_then(|name| QifAccountType::from_header(name.trim()))
+        {
+          Some(account_type) => Section::Transactions(QifAccount {
+            name: self.account_name.take(),
+            account_type,
+            opening_balance: None,
+            transactions: Vec::new(),
+            errors: Vec::new(),
+          }),
+          None => Section::Skipped,
+        };
+      }
+    }
+  }
+
+  fn end_record(&mut self) {
+    let Some(record) = self.record.take() else {
+      return;
+    };
+    match &mut self.section {
+      Section::Account => {
+        if let Some((_, name)) = record.fields.iter().find(|(code, _)| *code == 'N') {
+          self.account_name = Some(name.clone());
+        }
+      }
+      Section::Transactions(account) => match record.transaction(self.dates) {
+        Ok(transaction) if transaction.is_opening_balance() => {
+          account.opening_balance = Some(StatementBalance {
+            amount: transaction.amount,
+            date: transaction.date,
+          });
+        }
+        Ok(transaction) => account.transactions.push(transaction),
+        Err(err) => account.errors.push(err),
+      },
+      Section::None | Section::Skipped => {}
+    }
+  }
+
+  fn end_section(&mut self) {
+    // A section's last record may be missing its `^`.
+    self.end_record();
+    if let Section::Transactions(account) = std::mem::replace(&mut self.section, Section::None) {
+      self.accounts.push(account);
+    }
+  }
+}
+
+impl RawRecord {
+  fn transaction(self, dates: DateOrder) -> Result<QifTransaction, RowError> {
+    let mut date_text = None;
+    let mut amount_text = None;
+    let mut payee = None;
+    let mut memo = None;
+    let mut reference = None;
+    let mut cleared = ClearedStatus::Uncleared;
+    let mut category = None;
+    let mut splits: Vec<QifSplit> = Vec::new();
+    let mut problems = Vec::new();
+
+    for (code, value) in self.fields {
+      match code {
+        'D' => date_text = Some(value),
+        // `U` repeats `T` in newer Quicken exports.
+        'T' | 'U' if amount_text.is_none() => amount_text = Some(value),
+        'C' => cleared = ClearedStatus::parse(&value),
+        'N' => reference = Some(value).filter(|v| !v.is_empty()),
+        'P' => payee = Some(value).filter(|v| !v.is_empty()),
+        'M' => memo = Some(value).filter(|v| !v.is_empty()),
+        'L' => category = QifCategory::parse(&value),
+        'S' => splits.push(QifSplit {
+          category: QifCategory::parse(&value),
+          memo: None,
+          amount: Money::ZERO,
+        }),
+        'E' => {
+          if let Some(split) = splits.last_mut() {
+            split.memo = Some(value).filter(|v| !v.is_empty());
+          }
+        }
+        '$' => {
+          if let Some(split) = splits.last_mut() {
+            match parse_amount(&value) {
+              Some(amount) => split.amount = amount,
+              None => problems.push(format!("Unrecognised split amount: {value}")),
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+
+    let mut missing = Vec::new();
+    let date = match date_text {
+      Some(text) => parse_qif_date(&text, dates).or_else(|| {
+        problems.push(format!("Unrecognised date: {text}"));
+        None
+      }),
+      None => {
+        missing.push("D");
+        None
+      }
+    };
+    let amount = match amount_text {
+      Some(text) => parse_amount(&text).or_else(|| {
+        problems.push(format!("Unrecognised amount: {text}"));
+        None
+      }),
+      None => {
+        missing.push("T");
+        None
+      }
+    };
+    if !missing.is_empty() {
+      problems.insert(
+        0,
+        format!("Missing required fields: {}", missing.join(", ")),
+      );
+    }
+    if let Some(amount) = amount.filter(|_| !splits.is_empty() && problems.is_empty()) {
+      let total: Money = splits.iter().map(|split| split.amount).sum();
+      if total != amount {
+        problems.push(format!("Splits add up to {total}, not {amount}"));
+      }
+    }
+
+    match (date, amount) {
+      (Some(date), Some(amount)) if problems.is_empty() => Ok(QifTransaction {
+        line: self.line,
+        date,
+        amount,
+        payee,
+        memo,
+        reference,
+        cleared,
+        category,
+        splits,
+      }),
+      _ => Err(RowError {
+        line: self.line,
+        message: problems.join("; "),
+      }),
+    }
+  }
+}
+
+/// Parse a QIF date such as `31/01/2026`, `31/01/26`, `1/31'26` or
+/// `1/ 5' 4`. Quicken writes years from 2000 after an apostrophe, sometimes
+/// space padded; other two-digit years are 1970–2069.
+pub fn parse_qif_date(text: &str, order: DateOrder) -> Option<NaiveDate> {
+  let parts: Vec<&str> = text
+    .trim()
+    .split(['/', '\'', '-', '.'])
+    .map(str::trim)
+    .collect();
+  let [first, second, last] = parts[..] else {
+    return None;
+  };
+  let (day, month, year) = match order {
+    _ if first.len() == 4 => (last, second, first),
+    DateOrder::DayFirst => (first, second, last),
+    DateOrder::MonthFirst => (second, first, last),
+  };
+  let year = match (year.len(), year.parse::<i32>().ok()?) {
+    (4, year) => year,
+    (1 | 2, year) if text.contains('\'') || year < 70 => 2000 + year,
+    (1 | 2, year) => 1900 + year,
+    _ => return None,
+  };
+  NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+}
+
+/// How a category account is written in QIF: its `fullPath` without the
+/// `Expense`/`Income` root, with `:` between levels.
+pub fn category_path(account: &Account) -> String {
+  let Some(full_path) = &account.full_path else {
+    return account.name.clone();
+  };
+  let mut segments: Vec<&str> = full_path.split('/').collect();
+  if segments.len() > 1 && segments[0].eq_ignore_ascii_case(account.account_type.as_str()) {
+    segments.remove(0);
+  }
+  segments.join(":")
+}
+
+/// Write `account`'s transactions as one QIF section, with an `!Account`
+/// record so Quicken and MYOB know which account it is. Other accounts'
+/// postings become the `L` category, or `S`/`E`/`$` splits when there are
+/// several.
+pub fn write_qif(
+  account: &Account,
+  opening_balance: Option<StatementBalance>,
+  transactions: &[Transaction],
+  accounts: &HashMap<String, Account>,
+  dates: DateOrder,
+) -> String {
+  let account_type = QifAccountType::for_account(account);
+  let mut out = String::new();
+  let mut field = |code: char, value: &str| {
+    out.push(code);
+    // Fields are one line each.
+    out.push_str(&value.replace(['\r', '\n'], " "));
+    out.push('\n');
+  };
+  field('!', "Account");
+  field('N', &account.name);
+  field('T', account_type.header());
+  field('^', "");
+  field('!', &format!("Type:{}", account_type.header()));
+
+  if let Some(opening) = opening_balance {
+    field('D', &format_qif_date(opening.date, dates));
+    field('T', &opening.amount.to_string());
+    field('C', "X");
+    field('P', OPENING_BALANCE_PAYEE);
+    field('L', &format!("[{}]", account.name));
+    field('^', "");
+  }
+
+  for transaction in transactions.iter().filter(|t| !t.is_void()) {
+    let (own, others): (Vec<_>, Vec<_>) = transaction
+      .postings
+      .iter()
+      .partition(|p| p.account_id == account.id);
+    if own.is_empty() {
+      continue;
+    }
+    let amount: Money = own.iter().map(|p| p.amount).sum();
+    field('D', &format_qif_date(transaction.date.date_naive(), dates));
+    field('T', &amount.to_string());
+    if own.iter().all(|p| p.reconciled) {
+      field('C', "X");
+    } else if own.iter().all(|p| p.cleared) {
+      field('C', "*");
+    }
+    if let Some(reference) = &transaction.reference {
+      field('N', reference);
+    }
+    if !transaction.payee.is_empty() {
+      field('P', &transaction.payee);
+    }
+    if let Some(memo) = &transaction.memo {
+      field('M', memo);
+    }
+    let target = |account_id: &str| {
+      accounts.get(account_id).map(|other| match other.kind {
+        AccountKind::Category => QifCategory::Category(category_path(other)),
+        AccountKind::Transfer => QifCategory::Transfer(other.name.clone()),
+      })
+    };
+    match others[..] {
+      [] => {}
+      [other] => {
+        if let Some(category) = target(&other.account_id) {
+          field('L', &category.to_string());
+        }
+      }
+      _ => {
+        for other in others {
+          if let Some(category) = target(&other.account_id) {
+            field('S', &category.to_string());
+          }
+          if let Some(label) = &other.category_split_label {
+            field('E', label);
+          }
+          field('$', &(-other.amount).to_string());
+        }
+      }
+    }
+    field('^', "");
+  }
+  out
+}
+
+fn format_qif_date(date: NaiveDate, order: DateOrder) -> String {
+  match order {
+    DateOrder::DayFirst => date.format("%d/%m/%Y"),
+    DateOrder::MonthFirst => date.format("%m/%d/%Y"),
+  }
+  .to_string()
+}
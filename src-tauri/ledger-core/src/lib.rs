@@ -0,0 +1,33 @@
+//! Native double-entry core for Ledgerhound.
+//!
+//! Mirrors the Prisma models and the rules that `transactionService.ts` and
+//! `accountService.ts` enforce, so the desktop shell can validate and compute
+//! balances in-process without the Node sidecar.
+
+pub mod backup;
+pub mod balance;
+pub mod book_manager;
+pub mod db;
+pub mod encryption;
+pub mod import;
+pub mod matching;
+pub mod migrate;
+pub mod model;
+pub mod money;
+pub mod money_storage;
+pub mod reports;
+pub mod rules;
+pub mod secrets;
+pub mod tax;
+pub mod validation;
+
+pub use balance::{account_balance, account_balances, BalanceOptions};
+pub use db::{Database, DbError};
+pub use model::{
+  Account, AccountKind, AccountSubtype, AccountType, GstCode, Posting, Reconciliation, Transaction,
+  TransactionStatus,
+};
+pub use money::{Currency, Money};
+pub use rules::{RuleContext, RuleExplanation, RuleMatcher};
+pub use rusqlite;
+pub use validation::{validate_transaction, ValidationError};
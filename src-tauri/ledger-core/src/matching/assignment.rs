@@ -0,0 +1,102 @@
+//! Optimal one-to-one assignment (the Hungarian algorithm), as `munkres-js`
+//! does for `transferMatchingService.ts`.
+
+/// Pair rows with columns so the total score is as high as possible, each
+/// row and column used at most once. Returns each row's column; pairs that
+/// score zero or less aren't worth making and come back as `None`.
+///
+/// `scores` is a rectangular matrix, one `Vec` per row. Runs in
+/// O(n²·m) for n the smaller side.
+pub fn max_score_assignment(scores: &[Vec<i64>]) -> Vec<Option<usize>> {
+  let rows = scores.len();
+  let cols = scores.first().map_or(0, Vec::len);
+  if rows == 0 || cols == 0 {
+    return vec![None; rows];
+  }
+  let best = scores.iter().flatten().copied().max().unwrap_or(0).max(0);
+  // Minimise the shortfall from the best score instead; a pair that isn't
+  // worth making costs as much as leaving both sides unpaired.
+  let cost = |row: usize, col: usize| best - scores[row][col].max(0);
+
+  let mut assigned = if rows <= cols {
+    let mut assigned = vec![None; rows];
+    for (col, row) in solve(rows, cols, cost).into_iter().enumerate() {
+      if let Some(row) = row {
+        assigned[row] = Some(col);
+      }
+    }
+    assigned
+  } else {
+    // Transposed, each row is a column and comes back with its row.
+    solve(cols, rows, |col, row| cost(row, col))
+  };
+  for (row, col) in assigned.iter_mut().enumerate() {
+    if col.is_some_and(|col| scores[row][col] <= 0) {
+      *col = None;
+    }
+  }
+  assigned
+}
+
+/// The minimum-cost assignment of `n` rows to `m >= n` columns, as each
+/// column's row. Shortest augmenting paths with potentials, 1-indexed with
+/// column 0 as the path's root.
+fn solve(n: usize, m: usize, cost: impl Fn(usize, usize) -> i64) -> Vec<Option<usize>> {
+  let mut u = vec![0i64; n + 1];
+  let mut v = vec![0i64; m + 1];
+  let mut row_of = vec![0usize; m + 1];
+  let mut way = vec![0usize; m + 1];
+
+  for row in 1..=n {
+    row_of[0] = row;
+    let mut col0 = 0;
+    let mut min_reduced = vec![i64::MAX; m + 1];
+    let mut used = vec![false; m + 1];
+    loop {
+      used[col0] = true;
+      let row0 = row_of[col0];
+      let mut delta = i64::MAX;
+      let mut col1 = 0;
+      for col in 1..=m {
+        if used[col] {
+          continue;
+        }
+        let reduced = cost(row0 - 1, col - 1) - u[row0] - v[col];
+        if reduced < min_reduced[col] {
+          min_reduced[col] = reduced;
+          way[col] = col0;
+        }
+        if min_reduced[col] < delta {
+          delta = min_reduced[col];
+          col1 = col;
+        }
+      }
+      for col in 0..=m {
+        if used[col] {
+          u[row_of[col]] += delta;
+          v[col] -= delta;
+        } else {
+          min_reduced[col] -= delta;
+        }
+      }
+      col0 = col1;
+      if row_of[col0] == 0 {
+        break;
+      }
+    }
+    // Flip the augmenting path back to the root.
+    loop {
+      let col1 = way[col0];
+      row_of[col0] = row_of[col1];
+      col0 = col1;
+      if col0 == 0 {
+        break;
+      }
+    }
+  }
+
+  row_of[1..]
+    .iter()
+    .map(|&row| (row != 0).then(|| row - 1))
+    .collect()
+}
@@ -0,0 +1,24 @@
+//! Pairing up transactions: transfers imported separately into two
+//! accounts, mirroring `transferMatchingService.ts`, and statement lines
+//! with the postings they clear, mirroring
+//! `reconciliationMatchingService.ts`.
+//!
+//! Matching is pure, over candidates the [`crate::Database`] reads, and
+//! pairs are chosen by optimal assignment rather than greedily, so one
+//! strong pair never steals a candidate two others needed.
+
+mod assignment;
+mod reconciliation;
+mod transfers;
+
+pub use assignment::max_score_assignment;
+pub use reconciliation::{
+  description_similarity, match_statement_lines, normalize_description, DifferenceExplanation,
+  LedgerItem, ReconciliationMatch, ReconciliationMatchPreview, ReconciliationMatchSummary,
+  ReconciliationMatchType,
+};
+pub use transfers::{
+  match_transfer_candidates, transfer_match_score, TransferCandidate, TransferMatch,
+  TransferMatchConfig, TransferMatchPreview, TransferMatchSummary, TransferMatchType,
+  TRANSFER_KEYWORDS,
+};
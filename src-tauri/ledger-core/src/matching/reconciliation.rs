@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::assignment::max_score_assignment;
+use crate::import::StatementLine;
+use crate::model::{MatchType, MemorizedRule};
+use crate::money::Money;
+
+/// Pairs scoring less than this aren't proposed at all.
+const MIN_SCORE: u32 = 40;
+const EXACT_SCORE: u32 = 80;
+const PROBABLE_SCORE: u32 = 60;
+
+/// A posting waiting to be reconciled, as the matcher sees it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerItem {
+  pub transaction_id: String,
+  pub posting_id: String,
+  pub date: DateTime<Utc>,
+  pub payee: String,
+  pub memo: Option<String>,
+  pub amount: Money,
+  pub cleared: bool,
+  /// The bank's text the payee was cleaned up from on import, kept in
+  /// the transaction's metadata.
+  pub original_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconciliationMatchType {
+  Exact,
+  Probable,
+  Possible,
+}
+
+impl ReconciliationMatchType {
+  fn from_score(score: u32) -> Self {
+    match score {
+      s if s >= EXACT_SCORE => ReconciliationMatchType::Exact,
+      s if s >= PROBABLE_SCORE => ReconciliationMatchType::Probable,
+      _ => ReconciliationMatchType::Possible,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationMatch {
+  pub statement_line: StatementLine,
+  pub ledger_item: LedgerItem,
+  /// Out of 100: 50 for the amount, 30 for the date and 20 for the
+  /// description.
+  pub match_score: u32,
+  pub match_type: ReconciliationMatchType,
+  pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationMatchSummary {
+  pub total_statement: usize,
+  pub total_ledger: usize,
+  pub total_matched: usize,
+  pub unmatched_statement: usize,
+  pub unmatched_ledger: usize,
+}
+
+/// Why the ledger and the statement disagree, each part as it moves the
+/// ledger balance away from the statement's:
+///
+/// `difference = not_on_statement - missing_from_ledger + amount_differences + carried_forward`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DifferenceExplanation {
+  pub statement_balance: Option<Money>,
+  pub ledger_balance: Money,
+  /// Ledger minus statement, as `getReconciliationStatus` reports it.
+  pub difference: Option<Money>,
+  /// Statement lines the ledger doesn't have.
+  pub missing_from_ledger: Money,
+  /// Ledger postings the statement doesn't show.
+  pub not_on_statement: Money,
+  /// Ledger minus statement, over matched pairs whose amounts differ.
+  pub amount_differences: Money,
+  /// What's left: a difference that was already there before the
+  /// statement began.
+  pub carried_forward: Option<Money>,
+  /// The above in words, largest first.
+  pub notes: Vec<String>,
+}
+
+/// Statement lines paired with ledger postings, like `matchTransactions`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationMatchPreview {
+  pub exact_matches: Vec<ReconciliationMatch>,
+  pub probable_matches: Vec<ReconciliationMatch>,
+  pub possible_matches: Vec<ReconciliationMatch>,
+  pub unmatched_statement: Vec<StatementLine>,
+  pub unmatched_ledger: Vec<LedgerItem>,
+  pub summary: ReconciliationMatchSummary,
+  pub explanation: DifferenceExplanation,
+}
+
+/// A memorized rule that renames bank text to a payee, for recognising a
+/// ledger payee from the statement's description.
+struct PayeeRule<'a> {
+  rule: &'a MemorizedRule,
+  regex: Option<Regex>,
+}
+
+impl PayeeRule<'_> {
+  /// Like `matchesRulePattern`: looser than matching on import, since a
+  /// PDF statement's text is spaced and truncated differently from the CSV
+  /// the rule was written for.
+  fn matches(&self, description: &str) -> bool {
+    let description = collapse_spaces(&description.to_lowercase());
+    let pattern = collapse_spaces(&self.rule.match_value.to_lowercase());
+    match self.rule.match_type {
+      MatchType::Exact => description == pattern,
+      MatchType::Contains => {
+        if description.contains(&pattern) {
+          return true;
+        }
+        // The leading words are usually the merchant's name; the rest is
+        // a suburb or store number that varies.
+        let words: Vec<&str> = pattern
+          .split(' ')
+          .filter(|word| word.chars().count() > 2)
+          .collect();
+        if words.is_empty() {
+          return false;
+        }
+        let first_three = words[..words.len().min(3)].join(" ");
+        let first_two = words[..words.len().min(2)].join(" ");
+        description.contains(&first_three)
+          || first_two.chars().count() >= 5 && description.contains(&first_two)
+      }
+      MatchType::Regex => self
+        .regex
+        .as_ref()
+        .is_some_and(|regex| regex.is_match(&description)),
+    }
+  }
+}
+
+fn collapse_spaces(text: &str) -> String {
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lower case letters and digits, single spaced, like `normalizeDescription`.
+pub fn normalize_description(text: &str) -> String {
+  let kept: String = text
+    .to_lowercase()
+    .chars()
+    .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c.is_whitespace())
+    .collect();
+  collapse_spaces(&kept)
+}
+
+/// The Sørensen–Dice coefficient of two strings' letter pairs, ignoring
+/// spaces, as `compareTwoStrings` in `string-similarity` computes it: 1 for
+/// the same text, 0 for nothing in common.
+pub fn description_similarity(first: &str, second: &str) -> f64 {
+  let first: Vec<char> = first.chars().filter(|c| !c.is_whitespace()).collect();
+  let second: Vec<char> = second.chars().filter(|c| !c.is_whitespace()).collect();
+  if first.is_empty() || second.is_empty() {
+    return 0.0;
+  }
+  if first == second {
+    return 1.0;
+  }
+  if first.len() < 2 || second.len() < 2 {
+    return 0.0;
+  }
+  let mut bigrams: HashMap<(char, char), usize> = HashMap::new();
+  for pair in first.windows(2) {
+    *bigrams.entry((pair[0], pair[1])).or_default() += 1;
+  }
+  let mut shared = 0;
+  for pair in second.windows(2) {
+    if let Some(count) = bigrams
+      .get_mut(&(pair[0], pair[1]))
+      .filter(|count| **count > 0)
+    {
+      *count -= 1;
+      shared += 1;
+    }
+  }
+  (2 * shared) as f64 / (first.len() + second.len() - 2) as f64
+}
+
+/// A pair's score and reasons, and how alike the descriptions are.
+#[derive(Default)]
+struct Scored {
+  score: u32,
+  similarity: f64,
+  reasons: Vec<String>,
+}
+
+fn score_pair(line: &StatementLine, item: &LedgerItem, rules: &[PayeeRule]) -> Scored {
+  let mut score = 0;
+  let mut reasons = Vec::new();
+
+  let days = (line.date_time() - item.date).num_milliseconds().abs() as f64 / 86_400_000.0;
+  let date_points = if days < 0.5 {
+    Some((30, "Exact date match"))
+  } else if days <= 1.0 {
+    Some((25, "Date within 1 day"))
+  } else if days <= 3.0 {
+    Some((15, "Date within 3 days"))
+  } else if days <= 7.0 {
+    Some((5, "Date within 7 days"))
+  } else {
+    None
+  };
+  if let Some((points, reason)) = date_points {
+    score += points;
+    reasons.push(reason.to_string());
+  }
+
+  // Both sides are signed from the account's point of view: import posts
+  // statement lines to the account as they are.
+  let difference = (line.amount - item.amount).abs();
+  if difference.is_zero() {
+    score += 50;
+    reasons.push("Exact amount match".to_string());
+  } else if difference < Money::from_cents(100) {
+    score += 25;
+    reasons.push("Amount within $1".to_string());
+  }
+
+  let description = normalize_description(&line.payee);
+  let against_payee = description_similarity(&description, &normalize_description(&item.payee));
+  let against_original = item
+    .original_description
+    .as_deref()
+    .map_or(0.0, |original| {
+      description_similarity(&description, &normalize_description(original))
+    });
+  let via_rule = rules
+    .iter()
+    .find(|rule| {
+      rule.rule.default_payee.as_deref() == Some(item.payee.as_str()) && rule.matches(&line.payee)
+    })
+    .map(|rule| &rule.rule.name);
+
+  let (similarity, via) = match via_rule {
+    Some(name) if against_payee < 1.0 && against_original < 1.0 => {
+      (1.0, format!(" via rule \"{name}\""))
+    }
+    _ if against_original > against_payee => (against_original, " via original".to_string()),
+    _ => (against_payee, String::new()),
+  };
+  let percent = (similarity * 100.0).round();
+  let description_points = if similarity > 0.8 {
+    Some((20, "High"))
+  } else if similarity > 0.5 {
+    Some((10, "Medium"))
+  } else if similarity > 0.3 {
+    Some((5, "Low"))
+  } else {
+    None
+  };
+  if let Some((points, level)) = description_points {
+    score += points;
+    reasons.push(format!("{level} description similarity ({percent}%{via})"));
+  }
+
+  Scored {
+    score,
+    similarity,
+    reasons,
+  }
+}
+
+/// Pair statement lines with ledger postings one-to-one for the highest
+/// total score, like `matchTransactions`, and explain what's left of the
+/// difference between `statement_balance` and `ledger_balance`.
+///
+/// Pairs under the minimum score are kept out of the assignment, and
+/// description similarity breaks ties between pairs that score the same,
+/// so two identical amounts a few days apart go to the closer payee.
+pub fn match_statement_lines(
+  lines: Vec<StatementLine>,
+  ledger: Vec<LedgerItem>,
+  rules: &[MemorizedRule],
+  statement_balance: Option<Money>,
+  ledger_balance: Money,
+) -> ReconciliationMatchPreview {
+  let rules: Vec<PayeeRule> = rules
+    .iter()
+    .filter(|rule| rule.default_payee.is_some())
+    .map(|rule| PayeeRule {
+      rule,
+      regex: (rule.match_type == MatchType::Regex)
+        .then(|| {
+          RegexBuilder::new(&rule.match_value)
+            .case_insensitive(true)
+            .build()
+            .ok()
+        })
+        .flatten(),
+    })
+    .collect();
+
+  let mut scored: Vec<Vec<Scored>> = lines
+    .iter()
+    .map(|line| {
+      ledger
+        .iter()
+        .map(|item| score_pair(line, item, &rules))
+        .collect()
+    })
+    .collect();
+  let weights: Vec<Vec<i64>> = scored
+    .iter()
+    .map(|row| {
+      row
+        .iter()
+        .map(|pair| match pair.score {
+          score if score < MIN_SCORE => 0,
+          score => i64::from(score) * 100 + (pair.similarity * 99.0).round() as i64,
+        })
+        .collect()
+    })
+    .collect();
+  let assignment = max_score_assignment(&weights);
+
+  let mut preview = ReconciliationMatchPreview {
+    exact_matches: Vec::new(),
+    probable_matches: Vec::new(),
+    possible_matches: Vec::new(),
+    unmatched_statement: Vec::new(),
+    unmatched_ledger: Vec::new(),
+    summary: ReconciliationMatchSummary {
+      total_statement: lines.len(),
+      total_ledger: ledger.len(),
+      ..ReconciliationMatchSummary::default()
+    },
+    explanation: DifferenceExplanation {
+      statement_balance,
+      ledger_balance,
+      difference: statement_balance.map(|statement| ledger_balance - statement),
+      missing_from_ledger: Money::ZERO,
+      not_on_statement: Money::ZERO,
+      amount_differences: Money::ZERO,
+      carried_forward: None,
+      notes: Vec::new(),
+    },
+  };
+  let mut matched_ledger = vec![false; ledger.len()];
+  let mut mismatched_pairs = 0;
+  for (i, line) in lines.into_iter().enumerate() {
+    let Some(j) = assignment[i] else {
+      preview.explanation.missing_from_ledger += line.amount;
+      preview.unmatched_statement.push(line);
+      continue;
+    };
+    matched_ledger[j] = true;
+    let item = ledger[j].clone();
+    if item.amount != line.amount {
+      mismatched_pairs += 1;
+      preview.explanation.amount_differences += item.amount - line.amount;
+    }
+    let Scored { score, reasons, .. } = std::mem::take(&mut scored[i][j]);
+    let found = ReconciliationMatch {
+      statement_line: line,
+      ledger_item: item,
+      match_score: score,
+      match_type: ReconciliationMatchType::from_score(score),
+      reasons,
+    };
+    match found.match_type {
+      ReconciliationMatchType::Exact => preview.exact_matches.push(found),
+      ReconciliationMatchType::Probable => preview.probable_matches.push(found),
+      ReconciliationMatchType::Possible => preview.possible_matches.push(found),
+    }
+  }
+  for (item, matched) in ledger.into_iter().zip(matched_ledger) {
+    if !matched {
+      preview.explanation.not_on_statement += item.amount;
+      preview.unmatched_ledger.push(item);
+    }
+  }
+
+  let summary = &mut preview.summary;
+  summary.unmatched_statement = preview.unmatched_statement.len();
+  summary.unmatched_ledger = preview.unmatched_ledger.len();
+  summary.total_matched = summary.total_statement - summary.unmatched_statement;
+
+  let explanation = &mut preview.explanation;
+  explanation.carried_forward = explanation.difference.map(|difference| {
+    difference - explanation.not_on_statement + explanation.missing_from_ledger
+      - explanation.amount_differences
+  });
+  explanation.notes = difference_notes(
+    explanation,
+    preview.unmatched_statement.len(),
+    preview.unmatched_ledger.len(),
+    mismatched_pairs,
+  );
+  preview
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+  if count == 1 {
+    format!("1 {one}")
+  } else {
+    format!("{count} {many}")
+  }
+}
+
+fn difference_notes(
+  explanation: &DifferenceExplanation,
+  unmatched_statement: usize,
+  unmatched_ledger: usize,
+  mismatched_pairs: usize,
+) -> Vec<String> {
+  if explanation.difference.is_some_and(Money::is_zero) {
+    return vec!["The ledger agrees with the statement".to_string()];
+  }
+  let mut notes: Vec<(Money, String)> = Vec::new();
+  if unmatched_statement > 0 {
+    notes.push((
+      explanation.missing_from_ledger,
+      format!(
+        "{} totalling {} {} in the ledger yet",
+        plural(unmatched_statement, "statement line", "statement lines"),
+        explanation.missing_from_ledger,
+        if unmatched_statement == 1 {
+          "isn't"
+        } else {
+          "aren't"
+        },
+      ),
+    ));
+  }
+  if unmatched_ledger > 0 {
+    notes.push((
+      explanation.not_on_statement,
+      format!(
+        "{} totalling {} {} on the statement",
+        plural(unmatched_ledger, "ledger posting", "ledger postings"),
+        explanation.not_on_statement,
+        if unmatched_ledger == 1 {
+          "isn't"
+        } else {
+          "aren't"
+        },
+      ),
+    ));
+  }
+  if mismatched_pairs > 0 {
+    notes.push((
+      explanation.amount_differences,
+      format!(
+        "{} from the statement by {} in total",
+        plural(
+          mismatched_pairs,
+          "matched posting differs",
+          "matched postings differ"
+        ),
+        explanation.amount_differences,
+      ),
+    ));
+  }
+  if let Some(carried) = explanation
+    .carried_forward
+    .filter(|carried| !carried.is_zero())
+  {
+    notes.push((
+      carried,
+      format!(
+        "{carried} was already out before the statement began; check the opening balance and the last reconciliation"
+      ),
+    ));
+  }
+  notes.sort_by_key(|(amount, _)| std::cmp::Reverse(amount.abs().cents()));
+  notes.into_iter().map(|(_, note)| note).collect()
+}
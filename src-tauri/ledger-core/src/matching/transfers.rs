@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::assignment::max_score_assignment;
+use crate::money::Money;
+
+/// Payee text that marks a bank line as one side of a transfer.
+pub const TRANSFER_KEYWORDS: &[&str] = &[
+  "internal transfer",
+  "transfer",
+  "from macbank",
+  "to macbank",
+  "from linked account",
+  "to linked account",
+  "internet transfer",
+  "tfr",
+];
+
+/// Pairs scoring less than this aren't proposed at all.
+const MIN_SCORE: u32 = 40;
+const EXACT_SCORE: u32 = 80;
+const PROBABLE_SCORE: u32 = 60;
+
+/// How transfers between two accounts are matched. Saved per account pair,
+/// since a pair of accounts at the same bank settles same-day while one at
+/// another bank can take most of a week.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransferMatchConfig {
+  /// The most days apart the two sides can be.
+  pub date_window_days: u32,
+  /// Amounts differing by less than this still score, for fees taken out
+  /// of one side.
+  pub amount_tolerance: Money,
+  /// Payee text, matched case-insensitively, that marks a transfer.
+  pub keywords: Vec<String>,
+}
+
+impl Default for TransferMatchConfig {
+  fn default() -> Self {
+    TransferMatchConfig {
+      date_window_days: 7,
+      amount_tolerance: Money::from_cents(100),
+      keywords: TRANSFER_KEYWORDS.iter().map(|kw| kw.to_string()).collect(),
+    }
+  }
+}
+
+impl TransferMatchConfig {
+  pub fn has_keyword(&self, payee: &str) -> bool {
+    let payee = payee.to_lowercase();
+    self
+      .keywords
+      .iter()
+      .any(|keyword| !keyword.is_empty() && payee.contains(&keyword.to_lowercase()))
+  }
+
+  /// Whether a bank line booked to `category_name` looks like a transfer:
+  /// it's uncategorised, its category is about transfers or its payee has
+  /// a keyword.
+  pub fn is_transfer_likely(&self, category_name: &str, payee: &str) -> bool {
+    let category = category_name.to_lowercase();
+    category == "uncategorized" || category.contains("transfer") || self.has_keyword(payee)
+  }
+}
+
+/// One side of a possible transfer: a two-posting transaction between a
+/// real account and a category.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferCandidate {
+  pub transaction_id: String,
+  pub date: DateTime<Utc>,
+  pub payee: String,
+  pub memo: Option<String>,
+  pub account_id: String,
+  pub posting_id: String,
+  /// The real account's side.
+  pub amount: Money,
+  pub category_posting_id: String,
+  pub category_account_id: String,
+  pub category_name: String,
+  pub is_reconciled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMatchType {
+  Exact,
+  Probable,
+  Possible,
+}
+
+impl TransferMatchType {
+  fn from_score(score: u32) -> Self {
+    match score {
+      s if s >= EXACT_SCORE => TransferMatchType::Exact,
+      s if s >= PROBABLE_SCORE => TransferMatchType::Probable,
+      _ => TransferMatchType::Possible,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferMatch {
+  pub candidate_a: TransferCandidate,
+  pub candidate_b: TransferCandidate,
+  /// Out of 100: 50 for the amount, 30 for the date and 20 for keywords.
+  pub match_score: u32,
+  pub match_type: TransferMatchType,
+  pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferMatchSummary {
+  pub total_candidates_a: usize,
+  pub total_candidates_b: usize,
+  pub exact_matches: usize,
+  pub probable_matches: usize,
+  pub possible_matches: usize,
+  pub unmatched: usize,
+}
+
+/// Proposed transfers between two accounts, best first, and the candidates
+/// left over on each side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferMatchPreview {
+  pub matches: Vec<TransferMatch>,
+  pub unmatched_a: Vec<TransferCandidate>,
+  pub unmatched_b: Vec<TransferCandidate>,
+  pub summary: TransferMatchSummary,
+}
+
+/// Score `a` and `b` as two sides of one transfer, like
+/// `calculateMatchScore`, with the reasons for each point. Pairs further
+/// apart than the date window score nothing.
+pub fn transfer_match_score(
+  a: &TransferCandidate,
+  b: &TransferCandidate,
+  config: &TransferMatchConfig,
+) -> (u32, Vec<String>) {
+  let days = (a.date - b.date).num_milliseconds().abs() as f64 / 86_400_000.0;
+  if days > f64::from(config.date_window_days) {
+    return (0, Vec::new());
+  }
+  let mut score = 0;
+  let mut reasons = Vec::new();
+
+  let difference = (a.amount.abs() - b.amount.abs()).abs();
+  let opposite = a.amount.is_positive() && b.amount.is_negative()
+    || a.amount.is_negative() && b.amount.is_positive();
+  if difference.is_zero() && opposite {
+    score += 50;
+    reasons.push("Exact amount match with opposite signs".to_string());
+  } else if difference.is_zero() {
+    score += 30;
+    reasons.push("Exact amount match (same sign)".to_string());
+  } else if difference < config.amount_tolerance {
+    score += 15;
+    reasons.push(format!("Amount within {}", config.amount_tolerance));
+  }
+
+  let (points, reason) = if days < 0.5 {
+    (30, "Same date".to_string())
+  } else if days <= 1.0 {
+    (25, "Date within 1 day".to_string())
+  } else if days <= 3.0 {
+    (15, "Date within 3 days".to_string())
+  } else {
+    (5, format!("Date within {} days", config.date_window_days))
+  };
+  score += points;
+  reasons.push(reason);
+
+  match (config.has_keyword(&a.payee), config.has_keyword(&b.payee)) {
+    (true, true) => {
+      score += 20;
+      reasons.push("Both payees contain transfer keywords".to_string());
+    }
+    (true, false) | (false, true) => {
+      score += 10;
+      reasons.push("One payee contains transfer keyword".to_string());
+    }
+    (false, false) => {}
+  }
+  (score, reasons)
+}
+
+/// Pair candidates from two accounts one-to-one for the highest total
+/// score, like `matchTransfers`. Pairs under the minimum score are left out
+/// of the assignment rather than filtered after it, so a weak pair can't
+/// take a candidate a stronger pair needs.
+pub fn match_transfer_candidates(
+  candidates_a: Vec<TransferCandidate>,
+  candidates_b: Vec<TransferCandidate>,
+  config: &TransferMatchConfig,
+) -> TransferMatchPreview {
+  let mut scored: Vec<Vec<(u32, Vec<String>)>> = candidates_a
+    .iter()
+    .map(|a| {
+      candidates_b
+        .iter()
+        .map(|b| transfer_match_score(a, b, config))
+        .collect()
+    })
+    .collect();
+  let scores: Vec<Vec<i64>> = scored
+    .iter()
+    .map(|row| {
+      row
+        .iter()
+        .map(|(score, _)| {
+          if *score < MIN_SCORE {
+            0
+          } else {
+            i64::from(*score)
+          }
+        })
+        .collect()
+    })
+    .collect();
+  let assignment = max_score_assignment(&scores);
+
+  let mut matched_b = vec![false; candidates_b.len()];
+  let mut matches = Vec::new();
+  let mut unmatched_a = Vec::new();
+  for (i, a) in candidates_a.into_iter().enumerate() {
+    let Some(j) = assignment[i] else {
+      unmatched_a.push(a);
+      continue;
+    };
+    matched_b[j] = true;
+    let (score, reasons) = std::mem::take(&mut scored[i][j]);
+    matches.push(TransferMatch {
+      candidate_a: a,
+      candidate_b: candidates_b[j].clone(),
+      match_score: score,
+      match_type: TransferMatchType::from_score(score),
+      reasons,
+    });
+  }
+  let unmatched_b: Vec<TransferCandidate> = candidates_b
+    .into_iter()
+    .zip(matched_b)
+    .filter(|(_, matched)| !matched)
+    .map(|(b, _)| b)
+    .collect();
+  // Best first; ties stay in date order.
+  matches.sort_by_key(|m| Reverse(m.match_score));
+
+  let count = |match_type| {
+    matches
+      .iter()
+      .filter(|m| m.match_type == match_type)
+      .count()
+  };
+  let summary = TransferMatchSummary {
+    total_candidates_a: unmatched_a.len() + matches.len(),
+    total_candidates_b: unmatched_b.len() + matches.len(),
+    exact_matches: count(TransferMatchType::Exact),
+    probable_matches: count(TransferMatchType::Probable),
+    possible_matches: count(TransferMatchType::Possible),
+    unmatched: unmatched_a.len() + unmatched_b.len(),
+  };
+  TransferMatchPreview {
+    matches,
+    unmatched_a,
+    unmatched_b,
+    summary,
+  }
+}
@@ -0,0 +1,309 @@
+//! Applies `prisma/migrations` to a book without the Prisma CLI.
+//!
+//! The migration SQL is embedded at build time (see `build.rs`) and applied
+//! migrations are recorded in `_prisma_migrations` the way
+//! `prisma migrate deploy` records them, so a book can move freely between the
+//! desktop app and the Node tooling.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, TransactionBehavior};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::encryption::{self, BookKey, EncryptionError};
+
+/// A migration compiled into the binary.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedMigration {
+  /// Directory name, e.g. `20251004062759_init`.
+  pub name: &'static str,
+  pub sql: &'static str,
+}
+
+include!(concat!(env!("OUT_DIR"), "/migrations.rs"));
+
+/// Prisma's bookkeeping table, as created by the schema engine for SQLite.
+const CREATE_MIGRATIONS_TABLE: &str = r#"CREATE TABLE IF NOT EXISTS "_prisma_migrations" (
+    "id"                    TEXT PRIMARY KEY NOT NULL,
+    "checksum"              TEXT NOT NULL,
+    "finished_at"           DATETIME,
+    "migration_name"        TEXT NOT NULL,
+    "logs"                  TEXT,
+    "rolled_back_at"        DATETIME,
+    "started_at"            DATETIME NOT NULL DEFAULT current_timestamp,
+    "applied_steps_count"   INTEGER UNSIGNED NOT NULL DEFAULT 0
+)"#;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+  pub applied: Vec<String>,
+  /// Copy of the book taken before anything was applied.
+  pub backup: Option<PathBuf>,
+  /// Applied migrations whose SQL no longer matches the recorded checksum.
+  /// Prisma only warns about these, so they don't stop the book opening.
+  pub modified: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+  #[error(
+    "this book was created by a newer version of Ledgerhound (unknown migrations: {}); update the app to open it",
+    .unknown.join(", ")
+  )]
+  NewerSchema { unknown: Vec<String> },
+  #[error("migration {name} failed previously; restore a backup before opening this book")]
+  PreviouslyFailed { name: String },
+  #[error("migration {name} failed: {source}")]
+  Apply {
+    name: String,
+    #[source]
+    source: rusqlite::Error,
+  },
+  #[error("migrations left {count} foreign key violation(s); nothing was applied")]
+  ForeignKeyViolations { count: usize },
+  #[error("could not back up the book before migrating: {0}")]
+  Backup(#[source] std::io::Error),
+  #[error(transparent)]
+  Encryption(#[from] EncryptionError),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+}
+
+/// Hex SHA-256 of a migration script, as Prisma stores it in `checksum`.
+pub fn checksum(sql: &str) -> String {
+  Sha256::digest(sql.as_bytes())
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+/// Prisma accepts a script whose line endings changed since it was applied
+/// (e.g. a checkout with `core.autocrlf`), so do the same.
+fn checksum_matches(sql: &str, recorded: &str) -> bool {
+  checksum(sql) == recorded
+    || checksum(&sql.replace("\r\n", "\n")) == recorded
+    || checksum(&sql.replace('\n', "\r\n")) == recorded
+}
+
+/// What [`apply_migrations`] would do to a book.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPlan {
+  pub pending: Vec<EmbeddedMigration>,
+  /// See [`MigrationReport::modified`].
+  pub modified: Vec<String>,
+}
+
+struct AppliedMigration {
+  name: String,
+  checksum: String,
+  finished: bool,
+  rolled_back: bool,
+}
+
+fn applied_migrations(conn: &Connection) -> rusqlite::Result<Vec<AppliedMigration>> {
+  let exists: Option<i64> = conn
+    .query_row(
+      "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_prisma_migrations'",
+      [],
+      |row| row.get(0),
+    )
+    .optional()?;
+  if exists.is_none() {
+    return Ok(Vec::new());
+  }
+  let mut stmt = conn.prepare(
+    "SELECT migration_name, checksum, finished_at IS NOT NULL, rolled_back_at IS NOT NULL
+     FROM _prisma_migrations ORDER BY started_at",
+  )?;
+  let rows = stmt.query_map([], |row| {
+    Ok(AppliedMigration {
+      name: row.get(0)?,
+      checksum: row.get(1)?,
+      finished: row.get(2)?,
+      rolled_back: row.get(3)?,
+    })
+  })?;
+  rows.collect()
+}
+
+/// Embedded migrations not yet applied to the book, in order.
+///
+/// Fails if the book records a migration this build doesn't know (it was
+/// opened by a newer app) or one that failed part-way.
+pub fn plan_migrations(conn: &Connection) -> Result<MigrationPlan, MigrationError> {
+  let applied = applied_migrations(conn)?;
+
+  if let Some(failed) = applied.iter().find(|m| !m.finished && !m.rolled_back) {
+    return Err(MigrationError::PreviouslyFailed {
+      name: failed.name.clone(),
+    });
+  }
+
+  let finished: Vec<&AppliedMigration> = applied.iter().filter(|m| m.finished).collect();
+  let unknown: Vec<String> = finished
+    .iter()
+    .filter(|m| !MIGRATIONS.iter().any(|e| e.name == m.name))
+    .map(|m| m.name.clone())
+    .collect();
+  if !unknown.is_empty() {
+    return Err(MigrationError::NewerSchema { unknown });
+  }
+
+  let modified = finished
+    .iter()
+    .filter(|m| {
+      MIGRATIONS
+        .iter()
+        .any(|e| e.name == m.name && !checksum_matches(e.sql, &m.checksum))
+    })
+    .map(|m| m.name.clone())
+    .collect();
+  let pending = MIGRATIONS
+    .iter()
+    .filter(|e| !finished.iter().any(|m| m.name == e.name))
+    .copied()
+    .collect();
+  Ok(MigrationPlan { pending, modified })
+}
+
+/// Apply every pending migration in a single transaction.
+///
+/// Foreign keys are switched off for the duration (the table rebuilds in the
+/// Prisma scripts rely on that, and the PRAGMA is ignored inside a
+/// transaction) and checked with `foreign_key_check` before committing.
+pub fn apply_migrations(conn: &mut Connection) -> Result<MigrationReport, MigrationError> {
+  let MigrationPlan { pending, modified } = plan_migrations(conn)?;
+  let mut report = MigrationReport {
+    modified,
+    ..Default::default()
+  };
+  if pending.is_empty() {
+    return Ok(report);
+  }
+
+  let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+  conn.pragma_update(None, "foreign_keys", false)?;
+  let result = apply_in_transaction(conn, &pending);
+  conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+
+  report.applied = result?;
+  Ok(report)
+}
+
+fn apply_in_transaction(
+  conn: &mut Connection,
+  pending: &[EmbeddedMigration],
+) -> Result<Vec<String>, MigrationError> {
+  let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+  tx.execute_batch(CREATE_MIGRATIONS_TABLE)?;
+
+  let mut applied = Vec::with_capacity(pending.len());
+  for migration in pending {
+    let started_at = Utc::now().timestamp_millis();
+    tx.execute_batch(migration.sql)
+      .map_err(|source| MigrationError::Apply {
+        name: migration.name.to_string(),
+        source,
+      })?;
+    tx.execute(
+      "INSERT INTO _prisma_migrations
+         (id, checksum, finished_at, migration_name, logs, rolled_back_at, started_at, applied_steps_count)
+       VALUES (?1, ?2, ?3, ?4, NULL, NULL, ?5, 1)",
+      params![
+        uuid::Uuid::new_v4().to_string(),
+        checksum(migration.sql),
+        Utc::now().timestamp_millis(),
+        migration.name,
+        started_at,
+      ],
+    )?;
+    applied.push(migration.name.to_string());
+  }
+
+  let violations = {
+    let mut stmt = tx.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt.query_map([], |_| Ok(()))?;
+    rows.count()
+  };
+  if violations > 0 {
+    return Err(MigrationError::ForeignKeyViolations { count: violations });
+  }
+
+  tx.commit()?;
+  Ok(applied)
+}
+
+/// Whether the existing file at `path` is a book: a database with the first
+/// migration recorded, or an encrypted file (which can't be looked into
+/// without its passphrase). Used before migrating a file the user picked
+/// from outside the app, so another program's SQLite file isn't rewritten.
+pub fn is_book(path: &Path) -> Result<bool, MigrationError> {
+  if encryption::is_encrypted(path).map_err(EncryptionError::from)? {
+    return Ok(true);
+  }
+  if !path.is_file() {
+    return Ok(false);
+  }
+  let conn = encryption::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY, None)?;
+  let first = MIGRATIONS[0].name;
+  Ok(
+    applied_migrations(&conn)?
+      .iter()
+      .any(|m| m.finished && m.name == first),
+  )
+}
+
+/// Bring the book at `path` up to date, creating it if needed.
+///
+/// If the book already has migrations applied, it is first copied with the
+/// SQLite online backup API into `backups/` next to the book.
+pub fn migrate_book(path: &Path) -> Result<MigrationReport, MigrationError> {
+  migrate_encrypted_book(path, None)
+}
+
+/// [`migrate_book`] for a book encrypted with `key`; a new book is created
+/// encrypted. The backup is encrypted with the same key.
+pub fn migrate_encrypted_book(
+  path: &Path,
+  key: Option<&BookKey>,
+) -> Result<MigrationReport, MigrationError> {
+  let mut conn = encryption::open(path, key)?;
+  crate::db::apply_pragmas(&conn)?;
+
+  let plan = plan_migrations(&conn)?;
+  let backup = if !plan.pending.is_empty() && !applied_migrations(&conn)?.is_empty() {
+    Some(backup_before_migrating(&conn, path, key)?)
+  } else {
+    None
+  };
+
+  let mut report = apply_migrations(&mut conn)?;
+  report.backup = backup;
+  Ok(report)
+}
+
+fn backup_before_migrating(
+  conn: &Connection,
+  path: &Path,
+  key: Option<&BookKey>,
+) -> Result<PathBuf, MigrationError> {
+  let dir = path
+    .parent()
+    .unwrap_or_else(|| Path::new("."))
+    .join("backups");
+  std::fs::create_dir_all(&dir).map_err(MigrationError::Backup)?;
+  let stem = path
+    .file_stem()
+    .map(|s| s.to_string_lossy().into_owned())
+    .unwrap_or_else(|| "book".into());
+  let destination = dir.join(format!(
+    "{stem}-pre-migration-{}.db",
+    Utc::now().format("%Y%m%d-%H%M%S")
+  ));
+  encryption::copy_database(conn, &destination, key)
+    .map_err(|err| MigrationError::Backup(std::io::Error::other(err)))?;
+  Ok(destination)
+}
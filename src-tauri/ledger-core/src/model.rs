@@ -0,0 +1,395 @@
+//! Rust mirrors of the Prisma models in `prisma/schema.prisma`.
+//!
+//! Field names serialize in camelCase and enums in SCREAMING_SNAKE_CASE so the
+//! JSON matches what the Express API returns for the same records.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::money::{Currency, Money};
+
+/// Error returned when a stored enum value is not one the schema knows about.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown {kind} value: {value}")]
+pub struct UnknownVariant {
+  pub kind: &'static str,
+  pub value: String,
+}
+
+/// Declares a Prisma enum with its database string representation.
+macro_rules! prisma_enum {
+  ($(#[$meta:meta])* $name:ident { $($(#[$vmeta:meta])* $variant:ident => $text:literal),+ $(,)? }) => {
+    $(#[$meta])*
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub enum $name {
+      $($(#[$vmeta])* #[serde(rename = $text)] $variant),+
+    }
+
+    impl $name {
+      pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+      /// The value Prisma stores in SQLite for this variant.
+      pub const fn as_str(self) -> &'static str {
+        match self {
+          $($name::$variant => $text),+
+        }
+      }
+    }
+
+    impl fmt::Display for $name {
+      fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+      }
+    }
+
+    impl FromStr for $name {
+      type Err = UnknownVariant;
+
+      fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+          $($text => Ok($name::$variant),)+
+          _ => Err(UnknownVariant { kind: stringify!($name), value: s.to_string() }),
+        }
+      }
+    }
+  };
+}
+
+prisma_enum! {
+  /// Account types for double-entry accounting.
+  AccountType {
+    Asset => "ASSET",
+    Liability => "LIABILITY",
+    Equity => "EQUITY",
+    Income => "INCOME",
+    Expense => "EXPENSE",
+  }
+}
+
+prisma_enum! {
+  AccountSubtype {
+    Bank => "BANK",
+    Card => "CARD",
+    Psp => "PSP",
+    Cash => "CASH",
+    GstControl => "GST_CONTROL",
+    SavingsGoal => "SAVINGS_GOAL",
+    Loan => "LOAN",
+    Investment => "INVESTMENT",
+    Other => "OTHER",
+  }
+}
+
+prisma_enum! {
+  /// CATEGORY accounts are income/expense buckets; TRANSFER accounts are real
+  /// money locations (banks, cards, loans).
+  #[derive(Default)]
+  AccountKind {
+    Category => "CATEGORY",
+    #[default]
+    Transfer => "TRANSFER",
+  }
+}
+
+prisma_enum! {
+  /// GST codes for Australian tax.
+  GstCode {
+    Gst => "GST",
+    GstFree => "GST_FREE",
+    InputTaxed => "INPUT_TAXED",
+    Export => "EXPORT",
+    Other => "OTHER",
+  }
+}
+
+prisma_enum! {
+  #[derive(Default)]
+  TransactionStatus {
+    #[default]
+    Normal => "NORMAL",
+    Void => "VOID",
+  }
+}
+
+prisma_enum! {
+  BillFrequency {
+    Weekly => "WEEKLY",
+    Fortnightly => "FORTNIGHTLY",
+    Monthly => "MONTHLY",
+    Quarterly => "QUARTERLY",
+    Yearly => "YEARLY",
+  }
+}
+
+prisma_enum! {
+  #[derive(Default)]
+  BillStatus {
+    #[default]
+    Active => "ACTIVE",
+    Paused => "PAUSED",
+  }
+}
+
+prisma_enum! {
+  /// Memorized rule match types.
+  #[derive(Default)]
+  MatchType {
+    Exact => "EXACT",
+    #[default]
+    Contains => "CONTAINS",
+    Regex => "REGEX",
+  }
+}
+
+prisma_enum! {
+  /// Tax return labels for `Account.atoLabel`, as `ATOLabel` in
+  /// `src/types/index.ts`. The column itself is free text.
+  AtoLabel {
+    BusIncome => "BUS_INCOME",
+    BusCogs => "BUS_COGS",
+    BusExpense => "BUS_EXPENSE",
+    IncomeInterest => "INCOME_INTEREST",
+    IncomeDividends => "INCOME_DIVIDENDS",
+    IncomeRent => "INCOME_RENT",
+    IncomeForeign => "INCOME_FOREIGN",
+    IncomeOther => "INCOME_OTHER",
+    D1Car => "D1_CAR",
+    D2Travel => "D2_TRAVEL",
+    D3Clothing => "D3_CLOTHING",
+    D4SelfEd => "D4_SELF_ED",
+    D5OtherWork => "D5_OTHER_WORK",
+    D7Interest => "D7_INTEREST",
+    D9Gifts => "D9_GIFTS",
+    D10TaxAffairs => "D10_TAX_AFFAIRS",
+    D12Super => "D12_SUPER",
+    D15Other => "D15_OTHER",
+    G10CapitalPurchases => "G10",
+    G11NonCapitalPurchases => "G11",
+  }
+}
+
+impl AtoLabel {
+  /// `ATO_LABEL_DESCRIPTIONS` in `src/types/index.ts`.
+  pub fn description(self) -> &'static str {
+    match self {
+      AtoLabel::BusIncome => "Gross Business Income",
+      AtoLabel::BusCogs => "Cost of Goods Sold",
+      AtoLabel::BusExpense => "Business Expenses",
+      AtoLabel::IncomeInterest => "Item 10: Interest",
+      AtoLabel::IncomeDividends => "Item 11: Dividends",
+      AtoLabel::IncomeRent => "Item 20: Rental Income",
+      AtoLabel::IncomeForeign => "Item 19: Foreign Source Income",
+      AtoLabel::IncomeOther => "Item 24: Other Income",
+      AtoLabel::D1Car => "D1: Work-Related Car Expenses",
+      AtoLabel::D2Travel => "D2: Work-Related Travel",
+      AtoLabel::D3Clothing => "D3: Work-Related Clothing/Laundry",
+      AtoLabel::D4SelfEd => "D4: Work-Related Self-Education",
+      AtoLabel::D5OtherWork => "D5: Other Work-Related Expenses",
+      AtoLabel::D7Interest => "D7: Interest Deductions",
+      AtoLabel::D9Gifts => "D9: Gifts/Donations",
+      AtoLabel::D10TaxAffairs => "D10: Cost of Managing Tax Affairs",
+      AtoLabel::D12Super => "D12: Personal Super Contributions",
+      AtoLabel::D15Other => "D15: Other Deductions",
+      AtoLabel::G10CapitalPurchases => "G10: Capital Purchases (BAS)",
+      AtoLabel::G11NonCapitalPurchases => "G11: Non-Capital Purchases (BAS)",
+    }
+  }
+}
+
+/// Names of the GST categories created by the import and Stripe services.
+pub const GST_PAID_ACCOUNT: &str = "GST Paid";
+pub const GST_COLLECTED_ACCOUNT: &str = "GST Collected";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+  pub id: String,
+  pub name: String,
+  pub full_path: Option<String>,
+  #[serde(rename = "type")]
+  pub account_type: AccountType,
+  #[serde(default)]
+  pub kind: AccountKind,
+  pub parent_id: Option<String>,
+  #[serde(default)]
+  pub level: i32,
+  pub subtype: Option<AccountSubtype>,
+  pub is_real: bool,
+  #[serde(default)]
+  pub is_business_default: bool,
+  pub default_has_gst: bool,
+  #[serde(default)]
+  pub opening_balance: Money,
+  pub opening_date: DateTime<Utc>,
+  #[serde(default)]
+  pub currency: Currency,
+  pub ato_label: Option<String>,
+  #[serde(default)]
+  pub archived: bool,
+  #[serde(default)]
+  pub sort_order: i32,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl Account {
+  /// `atoLabel` as a tax return label; BAS labels and unknown text read as
+  /// `None`.
+  pub fn tax_label(&self) -> Option<AtoLabel> {
+    self.ato_label.as_deref()?.parse().ok()
+  }
+
+  /// Whether postings to this account carry the GST portion of a transaction.
+  ///
+  /// Books created before GST moved into categories use a `GST_CONTROL` subtype;
+  /// current books use the "GST Paid" (asset) and "GST Collected" (liability)
+  /// categories that `importService` and `stripeImportService` create.
+  pub fn is_gst_control(&self) -> bool {
+    self.subtype == Some(AccountSubtype::GstControl)
+      || (self.account_type == AccountType::Asset && self.name == GST_PAID_ACCOUNT)
+      || (self.account_type == AccountType::Liability && self.name == GST_COLLECTED_ACCOUNT)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Posting {
+  pub id: String,
+  pub transaction_id: String,
+  pub account_id: String,
+  /// Signed amount (positive/negative).
+  pub amount: Money,
+  #[serde(default)]
+  pub is_business: bool,
+  pub gst_code: Option<GstCode>,
+  pub gst_rate: Option<f64>,
+  pub gst_amount: Option<Money>,
+  pub category_split_label: Option<String>,
+  #[serde(default)]
+  pub cleared: bool,
+  #[serde(default)]
+  pub reconciled: bool,
+  pub reconcile_id: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// A transaction header together with its postings, like Prisma's
+/// `TransactionWithPostings` include.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+  pub id: String,
+  pub date: DateTime<Utc>,
+  pub payee: String,
+  pub memo: Option<String>,
+  pub reference: Option<String>,
+  /// JSON array of strings.
+  pub tags: Option<String>,
+  /// JSON object for provider-specific data (Stripe fees, etc.).
+  pub metadata: Option<String>,
+  pub import_batch_id: Option<String>,
+  pub external_id: Option<String>,
+  #[serde(default)]
+  pub status: TransactionStatus,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  #[serde(default)]
+  pub postings: Vec<Posting>,
+}
+
+impl Transaction {
+  pub fn is_void(&self) -> bool {
+    self.status == TransactionStatus::Void
+  }
+
+  /// Parsed `tags` column; malformed JSON is treated as no tags.
+  pub fn tag_list(&self) -> Vec<String> {
+    self
+      .tags
+      .as_deref()
+      .and_then(|tags| serde_json::from_str(tags).ok())
+      .unwrap_or_default()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconciliation {
+  pub id: String,
+  pub account_id: String,
+  pub statement_start_date: DateTime<Utc>,
+  pub statement_end_date: DateTime<Utc>,
+  pub statement_start_balance: Money,
+  pub statement_end_balance: Money,
+  pub notes: Option<String>,
+  #[serde(default)]
+  pub locked: bool,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemorizedRule {
+  pub id: String,
+  pub name: String,
+  #[serde(default)]
+  pub match_type: MatchType,
+  pub match_value: String,
+  pub default_payee: Option<String>,
+  pub default_account_id: Option<String>,
+  /// JSON array of split templates, or a business/personal split ratio.
+  pub default_splits: Option<String>,
+  pub apply_on_import: bool,
+  pub apply_on_manual_entry: bool,
+  #[serde(default)]
+  pub priority: i32,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBatch {
+  pub id: String,
+  pub source_account_id: String,
+  pub source_name: String,
+  /// JSON of column mappings.
+  pub mapping_json: String,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Setting {
+  pub id: String,
+  pub key: String,
+  /// JSON-encoded value.
+  pub value: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringBill {
+  pub id: String,
+  pub name: String,
+  pub payee: String,
+  pub expected_amount: Money,
+  pub frequency: BillFrequency,
+  /// Day-of-week (1=Mon..7=Sun) for WEEKLY/FORTNIGHTLY, day-of-month (1-31) for others.
+  pub due_day: i32,
+  pub start_date: DateTime<Utc>,
+  pub category_account_id: String,
+  pub pay_from_account_id: String,
+  #[serde(default)]
+  pub status: BillStatus,
+  pub last_paid_date: Option<DateTime<Utc>>,
+  pub next_due_date: DateTime<Utc>,
+  pub notes: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,321 @@
+//! Fixed-point money.
+//!
+//! Amounts are stored as whole cents with a currency tag so sums over
+//! thousands of postings never drift the way the Prisma `Float` columns do.
+//! Over JSON a `Money` is still a plain dollar number, which is what the
+//! frontend already expects.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MoneyError {
+  #[error("invalid currency code: {0}")]
+  InvalidCurrency(String),
+  #[error("invalid amount: {0}")]
+  InvalidAmount(String),
+  #[error("amount has more than two decimal places: {0}")]
+  TooPrecise(String),
+  #[error("cannot combine {0} and {1} amounts")]
+  CurrencyMismatch(Currency, Currency),
+}
+
+/// ISO 4217 currency code.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+  pub const AUD: Currency = Currency(*b"AUD");
+
+  pub fn new(code: &str) -> Result<Self, MoneyError> {
+    let bytes = code.as_bytes();
+    if bytes.len() != 3 || !bytes.iter().all(u8::is_ascii_uppercase) {
+      return Err(MoneyError::InvalidCurrency(code.to_string()));
+    }
+    Ok(Currency([bytes[0], bytes[1], bytes[2]]))
+  }
+
+  pub fn as_str(&self) -> &str {
+    // Only ever constructed from ASCII uppercase letters.
+    std::str::from_utf8(&self.0).unwrap()
+  }
+}
+
+impl Default for Currency {
+  fn default() -> Self {
+    Currency::AUD
+  }
+}
+
+impl fmt::Debug for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl fmt::Display for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for Currency {
+  type Err = MoneyError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Currency::new(s)
+  }
+}
+
+impl Serialize for Currency {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self.as_str())
+  }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let code = String::deserialize(deserializer)?;
+    Currency::new(&code).map_err(serde::de::Error::custom)
+  }
+}
+
+/// An exact amount of money in cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+  cents: i64,
+  currency: Currency,
+}
+
+impl Money {
+  pub const ZERO: Money = Money::from_cents(0);
+
+  /// An AUD amount in cents.
+  pub const fn from_cents(cents: i64) -> Self {
+    Money {
+      cents,
+      currency: Currency::AUD,
+    }
+  }
+
+  pub const fn from_cents_in(cents: i64, currency: Currency) -> Self {
+    Money { cents, currency }
+  }
+
+  /// Convert a floating point dollar amount, rounding half away from zero to
+  /// the nearest cent.
+  pub fn from_f64(dollars: f64) -> Self {
+    Money::from_cents((dollars * 100.0).round() as i64)
+  }
+
+  pub fn to_f64(self) -> f64 {
+    self.cents as f64 / 100.0
+  }
+
+  pub const fn cents(self) -> i64 {
+    self.cents
+  }
+
+  pub const fn currency(self) -> Currency {
+    self.currency
+  }
+
+  pub fn with_currency(self, currency: Currency) -> Self {
+    Money { currency, ..self }
+  }
+
+  pub fn abs(self) -> Self {
+    Money {
+      cents: self.cents.abs(),
+      ..self
+    }
+  }
+
+  pub fn is_zero(self) -> bool {
+    self.cents == 0
+  }
+
+  pub fn is_positive(self) -> bool {
+    self.cents > 0
+  }
+
+  pub fn is_negative(self) -> bool {
+    self.cents < 0
+  }
+
+  pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+    let currency = self.combined_currency(other)?;
+    Ok(Money::from_cents_in(self.cents + other.cents, currency))
+  }
+
+  pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+    self.checked_add(-other)
+  }
+
+  /// Multiply by a rate (e.g. a GST rate of `0.1`), rounding to the nearest cent.
+  pub fn mul_rate(self, rate: f64) -> Money {
+    Money {
+      cents: (self.cents as f64 * rate).round() as i64,
+      ..self
+    }
+  }
+
+  /// Divide evenly, rounding to the nearest cent (e.g. GST-inclusive `/ 11`).
+  pub fn div_round(self, divisor: i64) -> Money {
+    let cents = self.cents as i128 * 2 / divisor as i128;
+    let rounded = (cents + cents.signum()) / 2;
+    Money {
+      cents: rounded as i64,
+      ..self
+    }
+  }
+
+  /// Parse a decimal dollar string such as `"1,234.56"`, `"-12.5"` or `"$3"`.
+  pub fn parse(text: &str) -> Result<Money, MoneyError> {
+    let invalid = || MoneyError::InvalidAmount(text.to_string());
+    let trimmed = text.trim();
+    let (negative, rest) = match trimmed.strip_prefix('-') {
+      Some(rest) => (true, rest),
+      None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let digits: String = rest
+      .strip_prefix('$')
+      .unwrap_or(rest)
+      .chars()
+      .filter(|c| *c != ',')
+      .collect();
+
+    let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+    if whole.is_empty() && fraction.is_empty() {
+      return Err(invalid());
+    }
+    if !whole.bytes().all(|b| b.is_ascii_digit()) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+      return Err(invalid());
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > 2 {
+      return Err(MoneyError::TooPrecise(text.to_string()));
+    }
+
+    let whole: i64 = if whole.is_empty() {
+      0
+    } else {
+      whole.parse().map_err(|_| invalid())?
+    };
+    let fraction_cents: i64 = format!("{fraction:0<2}").parse().map_err(|_| invalid())?;
+    let cents = whole
+      .checked_mul(100)
+      .and_then(|c| c.checked_add(fraction_cents))
+      .ok_or_else(invalid)?;
+    Ok(Money::from_cents(if negative { -cents } else { cents }))
+  }
+
+  fn combined_currency(self, other: Money) -> Result<Currency, MoneyError> {
+    // Zero is currency-neutral so sums can start from `Money::ZERO`.
+    if self.currency == other.currency || other.cents == 0 {
+      Ok(self.currency)
+    } else if self.cents == 0 {
+      Ok(other.currency)
+    } else {
+      Err(MoneyError::CurrencyMismatch(self.currency, other.currency))
+    }
+  }
+}
+
+impl Default for Money {
+  fn default() -> Self {
+    Money::ZERO
+  }
+}
+
+impl fmt::Display for Money {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let sign = if self.cents < 0 { "-" } else { "" };
+    let abs = self.cents.unsigned_abs();
+    write!(f, "{sign}{}.{:02}", abs / 100, abs % 100)
+  }
+}
+
+impl FromStr for Money {
+  type Err = MoneyError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Money::parse(s)
+  }
+}
+
+impl Neg for Money {
+  type Output = Money;
+
+  fn neg(self) -> Money {
+    Money {
+      cents: -self.cents,
+      ..self
+    }
+  }
+}
+
+/// Panics when the currencies differ; use [`Money::checked_add`] for untrusted input.
+impl Add for Money {
+  type Output = Money;
+
+  fn add(self, other: Money) -> Money {
+    self
+      .checked_add(other)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+}
+
+impl Sub for Money {
+  type Output = Money;
+
+  fn sub(self, other: Money) -> Money {
+    self + -other
+  }
+}
+
+impl AddAssign for Money {
+  fn add_assign(&mut self, other: Money) {
+    *self = *self + other;
+  }
+}
+
+impl SubAssign for Money {
+  fn sub_assign(&mut self, other: Money) {
+    *self = *self - other;
+  }
+}
+
+impl Sum for Money {
+  fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+    iter.fold(Money::ZERO, Add::add)
+  }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+  fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+    iter.copied().sum()
+  }
+}
+
+impl PartialOrd for Money {
+  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    (self.currency == other.currency).then(|| self.cents.cmp(&other.cents))
+  }
+}
+
+impl Serialize for Money {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(self.to_f64())
+  }
+}
+
+impl<'de> Deserialize<'de> for Money {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    f64::deserialize(deserializer).map(Money::from_f64)
+  }
+}
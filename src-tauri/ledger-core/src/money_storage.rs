@@ -0,0 +1,197 @@
+//! One-off conversion of a book's money columns from float dollars to
+//! integer cents.
+//!
+//! Prisma declares `postings.amount`, `postings.gst_amount`,
+//! `accounts.opening_balance`, the reconciliation statement balances and
+//! `recurring_bills.expected_amount` as `Float`, so every sum picks up binary
+//! rounding noise. The migration rewrites those columns to whole cents,
+//! records the new storage format in the `settings` table under
+//! [`MONEY_STORAGE_SETTING`], and only commits if every account balance is
+//! unchanged to the cent.
+//!
+//! The TypeScript services still read these columns as dollars, so a migrated
+//! book must only be served by the native data layer. The app's
+//! `migrate_book_to_cents` command refuses the book the API server has open.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+use crate::money::Money;
+
+/// `settings` key recording how money columns are stored.
+pub const MONEY_STORAGE_SETTING: &str = "money_storage";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MoneyStorage {
+  /// Float dollars, as written by Prisma.
+  Dollars,
+  /// Integer cents, written by [`migrate_to_cents`].
+  Cents,
+}
+
+impl MoneyStorage {
+  /// Convert a raw column value to [`Money`].
+  pub fn decode(self, raw: f64) -> Money {
+    match self {
+      MoneyStorage::Dollars => Money::from_f64(raw),
+      MoneyStorage::Cents => Money::from_cents(raw.round() as i64),
+    }
+  }
+
+  /// Convert [`Money`] to the raw column value.
+  pub fn encode(self, money: Money) -> f64 {
+    match self {
+      MoneyStorage::Dollars => money.to_f64(),
+      MoneyStorage::Cents => money.cents() as f64,
+    }
+  }
+}
+
+/// Read the storage format of a book. Books without the setting are Prisma
+/// books storing dollars.
+pub fn money_storage(conn: &Connection) -> rusqlite::Result<MoneyStorage> {
+  let value: Option<String> = conn
+    .query_row(
+      "SELECT value FROM settings WHERE key = ?1",
+      [MONEY_STORAGE_SETTING],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(
+    value
+      .and_then(|v| serde_json::from_str(&v).ok())
+      .unwrap_or(MoneyStorage::Dollars),
+  )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceMismatch {
+  pub account_id: String,
+  pub account_name: String,
+  pub before: Money,
+  pub after: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CentsMigrationReport {
+  pub postings: usize,
+  pub accounts: usize,
+  pub reconciliations: usize,
+  pub recurring_bills: usize,
+  pub balances_verified: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CentsMigrationError {
+  #[error("book already stores money as integer cents")]
+  AlreadyMigrated,
+  #[error("{} account balance(s) would change; nothing was written", .0.len())]
+  BalanceMismatch(Vec<BalanceMismatch>),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+}
+
+/// Rewrite every money column to integer cents inside a single transaction.
+///
+/// Balances (opening balance plus non-void postings) are computed before and
+/// after the rewrite; if any account differs — e.g. because a posting held a
+/// sub-cent float — the transaction is rolled back and the mismatches are
+/// returned so the book can be corrected first.
+pub fn migrate_to_cents(
+  conn: &mut Connection,
+) -> Result<CentsMigrationReport, CentsMigrationError> {
+  let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+  if money_storage(&tx)? == MoneyStorage::Cents {
+    return Err(CentsMigrationError::AlreadyMigrated);
+  }
+
+  let before = account_balances(&tx, Money::from_f64)?;
+
+  let postings = rewrite_column(&tx, "postings", "amount")?;
+  rewrite_column(&tx, "postings", "gst_amount")?;
+  let accounts = rewrite_column(&tx, "accounts", "opening_balance")?;
+  let reconciliations = rewrite_column(&tx, "reconciliations", "statement_start_balance")?;
+  rewrite_column(&tx, "reconciliations", "statement_end_balance")?;
+  let recurring_bills = rewrite_column(&tx, "recurring_bills", "expected_amount")?;
+
+  let after = account_balances(&tx, |sum| Money::from_cents(sum.round() as i64))?;
+
+  let mismatches: Vec<BalanceMismatch> = before
+    .iter()
+    .zip(&after)
+    .filter(|((_, _, b), (_, _, a))| a != b)
+    .map(|((id, name, before), (_, _, after))| BalanceMismatch {
+      account_id: id.clone(),
+      account_name: name.clone(),
+      before: *before,
+      after: *after,
+    })
+    .collect();
+  if !mismatches.is_empty() {
+    // Dropping `tx` rolls back.
+    return Err(CentsMigrationError::BalanceMismatch(mismatches));
+  }
+
+  let now = Utc::now().timestamp_millis();
+  tx.execute(
+    "INSERT INTO settings (id, key, value, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)
+     ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    params![
+      uuid::Uuid::new_v4().to_string(),
+      MONEY_STORAGE_SETTING,
+      serde_json::to_string(&MoneyStorage::Cents).unwrap(),
+      now,
+    ],
+  )?;
+  tx.commit()?;
+
+  Ok(CentsMigrationReport {
+    postings,
+    accounts,
+    reconciliations,
+    recurring_bills,
+    balances_verified: before.len(),
+  })
+}
+
+/// Round each non-null value of `table.column` from dollars to cents,
+/// returning the number of rows touched.
+fn rewrite_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<usize> {
+  let mut select = conn.prepare(&format!(
+    "SELECT rowid, {column} FROM {table} WHERE {column} IS NOT NULL"
+  ))?;
+  let rows = select
+    .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)))?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+  let mut update = conn.prepare(&format!(
+    "UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"
+  ))?;
+  for (rowid, dollars) in &rows {
+    update.execute(params![Money::from_f64(*dollars).cents(), rowid])?;
+  }
+  Ok(rows.len())
+}
+
+/// Every account's balance in a stable order, decoding the raw sum with `decode`.
+fn account_balances(
+  conn: &Connection,
+  decode: impl Fn(f64) -> Money,
+) -> rusqlite::Result<Vec<(String, String, Money)>> {
+  let mut stmt = conn.prepare(
+    "SELECT a.id, a.name, a.opening_balance + COALESCE((
+       SELECT SUM(p.amount) FROM postings p
+       JOIN transactions t ON t.id = p.transaction_id
+       WHERE p.account_id = a.id AND t.status = 'NORMAL'
+     ), 0)
+     FROM accounts a ORDER BY a.id",
+  )?;
+  let rows = stmt.query_map([], |row| {
+    Ok((row.get(0)?, row.get(1)?, decode(row.get::<_, f64>(2)?)))
+  })?;
+  rows.collect()
+}
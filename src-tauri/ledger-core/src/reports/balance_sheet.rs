@@ -0,0 +1,114 @@
+//! Balance sheet, mirroring `ReportService.generateBalanceSheet`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::balance::{account_balances, BalanceOptions};
+use crate::model::{Account, AccountKind, AccountType, Transaction};
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceSheetLine {
+  pub account_id: String,
+  pub account_name: String,
+  pub balance: Money,
+  pub is_real: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquityLine {
+  pub account_id: String,
+  pub account_name: String,
+  pub balance: Money,
+}
+
+/// Same shape as `BalanceSheet` in `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceSheet {
+  pub as_of_date: DateTime<Utc>,
+  pub assets: Vec<BalanceSheetLine>,
+  pub liabilities: Vec<BalanceSheetLine>,
+  pub equity: Vec<EquityLine>,
+  pub retained_earnings: Money,
+  pub total_assets: Money,
+  pub total_liabilities: Money,
+  pub total_equity: Money,
+  pub is_balanced: bool,
+}
+
+/// Balances of the non-archived balance sheet accounts as of `as_of`.
+///
+/// Liabilities and equity are credit balances, so they are reported negated
+/// (positive when the account is in credit). The TS report takes their
+/// absolute value instead, which only differs for a liability in debit, e.g.
+/// an overpaid credit card, where negating keeps the sheet balanced.
+pub fn balance_sheet(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  as_of: DateTime<Utc>,
+) -> BalanceSheet {
+  let balances = account_balances(
+    accounts,
+    transactions,
+    BalanceOptions {
+      up_to: Some(as_of),
+      ..Default::default()
+    },
+  );
+  let balance_of = |account: &Account| balances.get(&account.id).copied().unwrap_or_default();
+
+  let mut assets = Vec::new();
+  let mut liabilities = Vec::new();
+  let mut equity = Vec::new();
+  for account in accounts.iter().filter(|a| !a.archived) {
+    let balance = balance_of(account);
+    if balance.is_zero() {
+      continue;
+    }
+    let line = |balance| BalanceSheetLine {
+      account_id: account.id.clone(),
+      account_name: account.name.clone(),
+      balance,
+      is_real: account.kind == AccountKind::Transfer,
+    };
+    match account.account_type {
+      AccountType::Asset => assets.push(line(balance)),
+      AccountType::Liability => liabilities.push(line(-balance)),
+      AccountType::Equity => equity.push(EquityLine {
+        account_id: account.id.clone(),
+        account_name: account.name.clone(),
+        balance: -balance,
+      }),
+      AccountType::Income | AccountType::Expense => {}
+    }
+  }
+  assets.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+  liabilities.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+  equity.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+
+  // Income minus expenses since inception, archived categories included.
+  let retained_earnings: Money = accounts
+    .iter()
+    .filter(|a| matches!(a.account_type, AccountType::Income | AccountType::Expense))
+    .map(|a| -(balance_of(a) - a.opening_balance))
+    .sum();
+
+  let total_assets: Money = assets.iter().map(|a| a.balance).sum();
+  let total_liabilities: Money = liabilities.iter().map(|l| l.balance).sum();
+  let total_equity = equity.iter().map(|e| e.balance).sum::<Money>() + retained_earnings;
+
+  BalanceSheet {
+    as_of_date: as_of,
+    is_balanced: total_assets == total_liabilities + total_equity,
+    assets,
+    liabilities,
+    equity,
+    retained_earnings,
+    total_assets,
+    total_liabilities,
+    total_equity,
+  }
+}
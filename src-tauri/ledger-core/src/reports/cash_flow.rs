@@ -0,0 +1,173 @@
+//! Cash flow statement (direct method), mirroring
+//! `ReportService.generateCashFlow`.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use super::Period;
+use crate::model::{Account, AccountKind, AccountType, Transaction};
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatingItem {
+  pub category_id: String,
+  pub category_name: String,
+  pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashMovement {
+  pub transaction_id: String,
+  pub description: String,
+  pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowSection<T> {
+  pub items: Vec<T>,
+  pub total: Money,
+}
+
+/// Same shape as `CashFlowStatement` in `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowStatement {
+  pub period: Period,
+  pub operating: CashFlowSection<OperatingItem>,
+  /// Always empty until capital assets are tracked.
+  pub investing: CashFlowSection<CashMovement>,
+  /// Transfers between real accounts; they net to zero, so `total` is zero.
+  pub financing: CashFlowSection<CashMovement>,
+  pub net_cash_change: Money,
+  pub opening_cash: Money,
+  pub closing_cash: Money,
+}
+
+/// Cash moving through the non-archived real (`TRANSFER`) accounts.
+pub fn cash_flow(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  period: Period,
+) -> CashFlowStatement {
+  let real: HashSet<&str> = accounts
+    .iter()
+    .filter(|a| a.kind == AccountKind::Transfer && !a.archived)
+    .map(|a| a.id.as_str())
+    .collect();
+  let by_id: HashMap<&str, &Account> = accounts.iter().map(|a| (a.id.as_str(), a)).collect();
+
+  let opening_balances: Money = accounts
+    .iter()
+    .filter(|a| real.contains(a.id.as_str()))
+    .map(|a| a.opening_balance)
+    .sum();
+  let mut opening_cash = opening_balances;
+  let mut closing_cash = opening_balances;
+  for transaction in transactions.iter().filter(|t| !t.is_void()) {
+    if transaction.date > period.end {
+      continue;
+    }
+    let moved: Money = transaction
+      .postings
+      .iter()
+      .filter(|p| real.contains(p.account_id.as_str()))
+      .map(|p| p.amount)
+      .sum();
+    closing_cash += moved;
+    if transaction.date < period.start {
+      opening_cash += moved;
+    }
+  }
+
+  let mut operating: HashMap<&str, Money> = HashMap::new();
+  let mut financing = Vec::new();
+  for transaction in period.transactions(transactions) {
+    let (real_postings, category_postings): (Vec<_>, Vec<_>) = transaction
+      .postings
+      .iter()
+      .partition(|p| real.contains(p.account_id.as_str()));
+    if real_postings.is_empty() {
+      continue;
+    }
+
+    if !category_postings.is_empty() {
+      let cash_impact: Money = real_postings.iter().map(|p| p.amount).sum();
+      for posting in category_postings {
+        let Some(account) = by_id.get(posting.account_id.as_str()) else {
+          continue;
+        };
+        if !matches!(
+          account.account_type,
+          AccountType::Income | AccountType::Expense
+        ) {
+          continue;
+        }
+        let direction = if cash_impact.is_zero() {
+          posting.amount
+        } else {
+          cash_impact
+        };
+        let amount = if direction.is_negative() {
+          -posting.amount.abs()
+        } else {
+          posting.amount.abs()
+        };
+        *operating.entry(account.id.as_str()).or_default() += amount;
+      }
+    } else if real_postings.len() >= 2 {
+      let from = real_postings.iter().find(|p| p.amount.is_negative());
+      let to = real_postings.iter().find(|p| p.amount.is_positive());
+      if let (Some(from), Some(to)) = (from, to) {
+        financing.push(CashMovement {
+          transaction_id: transaction.id.clone(),
+          description: format!(
+            "{} → {}",
+            by_id[from.account_id.as_str()].name,
+            by_id[to.account_id.as_str()].name
+          ),
+          amount: to.amount,
+        });
+      }
+    }
+  }
+
+  let mut items: Vec<OperatingItem> = operating
+    .into_iter()
+    .filter(|(_, amount)| !amount.is_zero())
+    .map(|(id, amount)| OperatingItem {
+      category_id: id.to_string(),
+      category_name: by_id[id].name.clone(),
+      amount,
+    })
+    .collect();
+  items.sort_by(|a, b| {
+    b.amount
+      .cents()
+      .cmp(&a.amount.cents())
+      .then_with(|| a.category_name.cmp(&b.category_name))
+  });
+  let operating_total = items.iter().map(|i| i.amount).sum();
+
+  CashFlowStatement {
+    period,
+    operating: CashFlowSection {
+      items,
+      total: operating_total,
+    },
+    investing: CashFlowSection {
+      items: Vec::new(),
+      total: Money::ZERO,
+    },
+    financing: CashFlowSection {
+      items: financing,
+      total: Money::ZERO,
+    },
+    net_cash_change: closing_cash - opening_cash,
+    opening_cash,
+    closing_cash,
+  }
+}
@@ -0,0 +1,472 @@
+//! GST summary and BAS draft, mirroring `ReportService.generateGSTSummary`
+//! and `ReportService.generateBASDraft`.
+//!
+//! GST reaches the ledger in two shapes:
+//!   1. `gstCode`/`gstAmount` on a business income or expense posting (manual
+//!      entry and CSV imports);
+//!   2. a separate posting to GST Collected/Paid alongside the category
+//!      posting, with no GST fields (Stripe imports).
+//!
+//! A transaction is read in the first shape if any of its postings are, and
+//! in the second otherwise. Both reports are derived from the same list of
+//! `GstEntry`s so they can't disagree, and the BAS drill-down lists exactly
+//! the rows its totals were summed from.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Period;
+use crate::model::{Account, AccountType, AtoLabel, GstCode, Posting, Transaction};
+use crate::money::Money;
+
+/// The BAS fields Ledgerhound fills in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BasLabel {
+  G1,
+  G2,
+  G3,
+  G10,
+  G11,
+  #[serde(rename = "1A")]
+  OneA,
+  #[serde(rename = "1B")]
+  OneB,
+}
+
+impl BasLabel {
+  pub const ALL: &'static [BasLabel] = &[
+    BasLabel::G1,
+    BasLabel::G2,
+    BasLabel::G3,
+    BasLabel::G10,
+    BasLabel::G11,
+    BasLabel::OneA,
+    BasLabel::OneB,
+  ];
+
+  /// The wording used in the BAS draft's reconciliation list.
+  pub fn description(self) -> &'static str {
+    match self {
+      BasLabel::G1 => "Total Sales (G1)",
+      BasLabel::G2 => "Export Sales (G2)",
+      BasLabel::G3 => "Other GST-free Sales (G3)",
+      BasLabel::G10 => "Capital Purchases (G10)",
+      BasLabel::G11 => "Non-capital Purchases (G11)",
+      BasLabel::OneA => "GST on Sales (1A)",
+      BasLabel::OneB => "GST on Purchases (1B)",
+    }
+  }
+}
+
+/// `atoLabel` values that pin an expense category to a BAS purchases field,
+/// overriding the TS report's guess from the category name. They're picked
+/// under "BAS Purchases" in the category's ATO label setting.
+pub const ATO_LABEL_CAPITAL: &str = AtoLabel::G10CapitalPurchases.as_str();
+pub const ATO_LABEL_NON_CAPITAL: &str = AtoLabel::G11NonCapitalPurchases.as_str();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+  Sale,
+  Purchase,
+}
+
+/// One sale or purchase that carries GST, in either recording shape.
+struct GstEntry<'a> {
+  side: Side,
+  transaction: &'a Transaction,
+  /// The income or expense posting; missing when a GST account posting has
+  /// no category beside it.
+  category: Option<(&'a Posting, &'a Account)>,
+  /// The posting holding the GST: the category posting itself in the first
+  /// shape, the GST Collected/Paid posting in the second.
+  gst_posting: (&'a Posting, &'a Account),
+  /// `None` for GST account postings, which are treated as `GST`.
+  gst_code: Option<GstCode>,
+  /// Signed so that refunds reduce the totals.
+  amount_ex_gst: Money,
+  gst: Money,
+}
+
+impl GstEntry<'_> {
+  fn is_capital(&self) -> bool {
+    let Some((_, account)) = self.category else {
+      return false;
+    };
+    match account.ato_label.as_deref() {
+      Some(ATO_LABEL_CAPITAL) => true,
+      Some(ATO_LABEL_NON_CAPITAL) => false,
+      _ => {
+        let name = account.name.to_lowercase();
+        name.contains("capital") || name.contains("asset")
+      }
+    }
+  }
+
+  /// The BAS fields this entry adds to.
+  fn labels(&self) -> Vec<BasLabel> {
+    let mut labels = Vec::new();
+    if !self.gst.is_zero() {
+      labels.push(match self.side {
+        Side::Sale => BasLabel::OneA,
+        Side::Purchase => BasLabel::OneB,
+      });
+    }
+    if self.category.is_none() {
+      return labels;
+    }
+    match (self.side, self.gst_code) {
+      (Side::Sale, None | Some(GstCode::Gst)) => labels.push(BasLabel::G1),
+      (Side::Sale, Some(GstCode::Export)) => labels.extend([BasLabel::G1, BasLabel::G2]),
+      (Side::Sale, Some(GstCode::GstFree)) => labels.extend([BasLabel::G1, BasLabel::G3]),
+      (Side::Purchase, None | Some(GstCode::Gst | GstCode::GstFree | GstCode::InputTaxed)) => {
+        labels.push(if self.is_capital() {
+          BasLabel::G10
+        } else {
+          BasLabel::G11
+        })
+      }
+      _ => {}
+    }
+    labels
+  }
+}
+
+/// A posting's value on the sales side: credits are sales, debits refunds.
+fn sales_value(amount: Money) -> Money {
+  if amount.is_positive() {
+    -amount.abs()
+  } else {
+    amount.abs()
+  }
+}
+
+fn gst_entries<'a>(
+  accounts: &'a HashMap<&str, &'a Account>,
+  transactions: &'a [Transaction],
+  period: Period,
+) -> Vec<GstEntry<'a>> {
+  let account_of = |posting: &Posting| accounts.get(posting.account_id.as_str()).copied();
+  let mut entries = Vec::new();
+
+  for transaction in period.transactions(transactions) {
+    let coded: Vec<_> = transaction
+      .postings
+      .iter()
+      .filter(|p| p.is_business && p.gst_code.is_some())
+      .filter_map(|p| account_of(p).map(|a| (p, a)))
+      .filter(|(_, a)| matches!(a.account_type, AccountType::Income | AccountType::Expense))
+      .collect();
+
+    if !coded.is_empty() {
+      for (posting, account) in coded {
+        let side = match account.account_type {
+          AccountType::Income => Side::Sale,
+          _ => Side::Purchase,
+        };
+        let negate = match side {
+          Side::Sale => posting.amount.is_positive(),
+          Side::Purchase => posting.amount.is_negative(),
+        };
+        let signed = |m: Money| if negate { -m.abs() } else { m.abs() };
+        entries.push(GstEntry {
+          side,
+          transaction,
+          category: Some((posting, account)),
+          gst_posting: (posting, account),
+          gst_code: posting.gst_code,
+          amount_ex_gst: signed(posting.amount),
+          gst: signed(posting.gst_amount.unwrap_or_default()),
+        });
+      }
+      continue;
+    }
+
+    let with_account: Vec<(&Posting, &Account)> = transaction
+      .postings
+      .iter()
+      .filter_map(|p| account_of(p).map(|a| (p, a)))
+      .collect();
+    let first =
+      |wanted: fn(&Account) -> bool| with_account.iter().copied().find(|(_, a)| wanted(a));
+    let collected = first(|a| a.is_gst_control() && a.account_type == AccountType::Liability);
+    let paid = first(|a| a.is_gst_control() && a.account_type == AccountType::Asset);
+
+    if let Some(gst_posting) = collected {
+      let gst = sales_value(gst_posting.0.amount);
+      let category = first(|a| a.account_type == AccountType::Income);
+      entries.push(GstEntry {
+        side: Side::Sale,
+        transaction,
+        category,
+        gst_posting,
+        gst_code: None,
+        amount_ex_gst: category.map_or(Money::ZERO, |(p, _)| with_sign(p.amount.abs(), gst)),
+        gst,
+      });
+    }
+    if let Some(gst_posting) = paid {
+      // GST Paid is an asset, so a purchase posts positive and a refund
+      // negative: the opposite of the sales side.
+      let gst = -sales_value(gst_posting.0.amount);
+      let category = first(|a| a.account_type == AccountType::Expense);
+      entries.push(GstEntry {
+        side: Side::Purchase,
+        transaction,
+        category,
+        gst_posting,
+        gst_code: None,
+        amount_ex_gst: category.map_or(Money::ZERO, |(p, _)| with_sign(p.amount.abs(), gst)),
+        gst,
+      });
+    }
+  }
+  entries
+}
+
+/// `amount` with the sign of `sign`.
+fn with_sign(amount: Money, sign: Money) -> Money {
+  if sign.is_negative() {
+    -amount
+  } else {
+    amount
+  }
+}
+
+fn accounts_by_id(accounts: &[Account]) -> HashMap<&str, &Account> {
+  accounts.iter().map(|a| (a.id.as_str(), a)).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GstCategoryLine {
+  pub category_id: String,
+  pub category_name: String,
+  /// GST-inclusive sales.
+  pub sales: Money,
+  /// GST-inclusive purchases.
+  pub purchases: Money,
+  pub gst_collected: Money,
+  pub gst_paid: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GstPayeeLine {
+  pub payee: String,
+  pub gst_collected: Money,
+  pub gst_paid: Money,
+}
+
+/// Same shape as `GSTSummary` in `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GstSummary {
+  pub period: Period,
+  pub gst_collected: Money,
+  pub gst_paid: Money,
+  #[serde(rename = "netGST")]
+  pub net_gst: Money,
+  pub by_category: Vec<GstCategoryLine>,
+  pub by_payee: Vec<GstPayeeLine>,
+}
+
+/// GST collected and paid on business transactions in `period`.
+pub fn gst_summary(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  period: Period,
+) -> GstSummary {
+  let accounts = accounts_by_id(accounts);
+  let mut gst_collected = Money::ZERO;
+  let mut gst_paid = Money::ZERO;
+  let mut by_category: BTreeMap<(&str, &str), GstCategoryLine> = BTreeMap::new();
+  let mut by_payee: BTreeMap<&str, GstPayeeLine> = BTreeMap::new();
+
+  for entry in gst_entries(&accounts, transactions, period) {
+    match entry.side {
+      Side::Sale => gst_collected += entry.gst,
+      Side::Purchase => gst_paid += entry.gst,
+    }
+    let Some((_, account)) = entry.category else {
+      continue;
+    };
+
+    let category = by_category
+      .entry((account.name.as_str(), account.id.as_str()))
+      .or_insert_with(|| GstCategoryLine {
+        category_id: account.id.clone(),
+        category_name: account.name.clone(),
+        sales: Money::ZERO,
+        purchases: Money::ZERO,
+        gst_collected: Money::ZERO,
+        gst_paid: Money::ZERO,
+      });
+    let payee = by_payee
+      .entry(entry.transaction.payee.as_str())
+      .or_insert_with(|| GstPayeeLine {
+        payee: entry.transaction.payee.clone(),
+        gst_collected: Money::ZERO,
+        gst_paid: Money::ZERO,
+      });
+    let inclusive = entry.amount_ex_gst + entry.gst;
+    match entry.side {
+      Side::Sale => {
+        category.sales += inclusive;
+        category.gst_collected += entry.gst;
+        payee.gst_collected += entry.gst;
+      }
+      Side::Purchase => {
+        category.purchases += inclusive;
+        category.gst_paid += entry.gst;
+        payee.gst_paid += entry.gst;
+      }
+    }
+  }
+
+  GstSummary {
+    period,
+    gst_collected,
+    gst_paid,
+    net_gst: gst_collected - gst_paid,
+    by_category: by_category.into_values().collect(),
+    by_payee: by_payee.into_values().collect(),
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BasReconciliationLine {
+  pub description: String,
+  pub value: Money,
+}
+
+/// Same shape as `BASDraft` in `src/types/index.ts`: cash basis, every field
+/// rounded to whole dollars.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasDraft {
+  pub period: Period,
+  pub g1_total_sales: Money,
+  pub g10_capital_purchases: Money,
+  pub g11_non_capital_purchases: Money,
+  pub g1_total_sales_inclusive: Money,
+  pub g10_capital_purchases_inclusive: Money,
+  pub g11_non_capital_purchases_inclusive: Money,
+  pub g2_export_sales: Money,
+  #[serde(rename = "g3OtherGSTFree")]
+  pub g3_other_gst_free: Money,
+  #[serde(rename = "oneAGSTOnSales")]
+  pub one_a_gst_on_sales: Money,
+  #[serde(rename = "oneBGSTOnPurchases")]
+  pub one_b_gst_on_purchases: Money,
+  #[serde(rename = "netGST")]
+  pub net_gst: Money,
+  pub reconciliation: Vec<BasReconciliationLine>,
+}
+
+/// A posting's contribution to one BAS field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasContribution {
+  pub label: BasLabel,
+  pub transaction_id: String,
+  pub posting_id: String,
+  pub date: DateTime<Utc>,
+  pub payee: String,
+  pub account_id: String,
+  pub account_name: String,
+  pub ato_label: Option<String>,
+  pub gst_code: Option<GstCode>,
+  /// What the posting adds to `label`: the GST for 1A and 1B, the
+  /// GST-exclusive amount otherwise. Unrounded.
+  pub amount: Money,
+  /// GST-inclusive amount of the sale or purchase.
+  pub amount_inclusive: Money,
+}
+
+/// Every posting behind the BAS draft for `period`, optionally for one field,
+/// in date order.
+pub fn bas_contributions(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  period: Period,
+  label: Option<BasLabel>,
+) -> Vec<BasContribution> {
+  let accounts = accounts_by_id(accounts);
+  let mut rows = Vec::new();
+  for entry in gst_entries(&accounts, transactions, period) {
+    for entry_label in entry.labels() {
+      if label.is_some_and(|wanted| wanted != entry_label) {
+        continue;
+      }
+      let (posting, account) = match entry_label {
+        BasLabel::OneA | BasLabel::OneB => entry.gst_posting,
+        _ => entry.category.unwrap_or(entry.gst_posting),
+      };
+      rows.push(BasContribution {
+        label: entry_label,
+        transaction_id: entry.transaction.id.clone(),
+        posting_id: posting.id.clone(),
+        date: entry.transaction.date,
+        payee: entry.transaction.payee.clone(),
+        account_id: account.id.clone(),
+        account_name: account.name.clone(),
+        ato_label: account.ato_label.clone(),
+        gst_code: entry.gst_code,
+        amount: match entry_label {
+          BasLabel::OneA | BasLabel::OneB => entry.gst,
+          _ => entry.amount_ex_gst,
+        },
+        amount_inclusive: entry.amount_ex_gst + entry.gst,
+      });
+    }
+  }
+  rows
+}
+
+/// Round to whole dollars the way `Math.round` does (halves go up).
+fn whole_dollars(amount: Money) -> Money {
+  Money::from_cents((amount.cents() + 50).div_euclid(100) * 100)
+}
+
+/// The BAS draft for `period`, summed from [`bas_contributions`].
+pub fn bas_draft(accounts: &[Account], transactions: &[Transaction], period: Period) -> BasDraft {
+  let mut totals: HashMap<BasLabel, (Money, Money)> = HashMap::new();
+  for row in bas_contributions(accounts, transactions, period, None) {
+    let (amount, inclusive) = totals.entry(row.label).or_default();
+    *amount += row.amount;
+    *inclusive += row.amount_inclusive;
+  }
+  let total = |label| totals.get(&label).map_or(Money::ZERO, |t| t.0);
+  let inclusive = |label| totals.get(&label).map_or(Money::ZERO, |t| t.1);
+
+  let mut reconciliation: Vec<BasReconciliationLine> = BasLabel::ALL
+    .iter()
+    .map(|&label| BasReconciliationLine {
+      description: label.description().to_string(),
+      value: whole_dollars(total(label)),
+    })
+    .collect();
+  let net_gst = whole_dollars(total(BasLabel::OneA) - total(BasLabel::OneB));
+  reconciliation.push(BasReconciliationLine {
+    description: "Net GST".into(),
+    value: net_gst,
+  });
+
+  BasDraft {
+    period,
+    g1_total_sales: whole_dollars(total(BasLabel::G1)),
+    g10_capital_purchases: whole_dollars(total(BasLabel::G10)),
+    g11_non_capital_purchases: whole_dollars(total(BasLabel::G11)),
+    g1_total_sales_inclusive: whole_dollars(inclusive(BasLabel::G1)),
+    g10_capital_purchases_inclusive: whole_dollars(inclusive(BasLabel::G10)),
+    g11_non_capital_purchases_inclusive: whole_dollars(inclusive(BasLabel::G11)),
+    g2_export_sales: whole_dollars(total(BasLabel::G2)),
+    g3_other_gst_free: whole_dollars(total(BasLabel::G3)),
+    one_a_gst_on_sales: whole_dollars(total(BasLabel::OneA)),
+    one_b_gst_on_purchases: whole_dollars(total(BasLabel::OneB)),
+    net_gst,
+    reconciliation,
+  }
+}
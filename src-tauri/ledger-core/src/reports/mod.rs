@@ -0,0 +1,95 @@
+//! Financial reports, mirroring `ReportService` in
+//! `src/lib/services/reportService.ts`.
+//!
+//! Every report is a pure function over accounts and transactions, like
+//! [`crate::balance`], so the same code serves the Tauri commands and the
+//! golden-file tests.
+
+mod balance_sheet;
+mod cash_flow;
+mod gst;
+mod profit_and_loss;
+mod tree;
+
+use std::ops::AddAssign;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Posting, Transaction};
+use crate::money::Money;
+
+pub use balance_sheet::{balance_sheet, BalanceSheet, BalanceSheetLine, EquityLine};
+pub use cash_flow::{cash_flow, CashFlowSection, CashFlowStatement, CashMovement, OperatingItem};
+pub use gst::{
+  bas_contributions, bas_draft, gst_summary, BasContribution, BasDraft, BasLabel,
+  BasReconciliationLine, GstCategoryLine, GstPayeeLine, GstSummary, ATO_LABEL_CAPITAL,
+  ATO_LABEL_NON_CAPITAL,
+};
+pub use profit_and_loss::{
+  profit_and_loss, ProfitAndLoss, ProfitAndLossLine, ProfitAndLossOptions, ProfitTotals,
+};
+pub use tree::CategoryNode;
+
+/// Inclusive date range a report covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Period {
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+}
+
+impl Period {
+  pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+    Period { start, end }
+  }
+
+  pub fn contains(&self, date: DateTime<Utc>) -> bool {
+    self.start <= date && date <= self.end
+  }
+
+  /// Non-void transactions dated within the period.
+  pub(crate) fn transactions<'a>(
+    &self,
+    transactions: &'a [Transaction],
+  ) -> impl Iterator<Item = &'a Transaction> + 'a {
+    let period = *self;
+    transactions
+      .iter()
+      .filter(move |t| !t.is_void() && period.contains(t.date))
+  }
+}
+
+/// An amount segregated by `Posting.isBusiness`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Split {
+  pub amount: Money,
+  pub business: Money,
+  pub personal: Money,
+}
+
+impl Split {
+  pub(crate) fn of(posting: &Posting, amount: Money) -> Self {
+    if posting.is_business {
+      Split {
+        amount,
+        business: amount,
+        personal: Money::ZERO,
+      }
+    } else {
+      Split {
+        amount,
+        business: Money::ZERO,
+        personal: amount,
+      }
+    }
+  }
+}
+
+impl AddAssign for Split {
+  fn add_assign(&mut self, other: Split) {
+    self.amount += other.amount;
+    self.business += other.business;
+    self.personal += other.personal;
+  }
+}
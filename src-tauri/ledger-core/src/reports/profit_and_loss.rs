@@ -0,0 +1,183 @@
+//! Profit and loss, mirroring `ReportService.generateProfitAndLoss`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::tree::{category_tree, CategoryNode};
+use super::{Period, Split};
+use crate::model::{Account, AccountType, Posting, Transaction};
+use crate::money::Money;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfitAndLossOptions {
+  pub business_only: bool,
+  pub personal_only: bool,
+  /// Report business amounts including GST instead of excluding it.
+  pub gst_inclusive: bool,
+}
+
+impl ProfitAndLossOptions {
+  fn includes(&self, posting: &Posting) -> bool {
+    if self.business_only {
+      posting.is_business
+    } else if self.personal_only {
+      !posting.is_business
+    } else {
+      true
+    }
+  }
+}
+
+/// One category row, as `ProfitAndLoss.income[]` in `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitAndLossLine {
+  pub category_id: String,
+  pub category_name: String,
+  pub full_path: Option<String>,
+  pub amount: Money,
+  /// Only reported for business-only reports, as in the TS service.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub gst_exclusive: Option<Money>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub gst: Option<Money>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitTotals {
+  pub income: Money,
+  pub expenses: Money,
+  pub net_profit: Money,
+}
+
+/// Same shape as `ProfitAndLoss` in `src/types/index.ts`, plus the category
+/// trees and the business/personal totals.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitAndLoss {
+  pub period: Period,
+  pub business_only: bool,
+  pub income: Vec<ProfitAndLossLine>,
+  pub expenses: Vec<ProfitAndLossLine>,
+  pub total_income: Money,
+  pub total_expenses: Money,
+  pub net_profit: Money,
+  pub income_tree: Vec<CategoryNode>,
+  pub expense_tree: Vec<CategoryNode>,
+  pub business: ProfitTotals,
+  pub personal: ProfitTotals,
+}
+
+#[derive(Default)]
+struct CategoryTotals {
+  gst_exclusive: Money,
+  gst: Money,
+  reported: Split,
+}
+
+/// Income and expenses for `period`, grouped by category.
+///
+/// Amounts are signed so refunds reduce their category. Category postings
+/// already carry the GST-exclusive amount (the GST sits in a separate posting
+/// to GST Collected/Paid, see `generateSplitPostings`), so `gstInclusive`
+/// adds `gstAmount` back rather than the TS report's subtracting it a second
+/// time.
+pub fn profit_and_loss(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  period: Period,
+  options: ProfitAndLossOptions,
+) -> ProfitAndLoss {
+  let types: HashMap<&str, AccountType> = accounts
+    .iter()
+    .map(|a| (a.id.as_str(), a.account_type))
+    .collect();
+
+  let mut totals: HashMap<String, CategoryTotals> = HashMap::new();
+  for transaction in period.transactions(transactions) {
+    for posting in &transaction.postings {
+      let value = match types.get(posting.account_id.as_str()) {
+        Some(AccountType::Income) => -posting.amount,
+        Some(AccountType::Expense) => posting.amount,
+        _ => continue,
+      };
+      if !options.includes(posting) {
+        continue;
+      }
+      let gst = match posting.gst_amount {
+        Some(gst) if posting.is_business && value.is_negative() => -gst.abs(),
+        Some(gst) if posting.is_business => gst.abs(),
+        _ => Money::ZERO,
+      };
+      let reported = if options.gst_inclusive {
+        value + gst
+      } else {
+        value
+      };
+
+      let entry = totals.entry(posting.account_id.clone()).or_default();
+      entry.gst_exclusive += value;
+      entry.gst += gst;
+      entry.reported += Split::of(posting, reported);
+    }
+  }
+
+  let reported: HashMap<String, Split> = totals
+    .iter()
+    .map(|(id, t)| (id.clone(), t.reported))
+    .collect();
+  let income_tree = category_tree(accounts, AccountType::Income, &reported);
+  let expense_tree = category_tree(accounts, AccountType::Expense, &reported);
+
+  let lines = |trees: &[CategoryNode]| -> Vec<ProfitAndLossLine> {
+    trees
+      .iter()
+      .flat_map(|t| t.walk())
+      .filter_map(|node| {
+        let category = totals.get(&node.account_id)?;
+        Some(ProfitAndLossLine {
+          category_id: node.account_id.clone(),
+          category_name: node.name.clone(),
+          full_path: node.full_path.clone(),
+          amount: category.reported.amount,
+          gst_exclusive: options.business_only.then_some(category.gst_exclusive),
+          gst: options.business_only.then_some(category.gst),
+        })
+      })
+      .collect()
+  };
+  let income = lines(&income_tree);
+  let expenses = lines(&expense_tree);
+
+  let subtotal = |trees: &[CategoryNode]| {
+    let mut sum = Split::default();
+    for tree in trees {
+      sum += tree.subtotal;
+    }
+    sum
+  };
+  let income_total = subtotal(&income_tree);
+  let expense_total = subtotal(&expense_tree);
+  let totals_of = |income: Money, expenses: Money| ProfitTotals {
+    income,
+    expenses,
+    net_profit: income - expenses,
+  };
+
+  ProfitAndLoss {
+    period,
+    business_only: options.business_only,
+    total_income: income_total.amount,
+    total_expenses: expense_total.amount,
+    net_profit: income_total.amount - expense_total.amount,
+    business: totals_of(income_total.business, expense_total.business),
+    personal: totals_of(income_total.personal, expense_total.personal),
+    income,
+    expenses,
+    income_tree,
+    expense_tree,
+  }
+}
@@ -0,0 +1,132 @@
+//! The `parentId`/`level` category hierarchy, as `CategoryService.getCategoryTree`
+//! builds it, with report amounts rolled up from the leaves.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use super::Split;
+use crate::model::{Account, AccountType};
+
+/// A category with its own postings and the subtotal of its whole subtree.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryNode {
+  pub account_id: String,
+  pub name: String,
+  pub full_path: Option<String>,
+  pub level: i32,
+  /// Postings to this category itself.
+  pub own: Split,
+  /// `own` plus the subtotals of every child.
+  pub subtotal: Split,
+  pub children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+  /// Pre-order walk: a parent comes before its children.
+  pub fn walk(&self) -> Box<dyn Iterator<Item = &CategoryNode> + '_> {
+    Box::new(std::iter::once(self).chain(self.children.iter().flat_map(|c| c.walk())))
+  }
+}
+
+/// Build the trees of `account_type` categories that have amounts in `own`.
+///
+/// Ancestors without postings of their own are included so every subtotal
+/// has somewhere to roll up to. A parent of a different type (or one that no
+/// longer exists) makes the category a root.
+pub(crate) fn category_tree(
+  accounts: &[Account],
+  account_type: AccountType,
+  own: &HashMap<String, Split>,
+) -> Vec<CategoryNode> {
+  let by_id: HashMap<&str, &Account> = accounts
+    .iter()
+    .filter(|a| a.account_type == account_type)
+    .map(|a| (a.id.as_str(), a))
+    .collect();
+
+  let mut included: HashSet<&str> = HashSet::new();
+  for id in own.keys() {
+    let mut current = by_id.get(id.as_str()).copied();
+    while let Some(account) = current {
+      if !included.insert(account.id.as_str()) {
+        break;
+      }
+      current = account
+        .parent_id
+        .as_deref()
+        .and_then(|parent| by_id.get(parent).copied());
+    }
+  }
+
+  let mut children: HashMap<&str, Vec<&Account>> = HashMap::new();
+  let mut roots: Vec<&Account> = Vec::new();
+  for id in &included {
+    let account = by_id[id];
+    match account.parent_id.as_deref() {
+      Some(parent) if parent != account.id && included.contains(parent) => {
+        children.entry(parent).or_default().push(account)
+      }
+      _ => roots.push(account),
+    }
+  }
+  sort_siblings(&mut roots);
+  for siblings in children.values_mut() {
+    sort_siblings(siblings);
+  }
+
+  let mut visited = HashSet::new();
+  let mut trees: Vec<CategoryNode> = roots
+    .iter()
+    .map(|account| build(account, &children, own, &mut visited))
+    .collect();
+
+  // A `parentId` cycle leaves its members without a root; report them
+  // rather than silently dropping their amounts.
+  let mut orphans: Vec<&Account> = included
+    .iter()
+    .filter(|id| !visited.contains(*id))
+    .map(|id| by_id[id])
+    .collect();
+  sort_siblings(&mut orphans);
+  for account in orphans {
+    if !visited.contains(account.id.as_str()) {
+      trees.push(build(account, &children, own, &mut visited));
+    }
+  }
+  trees
+}
+
+fn sort_siblings(accounts: &mut [&Account]) {
+  accounts.sort_by(|a, b| (a.sort_order, &a.name).cmp(&(b.sort_order, &b.name)));
+}
+
+fn build<'a>(
+  account: &'a Account,
+  children: &HashMap<&str, Vec<&'a Account>>,
+  own: &HashMap<String, Split>,
+  visited: &mut HashSet<&'a str>,
+) -> CategoryNode {
+  visited.insert(account.id.as_str());
+  let own_split = own.get(&account.id).copied().unwrap_or_default();
+  let mut subtotal = own_split;
+  let mut nodes = Vec::new();
+  for child in children.get(account.id.as_str()).into_iter().flatten() {
+    if visited.contains(child.id.as_str()) {
+      continue;
+    }
+    let node = build(child, children, own, visited);
+    subtotal += node.subtotal;
+    nodes.push(node);
+  }
+  CategoryNode {
+    account_id: account.id.clone(),
+    name: account.name.clone(),
+    full_path: account.full_path.clone(),
+    level: account.level,
+    own: own_split,
+    subtotal,
+    children: nodes,
+  }
+}
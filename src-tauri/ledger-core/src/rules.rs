@@ -0,0 +1,333 @@
+//! Memorized rule matching, mirroring `matchPayee` and the `defaultSplits`
+//! helpers in `memorizedRuleService.ts`.
+//!
+//! Every rule is compiled into one case-insensitive [`RegexSet`], pattern `i`
+//! for the `i`th rule in evaluation order, so a payee is tested against all
+//! of them in a single pass and the winner is the first applicable rule in
+//! the set's matches. Categorising a large import costs one scan per row
+//! rather than one per row per rule.
+
+use std::fmt;
+
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{GstCode, MatchType, MemorizedRule};
+
+/// Compiled size limit for the rule set. Hundreds of rules fit comfortably;
+/// the default (10 MiB) is too tight for users with long regex lists.
+const SET_SIZE_LIMIT: usize = 64 << 20;
+
+/// The `kind` that marks `defaultSplits` as a business/personal split ratio.
+const SPLIT_RATIO: &str = "SPLIT_RATIO";
+
+/// Where a payee is being matched: rules say whether they apply to each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleContext {
+  Import,
+  Manual,
+}
+
+impl fmt::Display for RuleContext {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      RuleContext::Import => "import",
+      RuleContext::Manual => "manual entry",
+    })
+  }
+}
+
+impl MemorizedRule {
+  pub fn applies_on(&self, context: RuleContext) -> bool {
+    match context {
+      RuleContext::Import => self.apply_on_import,
+      RuleContext::Manual => self.apply_on_manual_entry,
+    }
+  }
+
+  /// The rule's classic multi-category split templates, like
+  /// `getDefaultSplits`: empty if it has none, stores a split ratio instead
+  /// or its JSON doesn't parse.
+  pub fn default_split_templates(&self) -> Vec<SplitTemplate> {
+    match self.parsed_splits() {
+      Some(RuleSplits::Templates(templates)) => templates,
+      _ => Vec::new(),
+    }
+  }
+
+  /// The rule's business/personal split ratio, like `getSplitRatio`.
+  pub fn split_ratio(&self) -> Option<SplitRatio> {
+    match self.parsed_splits() {
+      Some(RuleSplits::Ratio(ratio)) => Some(ratio),
+      _ => None,
+    }
+  }
+
+  fn parsed_splits(&self) -> Option<RuleSplits> {
+    let value: serde_json::Value = serde_json::from_str(self.default_splits.as_deref()?).ok()?;
+    if value.get("kind").and_then(serde_json::Value::as_str) == Some(SPLIT_RATIO) {
+      serde_json::from_value(value).ok().map(RuleSplits::Ratio)
+    } else {
+      serde_json::from_value(value)
+        .ok()
+        .map(RuleSplits::Templates)
+    }
+  }
+}
+
+/// The two shapes `MemorizedRule.defaultSplits` is stored in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RuleSplits {
+  Templates(Vec<SplitTemplate>),
+  Ratio(SplitRatio),
+}
+
+/// One category of a rule's default split, as `SplitTemplate` in
+/// `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitTemplate {
+  pub account_id: String,
+  /// A percentage of the transaction, or a fixed amount in dollars.
+  pub percent_or_amount: f64,
+  pub is_business: bool,
+  pub gst_code: Option<GstCode>,
+  pub gst_rate: Option<f64>,
+  pub memo_template: Option<String>,
+}
+
+/// A mixed-use expense's business share, expanded into a business and a
+/// personal posting when the transaction is created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename = "SPLIT_RATIO", rename_all = "camelCase")]
+pub struct SplitRatio {
+  pub personal_category_id: String,
+  pub business_category_id: String,
+  /// 0–100.
+  pub business_percent: f64,
+  /// Claim GST on the business share.
+  pub gst_on_business: bool,
+}
+
+/// Every memorized rule, compiled for matching many payees.
+#[derive(Debug)]
+pub struct RuleMatcher {
+  /// In evaluation order: priority, then name.
+  rules: Vec<MemorizedRule>,
+  /// Each rule's pattern, or why it doesn't compile.
+  patterns: Vec<Result<Regex, String>>,
+  /// The compiled patterns; `None` only if they're too big to go together,
+  /// when they're tried one by one instead.
+  set: Option<RegexSet>,
+  /// Set pattern index to rule index, skipping rules that don't compile.
+  set_rules: Vec<usize>,
+}
+
+impl RuleMatcher {
+  pub fn new(mut rules: Vec<MemorizedRule>) -> Self {
+    rules.sort_by(|a, b| {
+      a.priority
+        .cmp(&b.priority)
+        .then_with(|| a.name.cmp(&b.name))
+    });
+    let sources: Vec<String> = rules.iter().map(pattern_source).collect();
+    let patterns: Vec<Result<Regex, String>> = sources
+      .iter()
+      .map(|source| {
+        RegexBuilder::new(source)
+          .case_insensitive(true)
+          .size_limit(SET_SIZE_LIMIT)
+          .build()
+          .map_err(|err| err.to_string())
+      })
+      .collect();
+    let set_rules: Vec<usize> = (0..rules.len()).filter(|&i| patterns[i].is_ok()).collect();
+    let set = RegexSetBuilder::new(set_rules.iter().map(|&i| &sources[i]))
+      .case_insensitive(true)
+      .size_limit(SET_SIZE_LIMIT)
+      .build()
+      .ok();
+    RuleMatcher {
+      rules,
+      patterns,
+      set,
+      set_rules,
+    }
+  }
+
+  /// The rules in evaluation order.
+  pub fn rules(&self) -> &[MemorizedRule] {
+    &self.rules
+  }
+
+  /// The first rule, in priority order, that applies in `context` and
+  /// matches `payee`.
+  pub fn find(&self, payee: &str, context: RuleContext) -> Option<&MemorizedRule> {
+    self
+      .matching(payee)
+      .into_iter()
+      .map(|i| &self.rules[i])
+      .find(|rule| rule.applies_on(context))
+  }
+
+  /// Every rule's verdict on `payee`, and which one wins.
+  pub fn explain(&self, payee: &str, context: RuleContext) -> RuleExplanation {
+    let matching = self.matching(payee);
+    let applicable: Vec<usize> = matching
+      .iter()
+      .copied()
+      .filter(|&i| self.rules[i].applies_on(context))
+      .collect();
+    let winner = applicable.first().map(|&i| &self.rules[i]);
+
+    let rules = self
+      .rules
+      .iter()
+      .enumerate()
+      .map(|(i, rule)| {
+        let (outcome, detail) = if let Err(err) = &self.patterns[i] {
+          (
+            RuleOutcome::InvalidPattern,
+            format!("The pattern isn't a valid regular expression: {err}"),
+          )
+        } else if !matching.contains(&i) {
+          (RuleOutcome::NoMatch, no_match_detail(rule))
+        } else if !rule.applies_on(context) {
+          (
+            RuleOutcome::NotApplicable,
+            format!("Matches, but the rule is turned off for {context}"),
+          )
+        } else if winner.is_some_and(|winner| winner.id == rule.id) {
+          (RuleOutcome::Won, won_detail(rule, applicable.len() - 1))
+        } else {
+          let winner = winner.expect("an applicable match means there is a winner");
+          (RuleOutcome::Outranked, outranked_detail(rule, winner))
+        };
+        RuleVerdict {
+          rule_id: rule.id.clone(),
+          name: rule.name.clone(),
+          match_type: rule.match_type,
+          match_value: rule.match_value.clone(),
+          priority: rule.priority,
+          outcome,
+          detail,
+        }
+      })
+      .collect();
+
+    RuleExplanation {
+      payee: payee.to_string(),
+      context,
+      winner: winner.cloned(),
+      rules,
+    }
+  }
+
+  /// Indices of the rules whose patterns match `payee`, in evaluation order.
+  fn matching(&self, payee: &str) -> Vec<usize> {
+    match &self.set {
+      Some(set) => set
+        .matches(payee)
+        .into_iter()
+        .map(|pattern| self.set_rules[pattern])
+        .collect(),
+      None => self
+        .set_rules
+        .iter()
+        .copied()
+        .filter(|&i| {
+          self.patterns[i]
+            .as_ref()
+            .is_ok_and(|regex| regex.is_match(payee))
+        })
+        .collect(),
+    }
+  }
+}
+
+/// A rule as a regular expression: exact and contains rules match their
+/// text literally, regex rules are used as written.
+fn pattern_source(rule: &MemorizedRule) -> String {
+  match rule.match_type {
+    MatchType::Exact => format!("^{}$", regex::escape(&rule.match_value)),
+    MatchType::Contains => regex::escape(&rule.match_value),
+    MatchType::Regex => rule.match_value.clone(),
+  }
+}
+
+fn no_match_detail(rule: &MemorizedRule) -> String {
+  match rule.match_type {
+    MatchType::Exact => format!("The payee isn't \"{}\"", rule.match_value),
+    MatchType::Contains => format!("The payee doesn't contain \"{}\"", rule.match_value),
+    MatchType::Regex => format!("The payee doesn't match /{}/", rule.match_value),
+  }
+}
+
+fn won_detail(rule: &MemorizedRule, others: usize) -> String {
+  match others {
+    0 => "The only rule that matches".to_string(),
+    1 => format!(
+      "Matches and comes first at priority {} (1 other rule also matches)",
+      rule.priority
+    ),
+    n => format!(
+      "Matches and comes first at priority {} ({n} other rules also match)",
+      rule.priority
+    ),
+  }
+}
+
+fn outranked_detail(rule: &MemorizedRule, winner: &MemorizedRule) -> String {
+  if rule.priority == winner.priority {
+    format!(
+      "Matches, but \"{}\" has the same priority ({}) and comes first by name",
+      winner.name, rule.priority
+    )
+  } else {
+    format!(
+      "Matches, but \"{}\" comes first at priority {} (this rule is {})",
+      winner.name, winner.priority, rule.priority
+    )
+  }
+}
+
+/// Why a rule did or didn't categorise a payee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleOutcome {
+  /// The first applicable match.
+  Won,
+  /// Matches, but a rule earlier in evaluation order does too.
+  Outranked,
+  /// Matches, but isn't turned on for the context.
+  NotApplicable,
+  NoMatch,
+  /// A regex rule whose pattern doesn't compile; it never matches.
+  InvalidPattern,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleVerdict {
+  pub rule_id: String,
+  pub name: String,
+  pub match_type: MatchType,
+  pub match_value: String,
+  pub priority: i32,
+  pub outcome: RuleOutcome,
+  /// The outcome in words, for the rule editor.
+  pub detail: String,
+}
+
+/// Which rules match a payee and why the winner won.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleExplanation {
+  pub payee: String,
+  pub context: RuleContext,
+  pub winner: Option<MemorizedRule>,
+  /// Every rule, in evaluation order.
+  pub rules: Vec<RuleVerdict>,
+}
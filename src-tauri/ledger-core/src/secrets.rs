@@ -0,0 +1,288 @@
+//! API keys kept out of the book.
+//!
+//! `stripeImportService.ts` and `aiCategorizationService.ts` used to keep
+//! their keys in the `stripe` and `ai_categorization` settings, in plaintext
+//! in every copy and backup of the book. They now live in a [`SecretStore`]:
+//! the OS keychain in the desktop app, or a [`FileSecretStore`] where there
+//! isn't one. [`move_secrets_out_of_settings`] moves keys left in a book
+//! into the store and blanks them. The desktop app hands the stored keys to
+//! the API server in [`Secret::env_var`], which the services read instead
+//! of the settings.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::backup::sibling;
+use crate::db::{Database, DbError};
+use crate::encryption::{self, EncryptionError, SALT_LEN};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Secret {
+  StripeApiKey,
+  AiApiKey,
+}
+
+impl Secret {
+  pub const ALL: &'static [Secret] = &[Secret::StripeApiKey, Secret::AiApiKey];
+
+  /// The name it's stored under, e.g. the keychain account.
+  pub fn name(self) -> &'static str {
+    match self {
+      Secret::StripeApiKey => "stripe-api-key",
+      Secret::AiApiKey => "ai-api-key",
+    }
+  }
+
+  /// The environment variable the API server reads it from.
+  pub fn env_var(self) -> &'static str {
+    match self {
+      Secret::StripeApiKey => "LEDGERHOUND_STRIPE_API_KEY",
+      Secret::AiApiKey => "LEDGERHOUND_AI_API_KEY",
+    }
+  }
+
+  /// The setting that used to hold it, and the field within that setting's
+  /// JSON.
+  fn setting(self) -> (&'static str, &'static str) {
+    match self {
+      Secret::StripeApiKey => ("stripe", "apiKey"),
+      Secret::AiApiKey => ("ai_categorization", "apiKey"),
+    }
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Db(#[from] DbError),
+  #[error(transparent)]
+  Encryption(#[from] EncryptionError),
+  #[error("secrets file {0} is damaged")]
+  Corrupt(PathBuf),
+  #[error("can't decrypt {0}: wrong passphrase, or the file was tampered with")]
+  Decrypt(String),
+  #[error("secret store: {0}")]
+  Store(String),
+}
+
+pub trait SecretStore: Send + Sync {
+  fn get(&self, secret: Secret) -> Result<Option<String>, SecretError>;
+  fn set(&self, secret: Secret, value: &str) -> Result<(), SecretError>;
+  /// Returns whether there was a value to delete.
+  fn delete(&self, secret: Secret) -> Result<bool, SecretError>;
+}
+
+/// Secrets in a JSON file, each encrypted with XChaCha20-Poly1305 under a
+/// key derived from a passphrase, for machines without a keychain (CI,
+/// headless Linux).
+pub struct FileSecretStore {
+  path: PathBuf,
+  key: Zeroizing<[u8; 32]>,
+  salt: [u8; SALT_LEN],
+  /// Serialises read-modify-write cycles on the file.
+  lock: Mutex<()>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsFile {
+  /// Hex salt the passphrase is stretched with.
+  salt: String,
+  secrets: BTreeMap<String, Sealed>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Sealed {
+  nonce: String,
+  ciphertext: String,
+}
+
+impl FileSecretStore {
+  /// Open the store at `path`, creating it on the first `set`.
+  pub fn open(path: impl Into<PathBuf>, passphrase: &str) -> Result<Self, SecretError> {
+    let path = path.into();
+    let salt = match read_file(&path)? {
+      Some(file) => hex::decode(&file.salt)
+        .ok()
+        .and_then(|salt| salt.try_into().ok())
+        .ok_or_else(|| SecretError::Corrupt(path.clone()))?,
+      None => encryption::random_bytes()?,
+    };
+    Ok(FileSecretStore {
+      key: encryption::derive_key(passphrase, &salt)?,
+      path,
+      salt,
+      lock: Mutex::new(()),
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  fn cipher(&self) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(Key::from_slice(&*self.key))
+  }
+
+  fn load(&self) -> Result<SecretsFile, SecretError> {
+    Ok(read_file(&self.path)?.unwrap_or_else(|| SecretsFile {
+      salt: hex::encode(self.salt),
+      secrets: BTreeMap::new(),
+    }))
+  }
+
+  /// Write via a temporary file, readable only by the owner.
+  fn save(&self, file: &SecretsFile) -> Result<(), SecretError> {
+    if let Some(dir) = self.path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    let temp = sibling(&self.path, ".tmp");
+    std::fs::write(
+      &temp,
+      serde_json::to_string_pretty(file).expect("secrets serialize to JSON"),
+    )?;
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      std::fs::set_permissions(&temp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&temp, &self.path)?;
+    Ok(())
+  }
+}
+
+fn read_file(path: &Path) -> Result<Option<SecretsFile>, SecretError> {
+  match std::fs::read_to_string(path) {
+    Ok(json) => serde_json::from_str(&json)
+      .map(Some)
+      .map_err(|_| SecretError::Corrupt(path.to_path_buf())),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err.into()),
+  }
+}
+
+impl SecretStore for FileSecretStore {
+  fn get(&self, secret: Secret) -> Result<Option<String>, SecretError> {
+    let _guard = self.lock.lock().unwrap();
+    let file = self.load()?;
+    let Some(sealed) = file.secrets.get(secret.name()) else {
+      return Ok(None);
+    };
+    let decrypt = || -> Option<String> {
+      let nonce = hex::decode(&sealed.nonce).ok()?;
+      let ciphertext = hex::decode(&sealed.ciphertext).ok()?;
+      if nonce.len() != 24 {
+        return None;
+      }
+      // The name is bound in as associated data so values can't be
+      // swapped between entries.
+      let plaintext = self
+        .cipher()
+        .decrypt(
+          XNonce::from_slice(&nonce),
+          Payload {
+            msg: &ciphertext,
+            aad: secret.name().as_bytes(),
+          },
+        )
+        .ok()?;
+      String::from_utf8(plaintext).ok()
+    };
+    decrypt()
+      .map(Some)
+      .ok_or_else(|| SecretError::Decrypt(secret.name().to_string()))
+  }
+
+  fn set(&self, secret: Secret, value: &str) -> Result<(), SecretError> {
+    let _guard = self.lock.lock().unwrap();
+    let mut file = self.load()?;
+    let nonce: [u8; 24] = encryption::random_bytes()?;
+    let ciphertext = self
+      .cipher()
+      .encrypt(
+        XNonce::from_slice(&nonce),
+        Payload {
+          msg: value.as_bytes(),
+          aad: secret.name().as_bytes(),
+        },
+      )
+      .map_err(|err| SecretError::Store(err.to_string()))?;
+    file.secrets.insert(
+      secret.name().to_string(),
+      Sealed {
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+      },
+    );
+    self.save(&file)
+  }
+
+  fn delete(&self, secret: Secret) -> Result<bool, SecretError> {
+    let _guard = self.lock.lock().unwrap();
+    let mut file = self.load()?;
+    if file.secrets.remove(secret.name()).is_none() {
+      return Ok(false);
+    }
+    self.save(&file)?;
+    Ok(true)
+  }
+}
+
+/// What [`move_secrets_out_of_settings`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretMigration {
+  /// Moved into the store (or already there) and blanked in the book.
+  pub moved: Vec<Secret>,
+  /// Left in the book because the store already holds a different value.
+  pub conflicts: Vec<Secret>,
+}
+
+/// Move API keys still in `db`'s settings into `store`, blanking them in the
+/// setting so the rest of it (model, account IDs) stays put. Safe to run on
+/// every open.
+///
+/// The store is shared by every book, so a key that differs from the one
+/// already stored is left where it is rather than overwriting it.
+pub fn move_secrets_out_of_settings(
+  db: &Database,
+  store: &dyn SecretStore,
+) -> Result<SecretMigration, SecretError> {
+  let mut migration = SecretMigration::default();
+  for &secret in Secret::ALL {
+    let (key, field) = secret.setting();
+    let Some(mut setting) = db.setting_json::<serde_json::Value>(key)? else {
+      continue;
+    };
+    let Some(value) = setting
+      .get(field)
+      .and_then(|value| value.as_str())
+      .filter(|value| !value.is_empty())
+      .map(str::to_string)
+    else {
+      continue;
+    };
+
+    match store.get(secret)? {
+      Some(stored) if stored != value => {
+        migration.conflicts.push(secret);
+        continue;
+      }
+      Some(_) => {}
+      None => store.set(secret, &value)?,
+    }
+    setting[field] = serde_json::Value::String(String::new());
+    db.set_setting_json(key, &setting)?;
+    migration.moved.push(secret);
+  }
+  Ok(migration)
+}
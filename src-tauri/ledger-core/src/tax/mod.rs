@@ -0,0 +1,337 @@
+//! Australian income tax estimate, mirroring `TaxService` in
+//! `src/lib/services/taxService.ts`.
+
+mod payg;
+mod tables;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Account, AccountKind, AccountType, AtoLabel, Transaction};
+use crate::money::Money;
+use crate::reports::Period;
+
+pub use payg::{
+  default_installments, PaygConfig, PaygInstallment, PaygMethod, PaygStatus, Quarter,
+};
+pub use tables::{
+  default_tax_tables, LitoConfig, MedicareLevyConfig, SmallBusinessOffsetConfig, TaxBracket,
+  TaxTablesConfig, BUILT_IN_YEARS,
+};
+
+/// An Australian financial year (1 July to 30 June), written `2025-26`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FinancialYear {
+  start_year: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("not a financial year (expected e.g. 2025-26): {0}")]
+pub struct InvalidFinancialYear(pub String);
+
+impl FinancialYear {
+  pub fn starting(start_year: i32) -> Self {
+    FinancialYear { start_year }
+  }
+
+  /// The financial year `date` falls in.
+  pub fn containing(date: NaiveDate) -> Self {
+    match date.month() {
+      7..=12 => FinancialYear::starting(date.year()),
+      _ => FinancialYear::starting(date.year() - 1),
+    }
+  }
+
+  pub fn start_year(self) -> i32 {
+    self.start_year
+  }
+
+  /// 1 July.
+  pub fn first_day(self) -> NaiveDate {
+    NaiveDate::from_ymd_opt(self.start_year, 7, 1).expect("1 July exists")
+  }
+
+  /// 30 June.
+  pub fn last_day(self) -> NaiveDate {
+    NaiveDate::from_ymd_opt(self.start_year + 1, 6, 30).expect("30 June exists")
+  }
+}
+
+impl fmt::Display for FinancialYear {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}-{:02}",
+      self.start_year,
+      (self.start_year + 1).rem_euclid(100)
+    )
+  }
+}
+
+impl FromStr for FinancialYear {
+  type Err = InvalidFinancialYear;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || InvalidFinancialYear(s.to_string());
+    let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+    let start_year: i32 = start.parse().map_err(|_| invalid())?;
+    let end: i32 = end.parse().map_err(|_| invalid())?;
+    if start.len() != 4 || end != (start_year + 1).rem_euclid(100) {
+      return Err(invalid());
+    }
+    Ok(FinancialYear { start_year })
+  }
+}
+
+impl TryFrom<String> for FinancialYear {
+  type Error = InvalidFinancialYear;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<FinancialYear> for String {
+  fn from(year: FinancialYear) -> String {
+    year.to_string()
+  }
+}
+
+/// Resident income tax on `taxable_income`, before offsets.
+pub fn income_tax(taxable_income: Money, brackets: &[TaxBracket]) -> Money {
+  if !taxable_income.is_positive() {
+    return Money::ZERO;
+  }
+  brackets
+    .iter()
+    .rev()
+    .find(|b| taxable_income >= b.min)
+    .map_or(Money::ZERO, |b| {
+      b.base_tax + (taxable_income - b.min + Money::from_cents(100)).mul_rate(b.rate)
+    })
+}
+
+/// Medicare levy for a single taxpayer, shading in above the low-income
+/// threshold.
+pub fn medicare_levy(taxable_income: Money, config: &MedicareLevyConfig) -> Money {
+  if taxable_income <= config.low_income_threshold {
+    Money::ZERO
+  } else if taxable_income <= config.shade_in_threshold {
+    (taxable_income - config.low_income_threshold).mul_rate(config.shade_in_rate)
+  } else {
+    taxable_income.mul_rate(config.rate)
+  }
+}
+
+/// Low income tax offset.
+pub fn lito(taxable_income: Money, config: &LitoConfig) -> Money {
+  if taxable_income <= config.full_threshold {
+    config.max_offset
+  } else if taxable_income <= config.phase_out1_threshold {
+    config.max_offset - (taxable_income - config.full_threshold).mul_rate(config.phase_out1_rate)
+  } else if taxable_income <= config.zero_threshold {
+    non_negative(
+      config.phase_out1_amount
+        - (taxable_income - config.phase_out1_threshold).mul_rate(config.phase_out2_rate),
+    )
+  } else {
+    Money::ZERO
+  }
+}
+
+/// Small business income tax offset, capped at `config.cap` and at the tax
+/// it offsets.
+pub fn small_business_offset(
+  net_business_income: Money,
+  income_tax: Money,
+  config: &SmallBusinessOffsetConfig,
+) -> Money {
+  if !net_business_income.is_positive() {
+    return Money::ZERO;
+  }
+  [
+    net_business_income.mul_rate(config.rate),
+    config.cap,
+    non_negative(income_tax),
+  ]
+  .into_iter()
+  .min_by_key(|m| m.cents())
+  .unwrap_or_default()
+}
+
+fn non_negative(amount: Money) -> Money {
+  if amount.is_negative() {
+    Money::ZERO
+  } else {
+    amount
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelledAmount {
+  /// The label's description, e.g. "Item 10: Interest".
+  pub label: String,
+  pub ato_label: AtoLabel,
+  pub amount: Money,
+}
+
+/// Same shape as `TaxEstimation` in `src/types/index.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxEstimation {
+  pub financial_year: FinancialYear,
+  pub period: Period,
+  pub gross_business_income: Money,
+  pub business_expenses: Money,
+  pub net_business_income: Money,
+  pub other_income: Vec<LabelledAmount>,
+  pub total_other_income: Money,
+  pub personal_deductions: Vec<LabelledAmount>,
+  pub total_personal_deductions: Money,
+  pub taxable_income: Money,
+  pub income_tax: Money,
+  pub medicare_levy: Money,
+  pub lito: Money,
+  pub small_business_offset: Money,
+  pub total_tax_payable: Money,
+  /// Percent of taxable income, to two decimal places.
+  pub effective_rate: f64,
+  #[serde(rename = "paygPaid")]
+  pub payg_paid: Money,
+  /// Positive when tax is owing, negative for a refund.
+  pub estimated_balance: Money,
+}
+
+const OTHER_INCOME: &[AtoLabel] = &[
+  AtoLabel::IncomeInterest,
+  AtoLabel::IncomeDividends,
+  AtoLabel::IncomeRent,
+  AtoLabel::IncomeForeign,
+  AtoLabel::IncomeOther,
+];
+
+const PERSONAL_DEDUCTIONS: &[AtoLabel] = &[
+  AtoLabel::D1Car,
+  AtoLabel::D2Travel,
+  AtoLabel::D3Clothing,
+  AtoLabel::D4SelfEd,
+  AtoLabel::D5OtherWork,
+  AtoLabel::D7Interest,
+  AtoLabel::D9Gifts,
+  AtoLabel::D10TaxAffairs,
+  AtoLabel::D12Super,
+  AtoLabel::D15Other,
+];
+
+/// Estimate the tax on `period`'s income with `tables`, less any PAYG
+/// instalments recorded in `payg`.
+///
+/// Business income is every business posting to an income or expense
+/// category. Category postings already hold the GST-exclusive amount, so
+/// unlike the TS service `gstAmount` isn't subtracted again. Other income
+/// and deductions come from personal postings to categories with an ATO
+/// label, so a labelled business category isn't counted twice; they're
+/// signed, so refunds reduce them.
+pub fn estimate_tax(
+  accounts: &[Account],
+  transactions: &[Transaction],
+  period: Period,
+  tables: &TaxTablesConfig,
+  payg: Option<&PaygConfig>,
+) -> TaxEstimation {
+  let categories: HashMap<&str, &Account> = accounts
+    .iter()
+    .filter(|a| a.kind == AccountKind::Category)
+    .map(|a| (a.id.as_str(), a))
+    .collect();
+
+  let mut gross_business_income = Money::ZERO;
+  let mut business_expenses = Money::ZERO;
+  let mut labelled: BTreeMap<&str, Money> = BTreeMap::new();
+  for transaction in period.transactions(transactions) {
+    for posting in &transaction.postings {
+      let Some(account) = categories.get(posting.account_id.as_str()) else {
+        continue;
+      };
+      let value = match account.account_type {
+        AccountType::Income => -posting.amount,
+        AccountType::Expense => posting.amount,
+        _ => continue,
+      };
+      if posting.is_business {
+        match account.account_type {
+          AccountType::Income => gross_business_income += value,
+          _ => business_expenses += value,
+        }
+      }
+      if let Some(label) = account.tax_label().filter(|_| !posting.is_business) {
+        *labelled.entry(label.as_str()).or_default() += value;
+      }
+    }
+  }
+
+  let items = |labels: &[AtoLabel]| -> Vec<LabelledAmount> {
+    labels
+      .iter()
+      .filter_map(|&label| {
+        let amount = labelled.get(label.as_str()).copied()?;
+        amount.is_positive().then(|| LabelledAmount {
+          label: label.description().to_string(),
+          ato_label: label,
+          amount,
+        })
+      })
+      .collect()
+  };
+  let other_income = items(OTHER_INCOME);
+  let personal_deductions = items(PERSONAL_DEDUCTIONS);
+  let total_other_income: Money = other_income.iter().map(|i| i.amount).sum();
+  let total_personal_deductions: Money = personal_deductions.iter().map(|i| i.amount).sum();
+
+  let net_business_income = gross_business_income - business_expenses;
+  let taxable_income =
+    non_negative(net_business_income + total_other_income - total_personal_deductions);
+
+  let income_tax = income_tax(taxable_income, &tables.brackets);
+  let medicare_levy = medicare_levy(taxable_income, &tables.medicare_levy_config);
+  let lito = lito(taxable_income, &tables.lito_config);
+  let small_business_offset = small_business_offset(
+    net_business_income,
+    income_tax,
+    &tables.small_business_offset,
+  );
+  let total_tax_payable = non_negative(income_tax + medicare_levy - lito - small_business_offset);
+  let effective_rate = if taxable_income.is_positive() {
+    (total_tax_payable.cents() as f64 / taxable_income.cents() as f64 * 10_000.0).round() / 100.0
+  } else {
+    0.0
+  };
+  let payg_paid = payg.map_or(Money::ZERO, PaygConfig::total_paid);
+
+  TaxEstimation {
+    financial_year: tables.financial_year,
+    period,
+    gross_business_income,
+    business_expenses,
+    net_business_income,
+    other_income,
+    total_other_income,
+    personal_deductions,
+    total_personal_deductions,
+    taxable_income,
+    income_tax,
+    medicare_levy,
+    lito,
+    small_business_offset,
+    total_tax_payable,
+    effective_rate,
+    payg_paid,
+    estimated_balance: total_tax_payable - payg_paid,
+  }
+}
@@ -0,0 +1,192 @@
+//! PAYG instalment tracking, stored per financial year in the `settings`
+//! table (key `payg_<year>`) in the same JSON shape as `PAYGConfig`.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::FinancialYear;
+use crate::money::Money;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaygMethod {
+  Amount,
+  Rate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quarter {
+  Q1,
+  Q2,
+  Q3,
+  Q4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaygStatus {
+  Upcoming,
+  Due,
+  Overdue,
+  Paid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaygInstallment {
+  pub id: String,
+  pub quarter: Quarter,
+  pub financial_year: FinancialYear,
+  pub period_start: NaiveDate,
+  pub period_end: NaiveDate,
+  pub due_date: NaiveDate,
+  pub method: PaygMethod,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rate: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub assessed_amount: Option<Money>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub calculated_amount: Option<Money>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub paid_amount: Option<Money>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub paid_date: Option<NaiveDate>,
+  pub status: PaygStatus,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub notes: Option<String>,
+}
+
+impl PaygInstallment {
+  fn is_paid(&self) -> bool {
+    self.paid_amount.is_some_and(Money::is_positive)
+  }
+
+  /// Status as of `today`: paid once any amount is recorded, otherwise due
+  /// from the start of the quarter and overdue after the due date.
+  pub fn status_on(&self, today: NaiveDate) -> PaygStatus {
+    if self.is_paid() {
+      PaygStatus::Paid
+    } else if today > self.due_date {
+      PaygStatus::Overdue
+    } else if today >= self.period_start {
+      PaygStatus::Due
+    } else {
+      PaygStatus::Upcoming
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaygConfig {
+  pub financial_year: FinancialYear,
+  pub method: PaygMethod,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub annual_rate: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub annual_amount: Option<Money>,
+  pub installments: Vec<PaygInstallment>,
+}
+
+impl PaygConfig {
+  /// A year's four instalments, each assessed at a quarter of
+  /// `annual_amount`.
+  pub fn new(
+    financial_year: FinancialYear,
+    method: PaygMethod,
+    annual_amount: Option<Money>,
+    annual_rate: Option<f64>,
+  ) -> Self {
+    let installments = default_installments(financial_year)
+      .into_iter()
+      .map(|installment| PaygInstallment {
+        method,
+        rate: annual_rate,
+        assessed_amount: annual_amount.map(|amount| amount.div_round(4)),
+        ..installment
+      })
+      .collect();
+    PaygConfig {
+      financial_year,
+      method,
+      annual_rate,
+      annual_amount,
+      installments,
+    }
+  }
+
+  pub fn update_statuses(&mut self, today: NaiveDate) {
+    for installment in &mut self.installments {
+      installment.status = installment.status_on(today);
+    }
+  }
+
+  /// Mark `quarter` paid. Returns `false` if the config has no instalment
+  /// for that quarter.
+  pub fn record_payment(&mut self, quarter: Quarter, amount: Money, date: NaiveDate) -> bool {
+    let Some(installment) = self.installments.iter_mut().find(|i| i.quarter == quarter) else {
+      return false;
+    };
+    installment.paid_amount = Some(amount);
+    installment.paid_date = Some(date);
+    installment.status = PaygStatus::Paid;
+    true
+  }
+
+  pub fn total_paid(&self) -> Money {
+    self.installments.iter().filter_map(|i| i.paid_amount).sum()
+  }
+}
+
+/// The standard quarterly instalments for `year`, due 28 October,
+/// 28 February, 28 April and 28 July.
+pub fn default_installments(year: FinancialYear) -> Vec<PaygInstallment> {
+  let (start, end) = (year.start_year(), year.start_year() + 1);
+  let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).expect("valid calendar date");
+  [
+    (
+      Quarter::Q1,
+      day(start, 7, 1),
+      day(start, 9, 30),
+      day(start, 10, 28),
+    ),
+    (
+      Quarter::Q2,
+      day(start, 10, 1),
+      day(start, 12, 31),
+      day(end, 2, 28),
+    ),
+    (
+      Quarter::Q3,
+      day(end, 1, 1),
+      day(end, 3, 31),
+      day(end, 4, 28),
+    ),
+    (
+      Quarter::Q4,
+      day(end, 4, 1),
+      day(end, 6, 30),
+      day(end, 7, 28),
+    ),
+  ]
+  .into_iter()
+  .map(
+    |(quarter, period_start, period_end, due_date)| PaygInstallment {
+      id: format!("{year}-{quarter:?}"),
+      quarter,
+      financial_year: year,
+      period_start,
+      period_end,
+      due_date,
+      method: PaygMethod::Amount,
+      rate: None,
+      assessed_amount: None,
+      calculated_amount: None,
+      paid_amount: None,
+      paid_date: None,
+      status: PaygStatus::Upcoming,
+      notes: None,
+    },
+  )
+  .collect()
+}
@@ -0,0 +1,193 @@
+//! Per-financial-year tax tables, as `TaxTablesConfig` in `src/types/index.ts`.
+//!
+//! Users can override a year's tables in the `settings` table (key
+//! `taxTables_<year>`); otherwise the built-in table for that year is used.
+
+use serde::{Deserialize, Serialize};
+
+use super::FinancialYear;
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxBracket {
+  pub min: Money,
+  /// `None` for the top bracket.
+  pub max: Option<Money>,
+  pub rate: f64,
+  /// Tax on income up to `min`.
+  pub base_tax: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MedicareLevyConfig {
+  pub rate: f64,
+  pub low_income_threshold: Money,
+  pub shade_in_threshold: Money,
+  /// Levy per dollar over `low_income_threshold` while shading in.
+  pub shade_in_rate: f64,
+  pub family_threshold: Money,
+  pub family_child_extra: Money,
+}
+
+/// Low income tax offset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LitoConfig {
+  pub max_offset: Money,
+  pub full_threshold: Money,
+  pub phase_out1_rate: f64,
+  pub phase_out1_threshold: Money,
+  /// Offset remaining at `phase_out1_threshold`.
+  pub phase_out1_amount: Money,
+  pub phase_out2_rate: f64,
+  pub zero_threshold: Money,
+}
+
+/// Small business income tax offset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmallBusinessOffsetConfig {
+  pub rate: f64,
+  pub cap: Money,
+  pub turnover_threshold: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxTablesConfig {
+  pub financial_year: FinancialYear,
+  pub brackets: Vec<TaxBracket>,
+  pub medicare_levy_config: MedicareLevyConfig,
+  pub lito_config: LitoConfig,
+  pub small_business_offset: SmallBusinessOffsetConfig,
+  pub super_guarantee_rate: f64,
+}
+
+fn dollars(amount: i64) -> Money {
+  Money::from_cents(amount * 100)
+}
+
+/// Resident brackets from `[(min, rate, base_tax)]`; each bracket ends a
+/// dollar below the next one's `min`.
+fn brackets(rows: &[(i64, f64, i64)]) -> Vec<TaxBracket> {
+  rows
+    .iter()
+    .enumerate()
+    .map(|(i, &(min, rate, base_tax))| TaxBracket {
+      min: dollars(min),
+      max: rows.get(i + 1).map(|&(next, _, _)| dollars(next - 1)),
+      rate,
+      base_tax: dollars(base_tax),
+    })
+    .collect()
+}
+
+/// Unchanged since 2020-21.
+fn lito() -> LitoConfig {
+  LitoConfig {
+    max_offset: dollars(700),
+    full_threshold: dollars(37_500),
+    phase_out1_rate: 0.05,
+    phase_out1_threshold: dollars(45_000),
+    phase_out1_amount: dollars(325),
+    phase_out2_rate: 0.015,
+    zero_threshold: dollars(66_667),
+  }
+}
+
+/// Unchanged since 2021-22.
+fn small_business_offset() -> SmallBusinessOffsetConfig {
+  SmallBusinessOffsetConfig {
+    rate: 0.16,
+    cap: dollars(1_000),
+    turnover_threshold: dollars(5_000_000),
+  }
+}
+
+fn medicare(low: i64, shade_in: i64, family: i64, child: i64) -> MedicareLevyConfig {
+  MedicareLevyConfig {
+    rate: 0.02,
+    low_income_threshold: dollars(low),
+    shade_in_threshold: dollars(shade_in),
+    shade_in_rate: 0.10,
+    family_threshold: dollars(family),
+    family_child_extra: dollars(child),
+  }
+}
+
+/// Financial years with tables built in, oldest first.
+pub const BUILT_IN_YEARS: &[&str] = &["2023-24", "2024-25", "2025-26", "2026-27", "2027-28"];
+
+/// The built-in tables for `year`. Years after the last built-in table use
+/// the latest one and years before the first use the earliest, the way the
+/// TS service falls back to its 2025-26 table.
+///
+/// From 2026-27 the 16% rate steps down to 15%, then 14% in 2027-28; the
+/// Medicare thresholds for those years aren't published yet, so 2025-26's
+/// are carried forward until someone saves newer ones in settings.
+pub fn default_tax_tables(year: FinancialYear) -> TaxTablesConfig {
+  let known = BUILT_IN_YEARS
+    .iter()
+    .rev()
+    .map(|y| {
+      y.parse::<FinancialYear>()
+        .expect("built-in years are valid")
+    })
+    .find(|y| *y <= year)
+    .unwrap_or_else(|| BUILT_IN_YEARS[0].parse().expect("built-in years are valid"));
+
+  let stage_three = |second_rate: f64| {
+    let at_45k = (26_800.0 * second_rate).round() as i64;
+    brackets(&[
+      (0, 0.0, 0),
+      (18_201, second_rate, 0),
+      (45_001, 0.30, at_45k),
+      (135_001, 0.37, at_45k + 27_000),
+      (190_001, 0.45, at_45k + 27_000 + 20_350),
+    ])
+  };
+  let (brackets, medicare_levy_config, super_guarantee_rate) = match known.start_year() {
+    2023 => (
+      brackets(&[
+        (0, 0.0, 0),
+        (18_201, 0.19, 0),
+        (45_001, 0.325, 5_092),
+        (120_001, 0.37, 29_467),
+        (180_001, 0.45, 51_667),
+      ]),
+      medicare(26_000, 32_500, 43_846, 4_027),
+      0.11,
+    ),
+    2024 => (
+      stage_three(0.16),
+      medicare(27_222, 34_027, 45_907, 3_760),
+      0.115,
+    ),
+    2025 => (
+      stage_three(0.16),
+      medicare(27_222, 34_027, 45_907, 3_760),
+      0.12,
+    ),
+    2026 => (
+      stage_three(0.15),
+      medicare(27_222, 34_027, 45_907, 3_760),
+      0.12,
+    ),
+    _ => (
+      stage_three(0.14),
+      medicare(27_222, 34_027, 45_907, 3_760),
+      0.12,
+    ),
+  };
+
+  TaxTablesConfig {
+    financial_year: year,
+    brackets,
+    medicare_levy_config,
+    lito_config: lito(),
+    small_business_offset: small_business_offset(),
+    super_guarantee_rate,
+  }
+}
@@ -0,0 +1,140 @@
+//! Double-entry and GST validation, mirroring `TransactionService` in
+//! `src/lib/services/transactionService.ts`.
+
+use std::collections::HashMap;
+
+use crate::model::{Account, GstCode, Posting, Transaction};
+use crate::money::Money;
+
+/// Slack above the maximum plausible GST to absorb ordinary rounding.
+pub const GST_TOLERANCE: Money = Money::from_cents(2);
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ValidationError {
+  #[error("Transaction postings must sum to zero. Current sum: {sum}")]
+  Unbalanced { sum: Money },
+
+  #[error("Personal transactions (isBusiness=false) cannot have GST information")]
+  PersonalWithGst { posting_id: String },
+
+  #[error("Business posting with GST code \"{code}\" must have gstRate and gstAmount")]
+  MissingGstDetails { posting_id: String, code: GstCode },
+
+  #[error(
+    "GST amount implausible: {gst_amount} on a {amount} line (at {:.0}% GST the most this line could carry is {max_gst}). \
+     Check the GST figure — it should be the tax portion only, not the gross amount.",
+    rate * 100.0
+  )]
+  ImplausibleGst {
+    posting_id: String,
+    gst_amount: Money,
+    amount: Money,
+    rate: f64,
+    max_gst: Money,
+  },
+
+  #[error("GST of {gst_amount} is not booked to a GST control account (GST Paid / GST Collected)")]
+  GstWithoutControlAccount { gst_amount: Money },
+
+  #[error("Account {account_id} not found")]
+  UnknownAccount { account_id: String },
+}
+
+/// Validate that postings sum to zero (double-entry requirement).
+///
+/// Amounts are exact cents, so unlike the TypeScript check there is no
+/// floating point tolerance: a one-cent imbalance is a real imbalance.
+pub fn validate_double_entry(postings: &[Posting]) -> Result<(), ValidationError> {
+  let sum: Money = postings.iter().map(|p| p.amount).sum();
+  if !sum.is_zero() {
+    return Err(ValidationError::Unbalanced { sum });
+  }
+  Ok(())
+}
+
+/// Validate the GST fields on a single posting.
+///
+/// Like the TypeScript validator this is a plausibility check, not an exact
+/// match: anything from zero up to `rate × |amount|` is a legitimate manual
+/// entry (partial credits, bundled GST-free charges), so only negative GST or
+/// GST above the maximum is rejected.
+pub fn validate_gst(posting: &Posting) -> Result<(), ValidationError> {
+  if !posting.is_business {
+    let has_gst = posting.gst_code.is_some()
+      || posting.gst_rate.is_some_and(|rate| rate != 0.0)
+      || posting.gst_amount.is_some_and(|amount| !amount.is_zero());
+    if has_gst {
+      return Err(ValidationError::PersonalWithGst {
+        posting_id: posting.id.clone(),
+      });
+    }
+    return Ok(());
+  }
+
+  let Some(code) = posting.gst_code else {
+    return Ok(());
+  };
+  if matches!(code, GstCode::GstFree | GstCode::InputTaxed) {
+    return Ok(());
+  }
+
+  let (rate, gst_amount) = match (posting.gst_rate, posting.gst_amount) {
+    (Some(rate), Some(amount)) if rate != 0.0 => (rate, amount),
+    _ => {
+      return Err(ValidationError::MissingGstDetails {
+        posting_id: posting.id.clone(),
+        code,
+      })
+    }
+  };
+
+  let amount = posting.amount.abs();
+  let max_gst = amount.mul_rate(rate);
+  if gst_amount.cents() < -GST_TOLERANCE.cents()
+    || gst_amount.abs().cents() > (max_gst + GST_TOLERANCE).cents()
+  {
+    return Err(ValidationError::ImplausibleGst {
+      posting_id: posting.id.clone(),
+      gst_amount,
+      amount,
+      rate,
+      max_gst,
+    });
+  }
+  Ok(())
+}
+
+/// Validate a whole transaction: balance, per-posting GST, and that any GST
+/// recorded on category lines is actually carried by a GST control posting.
+pub fn validate_transaction(
+  transaction: &Transaction,
+  accounts: &HashMap<String, Account>,
+) -> Result<(), ValidationError> {
+  validate_double_entry(&transaction.postings)?;
+  for posting in &transaction.postings {
+    validate_gst(posting)?;
+  }
+
+  let mut recorded_gst = Money::ZERO;
+  let mut has_control_posting = false;
+  for posting in &transaction.postings {
+    let account =
+      accounts
+        .get(&posting.account_id)
+        .ok_or_else(|| ValidationError::UnknownAccount {
+          account_id: posting.account_id.clone(),
+        })?;
+    if account.is_gst_control() {
+      has_control_posting = true;
+    } else {
+      recorded_gst += posting.gst_amount.unwrap_or_default().abs();
+    }
+  }
+
+  if !recorded_gst.is_zero() && !has_control_posting {
+    return Err(ValidationError::GstWithoutControlAccount {
+      gst_amount: recorded_gst,
+    });
+  }
+  Ok(())
+}
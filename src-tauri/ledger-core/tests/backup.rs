@@ -0,0 +1,254 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::*;
+use ledger_core::backup::{
+  sha256_file, BackupDir, BackupError, BackupKind, BackupManifest, BackupSchedule, ChangeMarker,
+};
+use ledger_core::db::{apply_pragmas, Database};
+use rusqlite::Connection;
+
+/// A migrated book on disk, in WAL mode like the app's.
+fn live_book(name: &str) -> (PathBuf, Connection) {
+  let path = scratch_dir(name).join("ledger.db");
+  let mut conn = Connection::open(&path).unwrap();
+  apply_pragmas(&conn).unwrap();
+  ledger_core::migrate::apply_migrations(&mut conn).unwrap();
+  insert_account(&conn, "bank", "Everyday", "ASSET", 0.0);
+  (path, conn)
+}
+
+fn account_count(path: &Path) -> i64 {
+  Connection::open(path)
+    .unwrap()
+    .query_row("SELECT COUNT(*) FROM accounts", [], |row| row.get(0))
+    .unwrap()
+}
+
+fn rewrite_manifest(dir: &BackupDir, filename: &str) {
+  let manifest_path = dir.path().join(format!("{filename}.manifest.json"));
+  let mut manifest: BackupManifest =
+    serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+  manifest.sha256 = sha256_file(&dir.path().join(filename)).unwrap();
+  std::fs::write(manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+}
+
+#[test]
+fn snapshots_carry_a_checksum_manifest() {
+  let (path, conn) = live_book("backup-create");
+  let dir = BackupDir::for_book(&path);
+
+  let info = dir.create(&conn, BackupKind::PreImport).unwrap();
+  assert!(info.filename.starts_with("backup-pre-import-"));
+  let snapshot = dir.path().join(&info.filename);
+  assert_eq!(
+    info.sha256.as_deref(),
+    Some(&*sha256_file(&snapshot).unwrap())
+  );
+  assert_eq!(account_count(&snapshot), 1);
+
+  let listed = dir.list().unwrap();
+  assert_eq!(listed, std::slice::from_ref(&info));
+  let manifest = dir.verify(&info.filename, None).unwrap();
+  assert_eq!(manifest.kind, BackupKind::PreImport);
+  assert_eq!(manifest.source.as_deref(), Some(&*path));
+  // Only the snapshot and its manifest are left behind.
+  assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn lists_snapshots_without_manifests_by_name() {
+  let (path, conn) = live_book("backup-legacy");
+  let dir = BackupDir::for_book(&path);
+  let info = dir.create(&conn, BackupKind::Manual).unwrap();
+  std::fs::copy(
+    dir.path().join(&info.filename),
+    dir
+      .path()
+      .join("backup-pre-reconcile-2024-01-01T00-00-00-000.db"),
+  )
+  .unwrap();
+  std::fs::copy(
+    dir.path().join(&info.filename),
+    dir.path().join("ledger-pre-migration-20240101-000000.db"),
+  )
+  .unwrap();
+
+  let legacy: Vec<(String, BackupKind, bool)> = dir
+    .list()
+    .unwrap()
+    .into_iter()
+    .filter(|b| b.filename != info.filename)
+    .map(|b| (b.filename, b.kind, b.sha256.is_some()))
+    .collect();
+  assert_eq!(legacy.len(), 2);
+  assert!(legacy.contains(&(
+    "backup-pre-reconcile-2024-01-01T00-00-00-000.db".into(),
+    BackupKind::PreReconcile,
+    false
+  )));
+  assert!(legacy.contains(&(
+    "ledger-pre-migration-20240101-000000.db".into(),
+    BackupKind::Manual,
+    false
+  )));
+  assert!(matches!(
+    dir.verify("backup-pre-reconcile-2024-01-01T00-00-00-000.db", None),
+    Err(BackupError::MissingManifest(_))
+  ));
+}
+
+#[test]
+fn rotation_keeps_the_newest_automatic_snapshots() {
+  let (path, conn) = live_book("backup-rotate");
+  let dir = BackupDir::for_book(&path);
+  let manual = dir.create(&conn, BackupKind::Manual).unwrap().filename;
+  let pre_import = dir.create(&conn, BackupKind::PreImport).unwrap().filename;
+  let mut created = Vec::new();
+  for _ in 0..4 {
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    created.push(dir.create(&conn, BackupKind::Auto).unwrap().filename);
+  }
+  // Neither has a manifest, so rotation leaves them alone.
+  let legacy = "backup-auto-2024-01-01T00-00-00-000.db";
+  let pre_migration = "ledger-pre-migration-20240101-000000.db";
+  for name in [legacy, pre_migration] {
+    std::fs::copy(dir.path().join(&manual), dir.path().join(name)).unwrap();
+  }
+
+  assert_eq!(dir.clean_old(2).unwrap(), 2);
+  let mut kept: Vec<String> = dir
+    .list()
+    .unwrap()
+    .into_iter()
+    .map(|b| b.filename)
+    .collect();
+  kept.sort();
+  let mut expected = vec![
+    created[3].clone(),
+    created[2].clone(),
+    manual,
+    pre_import,
+    legacy.to_string(),
+    pre_migration.to_string(),
+  ];
+  expected.sort();
+  assert_eq!(kept, expected);
+  // Manifests go with their snapshots.
+  assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 10);
+  assert_eq!(dir.clean_old(0).unwrap(), 2);
+  assert_eq!(dir.clean_old(10).unwrap(), 0);
+}
+
+#[test]
+fn restore_swaps_in_a_verified_snapshot() {
+  let (path, conn) = live_book("backup-restore");
+  let dir = BackupDir::for_book(&path);
+  let info = dir.create(&conn, BackupKind::Manual).unwrap();
+  insert_account(&conn, "card", "Card", "LIABILITY", 0.0);
+  drop(conn);
+  assert_eq!(account_count(&path), 2);
+
+  dir.restore(&info.filename, &path, None).unwrap();
+  assert_eq!(account_count(&path), 1);
+  let book_dir = path.parent().unwrap();
+  let leftovers: Vec<String> = std::fs::read_dir(book_dir)
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+    .filter(|name| name.contains("restor"))
+    .collect();
+  assert!(leftovers.is_empty(), "{leftovers:?}");
+  // The restored book opens and migrates as usual.
+  ledger_core::migrate::migrate_book(&path).unwrap();
+}
+
+#[test]
+fn restore_refuses_a_tampered_snapshot() {
+  let (path, conn) = live_book("backup-tampered");
+  let dir = BackupDir::for_book(&path);
+  let info = dir.create(&conn, BackupKind::Manual).unwrap();
+  drop(conn);
+  let snapshot = dir.path().join(&info.filename);
+  let mut bytes = std::fs::read(&snapshot).unwrap();
+  let last = bytes.len() - 1;
+  bytes[last] ^= 0xff;
+  std::fs::write(&snapshot, &bytes).unwrap();
+
+  let err = dir.restore(&info.filename, &path, None).unwrap_err();
+  assert!(matches!(err, BackupError::ChecksumMismatch { .. }), "{err}");
+  assert_eq!(account_count(&path), 1);
+}
+
+#[test]
+fn restore_refuses_a_snapshot_that_fails_integrity_check() {
+  let (path, conn) = live_book("backup-corrupt");
+  let dir = BackupDir::for_book(&path);
+  let info = dir.create(&conn, BackupKind::Manual).unwrap();
+  insert_account(&conn, "card", "Card", "LIABILITY", 0.0);
+  drop(conn);
+
+  // Scribble over the second page and record the damage in the manifest, as
+  // if the snapshot had been written corrupt.
+  let snapshot = dir.path().join(&info.filename);
+  let mut bytes = std::fs::read(&snapshot).unwrap();
+  let page_size = u16::from_be_bytes([bytes[16], bytes[17]]) as usize;
+  bytes[page_size..page_size + 64].fill(0xa5);
+  std::fs::write(&snapshot, &bytes).unwrap();
+  rewrite_manifest(&dir, &info.filename);
+
+  let err = dir.restore(&info.filename, &path, None).unwrap_err();
+  assert!(
+    matches!(
+      err,
+      BackupError::IntegrityCheck { .. } | BackupError::Sqlite(_)
+    ),
+    "{err}"
+  );
+  assert_eq!(account_count(&path), 2);
+}
+
+#[test]
+fn rejects_paths_outside_the_backup_directory() {
+  let (path, _conn) = live_book("backup-names");
+  let dir = BackupDir::for_book(&path);
+  for name in ["../ledger.db", "ledger.db.manifest.json", "/etc/passwd"] {
+    assert!(
+      matches!(dir.delete(name), Err(BackupError::InvalidFilename(_))),
+      "{name}"
+    );
+  }
+  assert!(matches!(
+    dir.delete("backup-manual-missing.db"),
+    Err(BackupError::NotFound(_))
+  ));
+}
+
+#[test]
+fn change_marker_sees_commits_from_other_connections() {
+  let (path, conn) = live_book("backup-dirty");
+  let before = ChangeMarker::of(&conn).unwrap();
+  assert_eq!(ChangeMarker::of(&conn).unwrap(), before);
+
+  let other = Connection::open(&path).unwrap();
+  insert_account(&other, "card", "Card", "LIABILITY", 0.0);
+  let after_other = ChangeMarker::of(&conn).unwrap();
+  assert_ne!(after_other, before);
+
+  insert_account(&conn, "cash", "Cash", "ASSET", 0.0);
+  assert_ne!(ChangeMarker::of(&conn).unwrap(), after_other);
+}
+
+#[test]
+fn schedule_defaults_until_saved() {
+  let db = Database::from_connection(book()).unwrap();
+  assert_eq!(db.backup_schedule().unwrap(), BackupSchedule::default());
+
+  let schedule = BackupSchedule {
+    interval_minutes: 0,
+    keep: 3,
+    ..BackupSchedule::default()
+  };
+  db.save_backup_schedule(&schedule).unwrap();
+  assert_eq!(db.backup_schedule().unwrap(), schedule);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::*;
+use ledger_core::model::TransactionStatus;
+use ledger_core::{account_balance, account_balances, BalanceOptions};
+
+fn ledger() -> Vec<ledger_core::Transaction> {
+  let mut salary = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Employer",
+    vec![posting("checking", 1000.0), posting("salary", -1000.0)],
+  );
+  salary.postings[0].cleared = true;
+
+  let groceries = transaction(
+    "t2",
+    date(2025, 8, 5),
+    "Woolworths",
+    vec![posting("checking", -120.0), posting("groceries", 120.0)],
+  );
+
+  let mut voided = transaction(
+    "t3",
+    date(2025, 8, 6),
+    "Duplicate",
+    vec![posting("checking", -500.0), posting("groceries", 500.0)],
+  );
+  voided.status = TransactionStatus::Void;
+
+  vec![salary, groceries, voided]
+}
+
+#[test]
+fn starts_from_the_opening_balance() {
+  let mut checking = bank("checking", "Checking");
+  checking.opening_balance = dollars(250.0);
+  let balance = account_balance(&checking, &ledger(), BalanceOptions::default());
+  assert_eq!(balance, dollars(250.0 + 1000.0 - 120.0));
+}
+
+#[test]
+fn excludes_void_transactions() {
+  let checking = bank("checking", "Checking");
+  let balances = account_balances(
+    &[checking, expense("groceries", "Groceries")],
+    &ledger(),
+    BalanceOptions::default(),
+  );
+  assert_eq!(balances["checking"], dollars(880.0));
+  assert_eq!(balances["groceries"], dollars(120.0));
+}
+
+#[test]
+fn filters_cleared_postings_and_dates() {
+  let checking = bank("checking", "Checking");
+  let cleared = BalanceOptions {
+    cleared_only: true,
+    ..Default::default()
+  };
+  assert_eq!(
+    account_balance(&checking, &ledger(), cleared),
+    dollars(1000.0)
+  );
+
+  let up_to = BalanceOptions {
+    up_to: Some(date(2025, 8, 2)),
+    ..Default::default()
+  };
+  assert_eq!(
+    account_balance(&checking, &ledger(), up_to),
+    dollars(1000.0)
+  );
+}
+
+#[test]
+fn sums_without_float_drift() {
+  let checking = bank("checking", "Checking");
+  let coffees: Vec<_> = (0..1000)
+    .map(|i| {
+      transaction(
+        &format!("t{i}"),
+        date(2025, 8, 1),
+        "Cafe",
+        vec![posting("checking", -0.1), posting("groceries", 0.1)],
+      )
+    })
+    .collect();
+  let balance = account_balance(&checking, &coffees, BalanceOptions::default());
+  assert_eq!(balance.cents(), -10_000);
+}
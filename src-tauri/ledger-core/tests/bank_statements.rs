@@ -0,0 +1,296 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::{
+  read_bank_statement, read_camt053, read_mt940, ImportError, RowError, StatementBalance,
+};
+
+/// A camt.053.001.02 end-of-day statement.
+const CAMT_V2: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <GrpHdr><MsgId>MSG-0131</MsgId><CreDtTm>2026-01-31T18:00:00</CreDtTm></GrpHdr>
+    <Stmt>
+      <Id>STMT-2026-01-31</Id>
+      <Acct>
+        <Id><Othr><Id>062000-12345678</Id></Othr></Id>
+        <Ccy>AUD</Ccy>
+      </Acct>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="AUD">1000.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Dt><Dt>2026-01-30</Dt></Dt>
+      </Bal>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="AUD">4154.50</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Dt><Dt>2026-01-31</Dt></Dt>
+      </Bal>
+      <Ntry>
+        <NtryRef>1</NtryRef>
+        <Amt Ccy="AUD">45.50</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2026-01-31</Dt></BookgDt>
+        <ValDt><Dt>2026-01-30</Dt></ValDt>
+        <AcctSvcrRef>BANK-REF-001</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <Refs><EndToEndId>INV-1001</EndToEndId></Refs>
+          <RltdPties>
+            <Dbtr><Nm>ACME PTY LTD</Nm></Dbtr>
+            <Cdtr><Nm>Officeworks</Nm></Cdtr>
+          </RltdPties>
+          <RmtInf><Ustrd>Stationery</Ustrd><Ustrd>January</Ustrd></RmtInf>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="AUD">3200.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2026-01-31</Dt></BookgDt>
+        <NtryDtls><TxDtls>
+          <Refs><AcctSvcrRef>BANK-REF-002</AcctSvcrRef><EndToEndId>NOTPROVIDED</EndToEndId></Refs>
+          <RltdPties><Dbtr><Nm>Smith &amp; Sons</Nm></Dbtr></RltdPties>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="AUD">12.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>PDNG</Sts>
+        <BookgDt><Dt>2026-01-31</Dt></BookgDt>
+      </Ntry>
+      <Ntry>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2026-01-31</Dt></BookgDt>
+        <AddtlNtryInf>ACCOUNT FEE</AddtlNtryInf>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>
+"#;
+
+/// A camt.053.001.08 statement with a namespace prefix, reporting the
+/// previous closing balance rather than an opening one.
+const CAMT_V8: &str = r#"<?xml version="1.0"?>
+<c:Document xmlns:c="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08">
+<c:BkToCstmrStmt><c:Stmt>
+  <c:Id>8</c:Id>
+  <c:Acct><c:Id><c:IBAN>GB33BUKB20201555555555</c:IBAN></c:Id></c:Acct>
+  <c:Bal><c:Tp><c:CdOrPrtry><c:Cd>PRCD</c:Cd></c:CdOrPrtry></c:Tp>
+    <c:Amt Ccy="GBP">20.00</c:Amt><c:CdtDbtInd>DBIT</c:CdtDbtInd>
+    <c:Dt><c:DtTm>2026-02-28T23:59:59</c:DtTm></c:Dt></c:Bal>
+  <c:Bal><c:Tp><c:CdOrPrtry><c:Cd>CLBD</c:Cd></c:CdOrPrtry></c:Tp>
+    <c:Amt Ccy="GBP">80.00</c:Amt><c:CdtDbtInd>CRDT</c:CdtDbtInd>
+    <c:Dt><c:Dt>2026-03-01</c:Dt></c:Dt></c:Bal>
+  <c:Ntry>
+    <c:Amt Ccy="GBP">100.00</c:Amt><c:CdtDbtInd>CRDT</c:CdtDbtInd>
+    <c:Sts><c:Cd>BOOK</c:Cd></c:Sts>
+    <c:BookgDt><c:DtTm>2026-03-01T10:15:00</c:DtTm></c:BookgDt>
+    <c:NtryDtls><c:TxDtls><c:RltdPties>
+      <c:Dbtr><c:Pty><c:Nm>Jones Consulting</c:Nm></c:Pty></c:Dbtr>
+    </c:RltdPties></c:TxDtls></c:NtryDtls>
+  </c:Ntry>
+</c:Stmt></c:BkToCstmrStmt>
+</c:Document>
+"#;
+
+/// Two MT940 messages making up one statement, in a SWIFT envelope.
+const MT940: &str = "{1:F01BANKAU2SAXXX0000000000}{2:I940CLIENTXXXXXXN}{4:
+:20:STMT0131
+:25:062000/12345678
+:28C:1/1
+:60F:C260130AUD1000,00
+:61:2601310131D45,50NTRFINV-1001//BANK-REF-001
+STATIONERY
+:86:OFFICEWORKS 0456 SYDNEY
+:61:260131C3200,NTRFNONREF//BANK-REF-002
+:86:?00SALARY?20JANUARY PAY?32SMITH & SONS
+:62M:C260131AUD4154,50
+-}
+{1:F01BANKAU2SAXXX0000000000}{2:I940CLIENTXXXXXXN}{4:
+:20:STMT0131
+:25:062000/12345678
+:28C:1/2
+:60M:C260131AUD4154,50
+:61:2602010201RC10,00NCHGNONREF
+:86:REVERSAL OF CREDIT: DUPLICATED PAYMENT FROM CUSTOMER ACCOUNT NUMB
+ER 99887766
+:61:260201X1,00NCHG
+:62F:C260201AUD4144,50
+-}
+";
+
+fn balance(amount: f64, on: NaiveDate) -> Option<StatementBalance> {
+  Some(StatementBalance {
+    amount: dollars(amount),
+    date: on,
+  })
+}
+
+#[test]
+fn reads_camt053_entries_and_balances() {
+  let statement = read_camt053(CAMT_V2.as_bytes()).unwrap().remove(0);
+  assert_eq!(statement.statement_id.as_deref(), Some("STMT-2026-01-31"));
+  assert_eq!(statement.account_id.as_deref(), Some("062000-12345678"));
+  assert_eq!(statement.currency.as_deref(), Some("AUD"));
+  assert_eq!(statement.opening_balance, balance(1000.0, day(2026, 1, 30)));
+  assert_eq!(statement.closing_balance, balance(4154.5, day(2026, 1, 31)));
+
+  // The pending entry is left for the statement it's booked in.
+  assert_eq!(statement.lines.len(), 2);
+  let fee = &statement.lines[0];
+  assert_eq!(fee.line, 23);
+  assert_eq!(fee.date, day(2026, 1, 31));
+  assert_eq!(fee.amount, dollars(-45.5));
+  assert_eq!(fee.payee, "Officeworks");
+  assert_eq!(fee.memo.as_deref(), Some("Stationery January"));
+  assert_eq!(fee.external_id.as_deref(), Some("BANK-REF-001"));
+  assert_eq!(fee.reference.as_deref(), Some("INV-1001"));
+
+  let salary = &statement.lines[1];
+  assert_eq!(salary.payee, "Smith & Sons");
+  assert_eq!(salary.amount, dollars(3200.0));
+  assert_eq!(salary.external_id.as_deref(), Some("BANK-REF-002"));
+  assert_eq!(salary.reference, None);
+
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 56,
+      message: "Missing required fields: Amt".into(),
+    }]
+  );
+
+  let statement = read_camt053(CAMT_V8.as_bytes()).unwrap().remove(0);
+  assert_eq!(
+    statement.account_id.as_deref(),
+    Some("GB33BUKB20201555555555")
+  );
+  assert_eq!(statement.currency.as_deref(), Some("GBP"));
+  assert_eq!(statement.opening_balance, balance(-20.0, day(2026, 2, 28)));
+  assert_eq!(statement.lines[0].payee, "Jones Consulting");
+  assert_eq!(statement.lines[0].date, day(2026, 3, 1));
+}
+
+#[test]
+fn reads_mt940_fields_across_messages() {
+  let statements = read_mt940(MT940.as_bytes()).unwrap();
+  assert_eq!(statements.len(), 1);
+  let statement = &statements[0];
+  assert_eq!(statement.statement_id.as_deref(), Some("STMT0131"));
+  assert_eq!(statement.account_id.as_deref(), Some("062000/12345678"));
+  assert_eq!(statement.currency.as_deref(), Some("AUD"));
+  assert_eq!(statement.opening_balance, balance(1000.0, day(2026, 1, 30)));
+  assert_eq!(statement.closing_balance, balance(4144.5, day(2026, 2, 1)));
+
+  let lines: Vec<_> = statement
+    .lines
+    .iter()
+    .map(|l| {
+      (
+        l.line,
+        l.date,
+        l.amount,
+        l.payee.as_str(),
+        l.external_id.as_deref(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    lines,
+    [
+      (
+        6,
+        day(2026, 1, 31),
+        dollars(-45.5),
+        "OFFICEWORKS 0456 SYDNEY",
+        Some("BANK-REF-001")
+      ),
+      (
+        9,
+        day(2026, 1, 31),
+        dollars(3200.0),
+        "SMITH & SONS",
+        Some("BANK-REF-002")
+      ),
+      (
+        18,
+        day(2026, 2, 1),
+        dollars(-10.0),
+        "REVERSAL OF CREDIT: DUPLICATED PAYMENT FROM CUSTOMER ACCOUNT NUMBER 99887766",
+        None
+      ),
+    ]
+  );
+  assert_eq!(statement.lines[0].memo.as_deref(), Some("STATIONERY"));
+  assert_eq!(statement.lines[0].reference.as_deref(), Some("INV-1001"));
+  assert_eq!(statement.lines[1].memo.as_deref(), Some("JANUARY PAY"));
+  assert_eq!(statement.lines[1].reference, None);
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 21,
+      message: "Unrecognised :61: field: 260201X1,00NCHG".into(),
+    }]
+  );
+}
+
+#[test]
+fn tells_the_formats_apart() {
+  let camt = read_bank_statement(CAMT_V8.as_bytes()).unwrap();
+  assert_eq!(camt[0].statement_id.as_deref(), Some("8"));
+  let mt940 = read_bank_statement(MT940.as_bytes()).unwrap();
+  assert_eq!(mt940[0].statement_id.as_deref(), Some("STMT0131"));
+
+  assert!(matches!(
+    read_bank_statement(b"<OFX><STMTRS></STMTRS></OFX>"),
+    Err(ImportError::Camt(_))
+  ));
+  assert!(matches!(
+    read_bank_statement(b"Date,Amount\n01/01/2026,1.00\n"),
+    Err(ImportError::Mt940(_))
+  ));
+}
+
+#[test]
+fn statement_balances_start_one_reconciliation() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [bank("bank", "Business Cheque"), expense("office", "Office")] {
+    db.insert_account(&account).unwrap();
+  }
+  let mut imported = transaction(
+    "t1",
+    date(2026, 1, 31),
+    "Officeworks",
+    vec![posting("bank", -45.5), posting("office", 45.5)],
+  );
+  imported.external_id = Some("BANK-REF-001".into());
+  db.insert_transaction(&imported).unwrap();
+
+  let statement = read_mt940(MT940.as_bytes()).unwrap().remove(0);
+  let previews = db.preview_import("bank", statement.lines).unwrap();
+  let flags: Vec<bool> = previews.iter().map(|p| p.is_duplicate).collect();
+  assert_eq!(flags, [true, false, false]);
+
+  let opening = statement.opening_balance.unwrap();
+  let closing = statement.closing_balance.unwrap();
+  let started = db
+    .start_statement_reconciliation("bank", opening, closing, Some("STMT0131".into()))
+    .unwrap();
+  assert_eq!(started.statement_start_date, date(2026, 1, 30));
+  assert_eq!(started.statement_end_date, date(2026, 2, 1));
+  assert_eq!(started.statement_start_balance, dollars(1000.0));
+  assert_eq!(started.statement_end_balance, dollars(4144.5));
+  assert!(!started.locked);
+
+  let again = db
+    .start_statement_reconciliation("bank", opening, closing, None)
+    .unwrap();
+  assert_eq!(again.id, started.id);
+  assert_eq!(db.reconciliations(Some("bank")).unwrap().len(), 1);
+}
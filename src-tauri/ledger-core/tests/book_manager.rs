@@ -0,0 +1,211 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::*;
+use ledger_core::book_manager::{
+  BookFileState, BookRegistry, BookUpdate, CreateBookData, DateFormat, RegistryError,
+};
+
+fn new_book(name: &str) -> CreateBookData {
+  CreateBookData {
+    name: name.into(),
+    owner_name: "Glenn".into(),
+    description: None,
+    database_path: None,
+    backup_path: None,
+    fiscal_year_start: "07-01".into(),
+    currency: "AUD".into(),
+    date_format: DateFormat::DayMonthYear,
+  }
+}
+
+fn registry(dir: &Path) -> BookRegistry {
+  BookRegistry::load(dir.join("books.json"), dir).unwrap()
+}
+
+/// A migrated book at `path` under `root`.
+fn write_book(root: &Path, path: &Path) {
+  let path = root.join(path);
+  std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+  ledger_core::migrate::migrate_book(&path).unwrap();
+}
+
+#[test]
+fn books_persist_across_loads() {
+  let dir = scratch_dir("registry-persist");
+  let mut books = registry(&dir);
+  assert!(books.is_first_run());
+
+  let personal = books.create_book(new_book("Personal")).unwrap();
+  assert!(personal.id.starts_with("book_"));
+  assert_eq!(
+    personal.database_path,
+    PathBuf::from(format!("books/{}/ledger.ledgerhound", personal.id))
+  );
+  let business = books.create_book(new_book("Business")).unwrap();
+  books.set_active_book(&business.id).unwrap();
+
+  let reloaded = registry(&dir);
+  assert!(!reloaded.is_first_run());
+  let names: Vec<(String, bool)> = reloaded
+    .all_books()
+    .into_iter()
+    .map(|b| (b.name, b.is_active))
+    .collect();
+  assert_eq!(
+    names,
+    [("Personal".into(), false), ("Business".into(), true)]
+  );
+  assert_eq!(reloaded.active_book().unwrap().id, business.id);
+  // Only the registry itself is left; the temporary file was renamed.
+  let files: Vec<String> = std::fs::read_dir(&dir)
+    .unwrap()
+    .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+    .collect();
+  assert_eq!(files, ["books.json"]);
+}
+
+#[test]
+fn update_delete_and_recent() {
+  let dir = scratch_dir("registry-update");
+  let mut books = registry(&dir);
+  let a = books.create_book(new_book("A")).unwrap();
+  let b = books.create_book(new_book("B")).unwrap();
+  let c = books.create_book(new_book("C")).unwrap();
+
+  let renamed = books
+    .update_book(
+      &a.id,
+      BookUpdate {
+        name: Some("A (renamed)".into()),
+        currency: Some("NZD".into()),
+        ..BookUpdate::default()
+      },
+    )
+    .unwrap();
+  assert_eq!(renamed.name, "A (renamed)");
+  assert_eq!(renamed.currency, "NZD");
+  assert_eq!(renamed.owner_name, "Glenn");
+  assert_eq!(renamed.created_at, a.created_at);
+
+  std::thread::sleep(std::time::Duration::from_millis(5));
+  books.set_active_book(&a.id).unwrap();
+  std::thread::sleep(std::time::Duration::from_millis(5));
+  books.set_active_book(&c.id).unwrap();
+  let recent: Vec<String> = books.recent_books(2).into_iter().map(|s| s.id).collect();
+  assert_eq!(recent, [c.id.clone(), a.id.clone()]);
+
+  books.delete_book(&c.id).unwrap();
+  assert!(books.active_book().is_none());
+  assert!(matches!(
+    books.delete_book(&c.id),
+    Err(RegistryError::NotFound(_))
+  ));
+  assert!(matches!(
+    books.update_book(&c.id, BookUpdate::default()),
+    Err(RegistryError::NotFound(_))
+  ));
+  assert_eq!(registry(&dir).all_books().len(), 2);
+  assert!(registry(&dir).book(&b.id).is_some());
+}
+
+#[test]
+fn imports_the_list_the_ts_manager_exported() {
+  let dir = scratch_dir("registry-import");
+  let mut books = registry(&dir);
+  let exported = r#"[
+    {
+      "id": "book_1728000000000_abc123xyz",
+      "name": "Glenn's Personal & Business",
+      "ownerName": "Glenn",
+      "databasePath": "books/book_1728000000000_abc123xyz/ledger.db",
+      "backupPath": "books/book_1728000000000_abc123xyz",
+      "fiscalYearStart": "07-01",
+      "currency": "AUD",
+      "dateFormat": "DD/MM/YYYY",
+      "createdAt": "2024-10-04T00:00:00.000Z",
+      "lastAccessedAt": "2024-10-05T00:00:00.000Z",
+      "isActive": false
+    }
+  ]"#;
+  books.import_book_list(exported).unwrap();
+  let book = books.book("book_1728000000000_abc123xyz").unwrap();
+  assert_eq!(book.date_format, DateFormat::DayMonthYear);
+  assert_eq!(book.description, None);
+
+  let round_trip: serde_json::Value = serde_json::from_str(&books.export_book_list()).unwrap();
+  assert_eq!(round_trip[0]["ownerName"], "Glenn");
+  assert_eq!(round_trip[0]["dateFormat"], "DD/MM/YYYY");
+  assert_eq!(round_trip[0]["lastAccessedAt"], "2024-10-05T00:00:00Z");
+
+  assert!(matches!(
+    books.import_book_list(r#"{"books": []}"#),
+    Err(RegistryError::InvalidBookList(_))
+  ));
+  assert_eq!(books.all_books().len(), 1);
+}
+
+#[test]
+fn a_corrupt_registry_is_reported_not_replaced() {
+  let dir = scratch_dir("registry-corrupt");
+  std::fs::write(dir.join("books.json"), "{ not json").unwrap();
+  assert!(matches!(
+    BookRegistry::load(dir.join("books.json"), &dir),
+    Err(RegistryError::Corrupt { .. })
+  ));
+  assert_eq!(
+    std::fs::read_to_string(dir.join("books.json")).unwrap(),
+    "{ not json"
+  );
+}
+
+#[test]
+fn detects_and_relocates_moved_books() {
+  let dir = scratch_dir("registry-moved");
+  let mut books = registry(&dir);
+  let book = books.create_book(new_book("Personal")).unwrap();
+  write_book(&dir, &book.database_path);
+  let other = books.create_book(new_book("Other")).unwrap();
+  std::fs::create_dir_all(dir.join(&other.database_path)).unwrap();
+  // Something other than SQLite, and bigger than a SQLite page.
+  let junk = books.create_book(new_book("Junk")).unwrap();
+  std::fs::create_dir_all(dir.join(&junk.database_path).parent().unwrap()).unwrap();
+  std::fs::write(dir.join(&junk.database_path), "not a book\n".repeat(100)).unwrap();
+
+  let states = |books: &BookRegistry| -> Vec<BookFileState> {
+    books.file_statuses().into_iter().map(|s| s.state).collect()
+  };
+  assert_eq!(
+    states(&books),
+    [
+      BookFileState::Available,
+      BookFileState::NotABook,
+      BookFileState::NotABook
+    ]
+  );
+
+  // The user moves the book's folder somewhere else.
+  let moved_dir = dir.join("Documents").join("Personal");
+  std::fs::create_dir_all(moved_dir.parent().unwrap()).unwrap();
+  std::fs::rename(dir.join(book.database_path.parent().unwrap()), &moved_dir).unwrap();
+  assert_eq!(states(&books)[0], BookFileState::Missing);
+
+  assert!(matches!(
+    books.relocate_book(&book.id, dir.join("Documents").join("nothing.ledgerhound")),
+    Err(RegistryError::NotABook(_))
+  ));
+  assert!(matches!(
+    books.relocate_book(&book.id, dir.join(&junk.database_path)),
+    Err(RegistryError::NotABook(_))
+  ));
+  let relocated = books
+    .relocate_book(&book.id, moved_dir.join("ledger.ledgerhound"))
+    .unwrap();
+  assert_eq!(
+    relocated.database_path,
+    moved_dir.join("ledger.ledgerhound")
+  );
+  assert_eq!(relocated.backup_path, moved_dir.join("backups"));
+  assert_eq!(states(&registry(&dir))[0], BookFileState::Available);
+}
@@ -0,0 +1,275 @@
+//! A small seeded ledger for report tests: a July 2025 month with salary,
+//! GST-registered consulting, card spending, transfers, a refund, a void and
+//! an archived category, laid out in the `prisma/seed.ts` hierarchy.
+
+use ledger_core::db::Database;
+use ledger_core::model::{Account, AccountKind, AccountSubtype, AccountType};
+
+use super::*;
+
+fn category(
+  id: &str,
+  name: &str,
+  account_type: AccountType,
+  parent: Option<&str>,
+  full_path: &str,
+  sort_order: i32,
+) -> Account {
+  Account {
+    parent_id: parent.map(Into::into),
+    level: full_path.matches('/').count() as i32,
+    full_path: Some(full_path.into()),
+    sort_order,
+    ..account(id, name, account_type, AccountKind::Category)
+  }
+}
+
+fn accounts() -> Vec<Account> {
+  use AccountType::{Equity, Expense, Income};
+  vec![
+    Account {
+      opening_balance: dollars(5000.0),
+      ..bank("everyday", "Everyday")
+    },
+    Account {
+      opening_balance: dollars(10000.0),
+      ..bank("savings", "Savings")
+    },
+    Account {
+      subtype: Some(AccountSubtype::Card),
+      ..account(
+        "card",
+        "Credit Card",
+        AccountType::Liability,
+        AccountKind::Transfer,
+      )
+    },
+    gst_paid(),
+    gst_collected(),
+    Account {
+      opening_balance: dollars(-15000.0),
+      ..account("opening", "Opening Balances", Equity, AccountKind::Category)
+    },
+    category(
+      "personal-income",
+      "Personal Income",
+      Income,
+      None,
+      "Income/Personal",
+      100,
+    ),
+    category(
+      "employment",
+      "Employment",
+      Income,
+      Some("personal-income"),
+      "Income/Personal/Employment",
+      101,
+    ),
+    category(
+      "salary",
+      "Salary",
+      Income,
+      Some("employment"),
+      "Income/Personal/Employment/Salary",
+      102,
+    ),
+    category(
+      "interest",
+      "Interest",
+      Income,
+      Some("personal-income"),
+      "Income/Personal/Interest",
+      110,
+    ),
+    category(
+      "business-income",
+      "Business Income",
+      Income,
+      None,
+      "Income/Business",
+      200,
+    ),
+    category(
+      "consulting",
+      "Consulting",
+      Income,
+      Some("business-income"),
+      "Income/Business/Consulting",
+      201,
+    ),
+    category(
+      "personal-expenses",
+      "Personal Expenses",
+      Expense,
+      None,
+      "Expense/Personal",
+      300,
+    ),
+    category(
+      "food",
+      "Food",
+      Expense,
+      Some("personal-expenses"),
+      "Expense/Personal/Food",
+      301,
+    ),
+    category(
+      "groceries",
+      "Groceries",
+      Expense,
+      Some("food"),
+      "Expense/Personal/Food/Groceries",
+      302,
+    ),
+    category(
+      "dining",
+      "Dining Out",
+      Expense,
+      Some("food"),
+      "Expense/Personal/Food/Dining Out",
+      303,
+    ),
+    category(
+      "business-expenses",
+      "Business Expenses",
+      Expense,
+      None,
+      "Expense/Business",
+      400,
+    ),
+    category(
+      "software",
+      "Software",
+      Expense,
+      Some("business-expenses"),
+      "Expense/Business/Software",
+      401,
+    ),
+    category(
+      "office",
+      "Office Supplies",
+      Expense,
+      Some("business-expenses"),
+      "Expense/Business/Office Supplies",
+      402,
+    ),
+    Account {
+      archived: true,
+      ..category(
+        "old",
+        "Old Category",
+        Expense,
+        Some("personal-expenses"),
+        "Expense/Personal/Old Category",
+        399,
+      )
+    },
+  ]
+}
+
+/// The fixture ledger in an in-memory book.
+pub fn seeded_ledger() -> Database {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in accounts() {
+    db.insert_account(&account).unwrap();
+  }
+
+  let transactions = vec![
+    transaction(
+      "pay-june",
+      date(2025, 6, 28),
+      "ACME Pty Ltd",
+      vec![posting("everyday", 3000.0), posting("salary", -3000.0)],
+    ),
+    transaction(
+      "pay-july",
+      date(2025, 7, 1),
+      "ACME Pty Ltd",
+      vec![posting("everyday", 4200.0), posting("salary", -4200.0)],
+    ),
+    transaction(
+      "woolworths",
+      date(2025, 7, 5),
+      "Woolworths",
+      vec![posting("card", -150.4), posting("groceries", 150.4)],
+    ),
+    transaction(
+      "invoice",
+      date(2025, 7, 10),
+      "Client Co",
+      vec![
+        posting("everyday", 1100.0),
+        with_gst(posting("consulting", -1000.0), 100.0),
+        business(posting("gst-collected", -100.0)),
+      ],
+    ),
+    transaction(
+      "software",
+      date(2025, 7, 12),
+      "Atlassian",
+      vec![
+        posting("card", -55.0),
+        with_gst(posting("software", 50.0), 5.0),
+        business(posting("gst-paid", 5.0)),
+      ],
+    ),
+    transaction(
+      "to-savings",
+      date(2025, 7, 15),
+      "Transfer",
+      vec![posting("everyday", -1000.0), posting("savings", 1000.0)],
+    ),
+    transaction(
+      "old-category",
+      date(2025, 7, 18),
+      "Newsagent",
+      vec![posting("everyday", -30.0), posting("old", 30.0)],
+    ),
+    transaction(
+      "card-payment",
+      date(2025, 7, 20),
+      "Card Payment",
+      vec![posting("everyday", -500.0), posting("card", 500.0)],
+    ),
+    transaction(
+      "dinner",
+      date(2025, 7, 22),
+      "Cafe",
+      vec![
+        posting("everyday", -80.0),
+        posting("dining", 60.0),
+        business(posting("office", 20.0)),
+      ],
+    ),
+    transaction(
+      "refund",
+      date(2025, 7, 25),
+      "Woolworths",
+      vec![posting("card", 20.4), posting("groceries", -20.4)],
+    ),
+    transaction(
+      "voided",
+      date(2025, 7, 28),
+      "Mistake",
+      vec![posting("everyday", -999.0), posting("dining", 999.0)],
+    ),
+    transaction(
+      "interest",
+      date(2025, 7, 31),
+      "Bank",
+      vec![posting("savings", 12.34), posting("interest", -12.34)],
+    ),
+    transaction(
+      "august",
+      date(2025, 8, 2),
+      "Coles",
+      vec![posting("everyday", -99.0), posting("groceries", 99.0)],
+    ),
+  ];
+  for transaction in transactions {
+    db.insert_transaction(&transaction).unwrap();
+  }
+  db.void_transaction("voided").unwrap();
+  db
+}
@@ -0,0 +1,226 @@
+//! Builders for test accounts and transactions, in the spirit of
+//! `src/lib/services/__test-utils__/fixtures.ts`.
+#![allow(dead_code)]
+
+pub mod fixture;
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ledger_core::model::{
+  Account, AccountKind, AccountSubtype, AccountType, GstCode, Posting, Transaction,
+  TransactionStatus,
+};
+use ledger_core::money::{Currency, Money};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// An in-memory book with every migration under `prisma/migrations` applied.
+pub fn book() -> Connection {
+  let mut conn = Connection::open_in_memory().unwrap();
+  ledger_core::migrate::apply_migrations(&mut conn).unwrap();
+  conn
+}
+
+/// An empty directory under the system temp dir, unique to this test binary.
+pub fn scratch_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("ledger-core-{}-{name}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+/// Insert an account row the way Prisma would (money as float dollars).
+pub fn insert_account(
+  conn: &Connection,
+  id: &str,
+  name: &str,
+  account_type: &str,
+  opening_balance: f64,
+) {
+  conn
+    .execute(
+      "INSERT INTO accounts (id, name, type, kind, opening_balance, updated_at)
+       VALUES (?1, ?2, ?3, 'TRANSFER', ?4, 0)",
+      params![id, name, account_type, opening_balance],
+    )
+    .unwrap();
+}
+
+/// Insert a transaction with `(account_id, amount)` postings.
+pub fn insert_transaction(conn: &Connection, id: &str, status: &str, postings: &[(&str, f64)]) {
+  conn
+    .execute(
+      "INSERT INTO transactions (id, date, payee, status, updated_at) VALUES (?1, 0, 'Payee', ?2, 0)",
+      params![id, status],
+    )
+    .unwrap();
+  for (i, (account_id, amount)) in postings.iter().enumerate() {
+    conn
+      .execute(
+        "INSERT INTO postings (id, transaction_id, account_id, amount) VALUES (?1, ?2, ?3, ?4)",
+        params![format!("{id}-{i}"), id, account_id, amount],
+      )
+      .unwrap();
+  }
+}
+
+pub fn dollars(amount: f64) -> Money {
+  Money::from_f64(amount)
+}
+
+pub fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+  self::day(year, month, day)
+    .and_hms_opt(0, 0, 0)
+    .map(|naive| Utc.from_utc_datetime(&naive))
+    .unwrap()
+}
+
+/// A calendar date, as statements and tax years deal in.
+pub fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+pub fn account(id: &str, name: &str, account_type: AccountType, kind: AccountKind) -> Account {
+  Account {
+    id: id.into(),
+    name: name.into(),
+    full_path: None,
+    account_type,
+    kind,
+    parent_id: None,
+    level: 0,
+    subtype: None,
+    is_real: kind == AccountKind::Transfer,
+    is_business_default: false,
+    default_has_gst: true,
+    opening_balance: Money::ZERO,
+    opening_date: date(2025, 7, 1),
+    currency: Currency::AUD,
+    ato_label: None,
+    archived: false,
+    sort_order: 0,
+    created_at: date(2025, 7, 1),
+    updated_at: date(2025, 7, 1),
+  }
+}
+
+pub fn bank(id: &str, name: &str) -> Account {
+  Account {
+    subtype: Some(AccountSubtype::Bank),
+    ..account(id, name, AccountType::Asset, AccountKind::Transfer)
+  }
+}
+
+pub fn expense(id: &str, name: &str) -> Account {
+  account(id, name, AccountType::Expense, AccountKind::Category)
+}
+
+pub fn income(id: &str, name: &str) -> Account {
+  account(id, name, AccountType::Income, AccountKind::Category)
+}
+
+pub fn gst_paid() -> Account {
+  account(
+    "gst-paid",
+    "GST Paid",
+    AccountType::Asset,
+    AccountKind::Category,
+  )
+}
+
+pub fn gst_collected() -> Account {
+  account(
+    "gst-collected",
+    "GST Collected",
+    AccountType::Liability,
+    AccountKind::Category,
+  )
+}
+
+pub fn posting(account_id: &str, amount: f64) -> Posting {
+  let amount = dollars(amount);
+  Posting {
+    id: format!("p-{account_id}-{amount}"),
+    transaction_id: String::new(),
+    account_id: account_id.into(),
+    amount,
+    is_business: false,
+    gst_code: None,
+    gst_rate: None,
+    gst_amount: None,
+    category_split_label: None,
+    cleared: false,
+    reconciled: false,
+    reconcile_id: None,
+    created_at: date(2025, 7, 1),
+  }
+}
+
+pub fn business(posting: Posting) -> Posting {
+  Posting {
+    is_business: true,
+    ..posting
+  }
+}
+
+pub fn with_gst(posting: Posting, gst_amount: f64) -> Posting {
+  let gst_amount = dollars(gst_amount);
+  Posting {
+    is_business: true,
+    gst_code: Some(GstCode::Gst),
+    gst_rate: Some(0.1),
+    gst_amount: Some(gst_amount),
+    ..posting
+  }
+}
+
+pub fn transaction(
+  id: &str,
+  on: DateTime<Utc>,
+  payee: &str,
+  postings: Vec<Posting>,
+) -> Transaction {
+  Transaction {
+    id: id.into(),
+    date: on,
+    payee: payee.into(),
+    memo: None,
+    reference: None,
+    tags: None,
+    metadata: None,
+    import_batch_id: None,
+    external_id: None,
+    status: TransactionStatus::Normal,
+    created_at: on,
+    updated_at: on,
+    postings: postings
+      .into_iter()
+      .map(|p| Posting {
+        transaction_id: id.into(),
+        ..p
+      })
+      .collect(),
+  }
+}
+
+/// Compare `value` as JSON with `tests/golden/<name>.json`, or rewrite the
+/// file when `UPDATE_GOLDEN` is set.
+pub fn assert_golden(name: &str, value: &impl Serialize) {
+  let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/golden")
+    .join(format!("{name}.json"));
+  let actual = serde_json::to_string_pretty(value).unwrap() + "\n";
+  if std::env::var_os("UPDATE_GOLDEN").is_some() {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, actual).unwrap();
+    return;
+  }
+  let expected = std::fs::read_to_string(&path)
+    .unwrap_or_else(|_| panic!("missing {}; run with UPDATE_GOLDEN=1", path.display()));
+  assert!(
+    expected.replace("\r\n", "\n") == actual,
+    "{name} no longer matches {}; run with UPDATE_GOLDEN=1 and review the diff",
+    path.display()
+  );
+}
@@ -0,0 +1,305 @@
+mod common;
+
+use common::*;
+use ledger_core::balance::BalanceOptions;
+use ledger_core::db::{apply_pragmas, Database, DbError};
+use ledger_core::model::{AccountType, BillFrequency, MatchType, MemorizedRule, RecurringBill};
+use ledger_core::money::Money;
+use ledger_core::money_storage::MoneyStorage;
+use ledger_core::validation::ValidationError;
+
+fn database() -> Database {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [bank("bank", "Everyday"), expense("groceries", "Groceries")] {
+    db.insert_account(&account).unwrap();
+  }
+  db.insert_transaction(&transaction(
+    "t1",
+    date(2025, 7, 3),
+    "Woolworths",
+    vec![posting("bank", -45.5), posting("groceries", 45.5)],
+  ))
+  .unwrap();
+  db
+}
+
+#[test]
+fn applies_the_prisma_client_pragmas() {
+  let dir = scratch_dir("pragmas");
+  let path = dir.join("pragmas.db");
+  let conn = rusqlite::Connection::open(&path).unwrap();
+  apply_pragmas(&conn).unwrap();
+
+  let journal: String = conn
+    .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+    .unwrap();
+  let timeout: i64 = conn
+    .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+    .unwrap();
+  let foreign_keys: i64 = conn
+    .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+    .unwrap();
+  assert_eq!(journal, "wal");
+  assert_eq!(timeout, 5000);
+  assert_eq!(foreign_keys, 1);
+
+  drop(conn);
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn round_trips_accounts_in_accounting_order() {
+  let db = database();
+  db.insert_account(&income("sales", "Sales")).unwrap();
+
+  let accounts = db.accounts(false).unwrap();
+  let types: Vec<AccountType> = accounts.iter().map(|a| a.account_type).collect();
+  assert_eq!(
+    types,
+    [
+      AccountType::Asset,
+      AccountType::Income,
+      AccountType::Expense
+    ]
+  );
+  assert_eq!(db.account("bank").unwrap(), bank("bank", "Everyday"));
+}
+
+#[test]
+fn reads_rows_written_by_prisma() {
+  let conn = book();
+  insert_account(&conn, "cash", "Cash", "ASSET", 10.1);
+  insert_account(&conn, "food", "Food", "EXPENSE", 0.0);
+  insert_transaction(&conn, "t1", "NORMAL", &[("cash", -0.2), ("food", 0.2)]);
+  insert_transaction(&conn, "t2", "VOID", &[("cash", -5.0), ("food", 5.0)]);
+  let db = Database::from_connection(conn).unwrap();
+
+  assert_eq!(db.money_storage(), MoneyStorage::Dollars);
+  assert_eq!(
+    db.account_balance("cash", BalanceOptions::default())
+      .unwrap(),
+    dollars(9.9)
+  );
+  let transaction = db.transaction("t1").unwrap();
+  assert_eq!(transaction.postings.len(), 2);
+  assert_eq!(transaction.postings[1].amount, dollars(0.2));
+}
+
+#[test]
+fn sql_balances_match_the_in_memory_calculation() {
+  let mut db = database();
+  let mut cleared = posting("bank", -10.0);
+  cleared.cleared = true;
+  db.insert_transaction(&transaction(
+    "t2",
+    date(2025, 8, 1),
+    "Coles",
+    vec![cleared, posting("groceries", 10.0)],
+  ))
+  .unwrap();
+
+  let accounts = db.accounts(true).unwrap();
+  let transactions = db.transactions_between(None, None).unwrap();
+  for options in [
+    BalanceOptions::default(),
+    BalanceOptions {
+      cleared_only: true,
+      ..Default::default()
+    },
+    BalanceOptions {
+      up_to: Some(date(2025, 7, 31)),
+      ..Default::default()
+    },
+  ] {
+    assert_eq!(
+      db.account_balances(options).unwrap(),
+      ledger_core::account_balances(&accounts, &transactions, options)
+    );
+  }
+  assert_eq!(
+    db.account_balance("bank", BalanceOptions::default())
+      .unwrap(),
+    dollars(-55.5)
+  );
+}
+
+#[test]
+fn rejects_unbalanced_transactions_without_writing() {
+  let mut db = database();
+  let err = db
+    .insert_transaction(&transaction(
+      "t2",
+      date(2025, 7, 4),
+      "Oops",
+      vec![posting("bank", -10.0), posting("groceries", 9.0)],
+    ))
+    .unwrap_err();
+
+  assert!(matches!(
+    err,
+    DbError::Validation(ValidationError::Unbalanced { .. })
+  ));
+  assert!(matches!(
+    db.transaction("t2"),
+    Err(DbError::NotFound { .. })
+  ));
+}
+
+#[test]
+fn refuses_to_delete_reconciled_transactions() {
+  let mut db = database();
+  let posting_id = db.transaction("t1").unwrap().postings[0].id.clone();
+  db.mark_reconciled(&[posting_id], true).unwrap();
+
+  assert!(matches!(
+    db.delete_transaction("t1"),
+    Err(DbError::Conflict(_))
+  ));
+  let reconciled = &db.transaction("t1").unwrap().postings[0];
+  assert!(reconciled.reconciled && reconciled.cleared);
+
+  db.void_transaction("t1").unwrap();
+  assert_eq!(
+    db.account_balance("bank", BalanceOptions::default())
+      .unwrap(),
+    Money::ZERO
+  );
+}
+
+#[test]
+fn writes_money_in_the_books_storage_format() {
+  let mut db = database();
+  db.migrate_to_cents().unwrap();
+  db.insert_transaction(&transaction(
+    "t2",
+    date(2025, 7, 5),
+    "Aldi",
+    vec![posting("bank", -0.3), posting("groceries", 0.3)],
+  ))
+  .unwrap();
+
+  let raw: f64 = db
+    .connection()
+    .query_row(
+      "SELECT amount FROM postings WHERE transaction_id = 't2' AND account_id = 'groceries'",
+      [],
+      |row| row.get(0),
+    )
+    .unwrap();
+  assert_eq!(raw, 30.0);
+  assert_eq!(
+    db.account_balance("groceries", BalanceOptions::default())
+      .unwrap(),
+    dollars(45.8)
+  );
+}
+
+#[test]
+fn settings_are_json_encoded_upserts() {
+  let db = database();
+  db.set_setting_json("theme", "dark").unwrap();
+  db.set_setting_json("theme", "light").unwrap();
+
+  assert_eq!(db.setting("theme").unwrap().unwrap().value, "\"light\"");
+  assert_eq!(
+    db.setting_json::<String>("theme").unwrap().as_deref(),
+    Some("light")
+  );
+  assert!(db.delete_setting("theme").unwrap());
+  assert_eq!(db.setting_json::<String>("theme").unwrap(), None);
+}
+
+#[test]
+fn memorized_rules_come_back_in_priority_order() {
+  let mut db = database();
+  for (id, priority) in [("b", 1), ("a", 1), ("c", 0)] {
+    db.insert_memorized_rule(&MemorizedRule {
+      id: id.into(),
+      name: id.to_uppercase(),
+      match_type: MatchType::Contains,
+      match_value: "woolworths".into(),
+      default_payee: None,
+      default_account_id: Some("groceries".into()),
+      default_splits: None,
+      apply_on_import: true,
+      apply_on_manual_entry: true,
+      priority,
+      created_at: date(2025, 7, 1),
+      updated_at: date(2025, 7, 1),
+    })
+    .unwrap();
+  }
+  let order = |db: &Database| -> Vec<String> {
+    db.memorized_rules()
+      .unwrap()
+      .into_iter()
+      .map(|r| r.id)
+      .collect()
+  };
+  assert_eq!(order(&db), ["c", "a", "b"]);
+
+  db.reorder_memorized_rules(&["b".into(), "a".into(), "c".into()])
+    .unwrap();
+  assert_eq!(order(&db), ["b", "a", "c"]);
+}
+
+#[test]
+fn recurring_bills_round_trip() {
+  let db = database();
+  let bill = RecurringBill {
+    id: "rent".into(),
+    name: "Rent".into(),
+    payee: "Landlord".into(),
+    expected_amount: dollars(2100.0),
+    frequency: BillFrequency::Monthly,
+    due_day: 1,
+    start_date: date(2025, 7, 1),
+    category_account_id: "groceries".into(),
+    pay_from_account_id: "bank".into(),
+    status: Default::default(),
+    last_paid_date: None,
+    next_due_date: date(2025, 8, 1),
+    notes: None,
+    created_at: date(2025, 7, 1),
+    updated_at: date(2025, 7, 1),
+  };
+  db.insert_recurring_bill(&bill).unwrap();
+
+  assert_eq!(db.recurring_bill("rent").unwrap(), bill);
+  assert_eq!(db.bills_due_by(date(2025, 7, 31)).unwrap(), []);
+  assert_eq!(db.bills_due_by(date(2025, 8, 1)).unwrap(), [bill]);
+}
+
+#[test]
+fn counts_upcoming_and_overdue_bills() {
+  let db = database();
+  let bill = |id: &str, due| RecurringBill {
+    id: id.into(),
+    name: id.into(),
+    payee: id.into(),
+    expected_amount: dollars(10.0),
+    frequency: BillFrequency::Monthly,
+    due_day: 1,
+    start_date: date(2025, 7, 1),
+    category_account_id: "groceries".into(),
+    pay_from_account_id: "bank".into(),
+    status: Default::default(),
+    last_paid_date: None,
+    next_due_date: due,
+    notes: None,
+    created_at: date(2025, 7, 1),
+    updated_at: date(2025, 7, 1),
+  };
+  for (id, due) in [
+    ("overdue", date(2025, 8, 9)),
+    ("today", date(2025, 8, 10)),
+    ("fortnight", date(2025, 8, 24)),
+    ("later", date(2025, 8, 25)),
+  ] {
+    db.insert_recurring_bill(&bill(id, due)).unwrap();
+  }
+
+  let today = chrono::NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
+  let count = db.upcoming_bill_count(today, 14).unwrap();
+  assert_eq!((count.upcoming, count.overdue), (2, 1));
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::*;
+use ledger_core::backup::{BackupDir, BackupError, BackupKind};
+use ledger_core::encryption::{self, BookKey, EncryptionError};
+use ledger_core::migrate::{migrate_book, migrate_encrypted_book};
+use rusqlite::Connection;
+
+const MEMO: &str = "TFN 123 456 782";
+
+/// A migrated book with one account whose name is `MEMO`, encrypted with
+/// `passphrase` if given.
+fn book_at(name: &str, passphrase: Option<&str>) -> (PathBuf, Option<BookKey>) {
+  let path = scratch_dir(name).join("ledger.db");
+  let key = passphrase.map(|p| BookKey::generate(p).unwrap());
+  migrate_encrypted_book(&path, key.as_ref()).unwrap();
+  let conn = encryption::open(&path, key.as_ref()).unwrap();
+  insert_account(&conn, "bank", MEMO, "ASSET", 0.0);
+  (path, key)
+}
+
+fn account_names(path: &Path, key: Option<&BookKey>) -> Vec<String> {
+  let conn = encryption::open(path, key).unwrap();
+  let mut stmt = conn.prepare("SELECT name FROM accounts").unwrap();
+  stmt
+    .query_map([], |row| row.get(0))
+    .unwrap()
+    .collect::<rusqlite::Result<_>>()
+    .unwrap()
+}
+
+fn contains(path: &Path, needle: &str) -> bool {
+  let bytes = std::fs::read(path).unwrap();
+  bytes
+    .windows(needle.len())
+    .any(|window| window == needle.as_bytes())
+}
+
+#[test]
+fn encrypted_books_need_their_passphrase() {
+  let (path, key) = book_at("encrypted-open", Some("correct horse"));
+  assert!(encryption::is_encrypted(&path).unwrap());
+  assert!(!contains(&path, MEMO));
+  assert_eq!(account_names(&path, key.as_ref()), [MEMO]);
+
+  // Plain SQLite sees noise.
+  assert!(Connection::open(&path)
+    .unwrap()
+    .query_row("SELECT COUNT(*) FROM accounts", [], |_| Ok(()))
+    .is_err());
+  assert!(matches!(
+    encryption::open(&path, None),
+    Err(EncryptionError::Locked)
+  ));
+  let wrong = BookKey::for_book(&path, "battery staple").unwrap();
+  assert!(matches!(
+    encryption::open(&path, Some(&wrong)),
+    Err(EncryptionError::WrongPassphrase)
+  ));
+  assert!(matches!(
+    BookKey::generate(""),
+    Err(EncryptionError::EmptyPassphrase)
+  ));
+}
+
+#[test]
+fn the_key_is_rederived_from_the_salt_in_the_header() {
+  let (path, key) = book_at("encrypted-salt", Some("correct horse"));
+  let key = key.unwrap();
+  let header = std::fs::read(&path).unwrap();
+  assert_eq!(header[..16], key.salt());
+
+  let again = BookKey::for_book(&path, "correct horse").unwrap();
+  assert_eq!(again, key);
+  // Another book with the same passphrase gets its own salt and key.
+  assert_ne!(BookKey::generate("correct horse").unwrap(), key);
+}
+
+#[test]
+fn plain_books_are_not_encrypted() {
+  let (path, key) = book_at("encrypted-plain", None);
+  assert!(key.is_none());
+  assert!(!encryption::is_encrypted(&path).unwrap());
+  assert!(!encryption::is_encrypted(&path.with_extension("missing")).unwrap());
+  // The plain path still works as before.
+  migrate_book(&path).unwrap();
+  assert_eq!(account_names(&path, None), [MEMO]);
+}
+
+#[test]
+fn rekeying_encrypts_changes_and_removes_the_passphrase() {
+  let (path, _) = book_at("encrypted-rekey", None);
+
+  let first = BookKey::generate("first passphrase").unwrap();
+  encryption::rekey(&path, None, Some(&first)).unwrap();
+  assert!(encryption::is_encrypted(&path).unwrap());
+  assert!(!contains(&path, MEMO));
+  assert_eq!(account_names(&path, Some(&first)), [MEMO]);
+
+  let second = BookKey::generate("second passphrase").unwrap();
+  assert!(matches!(
+    encryption::rekey(&path, Some(&second), Some(&second)),
+    Err(EncryptionError::WrongPassphrase)
+  ));
+  encryption::rekey(&path, Some(&first), Some(&second)).unwrap();
+  assert_eq!(
+    BookKey::for_book(&path, "second passphrase").unwrap(),
+    second
+  );
+  assert!(matches!(
+    encryption::open(&path, Some(&first)),
+    Err(EncryptionError::WrongPassphrase)
+  ));
+
+  encryption::rekey(&path, Some(&second), None).unwrap();
+  assert!(!encryption::is_encrypted(&path).unwrap());
+  assert_eq!(account_names(&path, None), [MEMO]);
+  // Migrations still recognise the book.
+  assert!(migrate_book(&path).unwrap().applied.is_empty());
+
+  let leftovers: Vec<String> = std::fs::read_dir(path.parent().unwrap())
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+    .filter(|name| name.contains("rekey"))
+    .collect();
+  assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn snapshots_of_encrypted_books_are_encrypted() {
+  let (path, key) = book_at("encrypted-backup", Some("correct horse"));
+  let dir = BackupDir::for_book(&path).with_key(key.clone());
+  let conn = encryption::open(&path, key.as_ref()).unwrap();
+  let info = dir.create(&conn, BackupKind::Manual).unwrap();
+  insert_account(&conn, "card", "Card", "LIABILITY", 0.0);
+  drop(conn);
+
+  let snapshot = dir.path().join(&info.filename);
+  assert!(encryption::is_encrypted(&snapshot).unwrap());
+  assert!(!contains(&snapshot, MEMO));
+  let manifest = dir.verify(&info.filename, None).unwrap();
+  assert!(manifest.encrypted);
+
+  // Without the key the snapshot can't be checked, let alone restored.
+  let keyless = BackupDir::for_book(&path);
+  assert!(matches!(
+    keyless.restore(&info.filename, &path, None),
+    Err(BackupError::Encryption(EncryptionError::Locked))
+  ));
+
+  dir.restore(&info.filename, &path, None).unwrap();
+  assert_eq!(account_names(&path, key.as_ref()), [MEMO]);
+}
+
+#[test]
+fn snapshots_from_before_a_passphrase_change_restore_with_the_old_passphrase() {
+  let (path, old_key) = book_at("encrypted-backup-rekeyed", Some("correct horse"));
+  let conn = encryption::open(&path, old_key.as_ref()).unwrap();
+  let info = BackupDir::for_book(&path)
+    .with_key(old_key.clone())
+    .create(&conn, BackupKind::Manual)
+    .unwrap();
+  insert_account(&conn, "card", "Card", "LIABILITY", 0.0);
+  drop(conn);
+
+  let new_key = BookKey::generate("battery staple").unwrap();
+  encryption::rekey(&path, old_key.as_ref(), Some(&new_key)).unwrap();
+  let dir = BackupDir::for_book(&path).with_key(Some(new_key));
+
+  // The book's key is the new one, which the snapshot was never encrypted with.
+  assert!(matches!(
+    dir.restore(&info.filename, &path, None),
+    Err(BackupError::Encryption(EncryptionError::WrongPassphrase))
+  ));
+  assert!(matches!(
+    dir.verify(&info.filename, Some("battery staple")),
+    Err(BackupError::Encryption(EncryptionError::WrongPassphrase))
+  ));
+
+  dir
+    .restore(&info.filename, &path, Some("correct horse"))
+    .unwrap();
+  let restored = BookKey::for_book(&path, "correct horse").unwrap();
+  assert_eq!(account_names(&path, Some(&restored)), [MEMO]);
+}
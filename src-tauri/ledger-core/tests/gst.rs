@@ -0,0 +1,250 @@
+mod common;
+
+use common::fixture::seeded_ledger;
+use common::*;
+use ledger_core::model::{Account, GstCode, Posting, Transaction};
+use ledger_core::money::Money;
+use ledger_core::reports::{
+  bas_contributions, bas_draft, gst_summary, BasDraft, BasLabel, Period, ATO_LABEL_CAPITAL,
+  ATO_LABEL_NON_CAPITAL,
+};
+
+fn coded(posting: Posting, code: GstCode, gst_amount: f64) -> Posting {
+  Posting {
+    gst_code: Some(code),
+    ..with_gst(posting, gst_amount)
+  }
+}
+
+/// The fixture ledger plus a quarter's worth of less common GST cases. The
+/// fixture already has a $1,100 taxable invoice and a $55 software purchase
+/// in July.
+fn ledger() -> (Vec<Account>, Vec<Transaction>) {
+  let mut db = seeded_ledger();
+  db.insert_account(&Account {
+    ato_label: Some(ATO_LABEL_CAPITAL.into()),
+    ..expense("equipment", "Equipment")
+  })
+  .unwrap();
+  db.insert_account(&Account {
+    ato_label: Some(ATO_LABEL_NON_CAPITAL.into()),
+    ..expense("asset-register", "Small Asset Purchases")
+  })
+  .unwrap();
+
+  for transaction in [
+    transaction(
+      "export",
+      date(2025, 8, 4),
+      "Overseas Client",
+      vec![
+        posting("everyday", 500.0),
+        coded(posting("consulting", -500.0), GstCode::Export, 0.0),
+      ],
+    ),
+    transaction(
+      "gst-free",
+      date(2025, 8, 6),
+      "Training Course",
+      vec![
+        posting("everyday", 200.0),
+        coded(posting("consulting", -200.0), GstCode::GstFree, 0.0),
+      ],
+    ),
+    transaction(
+      "stripe",
+      date(2025, 8, 8),
+      "Stripe Payout",
+      vec![
+        posting("everyday", 55.0),
+        business(posting("consulting", -50.0)),
+        business(posting("gst-collected", -5.0)),
+      ],
+    ),
+    transaction(
+      "laptop",
+      date(2025, 8, 12),
+      "JB Hi-Fi",
+      vec![
+        posting("everyday", -1100.0),
+        with_gst(posting("equipment", 1000.0), 100.0),
+        business(posting("gst-paid", 100.0)),
+      ],
+    ),
+    transaction(
+      "monitor",
+      date(2025, 8, 14),
+      "Officeworks",
+      vec![
+        posting("everyday", -330.0),
+        with_gst(posting("asset-register", 300.0), 30.0),
+        business(posting("gst-paid", 30.0)),
+      ],
+    ),
+    transaction(
+      "software-refund",
+      date(2025, 9, 2),
+      "Atlassian",
+      vec![
+        posting("card", 27.5),
+        with_gst(posting("software", -25.0), 2.5),
+        business(posting("gst-paid", -2.5)),
+      ],
+    ),
+  ] {
+    db.insert_transaction(&transaction).unwrap();
+  }
+  (
+    db.accounts(true).unwrap(),
+    db.transactions_between(None, None).unwrap(),
+  )
+}
+
+fn quarter() -> Period {
+  Period::new(date(2025, 7, 1), date(2025, 9, 30))
+}
+
+fn field(draft: &BasDraft, label: BasLabel) -> Money {
+  match label {
+    BasLabel::G1 => draft.g1_total_sales,
+    BasLabel::G2 => draft.g2_export_sales,
+    BasLabel::G3 => draft.g3_other_gst_free,
+    BasLabel::G10 => draft.g10_capital_purchases,
+    BasLabel::G11 => draft.g11_non_capital_purchases,
+    BasLabel::OneA => draft.one_a_gst_on_sales,
+    BasLabel::OneB => draft.one_b_gst_on_purchases,
+  }
+}
+
+#[test]
+fn fills_in_every_bas_label() {
+  let (accounts, transactions) = ledger();
+  let draft = bas_draft(&accounts, &transactions, quarter());
+
+  assert_eq!(draft.g1_total_sales, dollars(1750.0));
+  assert_eq!(draft.g1_total_sales_inclusive, dollars(1855.0));
+  assert_eq!(draft.g2_export_sales, dollars(500.0));
+  assert_eq!(draft.g3_other_gst_free, dollars(200.0));
+  assert_eq!(draft.g10_capital_purchases, dollars(1000.0));
+  assert_eq!(draft.g10_capital_purchases_inclusive, dollars(1100.0));
+  // Software $50 less the $25 refund, plus the $300 monitor.
+  assert_eq!(draft.g11_non_capital_purchases, dollars(325.0));
+  // $357.50 rounds half up, like `Math.round`.
+  assert_eq!(draft.g11_non_capital_purchases_inclusive, dollars(358.0));
+  assert_eq!(draft.one_a_gst_on_sales, dollars(105.0));
+  // $132.50.
+  assert_eq!(draft.one_b_gst_on_purchases, dollars(133.0));
+  // $105 - $132.50 = -$27.50 rounds up to -$27.
+  assert_eq!(draft.net_gst, dollars(-27.0));
+  assert_eq!(draft.reconciliation.len(), BasLabel::ALL.len() + 1);
+  assert_eq!(draft.reconciliation[0].description, "Total Sales (G1)");
+}
+
+#[test]
+fn drill_down_adds_up_to_each_label() {
+  let (accounts, transactions) = ledger();
+  let draft = bas_draft(&accounts, &transactions, quarter());
+
+  for &label in BasLabel::ALL {
+    let rows = bas_contributions(&accounts, &transactions, quarter(), Some(label));
+    assert!(rows.iter().all(|r| r.label == label));
+    let sum: Money = rows.iter().map(|r| r.amount).sum();
+    assert_eq!(
+      Money::from_cents((sum.cents() + 50).div_euclid(100) * 100),
+      field(&draft, label),
+      "{label:?}"
+    );
+  }
+
+  let one_a: Vec<(String, String)> =
+    bas_contributions(&accounts, &transactions, quarter(), Some(BasLabel::OneA))
+      .into_iter()
+      .map(|r| (r.transaction_id, r.account_id))
+      .collect();
+  assert_eq!(
+    one_a,
+    [
+      ("invoice".to_string(), "consulting".to_string()),
+      ("stripe".to_string(), "gst-collected".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn ato_label_decides_capital_purchases() {
+  let (accounts, transactions) = ledger();
+  let accounts_in = |label| -> Vec<String> {
+    bas_contributions(&accounts, &transactions, quarter(), Some(label))
+      .into_iter()
+      .map(|r| r.account_id)
+      .collect()
+  };
+
+  // "Small Asset Purchases" would be capital by name, but is labelled G11.
+  assert_eq!(accounts_in(BasLabel::G10), ["equipment"]);
+  assert_eq!(
+    accounts_in(BasLabel::G11),
+    ["software", "asset-register", "software"]
+  );
+  let equipment = bas_contributions(&accounts, &transactions, quarter(), Some(BasLabel::G10));
+  assert_eq!(equipment[0].ato_label.as_deref(), Some(ATO_LABEL_CAPITAL));
+}
+
+#[test]
+fn gst_summary_agrees_with_the_bas() {
+  let (accounts, transactions) = ledger();
+  let summary = gst_summary(&accounts, &transactions, quarter());
+
+  assert_eq!(summary.gst_collected, dollars(105.0));
+  assert_eq!(summary.gst_paid, dollars(132.5));
+  assert_eq!(summary.net_gst, dollars(-27.5));
+
+  let software = summary
+    .by_category
+    .iter()
+    .find(|c| c.category_id == "software")
+    .unwrap();
+  assert_eq!(software.purchases, dollars(27.5));
+  assert_eq!(software.gst_paid, dollars(2.5));
+  let consulting = summary
+    .by_category
+    .iter()
+    .find(|c| c.category_id == "consulting")
+    .unwrap();
+  assert_eq!(consulting.sales, dollars(1855.0));
+
+  let atlassian = summary
+    .by_payee
+    .iter()
+    .find(|p| p.payee == "Atlassian")
+    .unwrap();
+  assert_eq!(atlassian.gst_paid, dollars(2.5));
+  // The fixture's business dinner has no GST code.
+  assert!(summary.by_payee.iter().all(|p| p.payee != "Cafe"));
+}
+
+#[test]
+fn a_full_refund_nets_to_zero() {
+  let (accounts, transactions) = ledger();
+  let august = Period::new(date(2025, 8, 1), date(2025, 8, 31));
+  let mut transactions: Vec<Transaction> = transactions
+    .into_iter()
+    .filter(|t| t.id == "laptop")
+    .collect();
+  transactions.push(transaction(
+    "laptop-refund",
+    date(2025, 8, 20),
+    "JB Hi-Fi",
+    vec![
+      posting("everyday", 1100.0),
+      with_gst(posting("equipment", -1000.0), 100.0),
+      business(posting("gst-paid", -100.0)),
+    ],
+  ));
+
+  let draft = bas_draft(&accounts, &transactions, august);
+  assert_eq!(draft.g10_capital_purchases, Money::ZERO);
+  assert_eq!(draft.g10_capital_purchases_inclusive, Money::ZERO);
+  assert_eq!(draft.one_b_gst_on_purchases, Money::ZERO);
+  assert_eq!(draft.net_gst, Money::ZERO);
+}
@@ -0,0 +1,272 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::{
+  parse_amount, parse_date, ColumnMapping, CsvReader, RowError, StatementLine,
+};
+
+fn read(bytes: &[u8], mapping: &ColumnMapping) -> (Vec<StatementLine>, Vec<RowError>) {
+  let mut lines = Vec::new();
+  let mut errors = Vec::new();
+  for record in CsvReader::new(bytes, true).unwrap() {
+    match mapping.statement_line(&record.unwrap()) {
+      Ok(line) => lines.push(line),
+      Err(err) => errors.push(err),
+    }
+  }
+  (lines, errors)
+}
+
+fn line(date: NaiveDate, payee: &str, amount: f64) -> StatementLine {
+  StatementLine {
+    line: 2,
+    date,
+    payee: payee.into(),
+    amount: dollars(amount),
+    reference: None,
+    external_id: None,
+    memo: None,
+    balance: None,
+  }
+}
+
+#[test]
+fn parses_australian_dates_and_amounts() {
+  for (text, expected) in [
+    ("31/01/2026", day(2026, 1, 31)),
+    ("1/2/2026", day(2026, 2, 1)),
+    ("01/02/26", day(2026, 2, 1)),
+    ("5-3-99", day(1999, 3, 5)),
+    ("2026-01-31", day(2026, 1, 31)),
+    ("31 Jan 2026", day(2026, 1, 31)),
+    ("1 SEPT 2025", day(2025, 9, 1)),
+    ("31/01/2026 14:05:00", day(2026, 1, 31)),
+  ] {
+    assert_eq!(parse_date(text), Some(expected), "{text}");
+  }
+  for text in ["", "31/13/2026", "30/02/2026", "Jan 31", "01/02/3"] {
+    assert_eq!(parse_date(text), None, "{text}");
+  }
+
+  for (text, expected) in [
+    ("$1,234.56", 1234.56),
+    ("-45.50", -45.5),
+    ("(12.00)", -12.0),
+    ("$ 20", 20.0),
+    ("50.00 DR", -50.0),
+    ("50.00 CR", 50.0),
+    ("-$3.10", -3.1),
+  ] {
+    assert_eq!(parse_amount(text), Some(dollars(expected)), "{text}");
+  }
+  for text in ["", "abc", "1.2.3", "$"] {
+    assert_eq!(parse_amount(text), None, "{text}");
+  }
+}
+
+#[test]
+fn reads_quoted_multiline_fields_and_a_bom() {
+  let csv = "\u{feff}Date,Description,Amount,Balance\r\n\
+             31/01/2026,\"WOOLWORTHS 1234, SYDNEY\",-45.50,\"1,954.50\"\r\n\
+             01/02/2026,\"TRANSFER FROM J SMITH\nRent share\",500.00,\"2,454.50\"\r\n\
+             02/02/2026,\"Say \"\"hi\"\"\",-1.00,2453.50\r\n";
+  let mapping = ColumnMapping {
+    date: Some(0),
+    description: Some(1),
+    amount: Some(2),
+    balance: Some(3),
+    ..ColumnMapping::default()
+  };
+  let reader = CsvReader::new(csv.as_bytes(), true).unwrap();
+  assert_eq!(
+    reader.headers(),
+    ["Date", "Description", "Amount", "Balance"]
+  );
+
+  let (lines, errors) = read(csv.as_bytes(), &mapping);
+  assert!(errors.is_empty(), "{errors:?}");
+  assert_eq!(
+    lines[0],
+    StatementLine {
+      line: 2,
+      date: day(2026, 1, 31),
+      payee: "WOOLWORTHS 1234, SYDNEY".into(),
+      amount: dollars(-45.5),
+      reference: None,
+      external_id: None,
+      memo: None,
+      balance: Some(dollars(1954.5)),
+    }
+  );
+  assert_eq!(lines[1].payee, "TRANSFER FROM J SMITH\nRent share");
+  // The multiline record pushes the next one down a line.
+  assert_eq!(lines[2].line, 5);
+  assert_eq!(lines[2].payee, "Say \"hi\"");
+}
+
+#[test]
+fn reads_windows_1252_and_utf16() {
+  let mut csv = b"Date,Payee,Amount\n03/02/2026,Caf".to_vec();
+  csv.push(0xe9); // é in Windows-1252
+  csv.extend_from_slice(b" Cr\xe8me,-6.50\n");
+  let mapping = ColumnMapping {
+    date: Some(0),
+    payee: Some(1),
+    amount: Some(2),
+    ..ColumnMapping::default()
+  };
+  let (lines, _) = read(&csv, &mapping);
+  assert_eq!(lines[0].payee, "Café Crème");
+
+  let mut utf16 = vec![0xff, 0xfe];
+  for unit in "Date,Payee,Amount\n03/02/2026,Café,-6.50\n".encode_utf16() {
+    utf16.extend_from_slice(&unit.to_le_bytes());
+  }
+  let (lines, _) = read(&utf16, &mapping);
+  assert_eq!(lines[0].payee, "Café");
+  assert_eq!(lines[0].amount, dollars(-6.5));
+}
+
+#[test]
+fn splits_debit_and_credit_columns_and_reports_bad_rows() {
+  let csv = "Date,Narrative,Debit,Credit,Ref\n\
+             14/02/2026,Telstra,89.00,,TX1\n\
+             15/02/2026,Salary,,\"3,200.00\",TX2\n\
+             16/02/2026,Refund,-12.00,,\n\
+             31/02/2026,Nonsense date,1.00,,\n\
+             17/02/2026,,abc,,\n\
+             ,,,,\n\
+             \n\
+             Closing balance\n";
+  let mapping = ColumnMapping {
+    date: Some(0),
+    payee: Some(1),
+    debit: Some(2),
+    credit: Some(3),
+    reference: Some(4),
+    ..ColumnMapping::default()
+  };
+  let (lines, errors) = read(csv.as_bytes(), &mapping);
+  let amounts: Vec<_> = lines.iter().map(|l| (l.payee.as_str(), l.amount)).collect();
+  assert_eq!(
+    amounts,
+    [
+      ("Telstra", dollars(-89.0)),
+      ("Salary", dollars(3200.0)),
+      // A debit written as a negative number is still money out.
+      ("Refund", dollars(-12.0)),
+    ]
+  );
+  assert_eq!(lines[0].reference.as_deref(), Some("TX1"));
+  assert_eq!(lines[0].external_id.as_deref(), Some("TX1"));
+  assert_eq!(lines[2].reference, None);
+
+  let messages: Vec<_> = errors
+    .iter()
+    .map(|e| (e.line, e.message.as_str()))
+    .collect();
+  assert_eq!(
+    messages,
+    [
+      (5, "Unrecognised date: 31/02/2026"),
+      (
+        6,
+        "Missing required fields: payee/description; Unrecognised amount: abc"
+      ),
+      (
+        7,
+        "Missing required fields: date, amount, payee/description"
+      ),
+      (
+        9,
+        "Missing required fields: amount, payee/description; Unrecognised date: Closing balance"
+      ),
+    ]
+  );
+}
+
+#[test]
+fn flags_lines_the_account_already_has() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [bank("bank", "Everyday"), expense("groceries", "Groceries")] {
+    db.insert_account(&account).unwrap();
+  }
+  db.insert_transaction(&transaction(
+    "t1",
+    date(2026, 1, 30),
+    "WOOLWORTHS 1234 SYDNEY",
+    vec![posting("bank", -45.5), posting("groceries", 45.5)],
+  ))
+  .unwrap();
+  let mut renamed = transaction(
+    "t2",
+    date(2026, 1, 20),
+    "Coles",
+    vec![posting("bank", -12.0), posting("groceries", 12.0)],
+  );
+  renamed.metadata = Some(r#"{"originalDescription":"COLES 0456"}"#.into());
+  renamed.external_id = Some("REF-77".into());
+  db.insert_transaction(&renamed).unwrap();
+
+  let check = |line: StatementLine| db.is_duplicate_import("bank", &line).unwrap();
+  assert!(check(line(day(2026, 2, 2), "woolworths", -45.5)));
+  assert!(!check(line(day(2026, 2, 3), "WOOLWORTHS", -45.5)));
+  assert!(!check(line(day(2026, 1, 31), "WOOLWORTHS", 45.5)));
+  assert!(!check(line(day(2026, 1, 31), "ALDI", -45.5)));
+  assert!(check(line(day(2026, 1, 21), "COLES 0456", -12.0)));
+  assert!(check(StatementLine {
+    external_id: Some("REF-77".into()),
+    ..line(day(2026, 6, 1), "Anything", -1.0)
+  }));
+  assert!(!db
+    .is_duplicate_import("groceries", &line(day(2026, 1, 30), "WOOLWORTHS", -45.5))
+    .unwrap());
+
+  let previews = db
+    .preview_import(
+      "bank",
+      vec![
+        line(day(2026, 1, 30), "WOOLWORTHS", -45.5),
+        line(day(2026, 1, 30), "WOOLWORTHS", -4.55),
+      ],
+    )
+    .unwrap();
+  let flags: Vec<bool> = previews.iter().map(|p| p.is_duplicate).collect();
+  assert_eq!(flags, [true, false]);
+}
+
+#[test]
+fn mapping_templates_round_trip() {
+  let db = Database::from_connection(book()).unwrap();
+  let cba = ColumnMapping {
+    date: Some(0),
+    amount: Some(1),
+    description: Some(2),
+    balance: Some(3),
+    ..ColumnMapping::default()
+  };
+  let macquarie = ColumnMapping {
+    date: Some(0),
+    payee: Some(1),
+    debit: Some(3),
+    credit: Some(4),
+    ..ColumnMapping::default()
+  };
+  db.save_import_mapping_template("CommBank  Everyday", &cba, Some("bank"))
+    .unwrap();
+  db.save_import_mapping_template("Macquarie", &macquarie, None)
+    .unwrap();
+  db.save_import_mapping_template("commbank everyday", &cba, Some("bank"))
+    .unwrap();
+
+  let all = db.import_mapping_templates(None).unwrap();
+  let names: Vec<_> = all.iter().map(|t| t.name.as_str()).collect();
+  assert_eq!(names, ["commbank everyday", "macquarie"]);
+  assert_eq!(all[1].mapping, macquarie);
+
+  let for_bank = db.import_mapping_templates(Some("bank")).unwrap();
+  assert_eq!(for_bank.len(), 1);
+  assert_eq!(for_bank[0].mapping, cba);
+}
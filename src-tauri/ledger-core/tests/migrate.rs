@@ -0,0 +1,223 @@
+mod common;
+
+use common::*;
+use ledger_core::migrate::{self, MigrationError, MIGRATIONS};
+use rusqlite::{params, Connection};
+
+fn recorded(conn: &Connection) -> Vec<(String, String)> {
+  let mut stmt = conn
+    .prepare("SELECT migration_name, checksum FROM _prisma_migrations ORDER BY migration_name")
+    .unwrap();
+  stmt
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+    .unwrap()
+    .collect::<rusqlite::Result<_>>()
+    .unwrap()
+}
+
+/// Apply the first `count` migrations the way `prisma migrate deploy` would.
+fn prisma_deploy(conn: &Connection, count: usize) {
+  for migration in &MIGRATIONS[..count] {
+    conn.execute_batch(migration.sql).unwrap();
+  }
+  conn
+    .execute_batch(
+      r#"CREATE TABLE "_prisma_migrations" (
+        "id" TEXT PRIMARY KEY NOT NULL, "checksum" TEXT NOT NULL, "finished_at" DATETIME,
+        "migration_name" TEXT NOT NULL, "logs" TEXT, "rolled_back_at" DATETIME,
+        "started_at" DATETIME NOT NULL DEFAULT current_timestamp,
+        "applied_steps_count" INTEGER UNSIGNED NOT NULL DEFAULT 0)"#,
+    )
+    .unwrap();
+  for migration in &MIGRATIONS[..count] {
+    conn
+      .execute(
+        "INSERT INTO _prisma_migrations (id, checksum, finished_at, migration_name, applied_steps_count)
+         VALUES (?1, ?2, 1, ?1, 1)",
+        params![migration.name, migrate::checksum(migration.sql)],
+      )
+      .unwrap();
+  }
+}
+
+#[test]
+fn embeds_every_migration_directory_in_order() {
+  let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../prisma/migrations");
+  let mut names: Vec<String> = std::fs::read_dir(&dir)
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.join("migration.sql").is_file())
+    .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+    .collect();
+  names.sort();
+
+  let embedded: Vec<&str> = MIGRATIONS.iter().map(|m| m.name).collect();
+  assert_eq!(embedded, names);
+  for migration in MIGRATIONS {
+    let on_disk = std::fs::read_to_string(dir.join(migration.name).join("migration.sql")).unwrap();
+    assert_eq!(migration.sql, on_disk);
+  }
+}
+
+#[test]
+fn records_migrations_like_prisma() {
+  let mut conn = Connection::open_in_memory().unwrap();
+  let report = migrate::apply_migrations(&mut conn).unwrap();
+  assert_eq!(report.applied.len(), MIGRATIONS.len());
+
+  let expected: Vec<(String, String)> = MIGRATIONS
+    .iter()
+    .map(|m| (m.name.to_string(), migrate::checksum(m.sql)))
+    .collect();
+  assert_eq!(recorded(&conn), expected);
+
+  let again = migrate::apply_migrations(&mut conn).unwrap();
+  assert!(again.applied.is_empty());
+  assert!(again.modified.is_empty());
+}
+
+#[test]
+fn checksum_is_hex_sha256() {
+  assert_eq!(
+    migrate::checksum("abc"),
+    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+  );
+}
+
+#[test]
+fn continues_a_book_migrated_by_prisma() {
+  let mut conn = Connection::open_in_memory().unwrap();
+  prisma_deploy(&conn, 2);
+  insert_account(&conn, "bank", "Everyday", "ASSET", 12.5);
+  conn.pragma_update(None, "foreign_keys", true).unwrap();
+
+  let report = migrate::apply_migrations(&mut conn).unwrap();
+  let expected: Vec<&str> = MIGRATIONS[2..].iter().map(|m| m.name).collect();
+  assert_eq!(report.applied, expected);
+
+  let (balance, has_gst): (f64, bool) = conn
+    .query_row(
+      "SELECT opening_balance, default_has_gst FROM accounts WHERE id = 'bank'",
+      [],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .unwrap();
+  assert_eq!((balance, has_gst), (12.5, true));
+  let foreign_keys: bool = conn
+    .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+    .unwrap();
+  assert!(
+    foreign_keys,
+    "the connection's foreign_keys setting is restored"
+  );
+}
+
+#[test]
+fn refuses_books_from_a_newer_app() {
+  let mut conn = book();
+  conn
+    .execute(
+      "INSERT INTO _prisma_migrations (id, checksum, finished_at, migration_name, applied_steps_count)
+       VALUES ('x', 'x', 1, '29991231000000_from_the_future', 1)",
+      [],
+    )
+    .unwrap();
+
+  match migrate::apply_migrations(&mut conn) {
+    Err(MigrationError::NewerSchema { unknown }) => {
+      assert_eq!(unknown, ["29991231000000_from_the_future"])
+    }
+    other => panic!("expected NewerSchema, got {other:?}"),
+  }
+}
+
+#[test]
+fn refuses_books_with_a_failed_migration() {
+  let mut conn = Connection::open_in_memory().unwrap();
+  prisma_deploy(&conn, 1);
+  conn
+    .execute(
+      "UPDATE _prisma_migrations SET finished_at = NULL WHERE migration_name = ?1",
+      [MIGRATIONS[0].name],
+    )
+    .unwrap();
+
+  assert!(matches!(
+    migrate::apply_migrations(&mut conn),
+    Err(MigrationError::PreviouslyFailed { .. })
+  ));
+}
+
+#[test]
+fn failed_migrations_leave_the_book_untouched() {
+  let mut conn = Connection::open_in_memory().unwrap();
+  prisma_deploy(&conn, 2);
+  // Make the next migration's table rebuild collide with an existing table.
+  conn
+    .execute_batch(r#"CREATE TABLE "new_accounts" (id TEXT)"#)
+    .unwrap();
+
+  let err = migrate::apply_migrations(&mut conn).unwrap_err();
+  assert!(matches!(err, MigrationError::Apply { .. }), "{err}");
+  assert_eq!(recorded(&conn).len(), 2);
+}
+
+#[test]
+fn backs_up_before_migrating_a_book_on_disk() {
+  let dir = scratch_dir("migrate-backup");
+  let path = dir.join("book.db");
+  {
+    let conn = Connection::open(&path).unwrap();
+    prisma_deploy(&conn, 2);
+    insert_account(&conn, "bank", "Everyday", "ASSET", 0.0);
+  }
+
+  let report = migrate::migrate_book(&path).unwrap();
+  let backup = report.backup.expect("a backup is taken");
+  assert!(backup.starts_with(dir.join("backups")));
+  let backed_up = recorded(&Connection::open(&backup).unwrap());
+  assert_eq!(backed_up.len(), 2);
+  assert_eq!(
+    recorded(&Connection::open(&path).unwrap()).len(),
+    MIGRATIONS.len()
+  );
+
+  // Nothing pending, so no second backup.
+  assert_eq!(migrate::migrate_book(&path).unwrap().backup, None);
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn new_books_are_created_without_a_backup() {
+  let dir = scratch_dir("migrate-new");
+  let path = dir.join("fresh.db");
+
+  let report = migrate::migrate_book(&path).unwrap();
+  assert_eq!(report.applied.len(), MIGRATIONS.len());
+  assert_eq!(report.backup, None);
+  assert!(!dir.join("backups").exists());
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn tells_books_from_other_databases() {
+  let dir = scratch_dir("migrate-is-book");
+  let book = dir.join("ledger.db");
+  migrate::migrate_book(&book).unwrap();
+  let other = dir.join("photos.db");
+  Connection::open(&other)
+    .unwrap()
+    .execute_batch("CREATE TABLE photos (id INTEGER PRIMARY KEY)")
+    .unwrap();
+
+  assert!(migrate::is_book(&book).unwrap());
+  assert!(!migrate::is_book(&other).unwrap());
+  assert!(!migrate::is_book(&dir.join("missing.db")).unwrap());
+  // Looking doesn't migrate it.
+  let tables: i64 = Connection::open(&other)
+    .unwrap()
+    .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0))
+    .unwrap();
+  assert_eq!(tables, 1);
+  std::fs::remove_dir_all(dir).unwrap();
+}
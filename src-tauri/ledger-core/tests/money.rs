@@ -0,0 +1,76 @@
+use ledger_core::money::{Currency, Money, MoneyError};
+
+#[test]
+fn parses_decimal_strings() {
+  assert_eq!(Money::parse("1,234.56").unwrap().cents(), 123_456);
+  assert_eq!(Money::parse("-12.5").unwrap().cents(), -1_250);
+  assert_eq!(Money::parse("$3").unwrap().cents(), 300);
+  assert_eq!(Money::parse(".05").unwrap().cents(), 5);
+  assert_eq!(Money::parse("7.100").unwrap().cents(), 710);
+}
+
+#[test]
+fn rejects_malformed_and_sub_cent_strings() {
+  assert!(matches!(
+    Money::parse("abc"),
+    Err(MoneyError::InvalidAmount(_))
+  ));
+  assert!(matches!(
+    Money::parse(""),
+    Err(MoneyError::InvalidAmount(_))
+  ));
+  assert!(matches!(
+    Money::parse("1.234"),
+    Err(MoneyError::TooPrecise(_))
+  ));
+}
+
+#[test]
+fn displays_two_decimal_places() {
+  assert_eq!(Money::from_cents(123_456).to_string(), "1234.56");
+  assert_eq!(Money::from_cents(-5).to_string(), "-0.05");
+  assert_eq!(Money::ZERO.to_string(), "0.00");
+}
+
+#[test]
+fn rounds_floats_half_away_from_zero() {
+  assert_eq!(Money::from_f64(10.005).cents(), 1_001);
+  assert_eq!(Money::from_f64(-10.005).cents(), -1_001);
+  assert_eq!(Money::from_f64(0.1 + 0.2).cents(), 30);
+}
+
+#[test]
+fn splits_gst_inclusive_amounts() {
+  // $200 business portion at 10% GST: GST = gross / 11.
+  assert_eq!(Money::from_cents(20_000).div_round(11).cents(), 1_818);
+  assert_eq!(Money::from_cents(-20_000).div_round(11).cents(), -1_818);
+  assert_eq!(Money::from_cents(10_000).mul_rate(0.1).cents(), 1_000);
+}
+
+#[test]
+fn refuses_to_mix_currencies() {
+  let usd = Currency::new("USD").unwrap();
+  let aud = Money::from_cents(100);
+  let us = Money::from_cents_in(100, usd);
+  assert_eq!(
+    aud.checked_add(us),
+    Err(MoneyError::CurrencyMismatch(Currency::AUD, usd))
+  );
+  // Zero is currency-neutral, so sums can start from Money::ZERO.
+  assert_eq!(Money::ZERO.checked_add(us), Ok(us));
+}
+
+#[test]
+fn serializes_as_a_dollar_number() {
+  let json = serde_json::to_string(&Money::from_cents(-1_234)).unwrap();
+  assert_eq!(json, "-12.34");
+  let parsed: Money = serde_json::from_str("99.99").unwrap();
+  assert_eq!(parsed.cents(), 9_999);
+}
+
+#[test]
+fn validates_currency_codes() {
+  assert_eq!(Currency::new("AUD").unwrap(), Currency::AUD);
+  assert!(Currency::new("aud").is_err());
+  assert!(Currency::new("AUDX").is_err());
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::*;
+use ledger_core::money_storage::{
+  migrate_to_cents, money_storage, CentsMigrationError, MoneyStorage,
+};
+
+fn raw_amount(conn: &rusqlite::Connection, posting_id: &str) -> f64 {
+  conn
+    .query_row(
+      "SELECT amount FROM postings WHERE id = ?1",
+      [posting_id],
+      |row| row.get(0),
+    )
+    .unwrap()
+}
+
+#[test]
+fn rewrites_money_columns_to_cents() {
+  let mut conn = book();
+  insert_account(&conn, "checking", "Checking", "ASSET", 1_000.10);
+  insert_account(&conn, "groceries", "Groceries", "EXPENSE", 0.0);
+  insert_transaction(
+    &conn,
+    "t1",
+    "NORMAL",
+    &[("checking", -45.67), ("groceries", 45.67)],
+  );
+
+  assert_eq!(money_storage(&conn).unwrap(), MoneyStorage::Dollars);
+  let report = migrate_to_cents(&mut conn).unwrap();
+
+  assert_eq!(report.postings, 2);
+  assert_eq!(report.accounts, 2);
+  assert_eq!(report.balances_verified, 2);
+  assert_eq!(money_storage(&conn).unwrap(), MoneyStorage::Cents);
+  assert_eq!(raw_amount(&conn, "t1-0"), -4_567.0);
+  let opening: f64 = conn
+    .query_row(
+      "SELECT opening_balance FROM accounts WHERE id = 'checking'",
+      [],
+      |row| row.get(0),
+    )
+    .unwrap();
+  assert_eq!(opening, 100_010.0);
+}
+
+#[test]
+fn refuses_to_run_twice() {
+  let mut conn = book();
+  migrate_to_cents(&mut conn).unwrap();
+  assert!(matches!(
+    migrate_to_cents(&mut conn),
+    Err(CentsMigrationError::AlreadyMigrated)
+  ));
+}
+
+#[test]
+fn rolls_back_when_a_balance_would_change() {
+  let mut conn = book();
+  insert_account(&conn, "checking", "Checking", "ASSET", 0.0);
+  insert_account(&conn, "groceries", "Groceries", "EXPENSE", 0.0);
+  // Three sub-cent postings that each round down but sum to a whole cent.
+  insert_transaction(
+    &conn,
+    "t1",
+    "NORMAL",
+    &[
+      ("checking", -0.004),
+      ("checking", -0.004),
+      ("checking", -0.004),
+      ("groceries", 0.012),
+    ],
+  );
+
+  let Err(CentsMigrationError::BalanceMismatch(mismatches)) = migrate_to_cents(&mut conn) else {
+    panic!("expected a balance mismatch");
+  };
+  assert_eq!(mismatches.len(), 1);
+  assert_eq!(mismatches[0].account_id, "checking");
+  assert_eq!(mismatches[0].before.cents(), -1);
+  assert_eq!(mismatches[0].after.cents(), 0);
+
+  assert_eq!(money_storage(&conn).unwrap(), MoneyStorage::Dollars);
+  assert_eq!(raw_amount(&conn, "t1-0"), -0.004);
+}
+
+#[test]
+fn ignores_void_transactions_when_verifying() {
+  let mut conn = book();
+  insert_account(&conn, "checking", "Checking", "ASSET", 0.0);
+  insert_account(&conn, "groceries", "Groceries", "EXPENSE", 0.0);
+  insert_transaction(
+    &conn,
+    "t1",
+    "VOID",
+    &[("checking", -0.004), ("groceries", 0.004)],
+  );
+  assert!(migrate_to_cents(&mut conn).is_ok());
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::{read_ofx, ImportError, RowError, StatementBalance};
+
+/// An OFX 1.02 download in the shape CommBank's NetBank produces.
+const SGML: &str = "OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+SECURITY:NONE
+ENCODING:USASCII
+CHARSET:1252
+COMPRESSION:NONE
+OLDFILEUID:NONE
+NEWFILEUID:NONE
+
+<OFX>
+<SIGNONMSGSRSV1><SONRS>
+<STATUS><CODE>0<SEVERITY>INFO</STATUS>
+<DTSERVER>20260205120000<LANGUAGE>ENG
+</SONRS></SIGNONMSGSRSV1>
+<BANKMSGSRSV1><STMTTRNRS>
+<TRNUID>1
+<STMTRS>
+<CURDEF>AUD
+<BANKACCTFROM><BANKID>062000<ACCTID>12345678<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<DTSTART>20260101
+<DTEND>20260131
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20260130000000[+10:AEST]
+<TRNAMT>-45.50
+<FITID>2026013000001
+<NAME>WOOLWORTHS 1234
+<MEMO>WOOLWORTHS 1234 SYDNEY NS AUS Card xx1234
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20260131
+<TRNAMT>3200.00
+<FITID>2026013100002
+<NAME>SALARY ACME PTY &amp; CO
+<REFNUM>PAY-0131
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20260131
+<FITID>2026013100003
+<MEMO>BPAY ORIGIN ENERGY
+</STMTTRN>
+</BANKTRANLIST>
+<LEDGERBAL><BALAMT>4154.50<DTASOF>20260131</LEDGERBAL>
+<AVAILBAL><BALAMT>4054.50<DTASOF>20260131</AVAILBAL>
+</STMTRS>
+</STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+/// An OFX 2.x credit card statement.
+const XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<?OFX OFXHEADER="200" VERSION="211" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>
+<OFX>
+  <CREDITCARDMSGSRSV1>
+    <CCSTMTTRNRS>
+      <TRNUID>1</TRNUID>
+      <CCSTMTRS>
+        <CURDEF>AUD</CURDEF>
+        <CCACCTFROM><ACCTID>4564XXXXXXXX1234</ACCTID></CCACCTFROM>
+        <BANKTRANLIST>
+          <DTSTART>20260201000000.000[+11:AEDT]</DTSTART>
+          <DTEND>20260228000000.000[+11:AEDT]</DTEND>
+          <STMTTRN>
+            <TRNTYPE>DEBIT</TRNTYPE>
+            <DTPOSTED>20260203000000.000[+11:AEDT]</DTPOSTED>
+            <TRNAMT>-6,50</TRNAMT>
+            <FITID>W-77001</FITID>
+            <NAME>Café &lt;Bondi&gt;</NAME>
+          </STMTTRN>
+          <STMTTRN>
+            <TRNTYPE>DEBIT</TRNTYPE>
+            <DTPOSTED>2026-02-04</DTPOSTED>
+            <TRNAMT>-10.00</TRNAMT>
+            <NAME>Mystery</NAME>
+          </STMTTRN>
+        </BANKTRANLIST>
+        <LEDGERBAL><BALAMT>-1206.50</BALAMT><DTASOF>20260228</DTASOF></LEDGERBAL>
+      </CCSTMTRS>
+    </CCSTMTTRNRS>
+  </CREDITCARDMSGSRSV1>
+</OFX>
+"#;
+
+#[test]
+fn reads_an_sgml_bank_statement() {
+  let statements = read_ofx(SGML.as_bytes()).unwrap();
+  assert_eq!(statements.len(), 1);
+  let statement = &statements[0];
+  assert_eq!(statement.currency.as_deref(), Some("AUD"));
+  assert_eq!(statement.bank_id.as_deref(), Some("062000"));
+  assert_eq!(statement.account_id.as_deref(), Some("12345678"));
+  assert_eq!(statement.start, Some(day(2026, 1, 1)));
+  assert_eq!(statement.end, Some(day(2026, 1, 31)));
+
+  let lines: Vec<_> = statement
+    .lines
+    .iter()
+    .map(|l| {
+      (
+        l.line,
+        l.date,
+        l.payee.as_str(),
+        l.amount,
+        l.external_id.as_deref(),
+        l.reference.as_deref(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    lines,
+    [
+      (
+        24,
+        day(2026, 1, 30),
+        "WOOLWORTHS 1234",
+        dollars(-45.5),
+        Some("2026013000001"),
+        None
+      ),
+      (
+        32,
+        day(2026, 1, 31),
+        "SALARY ACME PTY & CO",
+        dollars(3200.0),
+        Some("2026013100002"),
+        Some("PAY-0131")
+      ),
+    ]
+  );
+  assert_eq!(
+    statement.lines[0].memo.as_deref(),
+    Some("WOOLWORTHS 1234 SYDNEY NS AUS Card xx1234")
+  );
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 40,
+      message: "Missing required fields: TRNAMT".into(),
+    }]
+  );
+  assert_eq!(
+    statement.ledger_balance,
+    Some(StatementBalance {
+      amount: dollars(4154.5),
+      date: day(2026, 1, 31),
+    })
+  );
+  assert_eq!(
+    statement.available_balance.map(|b| b.amount),
+    Some(dollars(4054.5))
+  );
+}
+
+#[test]
+fn reads_an_xml_credit_card_statement() {
+  let statement = read_ofx(XML.as_bytes()).unwrap().remove(0);
+  assert_eq!(statement.account_id.as_deref(), Some("4564XXXXXXXX1234"));
+  assert_eq!(statement.start, Some(day(2026, 2, 1)));
+  assert_eq!(statement.lines.len(), 1);
+  let line = &statement.lines[0];
+  assert_eq!(line.payee, "Café <Bondi>");
+  assert_eq!(line.amount, dollars(-6.5));
+  assert_eq!(line.date, day(2026, 2, 3));
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 20,
+      message: "Unrecognised date: 2026-02-04".into(),
+    }]
+  );
+  assert_eq!(
+    statement.ledger_balance.map(|b| b.amount),
+    Some(dollars(-1206.5))
+  );
+}
+
+#[test]
+fn rejects_files_without_a_statement() {
+  assert!(matches!(
+    read_ofx(b"Date,Amount\n01/01/2026,1.00\n"),
+    Err(ImportError::Ofx(_))
+  ));
+  assert!(matches!(
+    read_ofx(b"<OFX><SIGNONMSGSRSV1></SIGNONMSGSRSV1></OFX>"),
+    Err(ImportError::Ofx(_))
+  ));
+}
+
+#[test]
+fn fitid_marks_a_reimported_line_as_a_duplicate() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [bank("bank", "Everyday"), expense("groceries", "Groceries")] {
+    db.insert_account(&account).unwrap();
+  }
+  let mut imported = transaction(
+    "t1",
+    date(2026, 1, 10),
+    "Groceries",
+    vec![posting("bank", -45.5), posting("groceries", 45.5)],
+  );
+  imported.external_id = Some("2026013000001".into());
+  db.insert_transaction(&imported).unwrap();
+
+  let statement = read_ofx(SGML.as_bytes()).unwrap().remove(0);
+  let previews = db.preview_import("bank", statement.lines).unwrap();
+  let flags: Vec<bool> = previews.iter().map(|p| p.is_duplicate).collect();
+  assert_eq!(flags, [true, false]);
+}
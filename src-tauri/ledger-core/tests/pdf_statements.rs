@@ -0,0 +1,207 @@
+//! PDF statements in `tests/fixtures/pdf/`, read with the built-in bank
+//! profiles and pinned against golden files in `tests/golden/`.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::*;
+use ledger_core::import::{
+  extract_pdf_text, load_bank_profiles, read_pdf_statement, BankProfile, Confidence, ImportError,
+  RowError,
+};
+
+fn fixture(name: &str) -> Vec<u8> {
+  let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures/pdf")
+    .join(format!("{name}.pdf"));
+  std::fs::read(path).unwrap()
+}
+
+#[test]
+fn commbank_credit_card_matches_golden() {
+  let statement = read_pdf_statement(&fixture("commbank-cc"), &BankProfile::built_in()).unwrap();
+  assert_eq!(statement.profile_id, "commbank-cc");
+  assert_eq!(statement.account_id.as_deref(), Some("5523508201889606"));
+  assert_eq!(
+    (statement.start, statement.end),
+    (Some(day(2025, 11, 8)), Some(day(2026, 1, 8)))
+  );
+  // Printed with a space for the decimal point.
+  assert_eq!(statement.closing_balance, Some(dollars(2707.41)));
+
+  let lines: Vec<_> = statement
+    .lines
+    .iter()
+    .map(|l| (l.date, l.payee.as_str(), l.amount))
+    .collect();
+  assert_eq!(
+    lines,
+    [
+      (day(2025, 11, 8), "Apple.Com/Bill Sydney", dollars(-22.99)),
+      (
+        day(2025, 11, 24),
+        "Payment Received, Thank You",
+        dollars(4113.69)
+      ),
+      (
+        day(2025, 12, 29),
+        "Woolworths 3124 Chelsea",
+        dollars(-90.35)
+      ),
+      (day(2026, 1, 3), "Qantas Airways Mascot", dollars(-2500.0)),
+      (
+        day(2026, 1, 8),
+        "Interest charged on purchases",
+        dollars(-94.07)
+      ),
+    ]
+  );
+  assert_golden("pdf_commbank_cc", &statement);
+}
+
+#[test]
+fn commbank_savings_matches_golden() {
+  let statement =
+    read_pdf_statement(&fixture("commbank-savings"), &BankProfile::built_in()).unwrap();
+  assert_eq!(statement.profile_id, "commbank-savings");
+  assert_eq!(statement.account_id.as_deref(), Some("06311600623182"));
+  assert_eq!(statement.opening_balance, Some(dollars(108.42)));
+  assert_eq!(statement.closing_balance, Some(dollars(37.78)));
+  assert_eq!(statement.confidence, Confidence::High);
+
+  // Wrapped descriptions are joined, and every row's balance follows on.
+  assert_eq!(
+    statement.lines[2].payee,
+    "Direct Debit 408856 NETFLIX NETFLIX.COM 0001234"
+  );
+  let mut balance = statement.opening_balance.unwrap();
+  for line in &statement.lines {
+    balance += line.amount;
+    assert_eq!(line.balance, Some(balance), "line {}", line.line);
+  }
+  assert_eq!(Some(balance), statement.closing_balance);
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 12,
+      message: "Unrecognised row: 18 Jun Visa Debit Purchase Card 9876 pending".into(),
+    }]
+  );
+  assert_golden("pdf_commbank_savings", &statement);
+}
+
+#[test]
+fn other_banks_fall_back_to_the_generic_profile() {
+  let statement = read_pdf_statement(&fixture("generic"), &BankProfile::built_in()).unwrap();
+  assert_eq!(statement.profile_id, "generic");
+  assert_eq!(statement.account_id.as_deref(), Some("12345678"));
+  // Money in or out by the running balance, then by the description.
+  let amounts: Vec<_> = statement.lines.iter().map(|l| l.amount).collect();
+  assert_eq!(
+    amounts,
+    [
+      dollars(1500.0),
+      dollars(-1200.0),
+      dollars(34.56),
+      dollars(-100.0)
+    ]
+  );
+  assert_eq!(
+    statement.errors,
+    [RowError {
+      line: 11,
+      message: "Unrecognised date: 31/02/2026".into(),
+    }]
+  );
+  assert_golden("pdf_generic", &statement);
+}
+
+#[test]
+fn lays_text_out_in_reading_order() {
+  // Drawn upside down and balances first, as browsers print.
+  let text = extract_pdf_text(&fixture("generic")).unwrap();
+  let lines: Vec<&str> = text.lines().collect();
+  assert_eq!(lines[0], "Example Credit Union");
+  assert_eq!(lines[6], "02/03/2026 Salary ACME 1,500.00 2,500.00");
+  assert_eq!(
+    &lines[lines.len() - 2..],
+    [
+      "Questions? Call us on 13 00 00.",
+      "Thank you for banking with us."
+    ]
+  );
+
+  // Words placed apart with kerning are still words.
+  let text = extract_pdf_text(&fixture("commbank-savings")).unwrap();
+  assert!(text.contains("\n06 Jun Direct Credit 301500 ACME PAYROLL $2.27 $106.69 CR\n"));
+
+  assert!(matches!(
+    extract_pdf_text(b"Date,Amount\n"),
+    Err(ImportError::Pdf(_))
+  ));
+}
+
+#[test]
+fn profiles_are_added_and_replaced_with_data_files() {
+  let dir = scratch_dir("bank-profiles");
+  std::fs::write(
+    dir.join("example.toml"),
+    r#"
+id = "example-cu"
+name = "Example Credit Union"
+detect = [['Example Credit Union']]
+
+[statement]
+account_number = ['Account Number: (?P<number>[\d ]+)']
+closing_balance = ['Closing Balance: (?P<amount>\S+)']
+
+[transactions]
+start = ['^Date Description']
+end = ['^Questions']
+rows = ['^(?P<date>\S+) (?P<description>.+?) (?P<amount>[\d,.]+) (?P<balance>[\d,.]+)$']
+date_format = "%d/%m/%Y"
+"#,
+  )
+  .unwrap();
+  std::fs::write(dir.join("notes.txt"), "not a profile").unwrap();
+
+  let profiles = load_bank_profiles(&dir).unwrap();
+  let ids: Vec<&str> = profiles.iter().map(|p| p.id.as_str()).collect();
+  assert_eq!(
+    ids,
+    ["example-cu", "commbank-cc", "commbank-savings", "generic"]
+  );
+  let statement = read_pdf_statement(&fixture("generic"), &profiles).unwrap();
+  assert_eq!(statement.profile_id, "example-cu");
+  assert_eq!(statement.lines.len(), 3);
+  assert_eq!(statement.closing_balance, Some(dollars(1234.56)));
+  assert_eq!(statement.confidence, Confidence::Medium);
+
+  // A file with a built-in profile's id replaces it.
+  std::fs::write(
+    dir.join("generic.toml"),
+    "id = \"generic\"\nname = \"Mine\"\n[transactions]\nrows = ['^(?P<description>.+) (?P<amount>\\S+)$']\n",
+  )
+  .unwrap();
+  let profiles = load_bank_profiles(&dir).unwrap();
+  assert_eq!(profiles.len(), 4);
+  assert_eq!(profiles[1].name, "Mine");
+
+  std::fs::write(
+    dir.join("broken.toml"),
+    "id = \"broken\"\nname = \"Broken\"\n[transactions]\nrows = ['(?P<amount>\\d+)']\n",
+  )
+  .unwrap();
+  match load_bank_profiles(&dir) {
+    Err(ImportError::Profile(message)) => assert_eq!(
+      message,
+      "broken.toml: broken: a rows pattern has no `description` group"
+    ),
+    other => panic!("expected a profile error, got {other:?}"),
+  }
+  assert!(matches!(
+    BankProfile::from_toml("id = \"x\""),
+    Err(ImportError::Profile(_))
+  ));
+}
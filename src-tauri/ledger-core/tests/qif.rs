@@ -0,0 +1,371 @@
+mod common;
+
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::{
+  read_qif, ClearedStatus, DateOrder, ImportError, QifAccountType, QifCategory, QifSplit, RowError,
+  StatementBalance,
+};
+use ledger_core::model::{Account, AccountSubtype};
+
+/// A Quicken 2004 (Australian edition) export of two accounts.
+const QUICKEN: &str = "!Option:AutoSwitch
+!Account
+NEveryday
+TBank
+^
+NVisa
+TCCard
+^
+!Clear:AutoSwitch
+!Account
+NEveryday
+TBank
+^
+!Type:Bank
+D1/07'25
+T1,500.00
+CX
+POpening Balance
+L[Everyday]
+^
+D3/07'25
+U-120.45
+T-120.45
+C*
+N1001
+PWoolworths
+MWeekly shop
+LGroceries:Supermarket/Household
+^
+D4/07'25
+T-250.00
+PHarvey Norman
+SHome:Furniture
+EDesk
+$-200.00
+SOffice Supplies
+$-50.00
+^
+D5/07'25
+T-500.00
+PTransfer to Visa
+L[Visa]
+^
+D31/06'25
+T-1.00
+PNonsense
+^
+D6/07'25
+T-30.00
+PSplit that doesn't add up
+SGroceries:Supermarket
+$-20.00
+^
+!Type:Cat
+NGroceries
+E
+^
+!Account
+NVisa
+TCCard
+^
+!Type:CCard
+D7/07'25
+T-64.90
+cc
+PBunnings
+LHome
+";
+
+fn category(path: &str) -> Option<QifCategory> {
+  Some(QifCategory::Category(path.into()))
+}
+
+/// An expense category as `categoryService` creates them, under `parent`.
+fn subcategory(id: &str, name: &str, parent: Option<&Account>) -> Account {
+  let full_path = match parent {
+    Some(parent) => format!("{}/{name}", parent.full_path.as_ref().unwrap()),
+    None => format!("Expense/{name}"),
+  };
+  Account {
+    full_path: Some(full_path),
+    parent_id: parent.map(|p| p.id.clone()),
+    level: parent.map_or(1, |p| p.level + 1),
+    ..expense(id, name)
+  }
+}
+
+fn accounts() -> Vec<Account> {
+  let groceries = subcategory("groceries", "Groceries", None);
+  let supermarket = subcategory("supermarket", "Supermarket", Some(&groceries));
+  let home = subcategory("home", "Home", None);
+  let furniture = subcategory("furniture", "Furniture", Some(&home));
+  let visa = Account {
+    subtype: Some(AccountSubtype::Card),
+    ..bank("visa", "Visa")
+  };
+  vec![
+    bank("everyday", "Everyday"),
+    visa,
+    groceries,
+    supermarket,
+    home,
+    furniture,
+  ]
+}
+
+#[test]
+fn reads_bank_credit_card_and_split_records() {
+  let accounts = read_qif(QUICKEN.as_bytes(), DateOrder::DayFirst).unwrap();
+  assert_eq!(accounts.len(), 2);
+
+  let everyday = &accounts[0];
+  assert_eq!(everyday.name.as_deref(), Some("Everyday"));
+  assert_eq!(everyday.account_type, QifAccountType::Bank);
+  assert_eq!(
+    everyday.opening_balance,
+    Some(StatementBalance {
+      amount: dollars(1500.0),
+      date: day(2025, 7, 1),
+    })
+  );
+
+  let shop = &everyday.transactions[0];
+  assert_eq!(shop.line, 21);
+  assert_eq!(shop.date, day(2025, 7, 3));
+  assert_eq!(shop.amount, dollars(-120.45));
+  assert_eq!(shop.cleared, ClearedStatus::Cleared);
+  assert_eq!(shop.reference.as_deref(), Some("1001"));
+  assert_eq!(shop.payee.as_deref(), Some("Woolworths"));
+  assert_eq!(shop.memo.as_deref(), Some("Weekly shop"));
+  assert_eq!(shop.category, category("Groceries:Supermarket"));
+
+  let desk = &everyday.transactions[1];
+  assert_eq!(desk.cleared, ClearedStatus::Uncleared);
+  assert_eq!(
+    desk.splits,
+    [
+      QifSplit {
+        category: category("Home:Furniture"),
+        memo: Some("Desk".into()),
+        amount: dollars(-200.0),
+      },
+      QifSplit {
+        category: category("Office Supplies"),
+        memo: None,
+        amount: dollars(-50.0),
+      },
+    ]
+  );
+  assert_eq!(
+    everyday.transactions[2].category,
+    Some(QifCategory::Transfer("Visa".into()))
+  );
+  assert_eq!(everyday.transactions.len(), 3);
+  assert_eq!(
+    everyday.errors,
+    [
+      RowError {
+        line: 44,
+        message: "Unrecognised date: 31/06'25".into(),
+      },
+      RowError {
+        line: 48,
+        message: "Splits add up to -20.00, not -30.00".into(),
+      },
+    ]
+  );
+
+  // The category list is skipped, and the last record has no `^`.
+  let visa = &accounts[1];
+  assert_eq!(visa.name.as_deref(), Some("Visa"));
+  assert_eq!(visa.account_type, QifAccountType::CreditCard);
+  assert_eq!(visa.transactions.len(), 1);
+  assert_eq!(visa.transactions[0].cleared, ClearedStatus::Cleared);
+  assert_eq!(visa.transactions[0].category, category("Home"));
+}
+
+#[test]
+fn reads_us_dates_and_rejects_files_without_transactions() {
+  let us = "!Type:Bank\nD12/31/99\nT-5.00\nPA\n^\nD1/ 2' 4\nT5.00\nPB\n^\n";
+  let dates: Vec<_> = read_qif(us.as_bytes(), DateOrder::MonthFirst).unwrap()[0]
+    .transactions
+    .iter()
+    .map(|t| t.date)
+    .collect();
+  assert_eq!(dates, [day(1999, 12, 31), day(2004, 1, 2)]);
+
+  let option = format!("!Option:MDY\n{us}");
+  let section = read_qif(option.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  assert_eq!(section.name, None);
+  assert_eq!(section.transactions[1].date, day(2004, 1, 2));
+
+  let day_first = "!Type:Bank\nD31/12/1999\nT-5.00\n^\nD02/01/26\nPNo amount\n^\n";
+  let section = read_qif(day_first.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  assert_eq!(section.transactions[0].date, day(1999, 12, 31));
+  assert_eq!(section.transactions[0].payee, None);
+  assert_eq!(
+    section.errors,
+    [RowError {
+      line: 5,
+      message: "Missing required fields: T".into(),
+    }]
+  );
+
+  assert!(matches!(
+    read_qif(
+      b"!Type:Invst\nD1/07'25\nNBuy\nYBHP\n^\n",
+      DateOrder::DayFirst
+    ),
+    Err(ImportError::Qif(_))
+  ));
+  assert!(matches!(
+    read_qif(b"Date,Amount\n", DateOrder::DayFirst),
+    Err(ImportError::Qif(_))
+  ));
+}
+
+#[test]
+fn imports_into_categories_by_full_path() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in accounts() {
+    db.insert_account(&account).unwrap();
+  }
+  let section = read_qif(QUICKEN.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  assert_eq!(
+    db.unknown_qif_categories(&section.transactions).unwrap(),
+    ["Office Supplies"]
+  );
+
+  let summary = db
+    .import_qif("everyday", "quicken.qif", &section.transactions, true)
+    .unwrap();
+  assert_eq!(summary.imported, 3);
+  assert_eq!(summary.duplicates, 0);
+  assert_eq!(summary.unknown_categories, ["Office Supplies"]);
+
+  let imported = db.transactions_between(None, None).unwrap();
+  let uncategorized = db
+    .accounts(false)
+    .unwrap()
+    .into_iter()
+    .find(|a| a.name == "Uncategorized")
+    .unwrap();
+  let postings: Vec<Vec<_>> = imported
+    .iter()
+    .map(|t| {
+      t.postings
+        .iter()
+        .map(|p| (p.account_id.as_str(), p.amount, p.cleared))
+        .collect()
+    })
+    .collect();
+  assert_eq!(
+    postings,
+    [
+      vec![
+        ("everyday", dollars(-120.45), true),
+        ("supermarket", dollars(120.45), false),
+      ],
+      vec![
+        ("everyday", dollars(-250.0), false),
+        ("furniture", dollars(200.0), false),
+        (uncategorized.id.as_str(), dollars(50.0), false),
+      ],
+      vec![
+        ("everyday", dollars(-500.0), false),
+        ("visa", dollars(500.0), false),
+      ],
+    ]
+  );
+  assert_eq!(imported[0].reference.as_deref(), Some("1001"));
+  assert_eq!(imported[0].memo.as_deref(), Some("Weekly shop"));
+  assert_eq!(
+    imported[1].postings[1].category_split_label.as_deref(),
+    Some("Desk")
+  );
+  assert!(imported
+    .iter()
+    .all(|t| t.import_batch_id.as_deref() == Some(summary.batch_id.as_str())));
+
+  // The Visa file's copy of the transfer is already in the book.
+  let visa = "!Type:CCard\nD5/07'25\nT500.00\nPTransfer to Visa\nL[Everyday]\n^\n";
+  let section = read_qif(visa.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  let summary = db
+    .import_qif("visa", "visa.qif", &section.transactions, true)
+    .unwrap();
+  assert_eq!((summary.imported, summary.duplicates), (0, 1));
+}
+
+#[test]
+fn exports_what_it_imports() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in accounts() {
+    db.insert_account(&account).unwrap();
+  }
+  let everyday = Account {
+    opening_balance: dollars(1500.0),
+    opening_date: date(2025, 7, 1),
+    ..db.account("everyday").unwrap()
+  };
+  db.update_account(&everyday).unwrap();
+  let section = read_qif(QUICKEN.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  db.import_qif("everyday", "quicken.qif", &section.transactions, false)
+    .unwrap();
+
+  let qif = db
+    .export_qif("everyday", None, None, DateOrder::DayFirst)
+    .unwrap();
+  assert!(qif.starts_with("!Account\nNEveryday\nTBank\n^\n!Type:Bank\n"));
+  assert!(qif.contains(
+    "D03/07/2025\nT-120.45\nC*\nN1001\nPWoolworths\nMWeekly shop\nLGroceries:Supermarket\n^\n"
+  ));
+  assert!(qif.contains("SHome:Furniture\nEDesk\n$-200.00\nSUncategorized\n$-50.00\n^\n"));
+
+  let exported = read_qif(qif.as_bytes(), DateOrder::DayFirst)
+    .unwrap()
+    .remove(0);
+  assert_eq!(exported.name.as_deref(), Some("Everyday"));
+  assert_eq!(exported.opening_balance, section.opening_balance);
+  assert!(exported.errors.is_empty(), "{:?}", exported.errors);
+  let original: Vec<_> = section
+    .transactions
+    .iter()
+    .map(|t| (t.date, t.amount, t.payee.clone(), t.cleared))
+    .collect();
+  let round_tripped: Vec<_> = exported
+    .transactions
+    .iter()
+    .map(|t| (t.date, t.amount, t.payee.clone(), t.cleared))
+    .collect();
+  assert_eq!(round_tripped, original);
+  assert_eq!(
+    exported.transactions[2].category,
+    Some(QifCategory::Transfer("Visa".into()))
+  );
+
+  let from_august = db
+    .export_qif(
+      "everyday",
+      Some(date(2025, 8, 1)),
+      None,
+      DateOrder::MonthFirst,
+    )
+    .unwrap();
+  assert_eq!(
+    from_august,
+    "!Account\nNEveryday\nTBank\n^\n!Type:Bank\n\
+     D08/01/2025\nT629.55\nCX\nPOpening Balance\nL[Everyday]\n^\n"
+  );
+}
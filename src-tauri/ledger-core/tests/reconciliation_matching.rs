@@ -0,0 +1,265 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::StatementLine;
+use ledger_core::matching::{
+  description_similarity, match_statement_lines, normalize_description, LedgerItem,
+  ReconciliationMatchPreview, ReconciliationMatchType,
+};
+use ledger_core::model::{Account, MatchType, MemorizedRule, Posting};
+use ledger_core::Money;
+
+fn line(on: DateTime<Utc>, payee: &str, amount: f64) -> StatementLine {
+  StatementLine {
+    line: 0,
+    date: on.date_naive(),
+    payee: payee.into(),
+    amount: dollars(amount),
+    reference: None,
+    external_id: None,
+    memo: None,
+    balance: None,
+  }
+}
+
+fn item(id: &str, on: DateTime<Utc>, payee: &str, amount: f64) -> LedgerItem {
+  LedgerItem {
+    transaction_id: id.into(),
+    posting_id: format!("{id}-bank"),
+    date: on,
+    payee: payee.into(),
+    memo: None,
+    amount: dollars(amount),
+    cleared: false,
+    original_description: None,
+  }
+}
+
+/// Each match as (statement payee, ledger transaction, score, type).
+fn pairs(preview: &ReconciliationMatchPreview) -> Vec<(&str, &str, u32, ReconciliationMatchType)> {
+  preview
+    .exact_matches
+    .iter()
+    .chain(&preview.probable_matches)
+    .chain(&preview.possible_matches)
+    .map(|m| {
+      (
+        m.statement_line.payee.as_str(),
+        m.ledger_item.transaction_id.as_str(),
+        m.match_score,
+        m.match_type,
+      )
+    })
+    .collect()
+}
+
+#[test]
+fn descriptions_compare_like_string_similarity() {
+  assert_eq!(
+    normalize_description("EFTPOS Woolworths #1234   Sydney"),
+    "eftpos woolworths 1234 sydney"
+  );
+  assert_eq!(description_similarity("healed", "sealed"), 0.8);
+  assert_eq!(description_similarity("shell", "shell coles"), 8.0 / 13.0);
+  // Spaces don't count.
+  assert_eq!(description_similarity("shell coles", "shellcoles"), 1.0);
+  assert_eq!(description_similarity("", "shell"), 0.0);
+  assert_eq!(description_similarity("shell", "caltex"), 0.0);
+}
+
+#[test]
+fn assignment_beats_first_come_pairing() {
+  // The first line likes X best, but X is the only posting the second line
+  // could be; taking it would leave the second line unmatched.
+  let preview = match_statement_lines(
+    vec![
+      line(date(2025, 8, 1), "Shell", -50.0),
+      line(date(2025, 8, 5), "Shell", -50.4),
+    ],
+    vec![
+      item("x", date(2025, 8, 2), "Shell", -50.0),
+      item("y", date(2025, 8, 1), "Coles Express", -50.0),
+    ],
+    &[],
+    None,
+    dollars(-100.0),
+  );
+
+  assert_eq!(
+    pairs(&preview),
+    [
+      ("Shell", "y", 80, ReconciliationMatchType::Exact),
+      ("Shell", "x", 60, ReconciliationMatchType::Probable),
+    ]
+  );
+  assert_eq!(
+    preview.probable_matches[0].reasons,
+    [
+      "Date within 3 days",
+      "Amount within $1",
+      "High description similarity (100%)"
+    ]
+  );
+  assert_eq!(preview.summary.total_matched, 2);
+  assert_eq!(preview.explanation.difference, None);
+  assert_eq!(preview.explanation.amount_differences, dollars(0.4));
+  assert_eq!(
+    preview.explanation.notes,
+    ["1 matched posting differs from the statement by 0.40 in total"]
+  );
+}
+
+#[test]
+fn descriptions_match_through_rules_and_original_text() {
+  let rule = MemorizedRule {
+    id: "fuel".into(),
+    name: "Fuel".into(),
+    match_type: MatchType::Contains,
+    match_value: "EFTPOS WLWTHS PETROL 1234 Sydney".into(),
+    default_payee: Some("Woolworths Petrol".into()),
+    default_account_id: None,
+    default_splits: None,
+    apply_on_import: true,
+    apply_on_manual_entry: true,
+    priority: 0,
+    created_at: date(2025, 7, 1),
+    updated_at: date(2025, 7, 1),
+  };
+  let preview = match_statement_lines(
+    vec![
+      // Truncated on the PDF, but the pattern's first three words are there.
+      line(date(2025, 8, 4), "EFTPOS WLWTHS PETROL 12", -60.0),
+      line(date(2025, 8, 9), "NETFLIX.COM 866-579", -16.99),
+    ],
+    vec![
+      item("fuel", date(2025, 8, 4), "Woolworths Petrol", -60.0),
+      LedgerItem {
+        original_description: Some("Netflix.com 866-579".into()),
+        ..item("netflix", date(2025, 8, 9), "Netflix", -16.99)
+      },
+    ],
+    &[rule],
+    None,
+    Money::ZERO,
+  );
+
+  let reasons: Vec<&str> = preview
+    .exact_matches
+    .iter()
+    .map(|m| m.reasons[2].as_str())
+    .collect();
+  assert_eq!(
+    reasons,
+    [
+      "High description similarity (100% via rule \"Fuel\")",
+      "High description similarity (100% via original)"
+    ]
+  );
+  assert_eq!(preview.exact_matches[0].match_score, 100);
+}
+
+#[test]
+fn weak_pairs_stay_unmatched_on_both_sides() {
+  let preview = match_statement_lines(
+    vec![line(date(2025, 8, 1), "Bank fee", -5.0)],
+    vec![item("rent", date(2025, 8, 20), "Rent", -500.0)],
+    &[],
+    Some(dollars(995.0)),
+    dollars(500.0),
+  );
+
+  assert!(pairs(&preview).is_empty());
+  assert_eq!(preview.unmatched_statement.len(), 1);
+  assert_eq!(preview.unmatched_ledger[0].transaction_id, "rent");
+  let explanation = &preview.explanation;
+  assert_eq!(explanation.difference, Some(dollars(-495.0)));
+  assert_eq!(explanation.missing_from_ledger, dollars(-5.0));
+  assert_eq!(explanation.not_on_statement, dollars(-500.0));
+  assert_eq!(explanation.carried_forward, Some(Money::ZERO));
+}
+
+/// A bank line booked against groceries.
+fn spend(id: &str, on: DateTime<Utc>, payee: &str, amount: f64) -> ledger_core::Transaction {
+  transaction(
+    id,
+    on,
+    payee,
+    vec![
+      Posting {
+        id: format!("{id}-bank"),
+        ..posting("everyday", amount)
+      },
+      Posting {
+        id: format!("{id}-category"),
+        ..posting("groceries", -amount)
+      },
+    ],
+  )
+}
+
+#[test]
+fn finishes_a_month_against_the_book() {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [
+    Account {
+      opening_balance: dollars(1000.0),
+      ..bank("everyday", "Everyday")
+    },
+    expense("groceries", "Groceries"),
+  ] {
+    db.insert_account(&account).unwrap();
+  }
+  for transaction in [
+    spend("last-month", date(2025, 7, 20), "Woolworths", -100.0),
+    spend("rent", date(2025, 8, 1), "Rent", -500.0),
+    spend("coffee", date(2025, 8, 3), "Cafe", -4.5),
+    ledger_core::Transaction {
+      metadata: Some(r#"{"originalDescription":"ACME PTY LTD SALARY"}"#.into()),
+      ..spend("pay", date(2025, 8, 5), "Acme", 2000.0)
+    },
+    spend("cheque", date(2025, 8, 6), "Plumber", -120.0),
+  ] {
+    db.insert_transaction(&transaction).unwrap();
+  }
+  db.mark_reconciled(&["last-month-bank".into()], true)
+    .unwrap();
+
+  let lines = vec![
+    line(date(2025, 8, 1), "RENT PAYMENT", -500.0),
+    line(date(2025, 8, 3), "CAFE", -4.5),
+    line(date(2025, 8, 5), "ACME PTY LTD SALARY", 2000.0),
+    StatementLine {
+      balance: Some(dollars(2383.5)),
+      ..line(date(2025, 8, 7), "Account fee", -12.0)
+    },
+  ];
+  let preview = db
+    .match_statement_lines("everyday", lines, None, None, None)
+    .unwrap();
+
+  let matched: Vec<&str> = pairs(&preview).into_iter().map(|m| m.1).collect();
+  assert_eq!(matched, ["rent", "coffee", "pay"]);
+  assert_eq!(preview.unmatched_statement[0].payee, "Account fee");
+  let unmatched: Vec<&str> = preview
+    .unmatched_ledger
+    .iter()
+    .map(|item| item.transaction_id.as_str())
+    .collect();
+  // Last month's posting is already reconciled.
+  assert_eq!(unmatched, ["cheque"]);
+
+  let explanation = &preview.explanation;
+  assert_eq!(explanation.statement_balance, Some(dollars(2383.5)));
+  assert_eq!(explanation.ledger_balance, dollars(2275.5));
+  assert_eq!(explanation.difference, Some(dollars(-108.0)));
+  assert_eq!(explanation.carried_forward, Some(Money::ZERO));
+  assert_eq!(
+    explanation.notes,
+    [
+      "1 ledger posting totalling -120.00 isn't on the statement",
+      "1 statement line totalling -12.00 isn't in the ledger yet"
+    ]
+  );
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use common::*;
+use ledger_core::db::{Database, RegisterCursor, RegisterFilter};
+use ledger_core::model::Account;
+use ledger_core::money::Money;
+
+fn database() -> Database {
+  let mut db = Database::from_connection(book()).unwrap();
+  let everyday = Account {
+    opening_balance: dollars(1000.0),
+    ..bank("bank", "Everyday")
+  };
+  for account in [
+    everyday,
+    expense("groceries", "Groceries"),
+    expense("office", "Office"),
+    income("salary", "Salary"),
+  ] {
+    db.insert_account(&account).unwrap();
+  }
+
+  let rows = [
+    ("t3", date(2025, 7, 20), "Officeworks", -80.0, "office"),
+    ("t1", date(2025, 7, 1), "ACME Pty Ltd", 2500.0, "salary"),
+    ("t2", date(2025, 7, 5), "Woolworths", -120.0, "groceries"),
+    ("t4", date(2025, 8, 2), "Coles", -60.0, "groceries"),
+  ];
+  for (id, on, payee, amount, category) in rows {
+    let mut own = posting("bank", amount);
+    own.cleared = id != "t4";
+    let mut other = posting(category, -amount);
+    if category == "office" {
+      own = business(own);
+      other = business(other);
+    }
+    db.insert_transaction(&transaction(id, on, payee, vec![own, other]))
+      .unwrap();
+  }
+  db
+}
+
+fn ids(db: &Database, filter: &RegisterFilter) -> Vec<String> {
+  db.register_page("bank", filter, 0, 100)
+    .unwrap()
+    .into_iter()
+    .map(|e| e.id)
+    .collect()
+}
+
+#[test]
+fn runs_the_balance_from_the_opening_balance_in_date_order() {
+  let db = database();
+  let entries = db
+    .register_page("bank", &RegisterFilter::default(), 0, 100)
+    .unwrap();
+
+  let balances: Vec<(String, Money)> = entries
+    .iter()
+    .map(|e| (e.id.clone(), e.running_balance))
+    .collect();
+  assert_eq!(
+    balances,
+    [
+      ("t1".to_string(), dollars(3500.0)),
+      ("t2".to_string(), dollars(3380.0)),
+      ("t3".to_string(), dollars(3300.0)),
+      ("t4".to_string(), dollars(3240.0)),
+    ]
+  );
+  assert_eq!(
+    db.register_len("bank", &RegisterFilter::default()).unwrap(),
+    4
+  );
+
+  let opening = db.register_opening_entry("bank").unwrap();
+  assert_eq!(opening.running_balance, dollars(1000.0));
+  assert_eq!(opening.payee, "Opening Balance");
+}
+
+#[test]
+fn splits_amounts_into_debit_and_credit_and_includes_every_leg() {
+  let db = database();
+  let entries = db
+    .register_page("bank", &RegisterFilter::default(), 0, 2)
+    .unwrap();
+
+  assert_eq!(entries[0].credit, Some(dollars(2500.0)));
+  assert_eq!(entries[0].debit, None);
+  assert_eq!(entries[1].debit, Some(dollars(120.0)));
+  assert_eq!(entries[1].credit, None);
+
+  let legs: Vec<&str> = entries[1]
+    .postings
+    .iter()
+    .map(|p| p.account.name.as_str())
+    .collect();
+  assert_eq!(legs, ["Everyday", "Groceries"]);
+  assert!(entries[1].cleared);
+}
+
+#[test]
+fn filters_keep_the_true_running_balance() {
+  let db = database();
+  let filter = RegisterFilter {
+    date_from: Some(date(2025, 7, 10)),
+    ..Default::default()
+  };
+  let entries = db.register_page("bank", &filter, 0, 100).unwrap();
+
+  assert_eq!(entries.len(), 2);
+  assert_eq!(entries[0].running_balance, dollars(3300.0));
+  assert_eq!(db.register_len("bank", &filter).unwrap(), 2);
+}
+
+#[test]
+fn pages_through_the_register() {
+  let db = database();
+  let filter = RegisterFilter::default();
+  let second: Vec<String> = db
+    .register_page("bank", &filter, 2, 2)
+    .unwrap()
+    .into_iter()
+    .map(|e| e.id)
+    .collect();
+  assert_eq!(second, ["t3", "t4"]);
+  assert!(db.register_page("bank", &filter, 4, 2).unwrap().is_empty());
+}
+
+#[test]
+fn streams_to_the_end_when_rows_vanish_mid_stream() {
+  let db = database();
+  let filter = RegisterFilter::default();
+  let mut cursor = RegisterCursor::new(db.register_len("bank", &filter).unwrap(), 2);
+  let mut streamed = Vec::new();
+  while let Some(offset) = cursor.next_offset() {
+    let window = db
+      .register_window("bank", &filter, offset, cursor.page_size())
+      .unwrap();
+    cursor.advance(window.rows);
+    streamed.extend(window.entries.into_iter().map(|e| e.id));
+    if offset == 0 {
+      // The API server deletes a row the stream hasn't reached yet.
+      db.delete_transaction("t4").unwrap();
+    }
+  }
+  assert_eq!(streamed, ["t1", "t2", "t3"]);
+
+  // An empty register still sends the page with the opening balance.
+  let mut cursor = RegisterCursor::new(0, 2);
+  assert_eq!(cursor.next_offset(), Some(0));
+  let window = db
+    .register_window("groceries", &filter, 0, cursor.page_size())
+    .unwrap();
+  cursor.advance(window.rows);
+  assert_eq!(cursor.next_offset(), None);
+}
+
+#[test]
+fn filters_by_payee_business_and_cleared() {
+  let db = database();
+  let search = RegisterFilter {
+    search: Some("woolWORTHS".into()),
+    ..Default::default()
+  };
+  assert_eq!(ids(&db, &search), ["t2"]);
+
+  let business_only = RegisterFilter {
+    business_only: true,
+    ..Default::default()
+  };
+  assert_eq!(ids(&db, &business_only), ["t3"]);
+
+  let personal_only = RegisterFilter {
+    personal_only: true,
+    ..Default::default()
+  };
+  assert_eq!(ids(&db, &personal_only), ["t1", "t2", "t4"]);
+
+  let cleared_only = RegisterFilter {
+    cleared_only: true,
+    ..Default::default()
+  };
+  assert_eq!(ids(&db, &cleared_only), ["t1", "t2", "t3"]);
+}
+
+#[test]
+fn leaves_out_void_transactions() {
+  let db = database();
+  db.void_transaction("t2").unwrap();
+
+  let entries = db
+    .register_page("bank", &RegisterFilter::default(), 0, 100)
+    .unwrap();
+  assert_eq!(entries.len(), 3);
+  assert_eq!(entries.last().unwrap().running_balance, dollars(3360.0));
+}
+
+#[test]
+fn carries_the_split_label_of_the_accounts_own_posting() {
+  let mut db = database();
+  let mut own = posting("bank", -30.0);
+  own.category_split_label = Some("Lunch".into());
+  db.insert_transaction(&transaction(
+    "t5",
+    date(2025, 8, 3),
+    "Cafe",
+    vec![own, posting("groceries", 30.0)],
+  ))
+  .unwrap();
+
+  let entry = db
+    .register_page("bank", &RegisterFilter::default(), 4, 1)
+    .unwrap()
+    .remove(0);
+  assert_eq!(entry.split_label.as_deref(), Some("Lunch"));
+}
@@ -0,0 +1,138 @@
+//! Report totals pinned against golden files in `tests/golden/`.
+//!
+//! Run with `UPDATE_GOLDEN=1` to rewrite the files after an intended change,
+//! then review the diff.
+
+mod common;
+
+use common::fixture::seeded_ledger;
+use common::*;
+use ledger_core::model::{Account, Transaction};
+use ledger_core::reports::{
+  balance_sheet, cash_flow, profit_and_loss, CategoryNode, Period, ProfitAndLossOptions,
+};
+
+fn ledger() -> (Vec<Account>, Vec<Transaction>) {
+  let db = seeded_ledger();
+  (
+    db.accounts(true).unwrap(),
+    db.transactions_between(None, None).unwrap(),
+  )
+}
+
+fn july() -> Period {
+  Period::new(date(2025, 7, 1), date(2025, 7, 31))
+}
+
+#[test]
+fn profit_and_loss_matches_golden() {
+  let (accounts, transactions) = ledger();
+  let report = profit_and_loss(
+    &accounts,
+    &transactions,
+    july(),
+    ProfitAndLossOptions::default(),
+  );
+  assert_eq!(report.total_income, dollars(5212.34));
+  assert_eq!(report.total_expenses, dollars(290.0));
+  assert_golden("profit_and_loss", &report);
+}
+
+#[test]
+fn business_profit_and_loss_matches_golden() {
+  let (accounts, transactions) = ledger();
+  for (name, gst_inclusive) in [
+    ("profit_and_loss_business", false),
+    ("profit_and_loss_business_gst_inclusive", true),
+  ] {
+    let report = profit_and_loss(
+      &accounts,
+      &transactions,
+      july(),
+      ProfitAndLossOptions {
+        business_only: true,
+        gst_inclusive,
+        ..Default::default()
+      },
+    );
+    assert_golden(name, &report);
+  }
+}
+
+#[test]
+fn business_and_personal_totals_add_up_to_the_whole() {
+  let (accounts, transactions) = ledger();
+  let all = profit_and_loss(
+    &accounts,
+    &transactions,
+    july(),
+    ProfitAndLossOptions::default(),
+  );
+  let personal = profit_and_loss(
+    &accounts,
+    &transactions,
+    july(),
+    ProfitAndLossOptions {
+      personal_only: true,
+      ..Default::default()
+    },
+  );
+
+  assert_eq!(
+    all.business.net_profit + all.personal.net_profit,
+    all.net_profit
+  );
+  assert_eq!(personal.net_profit, all.personal.net_profit);
+  assert_eq!(personal.business.net_profit, ledger_core::Money::ZERO);
+}
+
+#[test]
+fn subtotals_roll_up_the_category_hierarchy() {
+  let (accounts, transactions) = ledger();
+  let report = profit_and_loss(
+    &accounts,
+    &transactions,
+    july(),
+    ProfitAndLossOptions::default(),
+  );
+
+  fn check(node: &CategoryNode) {
+    let mut children = node.own.amount;
+    for child in &node.children {
+      assert!(child.level > node.level);
+      children += child.subtotal.amount;
+      check(child);
+    }
+    assert_eq!(node.subtotal.amount, children, "{}", node.name);
+  }
+  for tree in report.income_tree.iter().chain(&report.expense_tree) {
+    check(tree);
+  }
+
+  let food = report.expense_tree[0]
+    .walk()
+    .find(|n| n.account_id == "food")
+    .unwrap();
+  // Groceries net of the refund, plus dining out; the void is ignored.
+  assert_eq!(food.subtotal.amount, dollars(190.0));
+  assert_eq!(food.own.amount, ledger_core::Money::ZERO);
+}
+
+#[test]
+fn balance_sheet_matches_golden() {
+  let (accounts, transactions) = ledger();
+  let report = balance_sheet(&accounts, &transactions, date(2025, 7, 31));
+  assert!(report.is_balanced);
+  assert_golden("balance_sheet", &report);
+}
+
+#[test]
+fn cash_flow_matches_golden() {
+  let (accounts, transactions) = ledger();
+  let report = cash_flow(&accounts, &transactions, july());
+  assert_eq!(
+    report.closing_cash - report.opening_cash,
+    report.net_cash_change
+  );
+  assert_golden("cash_flow", &report);
+}
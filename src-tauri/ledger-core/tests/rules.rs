@@ -0,0 +1,277 @@
+mod common;
+
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::import::StatementLine;
+use ledger_core::model::{GstCode, MatchType, MemorizedRule};
+use ledger_core::rules::{RuleContext, RuleMatcher, RuleOutcome, SplitRatio, SplitTemplate};
+
+fn rule(id: &str, match_type: MatchType, match_value: &str, priority: i32) -> MemorizedRule {
+  MemorizedRule {
+    id: id.into(),
+    name: id.into(),
+    match_type,
+    match_value: match_value.into(),
+    default_payee: None,
+    default_account_id: Some("groceries".into()),
+    default_splits: None,
+    apply_on_import: true,
+    apply_on_manual_entry: true,
+    priority,
+    created_at: date(2025, 7, 1),
+    updated_at: date(2025, 7, 1),
+  }
+}
+
+fn winner(matcher: &RuleMatcher, payee: &str, context: RuleContext) -> Option<String> {
+  matcher.find(payee, context).map(|rule| rule.id.clone())
+}
+
+#[test]
+fn match_types_are_case_insensitive() {
+  let matcher = RuleMatcher::new(vec![
+    rule("exact", MatchType::Exact, "Netflix.com", 0),
+    rule("contains", MatchType::Contains, "woolworths", 0),
+    rule("regex", MatchType::Regex, r"^uber\s*\*?\s*eats", 0),
+  ]);
+  let find = |payee| winner(&matcher, payee, RuleContext::Import);
+
+  assert_eq!(find("NETFLIX.COM").as_deref(), Some("exact"));
+  // Exact text only: the dot is literal and the whole payee must match.
+  assert_eq!(find("netflixxcom"), None);
+  assert_eq!(find("NETFLIX.COM SYDNEY"), None);
+  assert_eq!(find("EFTPOS WOOLWORTHS 1234").as_deref(), Some("contains"));
+  assert_eq!(find("Uber *Eats Help").as_deref(), Some("regex"));
+  assert_eq!(find("Coles"), None);
+}
+
+#[test]
+fn first_rule_in_priority_then_name_order_wins() {
+  let matcher = RuleMatcher::new(vec![
+    rule("b-woolies", MatchType::Contains, "wool", 1),
+    rule("a-woolworths", MatchType::Contains, "woolworths", 1),
+    rule("metro", MatchType::Contains, "metro", 0),
+  ]);
+  let find = |payee| winner(&matcher, payee, RuleContext::Import);
+
+  assert_eq!(find("Woolworths Metro").as_deref(), Some("metro"));
+  assert_eq!(find("Woolworths 1234").as_deref(), Some("a-woolworths"));
+  assert_eq!(find("Woolmart").as_deref(), Some("b-woolies"));
+  let ids: Vec<&str> = matcher.rules().iter().map(|r| r.id.as_str()).collect();
+  assert_eq!(ids, ["metro", "a-woolworths", "b-woolies"]);
+}
+
+#[test]
+fn rules_only_apply_in_their_contexts() {
+  let matcher = RuleMatcher::new(vec![
+    MemorizedRule {
+      apply_on_import: false,
+      ..rule("manual-only", MatchType::Contains, "woolworths", 0)
+    },
+    rule("everywhere", MatchType::Contains, "wool", 1),
+  ]);
+
+  assert_eq!(
+    winner(&matcher, "Woolworths", RuleContext::Manual).as_deref(),
+    Some("manual-only")
+  );
+  assert_eq!(
+    winner(&matcher, "Woolworths", RuleContext::Import).as_deref(),
+    Some("everywhere")
+  );
+}
+
+#[test]
+fn invalid_regexes_never_match() {
+  let matcher = RuleMatcher::new(vec![
+    rule("broken", MatchType::Regex, "(woolworths", 0),
+    rule("fallback", MatchType::Contains, "woolworths", 1),
+  ]);
+
+  assert_eq!(
+    winner(&matcher, "(woolworths", RuleContext::Import).as_deref(),
+    Some("fallback")
+  );
+  let explanation = matcher.explain("(woolworths", RuleContext::Import);
+  assert_eq!(explanation.rules[0].outcome, RuleOutcome::InvalidPattern);
+}
+
+#[test]
+fn explain_says_why_the_winner_won() {
+  let matcher = RuleMatcher::new(vec![
+    rule("groceries", MatchType::Contains, "woolworths", 1),
+    rule("metro", MatchType::Contains, "metro", 0),
+    rule("petrol", MatchType::Contains, "caltex", 0),
+    MemorizedRule {
+      apply_on_import: false,
+      ..rule("hand-entered", MatchType::Exact, "woolworths metro", 0)
+    },
+    rule("also-groceries", MatchType::Regex, "^wool", 1),
+  ]);
+  let explanation = matcher.explain("Woolworths Metro", RuleContext::Import);
+
+  assert_eq!(
+    explanation.winner.map(|rule| rule.id).as_deref(),
+    Some("metro")
+  );
+  let verdicts: Vec<(&str, RuleOutcome, &str)> = explanation
+    .rules
+    .iter()
+    .map(|v| (v.rule_id.as_str(), v.outcome, v.detail.as_str()))
+    .collect();
+  assert_eq!(
+    verdicts,
+    [
+      (
+        "hand-entered",
+        RuleOutcome::NotApplicable,
+        "Matches, but the rule is turned off for import"
+      ),
+      (
+        "metro",
+        RuleOutcome::Won,
+        "Matches and comes first at priority 0 (2 other rules also match)"
+      ),
+      (
+        "petrol",
+        RuleOutcome::NoMatch,
+        "The payee doesn't contain \"caltex\""
+      ),
+      (
+        "also-groceries",
+        RuleOutcome::Outranked,
+        "Matches, but \"metro\" comes first at priority 0 (this rule is 1)"
+      ),
+      (
+        "groceries",
+        RuleOutcome::Outranked,
+        "Matches, but \"metro\" comes first at priority 0 (this rule is 1)"
+      ),
+    ]
+  );
+}
+
+#[test]
+fn default_splits_read_both_shapes() {
+  let templates = MemorizedRule {
+    default_splits: Some(
+      r#"[{"accountId":"office","percentOrAmount":60,"isBusiness":true,"gstCode":"GST","gstRate":0.1},
+          {"accountId":"groceries","percentOrAmount":40,"isBusiness":false,"memoTemplate":"Home"}]"#
+        .into(),
+    ),
+    ..rule("templates", MatchType::Contains, "officeworks", 0)
+  };
+  assert_eq!(
+    templates.default_split_templates(),
+    [
+      SplitTemplate {
+        account_id: "office".into(),
+        percent_or_amount: 60.0,
+        is_business: true,
+        gst_code: Some(GstCode::Gst),
+        gst_rate: Some(0.1),
+        memo_template: None,
+      },
+      SplitTemplate {
+        account_id: "groceries".into(),
+        percent_or_amount: 40.0,
+        is_business: false,
+        gst_code: None,
+        gst_rate: None,
+        memo_template: Some("Home".into()),
+      },
+    ]
+  );
+  assert_eq!(templates.split_ratio(), None);
+
+  let ratio = MemorizedRule {
+    default_splits: Some(
+      r#"{"kind":"SPLIT_RATIO","personalCategoryId":"home","businessCategoryId":"office",
+          "businessPercent":25,"gstOnBusiness":true}"#
+        .into(),
+    ),
+    ..rule("ratio", MatchType::Contains, "energy", 0)
+  };
+  let split = SplitRatio {
+    personal_category_id: "home".into(),
+    business_category_id: "office".into(),
+    business_percent: 25.0,
+    gst_on_business: true,
+  };
+  assert_eq!(ratio.split_ratio(), Some(split.clone()));
+  assert!(ratio.default_split_templates().is_empty());
+  assert_eq!(
+    serde_json::to_value(&split).unwrap()["kind"],
+    serde_json::json!("SPLIT_RATIO")
+  );
+
+  let garbled = MemorizedRule {
+    default_splits: Some("not json".into()),
+    ..rule("garbled", MatchType::Contains, "x", 0)
+  };
+  assert!(garbled.default_split_templates().is_empty());
+  assert_eq!(garbled.split_ratio(), None);
+}
+
+#[test]
+fn import_previews_carry_the_matched_rule() {
+  let db = Database::from_connection(book()).unwrap();
+  for account in [bank("bank", "Everyday"), expense("groceries", "Groceries")] {
+    db.insert_account(&account).unwrap();
+  }
+  db.insert_memorized_rule(&rule("groceries", MatchType::Contains, "woolworths", 0))
+    .unwrap();
+  db.insert_memorized_rule(&MemorizedRule {
+    apply_on_import: false,
+    ..rule("coles", MatchType::Contains, "coles", 0)
+  })
+  .unwrap();
+
+  let line = |payee: &str| StatementLine {
+    line: 2,
+    date: date(2025, 7, 3).date_naive(),
+    payee: payee.into(),
+    amount: dollars(-45.5),
+    reference: None,
+    external_id: None,
+    memo: None,
+    balance: None,
+  };
+  let previews = db
+    .preview_import("bank", vec![line("WOOLWORTHS 1234"), line("Coles 99")])
+    .unwrap();
+  let matched: Vec<Option<&str>> = previews
+    .iter()
+    .map(|p| p.matched_rule.as_ref().map(|rule| rule.id.as_str()))
+    .collect();
+  assert_eq!(matched, [Some("groceries"), None]);
+}
+
+#[test]
+fn matching_many_payees_against_many_rules() {
+  let mut rules: Vec<MemorizedRule> = (0..500)
+    .map(|i| {
+      rule(
+        &format!("merchant-{i:03}"),
+        MatchType::Contains,
+        &format!("merchant {i:03} "),
+        1,
+      )
+    })
+    .collect();
+  rules.push(rule("catch-all", MatchType::Regex, r"\bpty\s+ltd\b", 2));
+  let matcher = RuleMatcher::new(rules);
+
+  let matched = (0..50_000)
+    .filter(|i| {
+      let payee = format!("EFTPOS MERCHANT {:03} PTY LTD", i % 1000);
+      let expected = if i % 1000 < 500 {
+        format!("merchant-{:03}", i % 1000)
+      } else {
+        "catch-all".to_string()
+      };
+      winner(&matcher, &payee, RuleContext::Import) == Some(expected)
+    })
+    .count();
+  assert_eq!(matched, 50_000);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use ledger_core::db::Database;
+use ledger_core::secrets::{
+  move_secrets_out_of_settings, FileSecretStore, Secret, SecretError, SecretMigration, SecretStore,
+};
+use serde_json::json;
+
+const STRIPE_KEY: &str = "sk_test_51HxYzAbCdEf";
+
+#[test]
+fn file_store_encrypts_and_persists() {
+  let path = scratch_dir("secrets-file").join("secrets.json");
+  let store = FileSecretStore::open(&path, "headless passphrase").unwrap();
+  assert_eq!(store.get(Secret::StripeApiKey).unwrap(), None);
+  store.set(Secret::StripeApiKey, STRIPE_KEY).unwrap();
+  store.set(Secret::AiApiKey, "sk-ant-api03-xyz").unwrap();
+  assert!(!std::fs::read_to_string(&path).unwrap().contains(STRIPE_KEY));
+
+  let reopened = FileSecretStore::open(&path, "headless passphrase").unwrap();
+  assert_eq!(
+    reopened.get(Secret::StripeApiKey).unwrap().as_deref(),
+    Some(STRIPE_KEY)
+  );
+  assert!(reopened.delete(Secret::AiApiKey).unwrap());
+  assert!(!reopened.delete(Secret::AiApiKey).unwrap());
+  assert_eq!(store.get(Secret::AiApiKey).unwrap(), None);
+
+  let wrong = FileSecretStore::open(&path, "not it").unwrap();
+  assert!(matches!(
+    wrong.get(Secret::StripeApiKey),
+    Err(SecretError::Decrypt(_))
+  ));
+}
+
+#[test]
+fn file_store_values_cannot_be_swapped_between_entries() {
+  let path = scratch_dir("secrets-swap").join("secrets.json");
+  let store = FileSecretStore::open(&path, "headless passphrase").unwrap();
+  store.set(Secret::StripeApiKey, STRIPE_KEY).unwrap();
+  store.set(Secret::AiApiKey, "sk-ant-api03-xyz").unwrap();
+
+  let mut file: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+  let stripe = file["secrets"]["stripe-api-key"].clone();
+  file["secrets"]["ai-api-key"] = stripe;
+  std::fs::write(&path, file.to_string()).unwrap();
+
+  assert!(matches!(
+    store.get(Secret::AiApiKey),
+    Err(SecretError::Decrypt(_))
+  ));
+}
+
+#[test]
+fn keys_move_out_of_settings_and_are_blanked() {
+  let db = Database::from_connection(book()).unwrap();
+  db.set_setting_json(
+    "stripe",
+    &json!({ "apiKey": STRIPE_KEY, "accountId": "acct_1", "payoutDestinationAccountId": "bank" }),
+  )
+  .unwrap();
+  db.set_setting_json(
+    "ai_categorization",
+    &json!({ "apiKey": "", "modelId": "claude-haiku-4-5", "enabled": false }),
+  )
+  .unwrap();
+  let store = FileSecretStore::open(
+    scratch_dir("secrets-migrate").join("secrets.json"),
+    "headless passphrase",
+  )
+  .unwrap();
+
+  let migration = move_secrets_out_of_settings(&db, &store).unwrap();
+  assert_eq!(
+    migration,
+    SecretMigration {
+      moved: vec![Secret::StripeApiKey],
+      conflicts: vec![],
+    }
+  );
+  assert_eq!(
+    store.get(Secret::StripeApiKey).unwrap().as_deref(),
+    Some(STRIPE_KEY)
+  );
+  let stripe: serde_json::Value = db.setting_json("stripe").unwrap().unwrap();
+  assert_eq!(
+    stripe,
+    json!({ "apiKey": "", "accountId": "acct_1", "payoutDestinationAccountId": "bank" })
+  );
+  assert_eq!(store.get(Secret::AiApiKey).unwrap(), None);
+
+  // Nothing left to move the second time.
+  assert_eq!(
+    move_secrets_out_of_settings(&db, &store).unwrap(),
+    SecretMigration::default()
+  );
+}
+
+#[test]
+fn a_different_stored_key_is_not_overwritten() {
+  let db = Database::from_connection(book()).unwrap();
+  db.set_setting_json(
+    "stripe",
+    &json!({ "apiKey": STRIPE_KEY, "accountId": "acct_1" }),
+  )
+  .unwrap();
+  let store = FileSecretStore::open(
+    scratch_dir("secrets-conflict").join("secrets.json"),
+    "headless passphrase",
+  )
+  .unwrap();
+  store.set(Secret::StripeApiKey, "sk_live_other").unwrap();
+
+  let migration = move_secrets_out_of_settings(&db, &store).unwrap();
+  assert_eq!(migration.conflicts, [Secret::StripeApiKey]);
+  assert_eq!(
+    store.get(Secret::StripeApiKey).unwrap().as_deref(),
+    Some("sk_live_other")
+  );
+  let stripe: serde_json::Value = db.setting_json("stripe").unwrap().unwrap();
+  assert_eq!(stripe["apiKey"], STRIPE_KEY);
+}
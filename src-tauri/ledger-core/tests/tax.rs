@@ -0,0 +1,315 @@
+mod common;
+
+use common::fixture::seeded_ledger;
+use common::*;
+use ledger_core::model::{Account, AtoLabel};
+use ledger_core::reports::Period;
+use ledger_core::tax::{
+  default_tax_tables, estimate_tax, income_tax, lito, medicare_levy, small_business_offset,
+  FinancialYear, PaygConfig, PaygMethod, PaygStatus, Quarter,
+};
+
+fn fy(year: &str) -> FinancialYear {
+  year.parse().unwrap()
+}
+
+#[test]
+fn financial_years_parse_and_contain_dates() {
+  assert_eq!(fy("2025-26").start_year(), 2025);
+  assert_eq!(fy("1999-00").to_string(), "1999-00");
+  for bad in ["2025-27", "2025", "25-26", "2025-2026"] {
+    assert!(bad.parse::<FinancialYear>().is_err(), "{bad}");
+  }
+  assert_eq!(FinancialYear::containing(day(2025, 6, 30)), fy("2024-25"));
+  assert_eq!(FinancialYear::containing(day(2025, 7, 1)), fy("2025-26"));
+  assert_eq!(fy("2025-26").last_day(), day(2026, 6, 30));
+}
+
+/// Resident tax from the ATO's "Individual income tax rates" tables.
+#[test]
+fn income_tax_matches_ato_worked_examples() {
+  let cases = [
+    ("2023-24", 18_200.0, 0.0),
+    ("2023-24", 45_000.0, 5_092.0),
+    ("2023-24", 100_000.0, 22_967.0),
+    ("2023-24", 200_000.0, 60_667.0),
+    ("2024-25", 18_200.0, 0.0),
+    ("2024-25", 45_000.0, 4_288.0),
+    ("2024-25", 60_000.0, 8_788.0),
+    ("2024-25", 100_000.0, 20_788.0),
+    ("2024-25", 150_000.0, 36_838.0),
+    ("2024-25", 200_000.0, 56_138.0),
+    ("2025-26", 100_000.0, 20_788.0),
+    // 15% from 2026-27, 14% from 2027-28.
+    ("2026-27", 45_000.0, 4_020.0),
+    ("2027-28", 45_000.0, 3_752.0),
+    ("2030-31", 45_000.0, 3_752.0),
+  ];
+  for (year, taxable, expected) in cases {
+    let tables = default_tax_tables(fy(year));
+    assert_eq!(
+      income_tax(dollars(taxable), &tables.brackets),
+      dollars(expected),
+      "{year} on ${taxable}"
+    );
+  }
+}
+
+#[test]
+fn medicare_levy_shades_in() {
+  let config = default_tax_tables(fy("2024-25")).medicare_levy_config;
+  let cases = [
+    (27_222.0, 0.0),
+    (30_000.0, 277.8),
+    (34_027.0, 680.5),
+    (40_000.0, 800.0),
+    (100_000.0, 2_000.0),
+  ];
+  for (taxable, expected) in cases {
+    assert_eq!(
+      medicare_levy(dollars(taxable), &config),
+      dollars(expected),
+      "${taxable}"
+    );
+  }
+}
+
+#[test]
+fn lito_phases_out_in_two_steps() {
+  let config = default_tax_tables(fy("2025-26")).lito_config;
+  let cases = [
+    (20_000.0, 700.0),
+    (37_500.0, 700.0),
+    (40_000.0, 575.0),
+    (45_000.0, 325.0),
+    (50_000.0, 250.0),
+    (66_667.0, 0.0),
+    (90_000.0, 0.0),
+  ];
+  for (taxable, expected) in cases {
+    assert_eq!(
+      lito(dollars(taxable), &config),
+      dollars(expected),
+      "${taxable}"
+    );
+  }
+}
+
+#[test]
+fn small_business_offset_is_capped() {
+  let config = default_tax_tables(fy("2025-26")).small_business_offset;
+  let cases = [
+    (-5_000.0, 4_000.0, 0.0),
+    (5_000.0, 4_000.0, 800.0),
+    (10_000.0, 4_000.0, 1_000.0),
+    // Never more than the tax it offsets.
+    (5_000.0, 300.0, 300.0),
+  ];
+  for (net, tax, expected) in cases {
+    assert_eq!(
+      small_business_offset(dollars(net), dollars(tax), &config),
+      dollars(expected),
+      "${net} net, ${tax} tax"
+    );
+  }
+}
+
+fn labelled(account: Account, label: AtoLabel) -> Account {
+  Account {
+    ato_label: Some(label.to_string()),
+    ..account
+  }
+}
+
+#[test]
+fn estimates_a_sole_trader_year() {
+  let accounts = vec![
+    bank("everyday", "Everyday"),
+    income("consulting", "Consulting"),
+    expense("software", "Software"),
+    labelled(income("interest", "Interest"), AtoLabel::IncomeInterest),
+    labelled(expense("donations", "Donations"), AtoLabel::D9Gifts),
+    labelled(expense("uniform", "Uniform"), AtoLabel::D3Clothing),
+  ];
+  let transactions = vec![
+    transaction(
+      "invoice",
+      date(2024, 9, 1),
+      "Client",
+      vec![
+        posting("everyday", 80_000.0),
+        business(posting("consulting", -80_000.0)),
+      ],
+    ),
+    transaction(
+      "software",
+      date(2024, 10, 1),
+      "Atlassian",
+      vec![
+        posting("everyday", -5_000.0),
+        business(posting("software", 5_000.0)),
+      ],
+    ),
+    transaction(
+      "interest",
+      date(2025, 6, 30),
+      "Bank",
+      vec![posting("everyday", 1_000.0), posting("interest", -1_000.0)],
+    ),
+    transaction(
+      "donation",
+      date(2024, 12, 20),
+      "Red Cross",
+      vec![posting("everyday", -1_000.0), posting("donations", 1_000.0)],
+    ),
+    transaction(
+      "uniform",
+      date(2025, 2, 1),
+      "Workwear",
+      vec![posting("everyday", -600.0), posting("uniform", 600.0)],
+    ),
+    transaction(
+      "uniform-refund",
+      date(2025, 2, 8),
+      "Workwear",
+      vec![posting("everyday", 100.0), posting("uniform", -100.0)],
+    ),
+    transaction(
+      "next-year",
+      date(2025, 7, 1),
+      "Client",
+      vec![
+        posting("everyday", 10_000.0),
+        business(posting("consulting", -10_000.0)),
+      ],
+    ),
+  ];
+  let year = fy("2024-25");
+  let mut payg = PaygConfig::new(year, PaygMethod::Amount, Some(dollars(12_000.0)), None);
+  payg.record_payment(Quarter::Q1, dollars(3_000.0), day(2024, 10, 20));
+
+  let estimate = estimate_tax(
+    &accounts,
+    &transactions,
+    Period::new(date(2024, 7, 1), date(2025, 6, 30)),
+    &default_tax_tables(year),
+    Some(&payg),
+  );
+
+  assert_eq!(estimate.financial_year, year);
+  assert_eq!(estimate.gross_business_income, dollars(80_000.0));
+  assert_eq!(estimate.business_expenses, dollars(5_000.0));
+  assert_eq!(estimate.net_business_income, dollars(75_000.0));
+  assert_eq!(estimate.total_other_income, dollars(1_000.0));
+  assert_eq!(estimate.other_income[0].label, "Item 10: Interest");
+  let deductions: Vec<_> = estimate
+    .personal_deductions
+    .iter()
+    .map(|d| (d.ato_label, d.amount))
+    .collect();
+  assert_eq!(
+    deductions,
+    [
+      (AtoLabel::D3Clothing, dollars(500.0)),
+      (AtoLabel::D9Gifts, dollars(1_000.0)),
+    ]
+  );
+  assert_eq!(estimate.taxable_income, dollars(74_500.0));
+  assert_eq!(estimate.income_tax, dollars(13_138.0));
+  assert_eq!(estimate.medicare_levy, dollars(1_490.0));
+  assert_eq!(estimate.lito, dollars(0.0));
+  assert_eq!(estimate.small_business_offset, dollars(1_000.0));
+  assert_eq!(estimate.total_tax_payable, dollars(13_628.0));
+  assert_eq!(estimate.effective_rate, 18.29);
+  assert_eq!(estimate.payg_paid, dollars(3_000.0));
+  assert_eq!(estimate.estimated_balance, dollars(10_628.0));
+
+  let json = serde_json::to_value(&estimate).unwrap();
+  assert_eq!(json["financialYear"], "2024-25");
+  assert_eq!(json["paygPaid"], 3000.0);
+  assert_eq!(json["personalDeductions"][0]["atoLabel"], "D3_CLOTHING");
+}
+
+#[test]
+fn payg_statuses_follow_the_calendar() {
+  let mut config = PaygConfig::new(
+    fy("2025-26"),
+    PaygMethod::Amount,
+    Some(dollars(4_000.0)),
+    None,
+  );
+  assert!(config
+    .installments
+    .iter()
+    .all(|i| i.assessed_amount == Some(dollars(1_000.0))));
+
+  let statuses = |config: &PaygConfig| -> Vec<PaygStatus> {
+    config.installments.iter().map(|i| i.status).collect()
+  };
+  config.update_statuses(day(2025, 6, 1));
+  assert_eq!(statuses(&config), [PaygStatus::Upcoming; 4]);
+
+  config.update_statuses(day(2025, 11, 15));
+  assert_eq!(
+    statuses(&config),
+    [
+      PaygStatus::Overdue,
+      PaygStatus::Due,
+      PaygStatus::Upcoming,
+      PaygStatus::Upcoming,
+    ]
+  );
+
+  assert!(config.record_payment(Quarter::Q1, dollars(1_000.0), day(2025, 11, 16)));
+  config.update_statuses(day(2026, 3, 1));
+  assert_eq!(
+    statuses(&config),
+    [
+      PaygStatus::Paid,
+      PaygStatus::Overdue,
+      PaygStatus::Due,
+      PaygStatus::Upcoming,
+    ]
+  );
+  assert_eq!(config.installments[1].due_date, day(2026, 2, 28));
+  assert_eq!(config.installments[3].due_date, day(2026, 7, 28));
+}
+
+#[test]
+fn tables_and_payg_round_trip_through_settings() {
+  let db = seeded_ledger();
+  let year = fy("2025-26");
+  assert_eq!(db.tax_tables(year).unwrap(), default_tax_tables(year));
+  assert!(db.payg_config(year, day(2025, 8, 1)).unwrap().is_none());
+
+  let mut custom = default_tax_tables(fy("2030-31"));
+  custom.brackets[1].rate = 0.10;
+  db.save_tax_tables(&custom).unwrap();
+  assert_eq!(db.tax_tables(fy("2030-31")).unwrap(), custom);
+  let years: Vec<String> = db
+    .available_financial_years()
+    .unwrap()
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+  assert_eq!(
+    years,
+    ["2023-24", "2024-25", "2025-26", "2026-27", "2027-28", "2030-31"]
+  );
+
+  let mut payg = PaygConfig::new(year, PaygMethod::Rate, None, Some(0.08));
+  payg.record_payment(Quarter::Q2, dollars(900.0), day(2026, 2, 20));
+  db.save_payg_config(&payg).unwrap();
+  let stored = db.payg_config(year, day(2026, 3, 1)).unwrap().unwrap();
+  assert_eq!(stored.installments[0].status, PaygStatus::Overdue);
+  assert_eq!(stored.installments[1].status, PaygStatus::Paid);
+  assert_eq!(stored.total_paid(), dollars(900.0));
+
+  // Stored in the same JSON shape the TS `TaxService` writes.
+  let raw: serde_json::Value = db.setting_json("payg_2025-26").unwrap().unwrap();
+  assert_eq!(raw["method"], "rate");
+  assert_eq!(raw["installments"][1]["id"], "2025-26-Q2");
+  assert_eq!(raw["installments"][1]["quarter"], "Q2");
+  assert_eq!(raw["installments"][1]["dueDate"], "2026-02-28");
+  assert_eq!(raw["installments"][1]["paidAmount"], 900.0);
+}
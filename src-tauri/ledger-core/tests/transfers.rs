@@ -0,0 +1,362 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::*;
+use ledger_core::db::{Database, TransferPair};
+use ledger_core::matching::{
+  max_score_assignment, transfer_match_score, TransferCandidate, TransferMatchConfig,
+  TransferMatchType,
+};
+use ledger_core::model::Posting;
+
+fn database() -> Database {
+  let mut db = Database::from_connection(book()).unwrap();
+  for account in [
+    bank("everyday", "Everyday"),
+    bank("savings", "Savings"),
+    expense("uncategorized", "Uncategorized"),
+    expense("groceries", "Groceries"),
+  ] {
+    db.insert_account(&account).unwrap();
+  }
+  for (id, on, payee, account, category, amount) in [
+    (
+      "a-exact",
+      date(2025, 8, 1),
+      "Transfer to Savings",
+      "everyday",
+      "uncategorized",
+      -500.0,
+    ),
+    (
+      "b-exact",
+      date(2025, 8, 1),
+      "Internet transfer from Everyday",
+      "savings",
+      "uncategorized",
+      500.0,
+    ),
+    (
+      "a-late",
+      date(2025, 8, 10),
+      "Online payment",
+      "everyday",
+      "uncategorized",
+      -200.0,
+    ),
+    (
+      "b-late",
+      date(2025, 8, 12),
+      "Deposit",
+      "savings",
+      "uncategorized",
+      200.0,
+    ),
+    (
+      "a-shop",
+      date(2025, 8, 1),
+      "Woolworths",
+      "everyday",
+      "groceries",
+      -500.0,
+    ),
+  ] {
+    db.insert_transaction(&side(id, on, payee, account, category, amount))
+      .unwrap();
+  }
+  db
+}
+
+/// A bank line booked against a category.
+fn side(
+  id: &str,
+  on: DateTime<Utc>,
+  payee: &str,
+  account: &str,
+  category: &str,
+  amount: f64,
+) -> ledger_core::Transaction {
+  transaction(
+    id,
+    on,
+    payee,
+    vec![
+      Posting {
+        id: format!("{id}-bank"),
+        ..posting(account, amount)
+      },
+      Posting {
+        id: format!("{id}-category"),
+        ..posting(category, -amount)
+      },
+    ],
+  )
+}
+
+fn candidate(payee: &str, on: DateTime<Utc>, amount: f64) -> TransferCandidate {
+  TransferCandidate {
+    transaction_id: payee.into(),
+    date: on,
+    payee: payee.into(),
+    memo: None,
+    account_id: "everyday".into(),
+    posting_id: "p".into(),
+    amount: dollars(amount),
+    category_posting_id: "c".into(),
+    category_account_id: "uncategorized".into(),
+    category_name: "Uncategorized".into(),
+    is_reconciled: false,
+  }
+}
+
+#[test]
+fn assignment_beats_greedy_pairing() {
+  // Greedy takes the 10 and is left with 0; the best total is 9 + 9.
+  assert_eq!(
+    max_score_assignment(&[vec![10, 9], vec![9, 0]]),
+    [Some(1), Some(0)]
+  );
+  // More rows than columns, and a row nothing is worth pairing with.
+  assert_eq!(
+    max_score_assignment(&[vec![0, 0], vec![5, 1], vec![4, 6]]),
+    [None, Some(0), Some(1)]
+  );
+  assert_eq!(max_score_assignment(&[vec![3, 7, 2]]), [Some(1)]);
+  assert_eq!(max_score_assignment(&[]), Vec::<Option<usize>>::new());
+}
+
+#[test]
+fn scores_amount_date_and_keywords() {
+  let config = TransferMatchConfig::default();
+  let (score, reasons) = transfer_match_score(
+    &candidate("Transfer to Savings", date(2025, 8, 1), -500.0),
+    &candidate("TFR from Everyday", date(2025, 8, 2), 500.0),
+    &config,
+  );
+  assert_eq!(score, 95);
+  assert_eq!(
+    reasons,
+    [
+      "Exact amount match with opposite signs",
+      "Date within 1 day",
+      "Both payees contain transfer keywords"
+    ]
+  );
+
+  let (score, reasons) = transfer_match_score(
+    &candidate("Payment", date(2025, 8, 1), -500.0),
+    &candidate("Deposit", date(2025, 8, 6), 499.5),
+    &config,
+  );
+  assert_eq!(score, 20);
+  assert_eq!(reasons, ["Amount within 1.00", "Date within 7 days"]);
+
+  // Outside the window nothing counts.
+  let (score, _) = transfer_match_score(
+    &candidate("Transfer", date(2025, 8, 1), -500.0),
+    &candidate("Transfer", date(2025, 8, 9), 500.0),
+    &config,
+  );
+  assert_eq!(score, 0);
+}
+
+#[test]
+fn finds_candidates_and_grades_matches() {
+  let db = database();
+  let candidates = db
+    .transfer_candidates("everyday", None, None, &TransferMatchConfig::default())
+    .unwrap();
+  let ids: Vec<&str> = candidates
+    .iter()
+    .map(|c| c.transaction_id.as_str())
+    .collect();
+  // Groceries isn't uncategorised and Woolworths isn't a transfer keyword.
+  assert_eq!(ids, ["a-exact", "a-late"]);
+
+  let preview = db
+    .match_transfers("everyday", "savings", None, None)
+    .unwrap();
+  let matches: Vec<(&str, &str, u32, TransferMatchType)> = preview
+    .matches
+    .iter()
+    .map(|m| {
+      (
+        m.candidate_a.transaction_id.as_str(),
+        m.candidate_b.transaction_id.as_str(),
+        m.match_score,
+        m.match_type,
+      )
+    })
+    .collect();
+  assert_eq!(
+    matches,
+    [
+      ("a-exact", "b-exact", 100, TransferMatchType::Exact),
+      ("a-late", "b-late", 65, TransferMatchType::Probable),
+    ]
+  );
+  assert_eq!(preview.summary.unmatched, 0);
+}
+
+#[test]
+fn config_is_per_account_pair() {
+  let db = database();
+  let config = TransferMatchConfig {
+    date_window_days: 1,
+    ..TransferMatchConfig::default()
+  };
+  // Saved one way round, read the other.
+  db.save_transfer_match_config("savings", "everyday", &config)
+    .unwrap();
+  assert_eq!(
+    db.transfer_match_config("everyday", "savings").unwrap(),
+    config
+  );
+  assert_eq!(
+    db.transfer_match_config("everyday", "groceries").unwrap(),
+    TransferMatchConfig::default()
+  );
+
+  let preview = db
+    .match_transfers("everyday", "savings", None, None)
+    .unwrap();
+  assert_eq!(preview.matches.len(), 1);
+  let unmatched: Vec<&str> = preview
+    .unmatched_a
+    .iter()
+    .chain(&preview.unmatched_b)
+    .map(|c| c.transaction_id.as_str())
+    .collect();
+  assert_eq!(unmatched, ["a-late", "b-late"]);
+}
+
+#[test]
+fn commits_exact_matches_in_bulk() {
+  let mut db = database();
+  let result = db
+    .commit_exact_transfer_matches("everyday", "savings", None, None)
+    .unwrap();
+  assert_eq!((result.merged, result.skipped), (1, 0));
+
+  let merged = db.transaction("a-exact").unwrap();
+  let postings: Vec<(&str, i64)> = merged
+    .postings
+    .iter()
+    .map(|p| (p.account_id.as_str(), p.amount.cents()))
+    .collect();
+  assert_eq!(postings, [("everyday", -50_000), ("savings", 50_000)]);
+  let metadata: serde_json::Value =
+    serde_json::from_str(merged.metadata.as_deref().unwrap()).unwrap();
+  assert_eq!(metadata["transferMatched"], true);
+  assert_eq!(metadata["mergedTransactionId"], "b-exact");
+  assert_eq!(metadata["mergedPayee"], "Internet transfer from Everyday");
+  assert!(db.transaction("b-exact").is_err());
+
+  // Only the probable match is left.
+  let preview = db
+    .match_transfers("everyday", "savings", None, None)
+    .unwrap();
+  assert_eq!(preview.summary.exact_matches, 0);
+  assert_eq!(preview.summary.probable_matches, 1);
+}
+
+#[test]
+fn reconciled_sides_are_skipped() {
+  let mut db = database();
+  db.mark_reconciled(&["b-late-bank".into()], true).unwrap();
+  let result = db
+    .commit_transfer_matches(&[
+      TransferPair {
+        candidate_a_id: "a-late".into(),
+        candidate_b_id: "b-late".into(),
+      },
+      TransferPair {
+        candidate_a_id: "a-exact".into(),
+        candidate_b_id: "b-exact".into(),
+      },
+    ])
+    .unwrap();
+  assert_eq!((result.merged, result.skipped), (1, 1));
+  assert_eq!(result.errors, ["Cannot merge reconciled transactions"]);
+  assert_eq!(db.transaction("a-late").unwrap().postings.len(), 2);
+  assert!(db.transaction("b-late").is_ok());
+}
+
+#[test]
+fn sides_going_the_same_way_are_not_merged() {
+  let mut db = database();
+  for (id, account) in [("a-same", "everyday"), ("b-same", "savings")] {
+    db.insert_transaction(&side(
+      id,
+      date(2025, 8, 20),
+      "Transfer",
+      account,
+      "uncategorized",
+      -300.0,
+    ))
+    .unwrap();
+  }
+  let preview = db
+    .match_transfers("everyday", "savings", None, None)
+    .unwrap();
+  assert!(preview
+    .matches
+    .iter()
+    .any(|m| m.candidate_a.transaction_id == "a-same" && m.match_type == TransferMatchType::Exact));
+
+  let result = db
+    .commit_exact_transfer_matches("everyday", "savings", None, None)
+    .unwrap();
+  assert_eq!((result.merged, result.skipped), (1, 0));
+  let kept = db.transaction("a-same").unwrap();
+  assert_eq!(kept.postings[0].amount.cents(), -30_000);
+  assert!(db.transaction("b-same").is_ok());
+
+  // Nor when the pair is picked by hand.
+  let result = db
+    .commit_transfer_matches(&[TransferPair {
+      candidate_a_id: "a-same".into(),
+      candidate_b_id: "b-same".into(),
+    }])
+    .unwrap();
+  assert_eq!(result.errors, ["Both transactions move money the same way"]);
+  assert!(db.transaction("b-same").is_ok());
+}
+
+#[test]
+fn splits_are_not_merged() {
+  let mut db = database();
+  db.insert_transaction(&transaction(
+    "a-split",
+    date(2025, 8, 20),
+    "Transfer and lunch",
+    vec![
+      posting("everyday", -320.0),
+      posting("uncategorized", 300.0),
+      posting("groceries", 20.0),
+    ],
+  ))
+  .unwrap();
+  db.insert_transaction(&side(
+    "b-split",
+    date(2025, 8, 20),
+    "Transfer",
+    "savings",
+    "uncategorized",
+    300.0,
+  ))
+  .unwrap();
+  let result = db
+    .commit_transfer_matches(&[TransferPair {
+      candidate_a_id: "a-split".into(),
+      candidate_b_id: "b-split".into(),
+    }])
+    .unwrap();
+  assert_eq!((result.merged, result.skipped), (0, 1));
+  assert_eq!(
+    result.errors,
+    ["Only transactions with exactly two postings can be merged"]
+  );
+  assert_eq!(db.transaction("a-split").unwrap().postings.len(), 3);
+  assert!(db.transaction("b-split").is_ok());
+}
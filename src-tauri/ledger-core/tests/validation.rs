@@ -0,0 +1,192 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::*;
+use ledger_core::model::{Account, AccountKind, AccountSubtype, AccountType, GstCode};
+use ledger_core::validation::{validate_double_entry, validate_gst, validate_transaction};
+use ledger_core::ValidationError;
+
+fn accounts() -> HashMap<String, Account> {
+  [
+    bank("checking", "Personal Checking"),
+    expense("groceries", "Groceries"),
+    expense("office", "Office Supplies"),
+    income("sales", "Sales"),
+    gst_paid(),
+    gst_collected(),
+  ]
+  .into_iter()
+  .map(|a| (a.id.clone(), a))
+  .collect()
+}
+
+#[test]
+fn accepts_a_simple_personal_transaction() {
+  let tx = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Woolworths",
+    vec![posting("checking", -50.0), posting("groceries", 50.0)],
+  );
+  assert_eq!(validate_transaction(&tx, &accounts()), Ok(()));
+}
+
+#[test]
+fn rejects_postings_that_do_not_sum_to_zero() {
+  let err =
+    validate_double_entry(&[posting("checking", -100.0), posting("groceries", 90.0)]).unwrap_err();
+  assert!(matches!(err, ValidationError::Unbalanced { .. }));
+  assert!(err.to_string().contains("must sum to zero"));
+}
+
+#[test]
+fn rejects_a_single_posting() {
+  let err = validate_double_entry(&[posting("checking", -100.0)]).unwrap_err();
+  assert!(err.to_string().contains("must sum to zero"));
+}
+
+#[test]
+fn balances_exactly_where_floats_would_drift() {
+  let postings = [
+    posting("checking", -0.3),
+    posting("groceries", 0.1),
+    posting("groceries", 0.2),
+  ];
+  assert_eq!(validate_double_entry(&postings), Ok(()));
+}
+
+#[test]
+fn rejects_a_one_cent_imbalance() {
+  let err =
+    validate_double_entry(&[posting("checking", -10.0), posting("groceries", 9.99)]).unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "Transaction postings must sum to zero. Current sum: -0.01"
+  );
+}
+
+#[test]
+fn rejects_personal_posting_with_gst_information() {
+  let mut p = posting("groceries", 110.0);
+  p.gst_code = Some(GstCode::Gst);
+  p.gst_amount = Some(dollars(10.0));
+  assert!(matches!(
+    validate_gst(&p),
+    Err(ValidationError::PersonalWithGst { .. })
+  ));
+}
+
+#[test]
+fn accepts_business_posting_without_gst_for_gst_free_items() {
+  let mut p = business(posting("office", 100.0));
+  p.gst_code = Some(GstCode::GstFree);
+  assert_eq!(validate_gst(&p), Ok(()));
+}
+
+#[test]
+fn requires_rate_and_amount_for_taxable_codes() {
+  let mut p = business(posting("office", 100.0));
+  p.gst_code = Some(GstCode::Gst);
+  assert!(matches!(
+    validate_gst(&p),
+    Err(ValidationError::MissingGstDetails {
+      code: GstCode::Gst,
+      ..
+    })
+  ));
+}
+
+#[test]
+fn accepts_a_manual_gst_amount_below_the_maximum() {
+  // Insurance premium with GST-free stamp duty bundled into the line.
+  assert_eq!(
+    validate_gst(&with_gst(posting("office", 200.0), 12.5)),
+    Ok(())
+  );
+}
+
+#[test]
+fn rejects_gst_above_the_maximum() {
+  // Gross amount typed into the GST field.
+  let err = validate_gst(&with_gst(posting("office", 100.0), 110.0)).unwrap_err();
+  assert!(matches!(err, ValidationError::ImplausibleGst { .. }));
+  assert!(err
+    .to_string()
+    .contains("most this line could carry is 10.00"));
+}
+
+#[test]
+fn accepts_gst_booked_to_gst_paid() {
+  let tx = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Officeworks",
+    vec![
+      business(posting("checking", -110.0)),
+      with_gst(posting("office", 100.0), 10.0),
+      business(posting("gst-paid", 10.0)),
+    ],
+  );
+  assert_eq!(validate_transaction(&tx, &accounts()), Ok(()));
+}
+
+#[test]
+fn rejects_gst_with_no_control_posting() {
+  let tx = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Officeworks",
+    vec![
+      business(posting("checking", -110.0)),
+      with_gst(posting("office", 110.0), 10.0),
+    ],
+  );
+  assert!(matches!(
+    validate_transaction(&tx, &accounts()),
+    Err(ValidationError::GstWithoutControlAccount { .. })
+  ));
+}
+
+#[test]
+fn accepts_a_legacy_gst_control_subtype() {
+  let mut accounts = accounts();
+  let control = Account {
+    subtype: Some(AccountSubtype::GstControl),
+    ..account(
+      "control",
+      "GST Control",
+      AccountType::Liability,
+      AccountKind::Transfer,
+    )
+  };
+  accounts.insert(control.id.clone(), control);
+
+  let tx = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Client",
+    vec![
+      business(posting("checking", 110.0)),
+      with_gst(posting("sales", -100.0), 10.0),
+      business(posting("control", -10.0)),
+    ],
+  );
+  assert_eq!(validate_transaction(&tx, &accounts), Ok(()));
+}
+
+#[test]
+fn rejects_postings_to_unknown_accounts() {
+  let tx = transaction(
+    "t1",
+    date(2025, 8, 1),
+    "Mystery",
+    vec![posting("checking", -5.0), posting("nowhere", 5.0)],
+  );
+  assert_eq!(
+    validate_transaction(&tx, &accounts()),
+    Err(ValidationError::UnknownAccount {
+      account_id: "nowhere".into()
+    })
+  );
+}
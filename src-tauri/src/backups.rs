@@ -0,0 +1,135 @@
+//! Scheduled snapshots of the active book.
+//!
+//! [`start`] takes one when the app comes up and then wakes every minute to
+//! check the book's [`BackupSchedule`]; once `interval_minutes` have passed
+//! and something has committed since the last snapshot, it takes another and
+//! rotates the old ones out. [`on_exit`] takes a last one if the book is
+//! still dirty.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ledger_core::backup::{BackupDir, BackupInfo, BackupKind, BackupSchedule, ChangeMarker};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::book::ActiveBook;
+use crate::commands::CommandResult;
+
+const TICK: Duration = Duration::from_secs(60);
+
+/// Managed state for the scheduler.
+#[derive(Default)]
+pub struct BackupTask {
+  stop: Arc<AtomicBool>,
+  /// The book and its change marker as of its last snapshot.
+  last: Mutex<Option<(PathBuf, ChangeMarker)>>,
+}
+
+impl BackupTask {
+  /// Treat the next scheduled check as dirty, e.g. after a restore.
+  pub fn forget(&self) {
+    self.last.lock().unwrap().take();
+  }
+}
+
+/// Snapshot the active book into `backups/` beside it, encrypted if the
+/// book is.
+pub fn snapshot(book: &ActiveBook, kind: BackupKind) -> CommandResult<BackupInfo> {
+  let dir = book.backup_dir()?;
+  book.with(|db| dir.create(db.connection(), kind))
+}
+
+/// The active book's schedule, or the default when no book is open.
+fn schedule(book: &ActiveBook) -> BackupSchedule {
+  book.with(|db| db.backup_schedule()).unwrap_or_default()
+}
+
+/// Take an automatic snapshot if the book changed since the last one (or
+/// unconditionally with `force`), then rotate.
+fn run<R: Runtime>(app: &AppHandle<R>, force: bool) {
+  let (Some(book), Some(task)) = (app.try_state::<ActiveBook>(), app.try_state::<BackupTask>())
+  else {
+    return;
+  };
+  let Some(path) = book.path() else {
+    return;
+  };
+  let marker = match book.with(|db| ChangeMarker::of(db.connection())) {
+    Ok(marker) => marker,
+    Err(err) => {
+      log::warn!("Skipping scheduled backup: {err}");
+      return;
+    }
+  };
+  let mut last = task.last.lock().unwrap();
+  if !force && last.as_ref() == Some(&(path.clone(), marker)) {
+    return;
+  }
+
+  match snapshot(&book, BackupKind::Auto) {
+    Ok(info) => {
+      log::info!("Created backup {}", info.filename);
+      // Snapshotting doesn't commit, so the marker still describes the book.
+      *last = Some((path.clone(), marker));
+      let keep = schedule(&book).keep;
+      match BackupDir::for_book(&path).clean_old(keep) {
+        Ok(0) => {}
+        Ok(removed) => log::info!("Removed {removed} old backup(s)"),
+        Err(err) => log::warn!("Failed to rotate backups: {err}"),
+      }
+    }
+    Err(err) => log::error!("Scheduled backup failed: {err}"),
+  }
+}
+
+/// Take the startup snapshot and start the periodic one on a background
+/// thread.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+  let task = BackupTask::default();
+  let stop = task.stop.clone();
+  app.manage(task);
+
+  let app = app.clone();
+  thread::Builder::new()
+    .name("backup-scheduler".into())
+    .spawn(move || {
+      if app
+        .try_state::<ActiveBook>()
+        .is_some_and(|book| schedule(&book).on_startup)
+      {
+        run(&app, true);
+      }
+
+      let mut since = Instant::now();
+      while !stop.load(Ordering::SeqCst) {
+        thread::sleep(TICK);
+        let Some(book) = app.try_state::<ActiveBook>() else {
+          continue;
+        };
+        let minutes = schedule(&book).interval_minutes;
+        if minutes > 0 && since.elapsed() >= Duration::from_secs(u64::from(minutes) * 60) {
+          run(&app, false);
+          since = Instant::now();
+        }
+      }
+    })
+    .expect("failed to spawn backup scheduler thread");
+}
+
+/// Stop the scheduler and, if the schedule asks for it, snapshot the book
+/// one last time.
+pub fn on_exit<R: Runtime>(app: &AppHandle<R>) {
+  let Some(task) = app.try_state::<BackupTask>() else {
+    return;
+  };
+  task.stop.store(true, Ordering::SeqCst);
+  if app
+    .try_state::<ActiveBook>()
+    .is_some_and(|book| schedule(&book).on_exit)
+  {
+    run(app, false);
+  }
+}
@@ -0,0 +1,259 @@
+//! The book the native commands read and write.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ledger_core::backup::BackupDir;
+use ledger_core::db::Database;
+use ledger_core::encryption::{self, BookKey};
+use ledger_core::migrate::{self, MigrationReport};
+use ledger_core::money_storage::{self, MoneyStorage};
+use ledger_core::rusqlite::OpenFlags;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::commands::{CommandError, CommandResult};
+
+/// Managed state holding the open [`Database`], if any.
+#[derive(Default)]
+pub struct ActiveBook(Mutex<BookSlot>);
+
+#[derive(Default)]
+struct BookSlot {
+  open: Option<OpenBook>,
+  /// Why the last attempt to open a book failed, for the frontend to show.
+  error: Option<String>,
+  /// An encrypted book waiting for its passphrase.
+  locked: Option<PathBuf>,
+}
+
+struct OpenBook {
+  path: PathBuf,
+  db: Database,
+  key: Option<BookKey>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookStatus {
+  pub path: Option<PathBuf>,
+  pub error: Option<String>,
+  pub encrypted: bool,
+  /// Set when the last book asked for was encrypted and needs
+  /// `unlock_book`.
+  pub locked: Option<PathBuf>,
+}
+
+impl ActiveBook {
+  /// Migrate and open `path`, replacing (and closing) any book already open.
+  ///
+  /// Books written by a newer app are refused rather than opened. An
+  /// encrypted book is left locked until [`ActiveBook::unlock`].
+  pub fn open(&self, path: PathBuf) -> CommandResult<MigrationReport> {
+    self.open_with_key(path, None)
+  }
+
+  /// Open the encrypted book at `path` with `passphrase`.
+  pub fn unlock(&self, path: PathBuf, passphrase: &str) -> CommandResult<MigrationReport> {
+    let key = BookKey::for_book(&path, passphrase)?;
+    self.open_with_key(path, Some(key))
+  }
+
+  /// [`ActiveBook::open`] with the key for an encrypted book. A new book
+  /// at `path` is created encrypted with `key`.
+  pub fn open_with_key(
+    &self,
+    path: PathBuf,
+    key: Option<BookKey>,
+  ) -> CommandResult<MigrationReport> {
+    let opened = migrate::migrate_encrypted_book(&path, key.as_ref())
+      .map_err(CommandError::from)
+      .and_then(|report| {
+        let conn = encryption::open(&path, key.as_ref())?;
+        Ok((report, Database::from_connection(conn)?))
+      });
+    let mut slot = self.0.lock().unwrap();
+    match opened {
+      Ok((report, db)) => {
+        *slot = BookSlot {
+          open: Some(OpenBook { path, db, key }),
+          error: None,
+          locked: None,
+        };
+        Ok(report)
+      }
+      Err(err) => {
+        let locked = matches!(encryption::is_encrypted(&path), Ok(true));
+        slot.locked = locked.then_some(path);
+        slot.error = Some(err.to_string());
+        Err(err)
+      }
+    }
+  }
+
+  pub fn close(&self) {
+    self.0.lock().unwrap().open.take();
+  }
+
+  pub fn path(&self) -> Option<PathBuf> {
+    let slot = self.0.lock().unwrap();
+    slot.open.as_ref().map(|book| book.path.clone())
+  }
+
+  /// The open book's key, if it's encrypted.
+  pub fn key(&self) -> Option<BookKey> {
+    let slot = self.0.lock().unwrap();
+    slot.open.as_ref().and_then(|book| book.key.clone())
+  }
+
+  /// Where the open book's snapshots go, keyed like the book.
+  pub fn backup_dir(&self) -> CommandResult<BackupDir> {
+    let slot = self.0.lock().unwrap();
+    let book = slot
+      .open
+      .as_ref()
+      .ok_or_else(|| CommandError::new("No book is open"))?;
+    Ok(BackupDir::for_book(&book.path).with_key(book.key.clone()))
+  }
+
+  pub fn status(&self) -> BookStatus {
+    let slot = self.0.lock().unwrap();
+    BookStatus {
+      path: slot.open.as_ref().map(|book| book.path.clone()),
+      error: slot.error.clone(),
+      encrypted: slot.open.as_ref().is_some_and(|book| book.key.is_some()),
+      locked: slot.locked.clone(),
+    }
+  }
+
+  /// Run `f` against the open book.
+  pub fn with<T, E>(&self, f: impl FnOnce(&mut Database) -> Result<T, E>) -> CommandResult<T>
+  where
+    E: std::error::Error,
+  {
+    let mut slot = self.0.lock().unwrap();
+    let book = slot
+      .open
+      .as_mut()
+      .ok_or_else(|| CommandError::new("No book is open"))?;
+    Ok(f(&mut book.db)?)
+  }
+}
+
+/// Refuse a book the API server can't work with: Prisma can't open an
+/// encrypted book, and reads the money columns as dollars, so not one
+/// migrated to cents either. A book that doesn't exist yet is fine; the
+/// server creates it.
+pub fn check_servable(book: &Path) -> std::io::Result<()> {
+  if !book.exists() {
+    return Ok(());
+  }
+  if encryption::is_encrypted(book)? {
+    return Err(std::io::Error::other(format!(
+      "{} is encrypted, and the API server can't read encrypted books; decrypt it first",
+      book.display()
+    )));
+  }
+  let storage = encryption::open_with_flags(book, OpenFlags::SQLITE_OPEN_READ_ONLY, None)
+    .map_err(std::io::Error::other)
+    .and_then(|conn| money_storage::money_storage(&conn).map_err(std::io::Error::other))?;
+  if storage == MoneyStorage::Cents {
+    return Err(std::io::Error::other(format!(
+      "{} stores amounts in cents, which the API server would read as dollars",
+      book.display()
+    )));
+  }
+  Ok(())
+}
+
+/// Whether `a` and `b` name the same file, however they're spelled.
+pub fn same_file(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => a == b,
+  }
+}
+
+/// The `prisma` directory books are found in: in the project (debug) or in
+/// app data (release).
+pub fn prisma_dir<R: Runtime>(app: &AppHandle<R>) -> std::io::Result<PathBuf> {
+  let prisma_dir = if cfg!(debug_assertions) {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+      .parent()
+      .expect("src-tauri has a parent directory")
+      .join("prisma")
+  } else {
+    app
+      .path()
+      .app_data_dir()
+      .map_err(|err| std::io::Error::other(err.to_string()))?
+      .join("prisma")
+  };
+  std::fs::create_dir_all(&prisma_dir)?;
+  Ok(prisma_dir)
+}
+
+/// The book the API server starts on: the file in `DATABASE_URL` if set,
+/// otherwise `dev.db` in the `prisma` directory.
+///
+/// Relative `file:` URLs resolve against that `prisma` directory, as Prisma
+/// resolves them against the schema.
+pub fn default_book_path<R: Runtime>(app: &AppHandle<R>) -> std::io::Result<PathBuf> {
+  let prisma_dir = prisma_dir(app)?;
+  Ok(match std::env::var("DATABASE_URL") {
+    Ok(url) => prisma_dir.join(url.trim_start_matches("file:")),
+    Err(_) => prisma_dir.join("dev.db"),
+  })
+}
+
+/// Where the API server records the book `/api/books/switch` last chose,
+/// in the `prisma` directory, so it reopens it when it restarts.
+const SERVED_BOOK_FILE: &str = "active-book.json";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServedBook {
+  database_path: PathBuf,
+}
+
+/// The book the API server serves: the one it recorded in
+/// `active-book.json`, or [`default_book_path`].
+pub fn served_book_path<R: Runtime>(app: &AppHandle<R>) -> std::io::Result<PathBuf> {
+  let prisma_dir = prisma_dir(app)?;
+  let saved = std::fs::read_to_string(prisma_dir.join(SERVED_BOOK_FILE))
+    .ok()
+    .and_then(|json| serde_json::from_str::<ServedBook>(&json).ok());
+  match saved {
+    // Like `resolveDbUrl`, an absolute path is taken as it is.
+    Some(saved) => Ok(prisma_dir.join(saved.database_path)),
+    None => default_book_path(app),
+  }
+}
+
+/// `book` as `/api/books/switch` takes it: relative to the `prisma`
+/// directory when it's inside it, otherwise absolute.
+pub fn served_book_name<R: Runtime>(app: &AppHandle<R>, book: &Path) -> std::io::Result<PathBuf> {
+  let prisma_dir = prisma_dir(app)?.canonicalize()?;
+  let book = book.canonicalize().unwrap_or_else(|_| book.to_path_buf());
+  Ok(match book.strip_prefix(&prisma_dir) {
+    // With `/` separators on every platform, like the paths `bookManager`
+    // makes (`books/<id>/ledger.ledgerhound`).
+    Ok(relative) => relative
+      .components()
+      .map(|part| part.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/")
+      .into(),
+    Err(_) => book,
+  })
+}
+
+/// Record `book` in `active-book.json`, as `/api/books/switch` does, so the
+/// API server opens it next time it starts.
+pub fn set_served_book_path<R: Runtime>(app: &AppHandle<R>, book: &Path) -> std::io::Result<()> {
+  let saved = ServedBook {
+    database_path: served_book_name(app, book)?,
+  };
+  let json = serde_json::to_string_pretty(&saved).map_err(std::io::Error::other)?;
+  std::fs::write(prisma_dir(app)?.join(SERVED_BOOK_FILE), json)
+}
@@ -0,0 +1,48 @@
+//! The books registry, kept in `books.json` in the app data directory in
+//! place of `bookManager`'s `localStorage` entries. Relative database paths
+//! resolve against the `prisma` directory, as the API server's
+//! `switchDatabase` resolves them.
+
+use std::sync::Mutex;
+
+use ledger_core::book_manager::{BookRegistry, RegistryError};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::book;
+use crate::commands::{CommandError, CommandResult};
+
+const FILE_NAME: &str = "books.json";
+
+/// Managed state holding the registry, or why it couldn't be loaded.
+pub struct Books(Result<Mutex<BookRegistry>, String>);
+
+impl Books {
+  pub fn with<T>(
+    &self,
+    f: impl FnOnce(&mut BookRegistry) -> Result<T, RegistryError>,
+  ) -> CommandResult<T> {
+    match &self.0 {
+      Ok(registry) => Ok(f(&mut registry.lock().unwrap())?),
+      Err(reason) => Err(CommandError::new(reason.clone())),
+    }
+  }
+}
+
+/// Load and manage the registry. A corrupt one is left on disk for the
+/// user to recover rather than replaced with an empty list.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+  let registry = app
+    .path()
+    .app_data_dir()
+    .map_err(|err| err.to_string())
+    .and_then(|dir| {
+      // Not the directory of the book `DATABASE_URL` names, which needn't
+      // be the root of the `books/<id>/` layout.
+      let root = book::prisma_dir(app).map_err(|err| err.to_string())?;
+      BookRegistry::load(dir.join(FILE_NAME), root).map_err(|err| err.to_string())
+    });
+  if let Err(reason) = &registry {
+    log::error!("No books registry: {reason}");
+  }
+  app.manage(Books(registry.map(Mutex::new)));
+}
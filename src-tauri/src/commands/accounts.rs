@@ -0,0 +1,64 @@
+use ledger_core::balance::BalanceOptions;
+use ledger_core::db::DbResult;
+use ledger_core::model::Account;
+use ledger_core::money::Money;
+use serde::Serialize;
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+/// Same shape as `AccountWithBalance` in `src/types/index.ts`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountWithBalance {
+  #[serde(flatten)]
+  pub account: Account,
+  pub current_balance: Money,
+  pub cleared_balance: Money,
+}
+
+#[tauri::command]
+pub async fn get_accounts_with_balances(
+  include_archived: Option<bool>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<AccountWithBalance>> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(include_archived.unwrap_or(false))?;
+    let current = db.account_balances(BalanceOptions::default())?;
+    let cleared = db.account_balances(BalanceOptions {
+      cleared_only: true,
+      ..Default::default()
+    })?;
+    Ok(
+      accounts
+        .into_iter()
+        .map(|account| AccountWithBalance {
+          current_balance: current.get(&account.id).copied().unwrap_or_default(),
+          cleared_balance: cleared.get(&account.id).copied().unwrap_or_default(),
+          account,
+        })
+        .collect(),
+    )
+  })
+}
+
+#[tauri::command]
+pub async fn get_account_balance(
+  account_id: String,
+  cleared_only: Option<bool>,
+  reconciled_only: Option<bool>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Money> {
+  book.with(|db| {
+    db.account_balance(
+      &account_id,
+      BalanceOptions {
+        up_to: None,
+        cleared_only: cleared_only.unwrap_or(false),
+        reconciled_only: reconciled_only.unwrap_or(false),
+      },
+    )
+  })
+}
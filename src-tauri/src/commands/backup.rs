@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use ledger_core::backup::{BackupInfo, BackupKind, BackupManifest, BackupSchedule};
+use ledger_core::encryption;
+use tauri::{AppHandle, Manager, State};
+
+use crate::backups::{self, BackupTask};
+use crate::book::ActiveBook;
+use crate::sidecar::ApiServer;
+use crate::{menu, secrets, tray};
+
+use super::{CommandError, CommandResult};
+
+/// Same default as `cleanOldBackups`.
+const DEFAULT_KEEP: usize = 10;
+
+fn book_path(book: &ActiveBook) -> CommandResult<PathBuf> {
+  book
+    .path()
+    .ok_or_else(|| CommandError::new("No book is open"))
+}
+
+/// `POST /api/backup/create`.
+#[tauri::command]
+pub async fn create_backup(
+  kind: Option<BackupKind>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<BackupInfo> {
+  backups::snapshot(&book, kind.unwrap_or(BackupKind::Manual))
+}
+
+/// `GET /api/backup/list`, newest first.
+#[tauri::command]
+pub async fn list_backups(book: State<'_, ActiveBook>) -> CommandResult<Vec<BackupInfo>> {
+  Ok(book.backup_dir()?.list()?)
+}
+
+/// `DELETE /api/backup/:filename`.
+#[tauri::command]
+pub async fn delete_backup(filename: String, book: State<'_, ActiveBook>) -> CommandResult<()> {
+  Ok(book.backup_dir()?.delete(&filename)?)
+}
+
+/// `POST /api/backup/clean`, for automatic snapshots only (see
+/// [`ledger_core::backup::BackupDir::clean_old`]). Returns how many were
+/// deleted.
+#[tauri::command]
+pub async fn clean_old_backups(
+  keep_count: Option<usize>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<usize> {
+  let keep = keep_count.unwrap_or(DEFAULT_KEEP);
+  Ok(book.backup_dir()?.clean_old(keep)?)
+}
+
+/// `POST /api/backup/restore`. Closes the book while the verified snapshot
+/// is swapped in, then opens it again; if the API server serves the book it
+/// is stopped meanwhile and comes back on the restored book. If the snapshot
+/// fails verification the book is reopened untouched.
+///
+/// The book comes back encrypted however the snapshot was, so a snapshot
+/// taken before the book was encrypted restores a plaintext book. An
+/// encrypted snapshot of the book the server serves is refused, since the
+/// server can't read it.
+///
+/// `passphrase` is the one an encrypted snapshot was taken under, when the
+/// book's has changed since; the book reopens with it.
+#[tauri::command]
+pub async fn restore_backup(
+  filename: String,
+  passphrase: Option<String>,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<BackupManifest> {
+  let path = book_path(&book)?;
+  let dir = book.backup_dir()?;
+  let server = app
+    .try_state::<ApiServer>()
+    .filter(|server| server.serves(&app, &path));
+  if server.is_some()
+    && matches!(
+      encryption::is_encrypted(&dir.path().join(&filename)),
+      Ok(true)
+    )
+  {
+    return Err(CommandError::new(format!(
+      "{filename} is encrypted, and the API server can't read encrypted books; open another book before restoring it"
+    )));
+  }
+  let key = book.key();
+  book.close();
+  let restore = || dir.restore(&filename, &path, passphrase.as_deref());
+  let restored = match server {
+    Some(server) => server.with_server_stopped(restore),
+    None => restore(),
+  };
+  let reopened = match (encryption::is_encrypted(&path), &passphrase) {
+    (Ok(true), Some(passphrase)) if restored.is_ok() => book.unlock(path, passphrase),
+    (Ok(true), _) => book.open_with_key(path, key),
+    _ => book.open(path),
+  };
+  if let Some(task) = app.try_state::<BackupTask>() {
+    task.forget();
+  }
+  menu::sync(&app);
+  tray::refresh(&app);
+
+  let manifest = restored?;
+  reopened?;
+  log::info!("Restored {filename}");
+  // An older snapshot may still have keys in its settings.
+  secrets::move_out_of_book(&app);
+  Ok(manifest)
+}
+
+#[tauri::command]
+pub async fn get_backup_schedule(book: State<'_, ActiveBook>) -> CommandResult<BackupSchedule> {
+  book.with(|db| db.backup_schedule())
+}
+
+/// Takes effect at the scheduler's next check.
+#[tauri::command]
+pub async fn save_backup_schedule(
+  schedule: BackupSchedule,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  book.with(|db| db.save_backup_schedule(&schedule))
+}
@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use ledger_core::encryption::{self, BookKey, EncryptionError};
+use ledger_core::migrate::{self, MigrationReport};
+use tauri::{AppHandle, Manager, State};
+
+use crate::backups::BackupTask;
+use crate::book::{ActiveBook, BookStatus};
+use crate::instance::{OpenBookRequest, PendingBookOpen};
+use crate::sidecar::{self, ApiServer};
+use crate::{menu, secrets, tray};
+
+use super::{CommandError, CommandResult};
+
+/// Open a book for the native commands, applying any pending migrations
+/// first. The Express sidecar keeps its own connection; both use WAL so they
+/// can share the file.
+#[tauri::command]
+pub async fn open_book(
+  database_path: String,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<MigrationReport> {
+  let report = book.open(PathBuf::from(&database_path))?;
+  log::info!(
+    "Opened {database_path} ({} migration(s) applied)",
+    report.applied.len()
+  );
+  secrets::move_out_of_book(&app);
+  menu::sync(&app);
+  tray::refresh(&app);
+  Ok(report)
+}
+
+/// Open the encrypted book at `database_path`, which `open_book` left
+/// locked (see [`BookStatus::locked`]).
+///
+/// This doesn't start the API server on it, since Prisma can't read an
+/// encrypted book; decrypting it with `change_book_passphrase` does.
+#[tauri::command]
+pub async fn unlock_book(
+  database_path: String,
+  passphrase: String,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<MigrationReport> {
+  let report = book.unlock(PathBuf::from(&database_path), &passphrase)?;
+  log::info!("Unlocked {database_path}");
+  secrets::move_out_of_book(&app);
+  menu::sync(&app);
+  tray::refresh(&app);
+  Ok(report)
+}
+
+/// Change the open book's passphrase. `current` must match for an
+/// encrypted book; `None` for `new` decrypts it, and setting one on a
+/// plaintext book encrypts it.
+///
+/// The book is closed and re-encrypted into a new file while the API server
+/// is stopped. Prisma can't read an encrypted book, so the book the server
+/// serves can't be encrypted; decrypting it starts the server if setup
+/// couldn't because it was encrypted. Existing backups keep the passphrase
+/// they were taken with.
+#[tauri::command]
+pub async fn change_book_passphrase(
+  current: Option<String>,
+  new: Option<String>,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  let path = book
+    .path()
+    .ok_or_else(|| CommandError::new("No book is open"))?;
+  let old_key = book.key();
+  if let Some(old_key) = &old_key {
+    let current = current.ok_or(EncryptionError::WrongPassphrase)?;
+    if BookKey::derive(&current, old_key.salt())? != *old_key {
+      return Err(EncryptionError::WrongPassphrase.into());
+    }
+  }
+  let serves_book =
+    crate::book::served_book_path(&app).is_ok_and(|served| crate::book::same_file(&served, &path));
+  let server = app.try_state::<ApiServer>();
+  if new.is_some() && (serves_book || server.as_ref().is_some_and(|s| s.serves(&app, &path))) {
+    return Err(CommandError::new(
+      "The API server has this book open and can't read encrypted books, so it can't be encrypted yet",
+    ));
+  }
+  let new_key = new.as_deref().map(BookKey::generate).transpose()?;
+  let decrypting = new_key.is_none();
+
+  book.close();
+  let rekey = || encryption::rekey(&path, old_key.as_ref(), new_key.as_ref());
+  let rekeyed = match server {
+    Some(server) => server.with_server_stopped(rekey),
+    None => rekey(),
+  };
+  let reopened = match rekeyed {
+    Ok(()) => book.open_with_key(path, new_key),
+    Err(_) => book.open_with_key(path, old_key),
+  };
+  if let Some(task) = app.try_state::<BackupTask>() {
+    task.forget();
+  }
+  menu::sync(&app);
+  tray::refresh(&app);
+
+  rekeyed?;
+  reopened?;
+  log::info!("Changed the book's passphrase");
+  if serves_book && decrypting {
+    sidecar::start_if_stopped(&app);
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn close_book(app: AppHandle, book: State<'_, ActiveBook>) -> CommandResult<()> {
+  book.close();
+  menu::sync(&app);
+  tray::refresh(&app);
+  Ok(())
+}
+
+/// The open book's name from `bookManager`, for the tray menu.
+#[tauri::command]
+pub async fn set_active_book_name(name: String, app: AppHandle) -> CommandResult<()> {
+  tray::set_book_name(&app, name);
+  Ok(())
+}
+
+/// Which book is open, or why the last one couldn't be.
+#[tauri::command]
+pub async fn book_status(book: State<'_, ActiveBook>) -> CommandResult<BookStatus> {
+  Ok(book.status())
+}
+
+/// Switch the API server to the book at `database_path` and wait for it to
+/// answer. `/api/books/switch` only reaches books in the server's `prisma`
+/// directory; this also covers book files opened from the shell.
+#[tauri::command]
+pub async fn serve_book(
+  database_path: String,
+  app: AppHandle,
+  server: State<'_, ApiServer>,
+) -> CommandResult<()> {
+  let path = PathBuf::from(&database_path);
+  if !migrate::is_book(&path)? {
+    return Err(CommandError::new(format!(
+      "{database_path} isn't a Ledgerhound book"
+    )));
+  }
+  server.switch_book(&app, &path)?;
+  if !server.wait_until_ready() {
+    return Err(CommandError::new(
+      "The API server didn't come back after switching books",
+    ));
+  }
+  Ok(())
+}
+
+/// The last book file the shell asked to open (see
+/// [`crate::instance::OPEN_BOOK_EVENT`]), if the frontend hasn't handled it
+/// yet. Called once at startup to pick up a book the app was launched with.
+#[tauri::command]
+pub async fn take_pending_book_open(
+  pending: State<'_, PendingBookOpen>,
+) -> CommandResult<Option<OpenBookRequest>> {
+  Ok(pending.take())
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use ledger_core::book_manager::{Book, BookFileStatus, BookSummary, BookUpdate, CreateBookData};
+use ledger_core::encryption::BookKey;
+use tauri::{AppHandle, State};
+
+use crate::book::ActiveBook;
+use crate::book_manager::Books;
+use crate::{menu, secrets, tray};
+
+use super::{CommandError, CommandResult};
+
+#[tauri::command]
+pub async fn get_all_books(books: State<'_, Books>) -> CommandResult<Vec<Book>> {
+  books.with(|registry| Ok(registry.all_books()))
+}
+
+#[tauri::command]
+pub async fn get_book(id: String, books: State<'_, Books>) -> CommandResult<Option<Book>> {
+  books.with(|registry| Ok(registry.book(&id)))
+}
+
+#[tauri::command]
+pub async fn get_active_book(books: State<'_, Books>) -> CommandResult<Option<Book>> {
+  books.with(|registry| Ok(registry.active_book()))
+}
+
+#[tauri::command]
+pub async fn is_first_run(books: State<'_, Books>) -> CommandResult<bool> {
+  books.with(|registry| Ok(registry.is_first_run()))
+}
+
+#[tauri::command]
+pub async fn get_book_summaries(books: State<'_, Books>) -> CommandResult<Vec<BookSummary>> {
+  books.with(|registry| Ok(registry.book_summaries()))
+}
+
+#[tauri::command]
+pub async fn get_recent_books(
+  limit: Option<usize>,
+  books: State<'_, Books>,
+) -> CommandResult<Vec<BookSummary>> {
+  books.with(|registry| Ok(registry.recent_books(limit.unwrap_or(5))))
+}
+
+/// Register a book and create its database, encrypted with `passphrase` if
+/// one is given. The new book is opened and made active.
+#[tauri::command]
+pub async fn create_book(
+  data: CreateBookData,
+  passphrase: Option<String>,
+  app: AppHandle,
+  books: State<'_, Books>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Book> {
+  let key = passphrase.as_deref().map(BookKey::generate).transpose()?;
+  let created = books.with(|registry| registry.create_book(data))?;
+  let path = books.with(|registry| Ok(registry.resolve(&created.database_path)))?;
+  if let Err(err) = create_database(&book, path, key) {
+    books.with(|registry| registry.delete_book(&created.id))?;
+    return Err(err);
+  }
+  let created = books.with(|registry| registry.set_active_book(&created.id))?;
+  log::info!("Created book {} ({})", created.name, created.id);
+  tray::set_book_name(&app, created.name.clone());
+  secrets::move_out_of_book(&app);
+  menu::sync(&app);
+  tray::refresh(&app);
+  Ok(created)
+}
+
+fn create_database(book: &ActiveBook, path: PathBuf, key: Option<BookKey>) -> CommandResult<()> {
+  if std::fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+    return Err(CommandError::new(format!(
+      "A book already exists at {}",
+      path.display()
+    )));
+  }
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+  book.open_with_key(path, key)?;
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn update_book(
+  id: String,
+  updates: BookUpdate,
+  app: AppHandle,
+  books: State<'_, Books>,
+) -> CommandResult<Book> {
+  let updated = books.with(|registry| registry.update_book(&id, updates))?;
+  if updated.is_active {
+    tray::set_book_name(&app, updated.name.clone());
+  }
+  Ok(updated)
+}
+
+/// Forget a book. Its database and backups stay where they are.
+#[tauri::command]
+pub async fn delete_book(id: String, books: State<'_, Books>) -> CommandResult<()> {
+  books.with(|registry| registry.delete_book(&id))
+}
+
+/// Mark a book active. The frontend still opens it with `open_book` once
+/// the API server has switched to it.
+#[tauri::command]
+pub async fn set_active_book(
+  id: String,
+  app: AppHandle,
+  books: State<'_, Books>,
+) -> CommandResult<Book> {
+  let active = books.with(|registry| registry.set_active_book(&id))?;
+  tray::set_book_name(&app, active.name.clone());
+  Ok(active)
+}
+
+#[tauri::command]
+pub async fn export_book_list(books: State<'_, Books>) -> CommandResult<String> {
+  books.with(|registry| Ok(registry.export_book_list()))
+}
+
+/// Replace the registry with a list from `export_book_list` (or the
+/// `localStorage` one the TypeScript book manager kept).
+#[tauri::command]
+pub async fn import_book_list(json: String, books: State<'_, Books>) -> CommandResult<()> {
+  books.with(|registry| registry.import_book_list(&json))
+}
+
+/// Whether each book's database is still where the registry says, for the
+/// launcher to offer relocating the ones that aren't.
+#[tauri::command]
+pub async fn check_book_files(books: State<'_, Books>) -> CommandResult<Vec<BookFileStatus>> {
+  books.with(|registry| Ok(registry.file_statuses()))
+}
+
+/// Point a moved book at its database's new location.
+#[tauri::command]
+pub async fn relocate_book(
+  id: String,
+  database_path: PathBuf,
+  books: State<'_, Books>,
+) -> CommandResult<Book> {
+  books.with(|registry| registry.relocate_book(&id, database_path))
+}
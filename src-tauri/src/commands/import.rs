@@ -0,0 +1,428 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use ledger_core::db::DbResult;
+use ledger_core::import::{
+  detect_bank_profile, extract_pdf_text, load_bank_profiles, read_bank_statement, read_ofx,
+  read_qif, BankProfile, ColumnMapping, Confidence, CsvReader, CsvRecord, DateOrder, ImportPreview,
+  MappingTemplate, QifAccountType, QifImportSummary, QifTransaction, RowError, StatementBalance,
+  StatementLine,
+};
+use ledger_core::matching::ReconciliationMatchPreview;
+use ledger_core::model::Reconciliation;
+use ledger_core::money::Money;
+use serde::Serialize;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+
+use crate::book::ActiveBook;
+
+use super::{CommandError, CommandResult};
+
+/// Records per batch sent while previewing.
+const PREVIEW_BATCH_SIZE: usize = 500;
+
+/// Records shown while the user maps columns.
+const DEFAULT_SAMPLE_SIZE: usize = 10;
+
+/// Where the user's own bank profiles go, under the app data directory.
+const BANK_PROFILES_DIR: &str = "bank-profiles";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvSample {
+  pub headers: Vec<String>,
+  pub records: Vec<CsvRecord>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPreviewBatch {
+  pub previews: Vec<ImportPreview>,
+  pub errors: Vec<RowError>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPreviewSummary {
+  pub lines: usize,
+  pub duplicates: usize,
+  pub errors: usize,
+}
+
+/// One account's statement from an OFX file, checked against `source_account_id`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfxPreview {
+  pub currency: Option<String>,
+  /// The bank's account number, for confirming the right account was picked.
+  pub account_id: Option<String>,
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+  /// The statement's closing balance, to start a reconciliation from.
+  pub ledger_balance: Option<StatementBalance>,
+  pub previews: Vec<ImportPreview>,
+  pub errors: Vec<RowError>,
+}
+
+/// One statement from a camt.053 or MT940 file, checked against
+/// `source_account_id`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankStatementPreview {
+  pub statement_id: Option<String>,
+  /// The bank's IBAN or account number, for confirming the right account
+  /// was picked.
+  pub account_id: Option<String>,
+  pub currency: Option<String>,
+  pub opening_balance: Option<StatementBalance>,
+  pub closing_balance: Option<StatementBalance>,
+  pub previews: Vec<ImportPreview>,
+  pub errors: Vec<RowError>,
+}
+
+/// A bank profile PDF statements can be read with.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankProfileSummary {
+  pub id: String,
+  pub name: String,
+}
+
+/// A PDF statement, checked against `source_account_id`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPreview {
+  pub profile_id: String,
+  pub account_id: Option<String>,
+  pub account_name: Option<String>,
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+  pub opening_balance: Option<Money>,
+  pub closing_balance: Option<Money>,
+  pub confidence: Confidence,
+  pub previews: Vec<ImportPreview>,
+  pub errors: Vec<RowError>,
+}
+
+/// A QIF record and whether the account seems to have it already.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifLinePreview {
+  pub parsed: QifTransaction,
+  pub is_duplicate: bool,
+}
+
+/// One section of a QIF file, checked against `source_account_id`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QifPreview {
+  /// The account name Quicken or MYOB exported it under.
+  pub name: Option<String>,
+  pub account_type: QifAccountType,
+  pub opening_balance: Option<StatementBalance>,
+  pub previews: Vec<QifLinePreview>,
+  pub errors: Vec<RowError>,
+  /// Categories that will be imported as "Uncategorized" unless they're
+  /// created first.
+  pub unknown_categories: Vec<String>,
+}
+
+/// The header row and first few records of a CSV file, for mapping its
+/// columns.
+#[tauri::command]
+pub async fn read_csv_sample(
+  path: PathBuf,
+  has_header: Option<bool>,
+  limit: Option<usize>,
+) -> CommandResult<CsvSample> {
+  let reader = CsvReader::new(File::open(path)?, has_header.unwrap_or(true))?;
+  let headers = reader.headers().to_vec();
+  let records = reader
+    .take(limit.unwrap_or(DEFAULT_SAMPLE_SIZE))
+    .collect::<Result<_, _>>()?;
+  Ok(CsvSample { headers, records })
+}
+
+/// `previewImport` for a CSV file, streamed through `on_batch` so a large
+/// export shows its first rows straight away. Rows that can't be read come
+/// back as errors with their line numbers rather than stopping the preview.
+/// The book is only locked while each batch is checked for duplicates.
+#[tauri::command]
+pub async fn preview_csv_import(
+  path: PathBuf,
+  mapping: ColumnMapping,
+  source_account_id: String,
+  has_header: Option<bool>,
+  on_batch: Channel<CsvPreviewBatch>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<CsvPreviewSummary> {
+  let mut records = CsvReader::new(File::open(path)?, has_header.unwrap_or(true))?.peekable();
+  let mut summary = CsvPreviewSummary::default();
+  while records.peek().is_some() {
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    for record in records.by_ref().take(PREVIEW_BATCH_SIZE) {
+      match mapping.statement_line(&record?) {
+        Ok(line) => lines.push(line),
+        Err(err) => errors.push(err),
+      }
+    }
+    let previews = book.with(|db| db.preview_import(&source_account_id, lines))?;
+    summary.lines += previews.len();
+    summary.duplicates += previews.iter().filter(|p| p.is_duplicate).count();
+    summary.errors += errors.len();
+    on_batch.send(CsvPreviewBatch { previews, errors })?;
+  }
+  Ok(summary)
+}
+
+/// `previewImport` for an OFX or QFX file. `FITID`s become the lines'
+/// external ids, so a statement that was imported before shows up as
+/// duplicates.
+#[tauri::command]
+pub async fn preview_ofx_import(
+  path: PathBuf,
+  source_account_id: String,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<OfxPreview>> {
+  let statements = read_ofx(&std::fs::read(path)?)?;
+  statements
+    .into_iter()
+    .map(|statement| {
+      Ok(OfxPreview {
+        previews: book.with(|db| db.preview_import(&source_account_id, statement.lines))?,
+        currency: statement.currency,
+        account_id: statement.account_id,
+        start: statement.start,
+        end: statement.end,
+        ledger_balance: statement.ledger_balance,
+        errors: statement.errors,
+      })
+    })
+    .collect()
+}
+
+/// `previewImport` for an ISO 20022 camt.053 or SWIFT MT940 file, told
+/// apart by content. The bank's entry references become the lines' external
+/// ids.
+#[tauri::command]
+pub async fn preview_statement_import(
+  path: PathBuf,
+  source_account_id: String,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<BankStatementPreview>> {
+  let statements = read_bank_statement(&std::fs::read(path)?)?;
+  statements
+    .into_iter()
+    .map(|statement| {
+      Ok(BankStatementPreview {
+        previews: book.with(|db| db.preview_import(&source_account_id, statement.lines))?,
+        statement_id: statement.statement_id,
+        account_id: statement.account_id,
+        currency: statement.currency,
+        opening_balance: statement.opening_balance,
+        closing_balance: statement.closing_balance,
+        errors: statement.errors,
+      })
+    })
+    .collect()
+}
+
+/// Open a reconciliation for an imported statement's opening and closing
+/// balances, or return the one already open for them.
+#[tauri::command]
+pub async fn start_statement_reconciliation(
+  source_account_id: String,
+  opening_balance: StatementBalance,
+  closing_balance: StatementBalance,
+  statement_id: Option<String>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Reconciliation> {
+  let notes = statement_id.map(|id| format!("Statement {id}"));
+  book.with(|db| {
+    db.start_statement_reconciliation(&source_account_id, opening_balance, closing_balance, notes)
+  })
+}
+
+/// Pair a statement's lines with the account's unreconciled postings and
+/// explain what's left of the difference, for the Reconcile screen.
+#[tauri::command]
+pub async fn match_statement_lines(
+  source_account_id: String,
+  lines: Vec<StatementLine>,
+  start_date: Option<DateTime<Utc>>,
+  end_date: Option<DateTime<Utc>>,
+  closing_balance: Option<Money>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<ReconciliationMatchPreview> {
+  book.with(|db| {
+    db.match_statement_lines(
+      &source_account_id,
+      lines,
+      start_date,
+      end_date,
+      closing_balance,
+    )
+  })
+}
+
+/// The user's bank profiles and the built-in ones they don't replace.
+fn bank_profiles(app: &AppHandle) -> CommandResult<Vec<BankProfile>> {
+  let dir = app.path().app_data_dir()?.join(BANK_PROFILES_DIR);
+  Ok(load_bank_profiles(&dir)?)
+}
+
+#[tauri::command]
+pub async fn get_bank_profiles(app: AppHandle) -> CommandResult<Vec<BankProfileSummary>> {
+  Ok(
+    bank_profiles(&app)?
+      .into_iter()
+      .map(|profile| BankProfileSummary {
+        id: profile.id,
+        name: profile.name,
+      })
+      .collect(),
+  )
+}
+
+/// `previewImport` for a PDF statement, read with `profile_id` or else the
+/// first bank profile that recognises it.
+#[tauri::command]
+pub async fn preview_pdf_import(
+  path: PathBuf,
+  source_account_id: String,
+  profile_id: Option<String>,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<PdfPreview> {
+  let text = extract_pdf_text(&std::fs::read(path)?)?;
+  let profiles = bank_profiles(&app)?;
+  let profile = match &profile_id {
+    Some(id) => profiles.iter().find(|profile| &profile.id == id),
+    None => detect_bank_profile(&text, &profiles),
+  }
+  .ok_or_else(|| CommandError::new("No bank profile can read this statement"))?;
+  let statement = profile.read(&text);
+  Ok(PdfPreview {
+    previews: book.with(|db| db.preview_import(&source_account_id, statement.lines))?,
+    profile_id: statement.profile_id,
+    account_id: statement.account_id,
+    account_name: statement.account_name,
+    start: statement.start,
+    end: statement.end,
+    opening_balance: statement.opening_balance,
+    closing_balance: statement.closing_balance,
+    confidence: statement.confidence,
+    errors: statement.errors,
+  })
+}
+
+/// Each account section of a QIF file, with duplicates flagged and the
+/// categories the book doesn't have listed.
+#[tauri::command]
+pub async fn preview_qif_import(
+  path: PathBuf,
+  source_account_id: String,
+  date_order: Option<DateOrder>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<QifPreview>> {
+  let sections = read_qif(&std::fs::read(path)?, date_order.unwrap_or_default())?;
+  book.with(|db| -> DbResult<_> {
+    sections
+      .into_iter()
+      .map(|section| {
+        let unknown_categories = db.unknown_qif_categories(&section.transactions)?;
+        let previews = section
+          .transactions
+          .into_iter()
+          .map(|parsed| {
+            Ok(QifLinePreview {
+              is_duplicate: db.is_duplicate_import(&source_account_id, &parsed.statement_line())?,
+              parsed,
+            })
+          })
+          .collect::<DbResult<_>>()?;
+        Ok(QifPreview {
+          name: section.name,
+          account_type: section.account_type,
+          opening_balance: section.opening_balance,
+          previews,
+          errors: section.errors,
+          unknown_categories,
+        })
+      })
+      .collect()
+  })
+}
+
+/// Import the `section`th account section of a QIF file into
+/// `source_account_id`.
+#[tauri::command]
+pub async fn import_qif(
+  path: PathBuf,
+  section: usize,
+  source_account_id: String,
+  date_order: Option<DateOrder>,
+  skip_duplicates: Option<bool>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<QifImportSummary> {
+  let source_name = path
+    .file_name()
+    .map_or_else(|| "QIF".into(), |name| name.to_string_lossy().into_owned());
+  let transactions = read_qif(&std::fs::read(&path)?, date_order.unwrap_or_default())?
+    .into_iter()
+    .nth(section)
+    .ok_or_else(|| CommandError::new(format!("The file has no section {section}")))?
+    .transactions;
+  book.with(|db| {
+    db.import_qif(
+      &source_account_id,
+      &source_name,
+      &transactions,
+      skip_duplicates.unwrap_or(true),
+    )
+  })
+}
+
+/// Write an account's transactions to `path` as QIF, for accountants still
+/// on Quicken or MYOB.
+#[tauri::command]
+pub async fn export_qif(
+  account_id: String,
+  path: PathBuf,
+  start_date: Option<DateTime<Utc>>,
+  end_date: Option<DateTime<Utc>>,
+  date_order: Option<DateOrder>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  let qif = book.with(|db| {
+    db.export_qif(
+      &account_id,
+      start_date,
+      end_date,
+      date_order.unwrap_or_default(),
+    )
+  })?;
+  std::fs::write(path, qif)?;
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn save_import_mapping_template(
+  name: String,
+  mapping: ColumnMapping,
+  account_id: Option<String>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  book.with(|db| db.save_import_mapping_template(&name, &mapping, account_id.as_deref()))
+}
+
+#[tauri::command]
+pub async fn get_import_mapping_templates(
+  account_id: Option<String>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<MappingTemplate>> {
+  book.with(|db| db.import_mapping_templates(account_id.as_deref()))
+}
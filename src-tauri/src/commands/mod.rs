@@ -0,0 +1,50 @@
+//! `#[tauri::command]` handlers registered in [`crate::run`].
+
+pub mod accounts;
+pub mod backup;
+pub mod book;
+pub mod book_manager;
+pub mod import;
+pub mod money;
+pub mod register;
+pub mod reports;
+pub mod rules;
+pub mod secrets;
+pub mod tax;
+pub mod transfers;
+pub mod window;
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Error returned to the frontend; serializes as `{ "message": "..." }` so it
+/// reads like the `{ error }` bodies the Express API sends.
+#[derive(Debug, Serialize)]
+pub struct CommandError {
+  message: String,
+}
+
+impl CommandError {
+  pub fn new(message: impl Into<String>) -> Self {
+    CommandError {
+      message: message.into(),
+    }
+  }
+}
+
+impl fmt::Display for CommandError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl<E: std::error::Error> From<E> for CommandError {
+  fn from(err: E) -> Self {
+    CommandError {
+      message: err.to_string(),
+    }
+  }
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
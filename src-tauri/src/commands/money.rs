@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use ledger_core::money_storage::{self, CentsMigrationReport};
+use ledger_core::rusqlite::Connection;
+use tauri::{AppHandle, Manager, State};
+
+use crate::book::ActiveBook;
+use crate::sidecar::ApiServer;
+
+use super::{CommandError, CommandResult};
+
+/// Rewrite a book's float money columns to integer cents.
+///
+/// Runs in a single transaction and leaves the book untouched if any account
+/// balance would change. Refused for the book the API server has open,
+/// since Prisma reads those columns as dollars and would show every amount
+/// a hundred times too large; for the same reason the server won't switch
+/// to a book once it's migrated (see [`crate::book::check_servable`]).
+#[tauri::command]
+pub async fn migrate_book_to_cents(
+  database_path: String,
+  app: AppHandle,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<CentsMigrationReport> {
+  if app
+    .try_state::<ApiServer>()
+    .is_some_and(|server| server.serves(&app, Path::new(&database_path)))
+  {
+    return Err(CommandError::new(
+      "The API server has this book open and reads its amounts as dollars, so it can't be migrated to cents yet",
+    ));
+  }
+  // Go through the open book when it's the one being migrated so its cached
+  // storage format stays in step.
+  let report = if book.path().as_deref() == Some(Path::new(&database_path)) {
+    book.with(|db| db.migrate_to_cents())?
+  } else {
+    let mut conn = Connection::open(&database_path)?;
+    money_storage::migrate_to_cents(&mut conn)?
+  };
+  log::info!(
+    "Migrated {database_path} to integer cents ({} postings, {} balances verified)",
+    report.postings,
+    report.balances_verified
+  );
+  Ok(report)
+}
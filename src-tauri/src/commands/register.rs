@@ -0,0 +1,106 @@
+use ledger_core::db::{
+  Database, DbResult, RegisterCursor, RegisterEntry, RegisterFilter, RegisterWindow,
+};
+use serde::Serialize;
+use tauri::ipc::Channel;
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+/// Rows per page when the caller doesn't ask for a size.
+const DEFAULT_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterPage {
+  pub account_id: String,
+  pub offset: usize,
+  /// Rows matching the filter across all pages, excluding `opening`.
+  pub total: usize,
+  /// The "Opening Balance" row; only on the first page.
+  pub opening: Option<RegisterEntry>,
+  pub entries: Vec<RegisterEntry>,
+}
+
+fn read_page(
+  db: &Database,
+  account_id: &str,
+  filter: &RegisterFilter,
+  offset: usize,
+  limit: usize,
+  total: usize,
+) -> DbResult<(RegisterPage, usize)> {
+  let opening = match offset {
+    0 => Some(db.register_opening_entry(account_id)?),
+    _ => None,
+  };
+  let RegisterWindow { entries, rows } = db.register_window(account_id, filter, offset, limit)?;
+  let page = RegisterPage {
+    account_id: account_id.to_string(),
+    offset,
+    total,
+    opening,
+    entries,
+  };
+  Ok((page, rows))
+}
+
+/// One page of `GET /api/transactions/register/:accountId`.
+#[tauri::command]
+pub async fn get_register_page(
+  account_id: String,
+  filter: Option<RegisterFilter>,
+  offset: Option<usize>,
+  limit: Option<usize>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<RegisterPage> {
+  let filter = filter.unwrap_or_default();
+  let offset = offset.unwrap_or(0);
+  let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+  book.with(|db| -> DbResult<_> {
+    let total = db.register_len(&account_id, &filter)?;
+    Ok(read_page(db, &account_id, &filter, offset, limit, total)?.0)
+  })
+}
+
+/// Send the whole register through `on_page` a page at a time, so the
+/// frontend can render the first rows while the rest load. The book is only
+/// locked while each page is read, so the API server can write in between;
+/// pages follow the rows read rather than the entries sent, so a row that
+/// can't be shown doesn't cut the stream short. Returns the number of
+/// entries sent.
+#[tauri::command]
+pub async fn stream_register(
+  account_id: String,
+  filter: Option<RegisterFilter>,
+  page_size: Option<usize>,
+  on_page: Channel<RegisterPage>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<usize> {
+  let filter = filter.unwrap_or_default();
+  let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+  let total = book.with(|db| db.register_len(&account_id, &filter))?;
+
+  let mut cursor = RegisterCursor::new(total, page_size);
+  let mut sent = 0;
+  while let Some(offset) = cursor.next_offset() {
+    let (page, rows) =
+      book.with(|db| read_page(db, &account_id, &filter, offset, page_size, total))?;
+    cursor.advance(rows);
+    sent += page.entries.len();
+    on_page.send(page)?;
+  }
+  Ok(sent)
+}
+
+/// Set or clear the cleared flag on postings from the register.
+#[tauri::command]
+pub async fn mark_postings_cleared(
+  posting_ids: Vec<String>,
+  cleared: bool,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<usize> {
+  book.with(|db| db.mark_cleared(&posting_ids, cleared))
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use ledger_core::db::DbResult;
+use ledger_core::reports::{
+  self, BalanceSheet, BasContribution, BasDraft, BasLabel, CashFlowStatement, GstSummary, Period,
+  ProfitAndLoss, ProfitAndLossOptions,
+};
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+/// `GET /api/reports/profit-loss`.
+#[tauri::command]
+pub async fn generate_profit_and_loss(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  options: Option<ProfitAndLossOptions>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<ProfitAndLoss> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(Some(start_date), Some(end_date))?;
+    Ok(reports::profit_and_loss(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+      options.unwrap_or_default(),
+    ))
+  })
+}
+
+/// `GET /api/reports/gst-summary`.
+#[tauri::command]
+pub async fn generate_gst_summary(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<GstSummary> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(Some(start_date), Some(end_date))?;
+    Ok(reports::gst_summary(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+    ))
+  })
+}
+
+/// `GET /api/reports/bas-draft`.
+#[tauri::command]
+pub async fn generate_bas_draft(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<BasDraft> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(Some(start_date), Some(end_date))?;
+    Ok(reports::bas_draft(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+    ))
+  })
+}
+
+/// The postings behind a BAS draft, for one label or all of them.
+#[tauri::command]
+pub async fn get_bas_drill_down(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  label: Option<BasLabel>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<BasContribution>> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(Some(start_date), Some(end_date))?;
+    Ok(reports::bas_contributions(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+      label,
+    ))
+  })
+}
+
+/// `GET /api/reports/balance-sheet`.
+#[tauri::command]
+pub async fn generate_balance_sheet(
+  as_of_date: DateTime<Utc>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<BalanceSheet> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(None, Some(as_of_date))?;
+    Ok(reports::balance_sheet(&accounts, &transactions, as_of_date))
+  })
+}
+
+/// `GET /api/reports/cash-flow`.
+#[tauri::command]
+pub async fn generate_cash_flow(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<CashFlowStatement> {
+  book.with(|db| -> DbResult<_> {
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(None, Some(end_date))?;
+    Ok(reports::cash_flow(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+    ))
+  })
+}
@@ -0,0 +1,30 @@
+use ledger_core::model::MemorizedRule;
+use ledger_core::rules::{RuleContext, RuleExplanation};
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+/// `matchPayee`: the first rule, in priority order, that applies in
+/// `context` and matches `payee`.
+#[tauri::command]
+pub async fn match_memorized_rule(
+  payee: String,
+  context: RuleContext,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Option<MemorizedRule>> {
+  let matcher = book.with(|db| db.rule_matcher())?;
+  Ok(matcher.find(&payee, context).cloned())
+}
+
+/// Which rules match `payee` and why the winner won, for the rule editor.
+#[tauri::command]
+pub async fn explain_rule_match(
+  payee: String,
+  context: RuleContext,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<RuleExplanation> {
+  let matcher = book.with(|db| db.rule_matcher())?;
+  Ok(matcher.explain(&payee, context))
+}
@@ -0,0 +1,42 @@
+use ledger_core::secrets::{Secret, SecretStore};
+use tauri::{AppHandle, State};
+
+use crate::secrets::Secrets;
+
+use super::CommandResult;
+
+/// The Stripe or AI API key, replacing the `apiKey` the services used to
+/// read from `settings`.
+#[tauri::command]
+pub async fn get_secret(
+  secret: Secret,
+  secrets: State<'_, Secrets>,
+) -> CommandResult<Option<String>> {
+  Ok(secrets.store()?.get(secret)?)
+}
+
+/// Store a key and restart the API server with it.
+#[tauri::command]
+pub async fn set_secret(
+  secret: Secret,
+  value: String,
+  app: AppHandle,
+  secrets: State<'_, Secrets>,
+) -> CommandResult<()> {
+  secrets.store()?.set(secret, &value)?;
+  crate::secrets::restart_server(&app);
+  Ok(())
+}
+
+/// Returns whether there was a key to delete. The API server is restarted
+/// without it.
+#[tauri::command]
+pub async fn delete_secret(
+  secret: Secret,
+  app: AppHandle,
+  secrets: State<'_, Secrets>,
+) -> CommandResult<bool> {
+  let deleted = secrets.store()?.delete(secret)?;
+  crate::secrets::restart_server(&app);
+  Ok(deleted)
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use ledger_core::db::{DbError, DbResult};
+use ledger_core::money::Money;
+use ledger_core::reports::Period;
+use ledger_core::tax::{
+  self, FinancialYear, PaygConfig, PaygMethod, Quarter, TaxEstimation, TaxTablesConfig,
+};
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+fn today() -> NaiveDate {
+  Utc::now().date_naive()
+}
+
+/// `GET /api/tax/estimation`. The financial year is the one `start_date`
+/// falls in.
+#[tauri::command]
+pub async fn generate_tax_estimation(
+  start_date: DateTime<Utc>,
+  end_date: DateTime<Utc>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TaxEstimation> {
+  let year = FinancialYear::containing(start_date.date_naive());
+  book.with(|db| -> DbResult<_> {
+    let tables = db.tax_tables(year)?;
+    let payg = db.payg_config(year, today())?;
+    let accounts = db.accounts(true)?;
+    let transactions = db.transactions_between(Some(start_date), Some(end_date))?;
+    Ok(tax::estimate_tax(
+      &accounts,
+      &transactions,
+      Period::new(start_date, end_date),
+      &tables,
+      payg.as_ref(),
+    ))
+  })
+}
+
+/// `GET /api/tax/tables/:fy`.
+#[tauri::command]
+pub async fn get_tax_tables(
+  financial_year: FinancialYear,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TaxTablesConfig> {
+  book.with(|db| db.tax_tables(financial_year))
+}
+
+/// `PUT /api/tax/tables/:fy`.
+#[tauri::command]
+pub async fn save_tax_tables(
+  tables: TaxTablesConfig,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  book.with(|db| db.save_tax_tables(&tables))
+}
+
+#[tauri::command]
+pub async fn get_available_financial_years(
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Vec<FinancialYear>> {
+  book.with(|db| db.available_financial_years())
+}
+
+/// `GET /api/tax/payg/:fy`.
+#[tauri::command]
+pub async fn get_payg_config(
+  financial_year: FinancialYear,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<Option<PaygConfig>> {
+  book.with(|db| db.payg_config(financial_year, today()))
+}
+
+/// `POST /api/tax/payg/:fy/initialize`.
+#[tauri::command]
+pub async fn initialize_payg(
+  financial_year: FinancialYear,
+  method: PaygMethod,
+  annual_amount: Option<Money>,
+  annual_rate: Option<f64>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<PaygConfig> {
+  book.with(|db| -> DbResult<_> {
+    let mut config = PaygConfig::new(financial_year, method, annual_amount, annual_rate);
+    config.update_statuses(today());
+    db.save_payg_config(&config)?;
+    Ok(config)
+  })
+}
+
+/// `POST /api/tax/payg/:fy/payment`. Starts an amount-method config if the
+/// year has none yet.
+#[tauri::command]
+pub async fn record_payg_payment(
+  financial_year: FinancialYear,
+  quarter: Quarter,
+  amount: Money,
+  date: NaiveDate,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<PaygConfig> {
+  book.with(|db| -> DbResult<_> {
+    let mut config = match db.payg_config(financial_year, today())? {
+      Some(config) => config,
+      None => PaygConfig::new(financial_year, PaygMethod::Amount, None, None),
+    };
+    if !config.record_payment(quarter, amount, date) {
+      return Err(DbError::NotFound {
+        entity: "PAYG instalment",
+        id: format!("{financial_year}-{quarter:?}"),
+      });
+    }
+    db.save_payg_config(&config)?;
+    Ok(config)
+  })
+}
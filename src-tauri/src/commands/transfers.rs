@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use ledger_core::db::{TransferCommitResult, TransferPair};
+use ledger_core::matching::{TransferMatchConfig, TransferMatchPreview};
+use tauri::State;
+
+use crate::book::ActiveBook;
+
+use super::CommandResult;
+
+/// Proposed transfers between two accounts, scored with the pair's
+/// configuration.
+#[tauri::command]
+pub async fn match_transfers(
+  account_id_a: String,
+  account_id_b: String,
+  start_date: Option<DateTime<Utc>>,
+  end_date: Option<DateTime<Utc>>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TransferMatchPreview> {
+  book.with(|db| db.match_transfers(&account_id_a, &account_id_b, start_date, end_date))
+}
+
+#[tauri::command]
+pub async fn commit_transfer_matches(
+  pairs: Vec<TransferPair>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TransferCommitResult> {
+  book.with(|db| db.commit_transfer_matches(&pairs))
+}
+
+/// Merge every exact match between two accounts without reviewing them.
+#[tauri::command]
+pub async fn commit_exact_transfer_matches(
+  account_id_a: String,
+  account_id_b: String,
+  start_date: Option<DateTime<Utc>>,
+  end_date: Option<DateTime<Utc>>,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TransferCommitResult> {
+  book
+    .with(|db| db.commit_exact_transfer_matches(&account_id_a, &account_id_b, start_date, end_date))
+}
+
+#[tauri::command]
+pub async fn get_transfer_match_config(
+  account_id_a: String,
+  account_id_b: String,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<TransferMatchConfig> {
+  book.with(|db| db.transfer_match_config(&account_id_a, &account_id_b))
+}
+
+#[tauri::command]
+pub async fn save_transfer_match_config(
+  account_id_a: String,
+  account_id_b: String,
+  config: TransferMatchConfig,
+  book: State<'_, ActiveBook>,
+) -> CommandResult<()> {
+  book.with(|db| db.save_transfer_match_config(&account_id_a, &account_id_b, &config))
+}
@@ -0,0 +1,24 @@
+use tauri::{AppHandle, State};
+
+use crate::window_state::{book_key, WindowStates};
+
+use super::CommandResult;
+
+/// UI state the frontend saved for the open book.
+#[tauri::command]
+pub async fn get_book_ui_state(
+  app: AppHandle,
+  states: State<'_, WindowStates>,
+) -> CommandResult<Option<serde_json::Value>> {
+  Ok(states.ui_state(&book_key(&app)))
+}
+
+#[tauri::command]
+pub async fn set_book_ui_state(
+  state: serde_json::Value,
+  app: AppHandle,
+  states: State<'_, WindowStates>,
+) -> CommandResult<()> {
+  states.set_ui_state(&book_key(&app), state);
+  Ok(())
+}
@@ -0,0 +1,150 @@
+//! Single-instance lock and opening book files from the shell.
+//!
+//! Two app processes checkpointing the same book's WAL can corrupt it, so a
+//! second launch hands its arguments to the running instance and exits. A
+//! book file among those arguments (or opened from Finder on macOS) is
+//! opened natively, the API server is switched to it, and the frontend is
+//! told so it can select it in `bookManager`.
+//!
+//! Books are the `ledger.ledgerhound` files `bookManager` creates. A file
+//! with the extension is still checked to be a book before it's touched.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use ledger_core::migrate;
+
+use crate::book::ActiveBook;
+use crate::sidecar::ApiServer;
+
+/// Extension registered in `bundle.fileAssociations`.
+pub const BOOK_EXTENSION: &str = "ledgerhound";
+
+/// Event emitted when the shell asks the app to open a book file.
+pub const OPEN_BOOK_EVENT: &str = "book://open-requested";
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBookRequest {
+  pub path: PathBuf,
+  /// `path` as `bookManager` and `/api/books/switch` name it: relative to
+  /// the `prisma` directory when it's inside it.
+  pub database_path: PathBuf,
+  /// Set when the book couldn't be opened natively.
+  pub error: Option<String>,
+}
+
+/// The last [`OpenBookRequest`], until the frontend takes it.
+#[derive(Default)]
+pub struct PendingBookOpen(Mutex<Option<OpenBookRequest>>);
+
+impl PendingBookOpen {
+  fn set(&self, request: OpenBookRequest) {
+    *self.0.lock().unwrap() = Some(request);
+  }
+
+  pub fn take(&self) -> Option<OpenBookRequest> {
+    self.0.lock().unwrap().take()
+  }
+}
+
+/// The last book file in `args`, resolved against the launching process's
+/// working directory. Accepts plain paths and `file://` URLs.
+pub fn book_argument<I, S>(args: I, cwd: &Path) -> Option<PathBuf>
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  args
+    .into_iter()
+    .skip(1)
+    .filter_map(|arg| {
+      let arg = arg.as_ref();
+      match tauri::Url::parse(arg) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+        _ => Some(PathBuf::from(arg)),
+      }
+    })
+    .filter(|path| is_book_file(path))
+    .map(|path| cwd.join(path))
+    .last()
+}
+
+fn is_book_file(path: &Path) -> bool {
+  path
+    .extension()
+    .is_some_and(|ext| ext.eq_ignore_ascii_case(BOOK_EXTENSION))
+}
+
+/// The single-instance plugin. Must be registered before any other plugin.
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+  tauri_plugin_single_instance::init(|app, args, cwd| {
+    log::info!("Second launch forwarded: {args:?}");
+    focus_main_window(app);
+    if let Some(path) = book_argument(&args, Path::new(&cwd)) {
+      request_book_open(app, path);
+    }
+  })
+}
+
+/// Open `path` natively, switch the API server to it and tell the
+/// frontend. The request is also kept in [`PendingBookOpen`], because a
+/// book the app was launched with arrives before the frontend is listening.
+pub fn request_book_open<R: Runtime>(app: &AppHandle<R>, path: PathBuf) {
+  let error = open_requested_book(app, &path).err();
+  if let Some(err) = &error {
+    log::error!("Failed to open {}: {err}", path.display());
+  }
+  crate::menu::sync(app);
+  crate::tray::refresh(app);
+  let database_path = crate::book::served_book_name(app, &path).unwrap_or_else(|_| path.clone());
+  let request = OpenBookRequest {
+    path,
+    database_path,
+    error,
+  };
+  app.state::<PendingBookOpen>().set(request.clone());
+  if let Err(err) = app.emit(OPEN_BOOK_EVENT, request) {
+    log::warn!("Failed to emit {OPEN_BOOK_EVENT}: {err}");
+  }
+  focus_main_window(app);
+}
+
+fn open_requested_book<R: Runtime>(app: &AppHandle<R>, path: &Path) -> Result<(), String> {
+  if !migrate::is_book(path).map_err(|err| err.to_string())? {
+    return Err(format!("{} isn't a Ledgerhound book", path.display()));
+  }
+  let report = app
+    .state::<ActiveBook>()
+    .open(path.to_path_buf())
+    .map_err(|err| err.to_string())?;
+  log::info!(
+    "Opened {} ({} migration(s) applied)",
+    path.display(),
+    report.applied.len()
+  );
+  crate::secrets::move_out_of_book(app);
+  if let Some(server) = app.try_state::<ApiServer>() {
+    server.switch_book(app, path).map_err(|err| {
+      format!("The book is open, but the API server couldn't switch to it: {err}")
+    })?;
+  }
+  Ok(())
+}
+
+fn focus_main_window<R: Runtime>(app: &AppHandle<R>) {
+  let Some(window) = app.get_webview_window("main") else {
+    return;
+  };
+  let focused = window
+    .unminimize()
+    .and_then(|()| window.show())
+    .and_then(|()| window.set_focus());
+  if let Err(err) = focused {
+    log::warn!("Failed to focus main window: {err}");
+  }
+}
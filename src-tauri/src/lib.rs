@@ -1,6 +1,26 @@
+mod backups;
+mod book;
+mod book_manager;
+mod commands;
+mod instance;
+mod menu;
+mod secrets;
+mod sidecar;
+mod tray;
+mod window_state;
+
+use tauri::{Manager, RunEvent};
+
+use book::ActiveBook;
+use commands::CommandError;
+use instance::PendingBookOpen;
+use sidecar::ApiServer;
+use window_state::WindowStates;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-  tauri::Builder::default()
+  let app = tauri::Builder::default()
+    .plugin(instance::plugin())
     .setup(|app| {
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -9,8 +29,154 @@ pub fn run() {
             .build(),
         )?;
       }
+
+      secrets::init(app.handle());
+      book_manager::init(app.handle());
+
+      // Bring the book the API server serves up to date before Prisma
+      // touches it, and open the same one natively.
+      let active_book = ActiveBook::default();
+      let opened = book::served_book_path(app.handle())
+        .map_err(CommandError::from)
+        .and_then(|path| active_book.open(path));
+      app.manage(active_book);
+      app.manage(PendingBookOpen::default());
+      // Before the API server starts, so it's handed any keys the book held.
+      secrets::move_out_of_book(app.handle());
+      match opened {
+        Ok(report) => {
+          if !report.applied.is_empty() {
+            log::info!("Applied migrations: {}", report.applied.join(", "));
+          }
+          for name in &report.modified {
+            log::warn!("Migration {name} was edited after it was applied");
+          }
+          // The main window stays hidden until the API server answers its health check.
+          app.manage(ApiServer::start(app.handle()));
+        }
+        Err(err) => {
+          // Leave the book alone; the frontend reads the reason from `book_status`.
+          log::error!("Not starting the API server: {err}");
+          if let Some(window) = app.get_webview_window("main") {
+            window.show()?;
+          }
+        }
+      }
+      backups::start(app.handle());
+
+      // Double-clicking a book file launches the app with its path.
+      let launch_book = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| instance::book_argument(std::env::args(), &cwd));
+      if let Some(path) = launch_book {
+        instance::request_book_open(app.handle(), path);
+      }
+
+      // Size the hidden main window for this book before anything shows it.
+      app.manage(WindowStates::load(app.handle()));
+      if let Some(window) = app.get_webview_window("main") {
+        window_state::restore_and_track(app.handle(), &window);
+      }
+
+      menu::create(app.handle())?;
+      tray::create(app.handle())?;
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .invoke_handler(tauri::generate_handler![
+      commands::accounts::get_account_balance,
+      commands::accounts::get_accounts_with_balances,
+      commands::backup::clean_old_backups,
+      commands::backup::create_backup,
+      commands::backup::delete_backup,
+      commands::backup::get_backup_schedule,
+      commands::backup::list_backups,
+      commands::backup::restore_backup,
+      commands::backup::save_backup_schedule,
+      commands::book::book_status,
+      commands::book::change_book_passphrase,
+      commands::book::close_book,
+      commands::book::open_book,
+      commands::book::serve_book,
+      commands::book::set_active_book_name,
+      commands::book::take_pending_book_open,
+      commands::book::unlock_book,
+      commands::book_manager::check_book_files,
+      commands::book_manager::create_book,
+      commands::book_manager::delete_book,
+      commands::book_manager::export_book_list,
+      commands::book_manager::get_active_book,
+      commands::book_manager::get_all_books,
+      commands::book_manager::get_book,
+      commands::book_manager::get_book_summaries,
+      commands::book_manager::get_recent_books,
+      commands::book_manager::import_book_list,
+      commands::book_manager::is_first_run,
+      commands::book_manager::relocate_book,
+      commands::book_manager::set_active_book,
+      commands::book_manager::update_book,
+      commands::import::export_qif,
+      commands::import::get_bank_profiles,
+      commands::import::get_import_mapping_templates,
+      commands::import::import_qif,
+      commands::import::match_statement_lines,
+      commands::import::preview_csv_import,
+      commands::import::preview_ofx_import,
+      commands::import::preview_pdf_import,
+      commands::import::preview_qif_import,
+      commands::import::preview_statement_import,
+      commands::import::read_csv_sample,
+      commands::import::save_import_mapping_template,
+      commands::import::start_statement_reconciliation,
+      commands::money::migrate_book_to_cents,
+      commands::register::get_register_page,
+      commands::register::mark_postings_cleared,
+      commands::register::stream_register,
+      commands::reports::generate_balance_sheet,
+      commands::reports::generate_bas_draft,
+      commands::reports::generate_cash_flow,
+      commands::reports::generate_gst_summary,
+      commands::reports::generate_profit_and_loss,
+      commands::reports::get_bas_drill_down,
+      commands::rules::explain_rule_match,
+      commands::rules::match_memorized_rule,
+      commands::secrets::delete_secret,
+      commands::secrets::get_secret,
+      commands::secrets::set_secret,
+      commands::tax::generate_tax_estimation,
+      commands::tax::get_available_financial_years,
+      commands::tax::get_payg_config,
+      commands::tax::get_tax_tables,
+      commands::tax::initialize_payg,
+      commands::tax::record_payg_payment,
+      commands::tax::save_tax_tables,
+      commands::transfers::commit_exact_transfer_matches,
+      commands::transfers::commit_transfer_matches,
+      commands::transfers::get_transfer_match_config,
+      commands::transfers::match_transfers,
+      commands::transfers::save_transfer_match_config,
+      commands::window::get_book_ui_state,
+      commands::window::set_book_ui_state,
+    ])
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application");
+
+  app.run(|app, event| match event {
+    RunEvent::Exit => {
+      if let Some(states) = app.try_state::<WindowStates>() {
+        states.save();
+      }
+      backups::on_exit(app);
+      if let Some(server) = app.try_state::<ApiServer>() {
+        server.shutdown();
+      }
+    }
+    // macOS delivers opened files as an event rather than as arguments.
+    #[cfg(target_os = "macos")]
+    RunEvent::Opened { urls } => {
+      for path in urls.iter().filter_map(|url| url.to_file_path().ok()) {
+        instance::request_book_open(app, path);
+      }
+    }
+    _ => {}
+  });
 }
@@ -2,10 +2,5 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-  tauri::Builder::default()
-    // 🔽 enable Rust-side logging
-    .plugin(tauri_plugin_log::Builder::default().build())
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+  app_lib::run();
 }
-
@@ -0,0 +1,255 @@
+//! Native application menu.
+//!
+//! Every ledger action is a [`MenuAction`] emitted to the frontend as
+//! [`MENU_EVENT`]; the frontend does the work, the same as if the action had
+//! been picked from its own UI. Items that need a book are disabled while
+//! none is open.
+//!
+//! Edit > Undo and Redo are the native items, which undo typing in the
+//! focused text field; the frontend has no ledger-level undo for them to
+//! drive.
+
+use serde::{Deserialize, Serialize};
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::book::ActiveBook;
+
+/// Event carrying a [`MenuActionEvent`].
+pub const MENU_EVENT: &str = "menu://action";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MenuAction {
+  NewBook,
+  OpenBook,
+  SwitchBook,
+  BackupNow,
+  Restore,
+  ExportJson,
+  ProfitAndLoss,
+  GstSummary,
+  BasDraft,
+  ImportCsv,
+  Reconcile,
+}
+
+impl MenuAction {
+  pub const ALL: &'static [MenuAction] = &[
+    MenuAction::NewBook,
+    MenuAction::OpenBook,
+    MenuAction::SwitchBook,
+    MenuAction::BackupNow,
+    MenuAction::Restore,
+    MenuAction::ExportJson,
+    MenuAction::ProfitAndLoss,
+    MenuAction::GstSummary,
+    MenuAction::BasDraft,
+    MenuAction::ImportCsv,
+    MenuAction::Reconcile,
+  ];
+
+  /// Menu item id; the same as the serialized name.
+  pub fn id(self) -> &'static str {
+    match self {
+      MenuAction::NewBook => "newBook",
+      MenuAction::OpenBook => "openBook",
+      MenuAction::SwitchBook => "switchBook",
+      MenuAction::BackupNow => "backupNow",
+      MenuAction::Restore => "restore",
+      MenuAction::ExportJson => "exportJson",
+      MenuAction::ProfitAndLoss => "profitAndLoss",
+      MenuAction::GstSummary => "gstSummary",
+      MenuAction::BasDraft => "basDraft",
+      MenuAction::ImportCsv => "importCsv",
+      MenuAction::Reconcile => "reconcile",
+    }
+  }
+
+  fn label(self) -> &'static str {
+    match self {
+      MenuAction::NewBook => "New Book…",
+      MenuAction::OpenBook => "Open Book…",
+      MenuAction::SwitchBook => "Switch Book…",
+      MenuAction::BackupNow => "Backup Now",
+      MenuAction::Restore => "Restore from Backup…",
+      MenuAction::ExportJson => "Export as JSON…",
+      MenuAction::ProfitAndLoss => "Profit and Loss",
+      MenuAction::GstSummary => "GST Summary",
+      MenuAction::BasDraft => "BAS Draft",
+      MenuAction::ImportCsv => "Import CSV…",
+      MenuAction::Reconcile => "Reconcile…",
+    }
+  }
+
+  fn accelerator(self) -> Option<&'static str> {
+    match self {
+      MenuAction::NewBook => Some("CmdOrCtrl+N"),
+      MenuAction::OpenBook => Some("CmdOrCtrl+O"),
+      MenuAction::SwitchBook => Some("CmdOrCtrl+Shift+O"),
+      MenuAction::BackupNow => Some("CmdOrCtrl+B"),
+      MenuAction::Restore => None,
+      MenuAction::ExportJson => Some("CmdOrCtrl+Shift+E"),
+      MenuAction::ProfitAndLoss => Some("CmdOrCtrl+1"),
+      MenuAction::GstSummary => Some("CmdOrCtrl+2"),
+      MenuAction::BasDraft => Some("CmdOrCtrl+3"),
+      MenuAction::ImportCsv => Some("CmdOrCtrl+I"),
+      MenuAction::Reconcile => Some("CmdOrCtrl+Shift+R"),
+    }
+  }
+
+  /// Whether the action only makes sense with a book open.
+  fn needs_book(self) -> bool {
+    !matches!(
+      self,
+      MenuAction::NewBook | MenuAction::OpenBook | MenuAction::SwitchBook
+    )
+  }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MenuActionEvent {
+  pub action: MenuAction,
+}
+
+enum Entry {
+  Action(MenuAction),
+  Separator,
+  // The webview only gets editing shortcuts on macOS from native items.
+  Undo,
+  Redo,
+  Cut,
+  Copy,
+  Paste,
+  SelectAll,
+}
+
+const SECTIONS: &[(&str, &[Entry])] = &[
+  (
+    "File",
+    &[
+      Entry::Action(MenuAction::NewBook),
+      Entry::Action(MenuAction::OpenBook),
+      Entry::Action(MenuAction::SwitchBook),
+      Entry::Separator,
+      Entry::Action(MenuAction::BackupNow),
+      Entry::Action(MenuAction::Restore),
+      Entry::Separator,
+      Entry::Action(MenuAction::ExportJson),
+    ],
+  ),
+  (
+    "Edit",
+    &[
+      Entry::Undo,
+      Entry::Redo,
+      Entry::Separator,
+      Entry::Cut,
+      Entry::Copy,
+      Entry::Paste,
+      Entry::SelectAll,
+    ],
+  ),
+  (
+    "Reports",
+    &[
+      Entry::Action(MenuAction::ProfitAndLoss),
+      Entry::Action(MenuAction::GstSummary),
+      Entry::Action(MenuAction::BasDraft),
+    ],
+  ),
+  (
+    "Tools",
+    &[
+      Entry::Action(MenuAction::ImportCsv),
+      Entry::Action(MenuAction::Reconcile),
+    ],
+  ),
+];
+
+/// The menu's action items, kept in managed state to enable and disable.
+pub struct AppMenu<R: Runtime> {
+  items: Vec<(MenuAction, MenuItem<R>)>,
+}
+
+/// Build the menu, install it for every window and start handling it.
+pub fn create<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+  let menu = Menu::new(app)?;
+  #[cfg(target_os = "macos")]
+  menu.append(&app_submenu(app)?)?;
+
+  let mut items = Vec::new();
+  for (title, entries) in SECTIONS {
+    let submenu = Submenu::new(app, *title, true)?;
+    for entry in *entries {
+      match entry {
+        Entry::Action(action) => {
+          let item =
+            MenuItem::with_id(app, action.id(), action.label(), true, action.accelerator())?;
+          submenu.append(&item)?;
+          items.push((*action, item));
+        }
+        Entry::Separator => submenu.append(&PredefinedMenuItem::separator(app)?)?,
+        Entry::Undo => submenu.append(&PredefinedMenuItem::undo(app, None)?)?,
+        Entry::Redo => submenu.append(&PredefinedMenuItem::redo(app, None)?)?,
+        Entry::Cut => submenu.append(&PredefinedMenuItem::cut(app, None)?)?,
+        Entry::Copy => submenu.append(&PredefinedMenuItem::copy(app, None)?)?,
+        Entry::Paste => submenu.append(&PredefinedMenuItem::paste(app, None)?)?,
+        Entry::SelectAll => submenu.append(&PredefinedMenuItem::select_all(app, None)?)?,
+      }
+    }
+    menu.append(&submenu)?;
+  }
+
+  app.set_menu(menu)?;
+  app.on_menu_event(on_menu_event);
+  app.manage(AppMenu { items });
+  sync(app);
+  Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn app_submenu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Submenu<R>> {
+  Submenu::with_items(
+    app,
+    "Ledgerhound",
+    true,
+    &[
+      &PredefinedMenuItem::about(app, None, None)?,
+      &PredefinedMenuItem::separator(app)?,
+      &PredefinedMenuItem::services(app, None)?,
+      &PredefinedMenuItem::separator(app)?,
+      &PredefinedMenuItem::hide(app, None)?,
+      &PredefinedMenuItem::hide_others(app, None)?,
+      &PredefinedMenuItem::show_all(app, None)?,
+      &PredefinedMenuItem::separator(app)?,
+      &PredefinedMenuItem::quit(app, None)?,
+    ],
+  )
+}
+
+fn on_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+  let Some(&action) = MenuAction::ALL
+    .iter()
+    .find(|action| action.id() == event.id().as_ref())
+  else {
+    return;
+  };
+  if let Err(err) = app.emit(MENU_EVENT, MenuActionEvent { action }) {
+    log::warn!("Failed to emit {MENU_EVENT}: {err}");
+  }
+}
+
+/// Enable exactly the actions that make sense right now.
+pub fn sync<R: Runtime>(app: &AppHandle<R>) {
+  let Some(menu) = app.try_state::<AppMenu<R>>() else {
+    return;
+  };
+  let book_open = app.state::<ActiveBook>().path().is_some();
+  for (action, item) in &menu.items {
+    let enabled = book_open || !action.needs_book();
+    if let Err(err) = item.set_enabled(enabled) {
+      log::warn!("Failed to update menu item {}: {err}", action.id());
+    }
+  }
+}
@@ -0,0 +1,153 @@
+//! Where the Stripe and AI API keys live: the platform keychain (Keychain
+//! on macOS, Credential Manager on Windows, Secret Service on Linux), or an
+//! encrypted file when [`PASSPHRASE_ENV`] is set, for headless machines
+//! with no Secret Service running.
+
+use ledger_core::secrets::{self, FileSecretStore, Secret, SecretError, SecretStore};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::book::ActiveBook;
+use crate::commands::{CommandError, CommandResult};
+use crate::sidecar::ApiServer;
+
+/// Passphrase for the file-based store; setting it opts into that store.
+pub const PASSPHRASE_ENV: &str = "LEDGERHOUND_SECRETS_PASSPHRASE";
+
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Managed state holding the secret store, or why it couldn't be opened.
+pub struct Secrets(Result<Box<dyn SecretStore>, String>);
+
+impl Secrets {
+  pub fn store(&self) -> CommandResult<&dyn SecretStore> {
+    match &self.0 {
+      Ok(store) => Ok(store.as_ref()),
+      Err(reason) => Err(CommandError::new(reason.clone())),
+    }
+  }
+}
+
+/// Entries in the platform keychain, under the app's identifier.
+struct KeyringStore {
+  service: String,
+}
+
+impl KeyringStore {
+  fn entry(&self, secret: Secret) -> Result<keyring::Entry, SecretError> {
+    keyring::Entry::new(&self.service, secret.name()).map_err(store_error)
+  }
+}
+
+fn store_error(err: keyring::Error) -> SecretError {
+  SecretError::Store(err.to_string())
+}
+
+impl SecretStore for KeyringStore {
+  fn get(&self, secret: Secret) -> Result<Option<String>, SecretError> {
+    match self.entry(secret)?.get_password() {
+      Ok(value) => Ok(Some(value)),
+      Err(keyring::Error::NoEntry) => Ok(None),
+      Err(err) => Err(store_error(err)),
+    }
+  }
+
+  fn set(&self, secret: Secret, value: &str) -> Result<(), SecretError> {
+    self.entry(secret)?.set_password(value).map_err(store_error)
+  }
+
+  fn delete(&self, secret: Secret) -> Result<bool, SecretError> {
+    match self.entry(secret)?.delete_credential() {
+      Ok(()) => Ok(true),
+      Err(keyring::Error::NoEntry) => Ok(false),
+      Err(err) => Err(store_error(err)),
+    }
+  }
+}
+
+/// Pick and manage the secret store.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+  let store = match std::env::var(PASSPHRASE_ENV) {
+    Ok(passphrase) => app
+      .path()
+      .app_config_dir()
+      .map_err(|err| err.to_string())
+      .and_then(|dir| {
+        FileSecretStore::open(dir.join(SECRETS_FILE), &passphrase).map_err(|err| err.to_string())
+      })
+      .map(|store| {
+        log::info!("Keeping secrets in {}", store.path().display());
+        Box::new(store) as Box<dyn SecretStore>
+      }),
+    Err(_) => Ok(Box::new(KeyringStore {
+      service: app.config().identifier.clone(),
+    }) as Box<dyn SecretStore>),
+  };
+  if let Err(reason) = &store {
+    log::error!("No secret store: {reason}");
+  }
+  app.manage(Secrets(store));
+}
+
+/// Move any API keys the open book still holds in `settings` into the
+/// store, restarting the API server so it reads them from there. Called
+/// whenever a book is opened.
+pub fn move_out_of_book<R: Runtime>(app: &AppHandle<R>) {
+  let (Some(secrets), Some(book)) = (app.try_state::<Secrets>(), app.try_state::<ActiveBook>())
+  else {
+    return;
+  };
+  let Ok(store) = secrets.store() else {
+    return;
+  };
+  match book.with(|db| secrets::move_secrets_out_of_settings(db, store)) {
+    Ok(migration) => {
+      for secret in &migration.moved {
+        log::info!("Moved {} out of the book's settings", secret.name());
+      }
+      for secret in &migration.conflicts {
+        log::warn!(
+          "Left {} in the book's settings: a different one is already stored",
+          secret.name()
+        );
+      }
+      if !migration.moved.is_empty() {
+        restart_server(app);
+      }
+    }
+    Err(err) => log::warn!("Failed to move secrets out of the book: {err}"),
+  }
+}
+
+/// The stored keys as the API server's environment (see
+/// [`Secret::env_var`]). A key that isn't stored is passed empty, which
+/// tells the server to keep keys it's given out of the settings too.
+/// Without a store the server is left to read and write the settings.
+pub fn server_env<R: Runtime>(app: &AppHandle<R>) -> Vec<(&'static str, String)> {
+  let Some(secrets) = app.try_state::<Secrets>() else {
+    return Vec::new();
+  };
+  let Ok(store) = secrets.store() else {
+    return Vec::new();
+  };
+  Secret::ALL
+    .iter()
+    .filter_map(|&secret| match store.get(secret) {
+      Ok(value) => Some((secret.env_var(), value.unwrap_or_default())),
+      Err(err) => {
+        log::warn!("Failed to read {} for the API server: {err}", secret.name());
+        None
+      }
+    })
+    .collect()
+}
+
+/// Restart the app's API server, if it's running, so it's started with the
+/// keys as they're stored now, and wait for it to answer.
+pub fn restart_server<R: Runtime>(app: &AppHandle<R>) {
+  if let Some(server) = app.try_state::<ApiServer>() {
+    server.with_server_stopped(|| ());
+    if !server.wait_until_ready() {
+      log::warn!("The API server didn't come back after its keys changed");
+    }
+  }
+}
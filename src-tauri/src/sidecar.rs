@@ -0,0 +1,499 @@
+//! Supervisor for the Express API server (`src-server/api.ts`).
+//!
+//! The frontend talks to the API on `localhost:3001`, so the desktop shell owns
+//! that process: it spawns it during setup, keeps the main window hidden until
+//! `/api/health` answers, restarts it with exponential backoff on the same
+//! book if it dies, and kills it when the app exits.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Port hard-coded in `src-server/api.ts`.
+pub const API_PORT: u16 = 3001;
+
+/// Event emitted to the frontend whenever the supervisor changes state.
+pub const STATUS_EVENT: &str = "api-server://status";
+
+/// File name of the bundled server binary (see `bundle.externalBin`).
+const SIDECAR_NAME: &str = "ledgerhound-api";
+
+const HEALTH_PATH: &str = "/api/health";
+const SWITCH_PATH: &str = "/api/books/switch";
+const SWITCH_TIMEOUT: Duration = Duration::from_secs(60);
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// A server that stays up this long is considered healthy again and resets the backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ApiStatus {
+  Starting,
+  Ready {
+    external: bool,
+  },
+  Restarting {
+    attempt: u32,
+    delay_ms: u64,
+    reason: String,
+  },
+  Failed {
+    reason: String,
+  },
+  /// Stopped on purpose while the book file is swapped out.
+  Paused,
+}
+
+/// Exponential backoff between restart attempts.
+struct Backoff {
+  current: Duration,
+  attempt: u32,
+}
+
+impl Backoff {
+  fn new() -> Self {
+    Self {
+      current: MIN_BACKOFF,
+      attempt: 0,
+    }
+  }
+
+  fn next_delay(&mut self) -> Duration {
+    let delay = self.current;
+    self.current = (self.current * 2).min(MAX_BACKOFF);
+    self.attempt += 1;
+    delay
+  }
+
+  fn reset(&mut self) {
+    *self = Self::new();
+  }
+}
+
+/// Handle to the supervised API server, stored in Tauri state.
+#[derive(Clone)]
+pub struct ApiServer {
+  child: Arc<Mutex<Option<Child>>>,
+  shutting_down: Arc<AtomicBool>,
+  paused: Arc<AtomicBool>,
+  /// Set while a server started outside the app owns the port.
+  external: Arc<AtomicBool>,
+}
+
+impl ApiServer {
+  /// Start supervising the API server on a background thread.
+  pub fn start<R: Runtime>(app: &AppHandle<R>) -> Self {
+    let server = Self {
+      child: Arc::new(Mutex::new(None)),
+      shutting_down: Arc::new(AtomicBool::new(false)),
+      paused: Arc::new(AtomicBool::new(false)),
+      external: Arc::new(AtomicBool::new(false)),
+    };
+
+    let supervisor = server.clone();
+    let app = app.clone();
+    thread::Builder::new()
+      .name("api-supervisor".into())
+      .spawn(move || supervisor.supervise(&app))
+      .expect("failed to spawn API supervisor thread");
+
+    server
+  }
+
+  /// Stop supervising and kill the child process. Safe to call more than once.
+  pub fn shutdown(&self) {
+    self.shutting_down.store(true, Ordering::SeqCst);
+    self.kill_child();
+  }
+
+  /// Run `f` with the server stopped, so nothing holds the book open while
+  /// it's replaced, then let the supervisor start it again.
+  ///
+  /// A server started outside the app can't be stopped and keeps running.
+  pub fn with_server_stopped<T>(&self, f: impl FnOnce() -> T) -> T {
+    self.paused.store(true, Ordering::SeqCst);
+    self.kill_child();
+    let result = f();
+    self.paused.store(false, Ordering::SeqCst);
+    result
+  }
+
+  /// Whether the server has `book` open. A server started outside the app
+  /// is assumed to, since there's no telling which book it has.
+  pub fn serves<R: Runtime>(&self, app: &AppHandle<R>, book: &Path) -> bool {
+    if self.is_shutting_down() {
+      return false;
+    }
+    if self.external.load(Ordering::SeqCst) {
+      return true;
+    }
+    crate::book::served_book_path(app).is_ok_and(|served| crate::book::same_file(&served, book))
+  }
+
+  /// Point the server at `book`, unless [`crate::book::check_servable`]
+  /// refuses it. The app's own server is restarted on it; a server started
+  /// outside the app is asked to switch with `/api/books/switch`, which
+  /// only accepts books in its `prisma` directory.
+  pub fn switch_book<R: Runtime>(&self, app: &AppHandle<R>, book: &Path) -> std::io::Result<()> {
+    crate::book::check_servable(book)?;
+    if self.external.load(Ordering::SeqCst) {
+      let name = crate::book::served_book_name(app, book)?;
+      if name.is_absolute() {
+        return Err(std::io::Error::other(format!(
+          "the API server on port {API_PORT} was started outside the app and can't open {}",
+          book.display()
+        )));
+      }
+      let body = serde_json::json!({ "databasePath": name }).to_string();
+      return match api_request("POST", SWITCH_PATH, Some(&body)) {
+        Some(200) => Ok(()),
+        status => Err(std::io::Error::other(format!(
+          "the API server refused to switch books ({status:?})"
+        ))),
+      };
+    }
+    if self.serves(app, book) {
+      return Ok(());
+    }
+    log::info!("Restarting API server on {}", book.display());
+    self.with_server_stopped(|| crate::book::set_served_book_path(app, book))
+  }
+
+  /// Wait for the server to answer its health check, e.g. after
+  /// [`ApiServer::switch_book`] restarted it.
+  pub fn wait_until_ready(&self) -> bool {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while Instant::now() < deadline && !self.is_shutting_down() {
+      if !self.is_paused() && health_check() {
+        return true;
+      }
+      thread::sleep(HEALTH_POLL_INTERVAL);
+    }
+    false
+  }
+
+  fn kill_child(&self) {
+    if let Some(mut child) = self.child.lock().unwrap().take() {
+      log::info!("Stopping API server (pid {})", child.id());
+      let _ = child.kill();
+      let _ = child.wait();
+    }
+  }
+
+  fn is_shutting_down(&self) -> bool {
+    self.shutting_down.load(Ordering::SeqCst)
+  }
+
+  fn is_paused(&self) -> bool {
+    self.paused.load(Ordering::SeqCst)
+  }
+
+  fn supervise<R: Runtime>(&self, app: &AppHandle<R>) {
+    let mut backoff = Backoff::new();
+    let mut window_shown = false;
+    emit_status(app, ApiStatus::Starting);
+
+    while !self.is_shutting_down() {
+      if self.is_paused() {
+        emit_status(app, ApiStatus::Paused);
+        while self.is_paused() && !self.is_shutting_down() {
+          thread::sleep(HEALTH_POLL_INTERVAL);
+        }
+        backoff.reset();
+        emit_status(app, ApiStatus::Starting);
+        continue;
+      }
+
+      // A server started outside the app (e.g. `npm run api`) already owns the
+      // port; use it rather than fighting it for the socket.
+      if health_check() {
+        log::info!("Using API server already listening on port {API_PORT}");
+        emit_status(app, ApiStatus::Ready { external: true });
+        show_main_window(app, &mut window_shown);
+        self.external.store(true, Ordering::SeqCst);
+        while !self.is_shutting_down() && health_check() {
+          thread::sleep(EXIT_POLL_INTERVAL);
+        }
+        self.external.store(false, Ordering::SeqCst);
+        continue;
+      }
+
+      let started = Instant::now();
+      let reason = match self.spawn(app) {
+        Ok(()) => {
+          if self.wait_until_healthy() {
+            log::info!("API server healthy after {:?}", started.elapsed());
+            emit_status(app, ApiStatus::Ready { external: false });
+          } else if !self.is_shutting_down() && !self.is_paused() {
+            log::warn!("API server did not become healthy within {STARTUP_TIMEOUT:?}");
+            emit_status(
+              app,
+              ApiStatus::Failed {
+                reason: "API server did not answer its health check".into(),
+              },
+            );
+          }
+          // Show the window either way so a failure is visible to the user.
+          show_main_window(app, &mut window_shown);
+          self.wait_for_exit()
+        }
+        Err(err) => {
+          show_main_window(app, &mut window_shown);
+          format!("failed to launch API server: {err}")
+        }
+      };
+
+      if self.is_shutting_down() {
+        break;
+      }
+      if self.is_paused() {
+        continue;
+      }
+
+      if started.elapsed() >= STABLE_AFTER {
+        backoff.reset();
+      }
+      let delay = backoff.next_delay();
+      log::warn!("API server stopped ({reason}); restarting in {delay:?}");
+      emit_status(
+        app,
+        ApiStatus::Restarting {
+          attempt: backoff.attempt,
+          delay_ms: delay.as_millis() as u64,
+          reason,
+        },
+      );
+      thread::sleep(delay);
+    }
+  }
+
+  fn spawn<R: Runtime>(&self, app: &AppHandle<R>) -> std::io::Result<()> {
+    let mut command = server_command(app)?;
+    command
+      .env(
+        "NODE_ENV",
+        if cfg!(debug_assertions) {
+          "development"
+        } else {
+          "production"
+        },
+      )
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped());
+
+    let mut child = command.spawn()?;
+    log::info!("Started API server (pid {})", child.id());
+
+    if let Some(stdout) = child.stdout.take() {
+      forward_output(stdout, log::Level::Info);
+    }
+    if let Some(stderr) = child.stderr.take() {
+      forward_output(stderr, log::Level::Warn);
+    }
+
+    *self.child.lock().unwrap() = Some(child);
+
+    // shutdown() or a pause may have come between the check in supervise()
+    // and storing the child.
+    if self.is_shutting_down() || self.is_paused() {
+      self.kill_child();
+    }
+    Ok(())
+  }
+
+  /// Poll the health endpoint until it answers, the child exits, or we time out.
+  fn wait_until_healthy(&self) -> bool {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while Instant::now() < deadline && !self.is_shutting_down() {
+      if health_check() {
+        return true;
+      }
+      if !self.child_running() {
+        return false;
+      }
+      thread::sleep(HEALTH_POLL_INTERVAL);
+    }
+    false
+  }
+
+  /// Block until the child exits, returning a description of why.
+  fn wait_for_exit(&self) -> String {
+    loop {
+      {
+        let mut guard = self.child.lock().unwrap();
+        match guard.as_mut().map(Child::try_wait) {
+          None => return "stopped".into(),
+          Some(Ok(Some(status))) => {
+            guard.take();
+            return format!("exited with {status}");
+          }
+          Some(Ok(None)) => {}
+          Some(Err(err)) => {
+            guard.take();
+            return format!("could not be polled: {err}");
+          }
+        }
+      }
+      thread::sleep(EXIT_POLL_INTERVAL);
+    }
+  }
+
+  fn child_running(&self) -> bool {
+    match self.child.lock().unwrap().as_mut() {
+      Some(child) => matches!(child.try_wait(), Ok(None)),
+      None => false,
+    }
+  }
+}
+
+/// Start supervising the server if setup didn't, because the book it serves
+/// couldn't be opened (e.g. it was encrypted).
+pub fn start_if_stopped<R: Runtime>(app: &AppHandle<R>) {
+  if app.try_state::<ApiServer>().is_none() {
+    app.manage(ApiServer::start(app));
+  }
+}
+
+/// Build the command that launches the API server on the book it serves
+/// (see [`crate::book::served_book_path`]), so a restart after a crash comes
+/// back on the book the user last switched to.
+///
+/// Debug builds run the TypeScript source through the `tsx` loader from the project
+/// root, like `npm run api`, but with `node` as the direct child so killing it
+/// does not orphan the server behind an `npx` wrapper. Release builds run the bundled sidecar binary that
+/// Tauri places next to the main executable, with the database in app data
+/// and Prisma's query engine from the bundle's resources.
+fn server_command<R: Runtime>(app: &AppHandle<R>) -> std::io::Result<Command> {
+  let book = crate::book::served_book_path(app)?;
+  log::info!("Starting API server on {}", book.display());
+  let database_url = format!("file:{}", book.display());
+
+  if cfg!(debug_assertions) {
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .parent()
+      .expect("src-tauri has a parent directory")
+      .to_path_buf();
+    let mut command = Command::new("node");
+    command
+      .args(["--import", "tsx", "src-server/api.ts"])
+      .current_dir(project_root)
+      .env("DATABASE_URL", database_url)
+      .envs(crate::secrets::server_env(app));
+    return Ok(command);
+  }
+
+  let exe_dir = std::env::current_exe()?
+    .parent()
+    .map(PathBuf::from)
+    .ok_or_else(|| std::io::Error::other("executable has no parent directory"))?;
+  let binary = exe_dir.join(format!("{SIDECAR_NAME}{}", std::env::consts::EXE_SUFFIX));
+
+  let data_dir = app
+    .path()
+    .app_data_dir()
+    .map_err(|err| std::io::Error::other(err.to_string()))?;
+
+  let mut command = Command::new(binary);
+  command
+    .current_dir(&data_dir)
+    .env("DATABASE_URL", database_url)
+    .envs(crate::secrets::server_env(app));
+  if let Some(engine) = query_engine(app) {
+    command.env("PRISMA_QUERY_ENGINE_LIBRARY", engine);
+  }
+  Ok(command)
+}
+
+/// The Prisma query engine `npm run build:sidecar` bundled as a resource.
+fn query_engine<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+  let dir = app.path().resource_dir().ok()?.join("binaries");
+  std::fs::read_dir(dir)
+    .ok()?
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .find(|path| {
+      path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().contains("query_engine"))
+    })
+}
+
+/// Issue `GET /api/health` and report whether it returned 200.
+fn health_check() -> bool {
+  api_request("GET", HEALTH_PATH, None) == Some(200)
+}
+
+/// Send a request to the API server, with `body` as JSON, and return the
+/// response status.
+fn api_request(method: &str, path: &str, body: Option<&str>) -> Option<u16> {
+  let addr = SocketAddr::from(([127, 0, 0, 1], API_PORT));
+  let mut stream = TcpStream::connect_timeout(&addr, Duration::from_millis(500)).ok()?;
+  // Switching books runs `prisma migrate deploy` before answering.
+  let read_timeout = if body.is_some() {
+    SWITCH_TIMEOUT
+  } else {
+    Duration::from_secs(2)
+  };
+  let _ = stream.set_read_timeout(Some(read_timeout));
+  let _ = stream.set_write_timeout(Some(Duration::from_secs(2)));
+
+  let mut request =
+    format!("{method} {path} HTTP/1.1\r\nHost: 127.0.0.1:{API_PORT}\r\nConnection: close\r\n");
+  // api.ts rejects unauthenticated /api requests when API_KEY is set.
+  if let Ok(key) = std::env::var("API_KEY") {
+    request.push_str(&format!("Authorization: Bearer {key}\r\n"));
+  }
+  if let Some(body) = body {
+    request.push_str(&format!(
+      "Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+      body.len()
+    ));
+  } else {
+    request.push_str("\r\n");
+  }
+  stream.write_all(request.as_bytes()).ok()?;
+
+  let mut status_line = String::new();
+  BufReader::new(stream).read_line(&mut status_line).ok()?;
+  status_line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Copy a child's output stream into the app log, one line per record.
+fn forward_output(stream: impl Read + Send + 'static, level: log::Level) {
+  thread::spawn(move || {
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+      log::log!(target: "api-server", level, "{line}");
+    }
+  });
+}
+
+fn emit_status<R: Runtime>(app: &AppHandle<R>, status: ApiStatus) {
+  if let Err(err) = app.emit(STATUS_EVENT, status) {
+    log::warn!("Failed to emit API status: {err}");
+  }
+}
+
+fn show_main_window<R: Runtime>(app: &AppHandle<R>, shown: &mut bool) {
+  if *shown {
+    return;
+  }
+  if let Some(window) = app.get_webview_window("main") {
+    if let Err(err) = window.show() {
+      log::warn!("Failed to show main window: {err}");
+    }
+    *shown = true;
+  }
+}
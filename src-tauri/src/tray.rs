@@ -0,0 +1,182 @@
+//! System tray icon: the active book, how many bills are coming up, and a
+//! small always-on-top window for jotting down a transaction without
+//! bringing the register forward.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use ledger_core::db::UpcomingBillCount;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::book::ActiveBook;
+
+/// Label of the quick-add window; `src/main.tsx` renders `QuickAddView` for
+/// its `#quick-add` URL.
+pub const QUICK_ADD_LABEL: &str = "quick-add";
+
+/// Same default window as `/api/recurring-bills/count`.
+const UPCOMING_DAYS: u64 = 14;
+
+const QUICK_ADD_ID: &str = "tray-quick-add";
+const SHOW_ID: &str = "tray-show";
+const QUIT_ID: &str = "tray-quit";
+
+/// The tray's menu items that change, kept in managed state.
+pub struct Tray<R: Runtime> {
+  book: MenuItem<R>,
+  bills: MenuItem<R>,
+  /// The display name `bookManager` uses for the book at this path.
+  book_name: Mutex<Option<(PathBuf, String)>>,
+}
+
+pub fn create<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+  let book = MenuItem::with_id(app, "tray-book", "No book open", false, None::<&str>)?;
+  let bills = MenuItem::with_id(app, "tray-bills", "No bills due", false, None::<&str>)?;
+  let menu = Menu::with_items(
+    app,
+    &[
+      &book,
+      &bills,
+      &PredefinedMenuItem::separator(app)?,
+      &MenuItem::with_id(
+        app,
+        QUICK_ADD_ID,
+        "Quick add transaction…",
+        true,
+        None::<&str>,
+      )?,
+      &MenuItem::with_id(app, SHOW_ID, "Show Ledgerhound", true, None::<&str>)?,
+      &PredefinedMenuItem::separator(app)?,
+      &MenuItem::with_id(app, QUIT_ID, "Quit", true, None::<&str>)?,
+    ],
+  )?;
+
+  let mut builder = TrayIconBuilder::with_id("main")
+    .tooltip("Ledgerhound")
+    .menu(&menu)
+    .on_menu_event(on_menu_event)
+    // Counts go stale as days pass, so re-read them whenever the icon is
+    // touched.
+    .on_tray_icon_event(|tray, event| {
+      if matches!(
+        event,
+        TrayIconEvent::Click { .. } | TrayIconEvent::Enter { .. }
+      ) {
+        refresh(tray.app_handle());
+      }
+    });
+  if let Some(icon) = app.default_window_icon() {
+    builder = builder.icon(icon.clone());
+  }
+  builder.build(app)?;
+
+  app.manage(Tray {
+    book,
+    bills,
+    book_name: Mutex::new(None),
+  });
+  refresh(app);
+  Ok(())
+}
+
+/// Show `name` for the open book until a different book is opened.
+pub fn set_book_name<R: Runtime>(app: &AppHandle<R>, name: String) {
+  let Some(tray) = app.try_state::<Tray<R>>() else {
+    return;
+  };
+  if let Some(path) = app.state::<ActiveBook>().path() {
+    *tray.book_name.lock().unwrap() = Some((path, name));
+  }
+  refresh(app);
+}
+
+/// Re-read the active book and its bill count into the menu.
+pub fn refresh<R: Runtime>(app: &AppHandle<R>) {
+  let Some(tray) = app.try_state::<Tray<R>>() else {
+    return;
+  };
+  let active = app.state::<ActiveBook>();
+  let book_text = match active.path() {
+    None => "No book open".to_string(),
+    Some(path) => match &*tray.book_name.lock().unwrap() {
+      Some((named, name)) if *named == path => format!("Book: {name}"),
+      _ => format!(
+        "Book: {}",
+        path
+          .file_name()
+          .unwrap_or(path.as_os_str())
+          .to_string_lossy()
+      ),
+    },
+  };
+  let bills_text =
+    match active.with(|db| db.upcoming_bill_count(Utc::now().date_naive(), UPCOMING_DAYS)) {
+      Ok(count) => bills_text(count),
+      Err(_) => "Bills unavailable".to_string(),
+    };
+
+  for (item, text) in [(&tray.book, book_text), (&tray.bills, bills_text)] {
+    if let Err(err) = item.set_text(text) {
+      log::warn!("Failed to update tray menu: {err}");
+    }
+  }
+}
+
+fn bills_text(count: UpcomingBillCount) -> String {
+  let due = match count.upcoming {
+    0 => "No bills due".to_string(),
+    1 => format!("1 bill due in the next {UPCOMING_DAYS} days"),
+    n => format!("{n} bills due in the next {UPCOMING_DAYS} days"),
+  };
+  match count.overdue {
+    0 => due,
+    n => format!("{due}, {n} overdue"),
+  }
+}
+
+fn on_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+  let result = match event.id().as_ref() {
+    QUICK_ADD_ID => open_quick_add(app),
+    SHOW_ID => show_main_window(app),
+    QUIT_ID => {
+      app.exit(0);
+      Ok(())
+    }
+    _ => Ok(()),
+  };
+  if let Err(err) = result {
+    log::warn!("Tray action {} failed: {err}", event.id().as_ref());
+  }
+}
+
+fn open_quick_add<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+  if let Some(window) = app.get_webview_window(QUICK_ADD_LABEL) {
+    window.show()?;
+    return window.set_focus();
+  }
+  WebviewWindowBuilder::new(
+    app,
+    QUICK_ADD_LABEL,
+    WebviewUrl::App("index.html#quick-add".into()),
+  )
+  .title("Quick add transaction")
+  .inner_size(420.0, 360.0)
+  .resizable(false)
+  .always_on_top(true)
+  .skip_taskbar(true)
+  .center()
+  .build()?;
+  Ok(())
+}
+
+fn show_main_window<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+  let Some(window) = app.get_webview_window("main") else {
+    return Ok(());
+  };
+  window.unminimize()?;
+  window.show()?;
+  window.set_focus()
+}
@@ -0,0 +1,271 @@
+//! Main window geometry and frontend UI state, remembered per book.
+//!
+//! Kept in `window-state.json` in the app config directory, keyed by book
+//! path. Geometry is in physical pixels, as the monitors report theirs. A
+//! window saved on a monitor that's since gone (or moved) is brought back
+//! onto one that's still there.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+  AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, WindowEvent,
+};
+
+use crate::book::ActiveBook;
+
+const FILE_NAME: &str = "window-state.json";
+
+/// How much of the title bar must be on a monitor for the window to count
+/// as reachable.
+const MIN_VISIBLE_WIDTH: i64 = 100;
+const TITLE_BAR_HEIGHT: i64 = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Geometry {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+  pub maximized: bool,
+  /// Name of the monitor the window was last on.
+  pub monitor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BookWindowState {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  geometry: Option<Geometry>,
+  /// Whatever the frontend wants kept per book (column widths, last view).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  ui: Option<serde_json::Value>,
+}
+
+/// Managed state holding every book's window state.
+pub struct WindowStates {
+  file: Option<PathBuf>,
+  books: Mutex<BTreeMap<String, BookWindowState>>,
+}
+
+impl WindowStates {
+  pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+    let file = app
+      .path()
+      .app_config_dir()
+      .ok()
+      .map(|dir| dir.join(FILE_NAME));
+    let books = file
+      .as_deref()
+      .and_then(|file| std::fs::read_to_string(file).ok())
+      .and_then(|json| serde_json::from_str(&json).ok())
+      .unwrap_or_default();
+    WindowStates {
+      file,
+      books: Mutex::new(books),
+    }
+  }
+
+  pub fn save(&self) {
+    let Some(file) = &self.file else {
+      return;
+    };
+    let json = serde_json::to_string_pretty(&*self.books.lock().unwrap())
+      .expect("window state serializes to JSON");
+    if let Err(err) = write_atomically(file, &json) {
+      log::warn!("Failed to save {}: {err}", file.display());
+    }
+  }
+
+  pub fn ui_state(&self, book: &str) -> Option<serde_json::Value> {
+    self.books.lock().unwrap().get(book)?.ui.clone()
+  }
+
+  pub fn set_ui_state(&self, book: &str, ui: serde_json::Value) {
+    self
+      .books
+      .lock()
+      .unwrap()
+      .entry(book.to_string())
+      .or_default()
+      .ui = Some(ui);
+    self.save();
+  }
+
+  fn geometry(&self, book: &str) -> Option<Geometry> {
+    self.books.lock().unwrap().get(book)?.geometry.clone()
+  }
+
+  fn set_geometry(&self, book: &str, geometry: Geometry) {
+    self
+      .books
+      .lock()
+      .unwrap()
+      .entry(book.to_string())
+      .or_default()
+      .geometry = Some(geometry);
+  }
+}
+
+fn write_atomically(file: &Path, contents: &str) -> std::io::Result<()> {
+  if let Some(dir) = file.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+  let temp = file.with_extension("json.tmp");
+  std::fs::write(&temp, contents)?;
+  std::fs::rename(&temp, file)
+}
+
+/// Key for the open book's state; books are told apart by path.
+pub fn book_key<R: Runtime>(app: &AppHandle<R>) -> String {
+  app
+    .state::<ActiveBook>()
+    .path()
+    .map(|path| path.display().to_string())
+    .unwrap_or_default()
+}
+
+/// Put `window` where it was last time this book was open, then keep track
+/// of where it goes.
+pub fn restore_and_track<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+  let states = app.state::<WindowStates>();
+  if let Some(saved) = states.geometry(&book_key(app)) {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let primary = window.primary_monitor().ok().flatten();
+    let geometry = clamp(&saved, &monitors, primary.as_ref());
+    let applied = window
+      .set_size(PhysicalSize::new(geometry.width, geometry.height))
+      .and_then(|()| window.set_position(PhysicalPosition::new(geometry.x, geometry.y)))
+      .and_then(|()| {
+        if geometry.maximized {
+          window.maximize()
+        } else {
+          Ok(())
+        }
+      });
+    if let Err(err) = applied {
+      log::warn!("Failed to restore window geometry: {err}");
+    }
+  }
+
+  let app = app.clone();
+  let tracked = window.clone();
+  window.on_window_event(move |event| match event {
+    WindowEvent::Moved(_) | WindowEvent::Resized(_) => record(&app, &tracked),
+    WindowEvent::CloseRequested { .. } => app.state::<WindowStates>().save(),
+    _ => {}
+  });
+}
+
+fn record<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+  if window.is_minimized().unwrap_or(false) {
+    return;
+  }
+  let states = app.state::<WindowStates>();
+  let book = book_key(app);
+  let previous = states.geometry(&book);
+  let geometry = if window.is_maximized().unwrap_or(false) {
+    // Keep the bounds to return to when un-maximised.
+    match previous {
+      Some(previous) => Geometry {
+        maximized: true,
+        ..previous
+      },
+      None => return,
+    }
+  } else {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+      return;
+    };
+    Geometry {
+      x: position.x,
+      y: position.y,
+      width: size.width,
+      height: size.height,
+      maximized: false,
+      monitor: window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned()),
+    }
+  };
+  states.set_geometry(&book, geometry);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Area {
+  x: i64,
+  y: i64,
+  width: i64,
+  height: i64,
+}
+
+impl Area {
+  fn of(monitor: &Monitor) -> Self {
+    let rect = monitor.work_area();
+    Area {
+      x: rect.position.x.into(),
+      y: rect.position.y.into(),
+      width: rect.size.width.into(),
+      height: rect.size.height.into(),
+    }
+  }
+
+  /// Whether enough of a title bar at (`x`, `y`), `width` wide, is inside.
+  fn shows_title_bar(&self, x: i64, y: i64, width: i64) -> bool {
+    let left = x.max(self.x);
+    let right = (x + width).min(self.x + self.width);
+    let top_ok = y >= self.y && y + TITLE_BAR_HEIGHT <= self.y + self.height;
+    top_ok && right - left >= MIN_VISIBLE_WIDTH
+  }
+}
+
+/// `saved`, moved onto the monitors that exist now if its title bar would
+/// otherwise be unreachable, and shrunk to fit whichever monitor it ends up
+/// on.
+fn clamp(saved: &Geometry, monitors: &[Monitor], primary: Option<&Monitor>) -> Geometry {
+  let (x, y) = (i64::from(saved.x), i64::from(saved.y));
+  let width = i64::from(saved.width);
+  let height = i64::from(saved.height);
+
+  let visible_on = monitors
+    .iter()
+    .find(|monitor| Area::of(monitor).shows_title_bar(x, y, width));
+  let (area, recentre) = match visible_on {
+    Some(monitor) => (Area::of(monitor), false),
+    None => {
+      let target = monitors
+        .iter()
+        .find(|monitor| monitor.name().is_some() && monitor.name() == saved.monitor.as_ref())
+        .or(primary)
+        .or(monitors.first());
+      match target {
+        Some(monitor) => (Area::of(monitor), true),
+        None => return saved.clone(),
+      }
+    }
+  };
+
+  let width = width.min(area.width);
+  let height = height.min(area.height);
+  let (x, y) = if recentre {
+    (
+      area.x + (area.width - width) / 2,
+      area.y + (area.height - height) / 2,
+    )
+  } else {
+    (x, y)
+  };
+  Geometry {
+    x: x as i32,
+    y: y as i32,
+    width: width as u32,
+    height: height as u32,
+    maximized: saved.maximized,
+    monitor: saved.monitor.clone(),
+  }
+}
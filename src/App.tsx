@@ -2,14 +2,24 @@ import { useState, useEffect } from 'react';
 import { MainLayout } from './components/Layout/MainLayout';
 import { OnboardingWizard } from './components/Onboarding/OnboardingWizard';
 import { AccountSetupWizard } from './components/Account/AccountSetupWizard';
+import { LockedBookScreen } from './components/Book/LockedBookScreen';
 import { ToastProvider } from './components/UI/Toast';
 import { ToastContextProvider } from './hooks/useToast';
 import { bookManager } from './lib/services/bookManager';
 import { booksAPI } from './lib/api';
+import { getBookStatus, isAbsolutePath, isDesktop, onBookOpenRequested, serveBook } from './lib/desktop';
+import type { OpenBookRequest } from './lib/desktop';
 import type { Book } from './types/book';
 
 // Helper: tell the server to switch to a book's database
 async function switchServerDatabase(book: Book): Promise<void> {
+  // A book file opened from the OS lives outside the server's prisma
+  // directory, which /api/books/switch won't leave; the shell restarts the
+  // server on it instead.
+  if (isDesktop() && isAbsolutePath(book.databasePath)) {
+    await serveBook(book.databasePath);
+    return;
+  }
   // Legacy books may have old path format — treat as dev.db (the original database)
   const dbPath = book.databasePath.startsWith('books/')
     ? book.databasePath
@@ -17,6 +27,28 @@ async function switchServerDatabase(book: Book): Promise<void> {
   await booksAPI.switchDatabase(dbPath);
 }
 
+// Helper: the book in bookManager for a file the shell opened, added if new
+function bookForOpenedFile(request: OpenBookRequest): Book {
+  const existing = bookManager.getAllBooks().find(b => b.databasePath === request.databasePath);
+  if (existing) {
+    return existing;
+  }
+  // books/<id>/ledger.ledgerhound is named for its folder; any other file for itself
+  const parts = request.path.split(/[\\/]/).filter(Boolean);
+  const fileName = parts[parts.length - 1] ?? 'Book';
+  const name = /^ledger\.(ledgerhound|db)$/i.test(fileName) && parts.length > 1
+    ? parts[parts.length - 2]
+    : fileName.replace(/\.ledgerhound$/i, '');
+  return bookManager.createBook({
+    name,
+    ownerName: '',
+    databasePath: request.databasePath,
+    currency: 'AUD',
+    dateFormat: 'DD/MM/YYYY',
+    fiscalYearStart: '07-01',
+  });
+}
+
 export default function App() {
   // Initialize isFirstRun immediately to avoid flash of onboarding when books exist
   const [isFirstRun, setIsFirstRun] = useState(() => bookManager.isFirstRun());
@@ -25,9 +57,22 @@ export default function App() {
   const [isLoading, setIsLoading] = useState(true);
   // Store the account ID to navigate to after account creation (Issue #5)
   const [initialAccountId, setInitialAccountId] = useState<string | null>(null);
+  // Desktop only: an encrypted book the API server can't start on
+  const [lockedBookPath, setLockedBookPath] = useState<string | null>(null);
 
   useEffect(() => {
     const init = async () => {
+      try {
+        const status = await getBookStatus();
+        if (status?.locked) {
+          setLockedBookPath(status.locked);
+          setIsLoading(false);
+          return;
+        }
+      } catch (error) {
+        console.error('Failed to read book status:', error);
+      }
+
       // Re-check first run status (in case of localStorage changes)
       const firstRun = bookManager.isFirstRun();
       setIsFirstRun(firstRun);
@@ -106,6 +151,27 @@ export default function App() {
     init();
   }, []);
 
+  // Book files opened from Finder/Explorer (desktop only). The shell has
+  // already opened the book and switched the server to it.
+  useEffect(() => {
+    const stopListening = onBookOpenRequested(request => {
+      if (request.error) {
+        window.alert(`Couldn't open ${request.path}: ${request.error}`);
+        return;
+      }
+      const book = bookForOpenedFile(request);
+      bookManager.setActiveBook(book.id);
+      // Reload to reinitialize with the opened book, as handleBookSwitch does
+      window.location.reload();
+    }).catch(error => {
+      console.error('Failed to listen for opened books:', error);
+      return () => {};
+    });
+    return () => {
+      stopListening.then(stop => stop());
+    };
+  }, []);
+
   const handleOnboardingComplete = async (bookId: string) => {
     const book = bookManager.getBook(bookId);
     if (book) {
@@ -180,6 +246,10 @@ export default function App() {
     );
   }
 
+  if (lockedBookPath) {
+    return <LockedBookScreen databasePath={lockedBookPath} />;
+  }
+
   return (
     <ToastProvider>
       <ToastContextProvider>
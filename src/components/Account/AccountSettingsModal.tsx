@@ -2,7 +2,7 @@ import { useState, useEffect } from 'react';
 import * as Dialog from '@radix-ui/react-dialog';
 import { X, Briefcase, DollarSign, Calendar, Hash, FolderTree } from 'lucide-react';
 import type { AccountWithBalance, AccountType, AccountSubtype, ATOLabel } from '../../types';
-import { ATO_LABEL_DESCRIPTIONS } from '../../types';
+import { ATO_LABEL_DESCRIPTIONS, BAS_PURCHASE_LABELS } from '../../types';
 import { accountAPI, categoryAPI } from '../../lib/api';
 
 interface AccountSettingsModalProps {
@@ -278,10 +278,18 @@ export function AccountSettingsModal({
                           <option key={label} value={label}>{ATO_LABEL_DESCRIPTIONS[label]}</option>
                         ))}
                       </optgroup>
+                      {account.type === 'EXPENSE' && (
+                        <optgroup label="BAS Purchases">
+                          {BAS_PURCHASE_LABELS.map(label => (
+                            <option key={label} value={label}>{ATO_LABEL_DESCRIPTIONS[label]}</option>
+                          ))}
+                        </optgroup>
+                      )}
                     </select>
                     <p className="text-xs text-slate-500 dark:text-slate-400 mt-1">
                       Maps this category to a specific ATO tax return item for the Tax Summary report.
                       Business categories without a label auto-detect as Business Income/Expense.
+                      A BAS purchases label sets whether purchases go to G10 or G11 and still counts as a business expense.
                     </p>
                   </div>
                 )}
@@ -0,0 +1,66 @@
+/**
+ * Locked Book Screen
+ * Shown at startup when the book the API server serves is encrypted. The
+ * server can't read encrypted books, so the book is decrypted to open it.
+ */
+
+import { useState } from 'react';
+import { Lock } from 'lucide-react';
+import { decryptBook } from '../../lib/desktop';
+
+interface LockedBookScreenProps {
+  databasePath: string;
+}
+
+export function LockedBookScreen({ databasePath }: LockedBookScreenProps) {
+  const [passphrase, setPassphrase] = useState('');
+  const [isDecrypting, setIsDecrypting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setIsDecrypting(true);
+    setError(null);
+    try {
+      await decryptBook(databasePath, passphrase);
+      // Reload to initialize with the server on the decrypted book
+      window.location.reload();
+    } catch (err) {
+      setError(String(err));
+      setIsDecrypting(false);
+    }
+  };
+
+  return (
+    <div className="h-screen flex items-center justify-center bg-slate-50 dark:bg-slate-900">
+      <form onSubmit={handleSubmit} className="w-full max-w-sm bg-white dark:bg-slate-800 rounded-lg shadow p-6 space-y-4">
+        <div className="flex items-center gap-2 text-slate-900 dark:text-white">
+          <Lock className="w-5 h-5" />
+          <h1 className="text-lg font-semibold">This book is encrypted</h1>
+        </div>
+        <p className="text-sm text-slate-600 dark:text-slate-400">
+          Ledgerhound can't work with an encrypted book yet, so it needs to be decrypted
+          before it can be opened. Enter its passphrase to decrypt and open it.
+        </p>
+        <p className="text-xs text-slate-500 dark:text-slate-400 break-all">{databasePath}</p>
+        <input
+          type="password"
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          required
+          autoFocus
+          placeholder="Passphrase"
+          className="w-full px-3 py-1.5 text-sm border border-slate-300 dark:border-slate-600 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent bg-white dark:bg-slate-700 text-slate-900 dark:text-white"
+        />
+        {error && <p className="text-xs text-red-600 dark:text-red-400">{error}</p>}
+        <button
+          type="submit"
+          disabled={isDecrypting}
+          className="w-full px-3 py-1.5 text-sm bg-blue-600 hover:bg-blue-700 text-white rounded-lg font-medium transition-colors disabled:opacity-50"
+        >
+          {isDecrypting ? 'Decrypting...' : 'Decrypt and open'}
+        </button>
+      </form>
+    </div>
+  );
+}
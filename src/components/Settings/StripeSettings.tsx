@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { toast } from 'react-hot-toast';
 import { CreditCard, Key, Trash2, Wallet } from 'lucide-react';
+import { deleteSecret, setSecret } from '../../lib/desktop';
 
 const API_BASE = import.meta.env.DEV ? 'http://localhost:3001/api' : '/api';
 
@@ -124,6 +125,10 @@ export const StripeSettings: React.FC = () => {
 
     setLoading(true);
     try {
+      // On the desktop the key goes to the keychain; the server leaves it out of the book
+      if (apiKey) {
+        await setSecret('stripeApiKey', apiKey);
+      }
       const response = await fetch(`${API_BASE}/stripe/settings`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
@@ -192,6 +197,7 @@ export const StripeSettings: React.FC = () => {
       });
 
       if (response.ok) {
+        await deleteSecret('stripeApiKey');
         toast.success('Stripe disconnected');
         setSettings(null);
         setApiKey('');
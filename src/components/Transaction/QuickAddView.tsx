@@ -0,0 +1,178 @@
+import { useState, useEffect } from 'react';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import type { AccountWithBalance } from '../../types';
+import { transactionAPI, accountAPI } from '../../lib/api';
+import { CategorySelector } from '../Category/CategorySelector';
+
+// Helper to format date for input field (YYYY-MM-DD) without timezone shift
+const formatDateForInput = (date: Date): string => {
+  const year = date.getFullYear();
+  const month = String(date.getMonth() + 1).padStart(2, '0');
+  const day = String(date.getDate()).padStart(2, '0');
+  return `${year}-${month}-${day}`;
+};
+
+const LAST_ACCOUNT_KEY = 'ledgerhound-quick-add-account';
+
+const inputClassName =
+  'w-full px-3 py-1.5 text-sm border border-slate-300 dark:border-slate-600 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent bg-white dark:bg-slate-700 text-slate-900 dark:text-white';
+const labelClassName = 'block text-xs font-medium text-slate-700 dark:text-slate-300 mb-1.5';
+
+/**
+ * The tray's "Quick add transaction" window (src-tauri/src/tray.rs), rendered
+ * at `index.html#quick-add`: one expense paid from one account, for jotting
+ * down a cash purchase without bringing the full register forward.
+ */
+export function QuickAddView() {
+  const [accounts, setAccounts] = useState<AccountWithBalance[]>([]);
+  const [accountId, setAccountId] = useState('');
+  const [categoryId, setCategoryId] = useState<string | null>(null);
+  const [date, setDate] = useState(formatDateForInput(new Date()));
+  const [payee, setPayee] = useState('');
+  const [amount, setAmount] = useState('');
+  const [memo, setMemo] = useState('');
+  const [saving, setSaving] = useState(false);
+  const [message, setMessage] = useState<{ kind: 'success' | 'error'; text: string } | null>(null);
+
+  useEffect(() => {
+    document.title = 'Quick add transaction';
+    const loadAccounts = async () => {
+      try {
+        const transferAccounts = await accountAPI.getAllAccountsWithBalances({ kind: 'TRANSFER', isReal: true });
+        setAccounts(transferAccounts);
+        // Default to the account used last time, else the first cash account
+        const last = localStorage.getItem(LAST_ACCOUNT_KEY);
+        const preferred = transferAccounts.find(a => a.id === last)
+          ?? transferAccounts.find(a => a.name.toLowerCase().includes('cash'))
+          ?? transferAccounts[0];
+        if (preferred) {
+          setAccountId(preferred.id);
+        }
+      } catch (error) {
+        console.error('Failed to load accounts:', error);
+        setMessage({ kind: 'error', text: 'Could not load accounts. Is the book open?' });
+      }
+    };
+    loadAccounts();
+  }, []);
+
+  const closeWindow = () => {
+    getCurrentWindow().close().catch(error => console.error('Failed to close quick add:', error));
+  };
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    const amountNum = parseFloat(amount);
+    if (!accountId || !categoryId || !payee.trim() || !(amountNum > 0)) {
+      setMessage({ kind: 'error', text: 'Enter a payee, an amount, an account and a category.' });
+      return;
+    }
+
+    setSaving(true);
+    setMessage(null);
+    try {
+      await transactionAPI.createTransaction({
+        date: new Date(date),
+        payee: payee.trim(),
+        memo: memo || undefined,
+        postings: [
+          // Money out of the account, into the expense category
+          { accountId, amount: -amountNum },
+          { accountId: categoryId, amount: amountNum },
+        ],
+      });
+      localStorage.setItem(LAST_ACCOUNT_KEY, accountId);
+      setMessage({ kind: 'success', text: `Added ${payee.trim()} for $${amountNum.toFixed(2)}` });
+      // Keep the account and category for the next entry
+      setPayee('');
+      setAmount('');
+      setMemo('');
+    } catch (error) {
+      setMessage({ kind: 'error', text: (error as Error).message });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <div className="min-h-screen bg-white dark:bg-slate-800 p-4">
+      <form onSubmit={handleSubmit} className="space-y-3">
+        <div className="grid grid-cols-2 gap-3">
+          <div>
+            <label className={labelClassName}>Date</label>
+            <input type="date" value={date} onChange={(e) => setDate(e.target.value)} required className={inputClassName} />
+          </div>
+          <div>
+            <label className={labelClassName}>Amount</label>
+            <input
+              type="number"
+              step="0.01"
+              min="0"
+              value={amount}
+              onChange={(e) => setAmount(e.target.value)}
+              required
+              placeholder="0.00"
+              className={inputClassName}
+            />
+          </div>
+        </div>
+
+        <div>
+          <label className={labelClassName}>Payee</label>
+          <input
+            type="text"
+            value={payee}
+            onChange={(e) => setPayee(e.target.value)}
+            required
+            autoFocus
+            placeholder="Enter payee name"
+            className={inputClassName}
+          />
+        </div>
+
+        <div className="grid grid-cols-2 gap-3">
+          <div>
+            <label className={labelClassName}>Paid from</label>
+            <select value={accountId} onChange={(e) => setAccountId(e.target.value)} required className={inputClassName}>
+              {accounts.map(account => (
+                <option key={account.id} value={account.id}>{account.name}</option>
+              ))}
+            </select>
+          </div>
+          <div>
+            <label className={labelClassName}>Category</label>
+            <CategorySelector value={categoryId} onChange={setCategoryId} type="EXPENSE" required />
+          </div>
+        </div>
+
+        <div>
+          <label className={labelClassName}>Memo</label>
+          <input type="text" value={memo} onChange={(e) => setMemo(e.target.value)} className={inputClassName} />
+        </div>
+
+        {message && (
+          <p className={`text-xs ${message.kind === 'success' ? 'text-emerald-600 dark:text-emerald-400' : 'text-red-600 dark:text-red-400'}`}>
+            {message.text}
+          </p>
+        )}
+
+        <div className="flex justify-end gap-2">
+          <button
+            type="button"
+            onClick={closeWindow}
+            className="px-3 py-1.5 text-sm text-slate-700 dark:text-slate-300 hover:bg-slate-100 dark:hover:bg-slate-700 rounded-lg font-medium transition-colors"
+          >
+            Close
+          </button>
+          <button
+            type="submit"
+            disabled={saving}
+            className="px-3 py-1.5 text-sm bg-blue-600 hover:bg-blue-700 text-white rounded-lg font-medium transition-colors disabled:opacity-50"
+          >
+            {saving ? 'Saving...' : 'Add'}
+          </button>
+        </div>
+      </form>
+    </div>
+  );
+}
@@ -1,4 +1,5 @@
 import type { AccountKind } from '@/domain';
+import { deleteSecret, setSecret } from './desktop';
 import type {
   CSVColumnMapping,
   ImportPreview,
@@ -1348,6 +1349,10 @@ export const aiAPI = {
   },
 
   async saveSettings(data: { apiKey?: string; modelId?: string; enabled?: boolean }): Promise<{ success: boolean; settings: AISettingsPublic }> {
+    // On the desktop the key goes to the keychain; the server leaves it out of the book
+    if (data.apiKey) {
+      await setSecret('aiApiKey', data.apiKey);
+    }
     const response = await fetch(`${API_BASE}/ai/settings`, {
       method: 'POST',
       headers: { 'Content-Type': 'application/json' },
@@ -1363,6 +1368,7 @@ export const aiAPI = {
   async deleteSettings(): Promise<void> {
     const response = await fetch(`${API_BASE}/ai/settings`, { method: 'DELETE' });
     if (!response.ok) throw new Error('Failed to delete AI settings');
+    await deleteSecret('aiApiKey');
   },
 
   async validateKey(apiKey: string): Promise<{ valid: boolean; error?: string }> {
@@ -0,0 +1,107 @@
+/**
+ * Desktop shell (src-tauri) integration. Everything here is a no-op when the
+ * app runs in a browser against `npm run api`.
+ */
+
+import { invoke, isTauri } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/** A book file opened from the OS shell (see src-tauri/src/instance.rs). */
+export interface OpenBookRequest {
+  path: string;
+  /** `path` relative to the server's prisma directory when it's inside it */
+  databasePath: string;
+  /** Set when the shell couldn't open the book */
+  error: string | null;
+}
+
+const OPEN_BOOK_EVENT = 'book://open-requested';
+
+export function isDesktop(): boolean {
+  return isTauri();
+}
+
+/**
+ * Call `handler` for each book file the shell opens, including the one the
+ * app was launched with. The shell has already switched the API server to
+ * the book by the time `handler` runs. Returns a function that stops
+ * listening.
+ */
+export async function onBookOpenRequested(
+  handler: (request: OpenBookRequest) => void
+): Promise<() => void> {
+  if (!isDesktop()) {
+    return () => {};
+  }
+  // The shell keeps the request until it's taken, so one that arrived
+  // before we were listening isn't lost and none is handled twice.
+  const takePending = async () => {
+    const request = await invoke<OpenBookRequest | null>('take_pending_book_open');
+    if (request) {
+      handler(request);
+    }
+  };
+  const unlisten = await listen(OPEN_BOOK_EVENT, () => {
+    takePending().catch(error => console.error('Failed to take opened book:', error));
+  });
+  await takePending();
+  return unlisten;
+}
+
+/** Which book the shell has open (see `BookStatus` in src-tauri/src/book.rs). */
+export interface BookStatus {
+  path: string | null;
+  error: string | null;
+  encrypted: boolean;
+  /** Set when the book needs its passphrase before it can be opened */
+  locked: string | null;
+}
+
+export async function getBookStatus(): Promise<BookStatus | null> {
+  if (!isDesktop()) {
+    return null;
+  }
+  return invoke<BookStatus>('book_status');
+}
+
+/**
+ * Unlock the encrypted book at `databasePath` and decrypt it, so the API
+ * server (which can't read encrypted books) can serve it.
+ */
+export async function decryptBook(databasePath: string, passphrase: string): Promise<void> {
+  await invoke('unlock_book', { databasePath, passphrase });
+  await invoke('change_book_passphrase', { current: passphrase, new: null });
+  await serveBook(databasePath);
+}
+
+/** Have the shell restart the API server on the book file at `databasePath`. */
+export async function serveBook(databasePath: string): Promise<void> {
+  await invoke('serve_book', { databasePath });
+}
+
+/** An API key the shell keeps in the OS keychain (`Secret` in src-tauri/ledger-core/src/secrets.rs). */
+export type SecretName = 'stripeApiKey' | 'aiApiKey';
+
+/**
+ * Keep an API key in the OS keychain rather than the book. The shell restarts
+ * the API server with it before this resolves.
+ */
+export async function setSecret(secret: SecretName, value: string): Promise<void> {
+  if (!isDesktop()) {
+    return;
+  }
+  await invoke('set_secret', { secret, value });
+}
+
+/** Remove an API key from the OS keychain, restarting the API server without it. */
+export async function deleteSecret(secret: SecretName): Promise<void> {
+  if (!isDesktop()) {
+    return;
+  }
+  await invoke('delete_secret', { secret });
+}
+
+/** Whether `path` is absolute on any platform (POSIX or Windows). */
+export function isAbsolutePath(path: string): boolean {
+  return path.startsWith('/') || /^[A-Za-z]:[\\/]/.test(path) || path.startsWith('\\\\');
+}
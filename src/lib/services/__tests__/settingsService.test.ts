@@ -1,5 +1,5 @@
-import { describe, it, expect, beforeAll, beforeEach, afterAll } from 'vitest';
-import { SettingsService } from '../settingsService';
+import { describe, it, expect, beforeAll, beforeEach, afterAll, afterEach } from 'vitest';
+import { SECRET_ENV, SettingsService } from '../settingsService';
 import type { PrismaClient } from '@prisma/client';
 import { getTestDb, resetTestDb, cleanupTestDb } from '../__test-utils__/testDb';
 import { seedTestAccounts } from '../__test-utils__/fixtures';
@@ -214,6 +214,27 @@ describe('SettingsService', () => {
       });
     });
 
+    describe('with the API key from the desktop app', () => {
+      afterEach(() => {
+        delete process.env[SECRET_ENV.stripe];
+      });
+
+      it('should read the key from the environment and keep it out of the settings', async () => {
+        process.env[SECRET_ENV.stripe] = 'sk_test_keychain';
+
+        await settingsService.saveStripeSettings(
+          'sk_test_keychain',
+          accounts.businessChecking.id
+        );
+
+        const stored = await settingsService.getJSON<{ apiKey: string }>('stripe');
+        expect(stored?.apiKey).toBe('');
+        const settings = await settingsService.getStripeSettings();
+        expect(settings?.apiKey).toBe('sk_test_keychain');
+        expect(settings?.accountId).toBe(accounts.businessChecking.id);
+      });
+    });
+
     describe('deleteStripeSettings', () => {
       it('should delete Stripe settings', async () => {
         await settingsService.saveStripeSettings(
@@ -6,7 +6,7 @@
 
 import Anthropic from '@anthropic-ai/sdk';
 import { getPrismaClient } from '../db';
-import { settingsService } from './settingsService';
+import { settingsService, SECRET_ENV, withSecretFromEnv, withoutSecretFromEnv } from './settingsService';
 import { memorizedRuleService } from './memorizedRuleService';
 import type { PrismaClient } from '@prisma/client';
 
@@ -116,12 +116,12 @@ export class AICategorizationService {
   // ── Settings Management ──────────────────────────────────────────────────
 
   async getSettings(): Promise<AISettings | null> {
-    return settingsService.getJSON<AISettings>('ai_categorization');
+    return withSecretFromEnv(await settingsService.getJSON<AISettings>('ai_categorization'), SECRET_ENV.ai);
   }
 
   async saveSettings(apiKey: string, modelId: string, enabled: boolean): Promise<AISettings> {
     const settings: AISettings = { apiKey, modelId, enabled };
-    await settingsService.setJSON('ai_categorization', settings);
+    await settingsService.setJSON('ai_categorization', withoutSecretFromEnv(settings, SECRET_ENV.ai));
     return settings;
   }
 
@@ -193,7 +193,7 @@ export class BookManager {
   private getDefaultDatabasePath(bookId: string): string {
     // In a real app, this would use the user's documents folder
     // For now, we'll use a relative path pattern
-    return `${DEFAULT_DB_DIR}/${bookId}/ledger.db`;
+    return `${DEFAULT_DB_DIR}/${bookId}/ledger.ledgerhound`;
   }
 
   /**
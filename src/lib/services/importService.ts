@@ -423,7 +423,7 @@ export class ImportService {
             payee: preview.suggestedPayee || preview.parsed.payee || 'Transfer',
             reference: preview.parsed.reference,
             importBatchId: batch.id,
-            externalId: preview.parsed.reference,
+            externalId: preview.parsed.externalId ?? preview.parsed.reference,
             metadata: Object.keys(metadata).length > 0 ? JSON.stringify(metadata) : null,
             postings: {
               create: [
@@ -483,7 +483,7 @@ export class ImportService {
               payee: preview.suggestedPayee || preview.parsed.payee!,
               reference: preview.parsed.reference,
               importBatchId: batch.id,
-              externalId: preview.parsed.reference,
+              externalId: preview.parsed.externalId ?? preview.parsed.reference,
               metadata: Object.keys(metadata).length > 0 ? JSON.stringify(metadata) : null,
               postings: {
                 create: [
@@ -655,7 +655,7 @@ export class ImportService {
           payee: preview.suggestedPayee || preview.parsed.payee,
           reference: preview.parsed.reference,
           importBatchId: batch.id,
-          externalId: preview.parsed.reference,
+          externalId: preview.parsed.externalId ?? preview.parsed.reference,
           metadata: Object.keys(metadata).length > 0 ? JSON.stringify(metadata) : null,
           postings: {
             create: postings,
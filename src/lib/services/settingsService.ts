@@ -21,6 +21,33 @@ export interface StripeSettingsPublic {
   apiKeyMasked?: string;
 }
 
+/**
+ * Environment variables the desktop app passes the API keys in, from the OS
+ * keychain (see `Secret::env_var` in src-tauri/ledger-core/src/secrets.rs).
+ * The desktop app blanks the keys in the settings, so when these are set the
+ * keys are read from them and never written back. Run on its own
+ * (`npm run api`), the server keeps the keys in the settings as before.
+ */
+export const SECRET_ENV = {
+  stripe: 'LEDGERHOUND_STRIPE_API_KEY',
+  ai: 'LEDGERHOUND_AI_API_KEY',
+} as const;
+
+/**
+ * `stored` with its `apiKey` taken from `env` when the desktop app passed one,
+ * else as stored
+ */
+export function withSecretFromEnv<T extends { apiKey: string }>(stored: T | null, env: string): T | null {
+  const secret = process.env[env];
+  if (!stored || !secret) return stored;
+  return { ...stored, apiKey: secret };
+}
+
+/** `settings` as it should be stored: without its key if the keychain has it */
+export function withoutSecretFromEnv<T extends { apiKey: string }>(settings: T, env: string): T {
+  return process.env[env] === undefined ? settings : { ...settings, apiKey: '' };
+}
+
 export class SettingsService {
   private prisma: PrismaClient;
 
@@ -86,7 +113,7 @@ export class SettingsService {
    * Get Stripe settings
    */
   async getStripeSettings(): Promise<StripeSettings | null> {
-    return await this.getJSON<StripeSettings>('stripe');
+    return withSecretFromEnv(await this.getJSON<StripeSettings>('stripe'), SECRET_ENV.stripe);
   }
 
   /**
@@ -103,7 +130,7 @@ export class SettingsService {
       payoutDestinationAccountId,
     };
 
-    await this.setJSON('stripe', settings);
+    await this.setJSON('stripe', withoutSecretFromEnv(settings, SECRET_ENV.stripe));
     return settings;
   }
 
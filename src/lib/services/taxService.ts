@@ -14,7 +14,7 @@ import type {
   PAYGInstallment,
   PAYGConfig,
 } from '../../types';
-import { ATO_LABEL_DESCRIPTIONS as atoDescriptions } from '../../types';
+import { ATO_LABEL_DESCRIPTIONS as atoDescriptions, BAS_PURCHASE_LABELS } from '../../types';
 
 // Default tax tables for 2025-26 FY
 const DEFAULT_TAX_TABLES_2025_26: TaxTablesConfig = {
@@ -325,7 +325,13 @@ export class TaxService {
 
     // Process explicitly labelled postings
     for (const posting of postings) {
-      const label = posting.account.atoLabel as ATOLabel;
+      let label = posting.account.atoLabel as ATOLabel;
+      // BAS purchase labels only say where GST is reported; the category is
+      // still a business expense, and personal spending on it isn't claimable.
+      if (BAS_PURCHASE_LABELS.includes(label)) {
+        if (!posting.isBusiness) continue;
+        label = 'BUS_EXPENSE';
+      }
       const categoryName = posting.account.name;
       const amount = this.getGSTExclusiveAmount(posting);
 
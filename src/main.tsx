@@ -1,6 +1,9 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import App from './App';
+import { QuickAddView } from './components/Transaction/QuickAddView';
+import { ToastProvider } from './components/UI/Toast';
+import { ToastContextProvider } from './hooks/useToast';
 import './index.css';
 import { attachConsole } from "@tauri-apps/plugin-log";
 
@@ -11,9 +14,20 @@ declare global {
   }
 }
 
+// The tray's quick-add window loads index.html#quick-add (src-tauri/src/tray.rs)
+const isQuickAdd = window.location.hash === '#quick-add';
+
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <React.StrictMode>
-    <App />
+    {isQuickAdd ? (
+      <ToastProvider>
+        <ToastContextProvider>
+          <QuickAddView />
+        </ToastContextProvider>
+      </ToastProvider>
+    ) : (
+      <App />
+    )}
   </React.StrictMode>
 );
 
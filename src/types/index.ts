@@ -120,6 +120,7 @@ export interface ImportPreview {
     payee?: string;
     amount?: number;
     reference?: string;
+    externalId?: string; // Bank's transaction id (OFX FITID); falls back to reference
   };
   isDuplicate: boolean;
   matchedRule?: MemorizedRule;
@@ -389,7 +390,14 @@ export type ATOLabel =
   | 'D9_GIFTS'           // Gifts/donations
   | 'D10_TAX_AFFAIRS'    // Cost of managing tax affairs
   | 'D12_SUPER'          // Personal superannuation contributions
-  | 'D15_OTHER';         // Other deductions
+  | 'D15_OTHER'          // Other deductions
+  // BAS purchases (business expense categories; GST reporting only)
+  | 'G10'                // Capital purchases
+  | 'G11';               // Non-capital purchases
+
+// Labels that only place a category's purchases on the BAS. For income tax
+// the category is still a business expense.
+export const BAS_PURCHASE_LABELS: ATOLabel[] = ['G10', 'G11'];
 
 export const ATO_LABEL_DESCRIPTIONS: Record<ATOLabel, string> = {
   BUS_INCOME: 'Gross Business Income',
@@ -410,6 +418,8 @@ export const ATO_LABEL_DESCRIPTIONS: Record<ATOLabel, string> = {
   D10_TAX_AFFAIRS: 'D10: Cost of Managing Tax Affairs',
   D12_SUPER: 'D12: Personal Super Contributions',
   D15_OTHER: 'D15: Other Deductions',
+  G10: 'G10: Capital Purchases (BAS)',
+  G11: 'G11: Non-Capital Purchases (BAS)',
 };
 
 export interface TaxEstimation {